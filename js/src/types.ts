@@ -153,7 +153,7 @@ export interface InteractiveElement {
   style: ElementStyle;
 }
 
-export type ElementType = 'Chart' | 'Animation' | 'Interactive' | 'Vector' | 'Text' | 'Image' | 'Container';
+export type ElementType = 'Chart' | 'Animation' | 'Interactive' | 'Vector' | 'Text' | 'Image' | 'Container' | 'Embed' | 'Question';
 
 export interface EventHandler {
   eventType: string;
@@ -234,6 +234,417 @@ export interface WASMError {
   details?: Record<string, any>;
 }
 
+export interface ErrorCodeInfo {
+  code: string;
+  subsystem: string;
+  description: string;
+}
+
+// Property schemas
+export type PropertyType =
+  | 'String' | 'Number' | 'Integer' | 'Boolean' | 'Color' | 'Any'
+  | { Choice: string[] };
+
+export interface PropertySchema {
+  name: string;
+  property_type: PropertyType;
+  default?: any;
+  min?: number;
+  max?: number;
+  description: string;
+}
+
+export interface PropertyIssue {
+  element_id: string;
+  property: string;
+  message: string;
+  suggestion?: string;
+}
+
+// Presets
+export interface PresetOptions {
+  parent_id?: string;
+  data_source?: string;
+  title?: string;
+  value_field?: string;
+  chart_type?: string;
+  width?: number;
+  height?: number;
+}
+
+export interface CreatedPreset {
+  root_id: string;
+  element_ids: string[];
+  chart_ids: string[];
+  shape_ids: string[];
+  binding_ids: string[];
+}
+
+// Schema versions
+export type SchemaKind = 'Document' | 'Snapshot' | 'DataSync';
+
+export interface SupportedVersions {
+  kind: SchemaKind;
+  current: number;
+  oldest: number;
+}
+
+// Resources released for removed elements
+export interface GcReport {
+  animations: number;
+  bindings: number;
+  interaction_states: number;
+  delegates: number;
+  subscriptions: number;
+  visibility_triggers: number;
+  rate_limits: number;
+  charts: number;
+  chart_renders: number;
+  shapes: number;
+  cached_updates: number;
+  element_views: number;
+  embedded_documents: number;
+  dangling_links: number;
+}
+
+// Upserts; a null property removes it, parent_id only applies on creation
+export interface ElementSpec {
+  element_type: ElementType;
+  properties?: Record<string, unknown>;
+  parent_id?: string | null;
+}
+
+export interface DataSourceSpec {
+  source_type: 'Static' | 'Dynamic' | 'Stream' | 'Computed';
+  data: unknown;
+}
+
+export interface UpsertResult {
+  created: boolean;
+  changed: string[];
+  removed: string[];
+}
+
+// Per-row uncertainty of a chart series (ChartSeries.error); rendered points carry the
+// resulting { lower, upper } as DataPoint.error for tooltips
+export type ErrorFields =
+  | { Symmetric: { field: string; label?: string | null } }
+  | { Bounds: { lower: string; upper: string; label?: string | null } };
+
+export interface ErrorRange {
+  lower: number;
+  upper: number;
+}
+
+// Chart markers. ChartSeries.markers lets rows pick their own shape (shape_field) and size
+// (size_field mapped onto size_range); rendered points carry the result as DataPoint.marker
+export type MarkerShape = 'Circle' | 'Square' | 'Triangle' | 'Diamond' | 'Cross' | 'Plus';
+
+export interface MarkerOptions {
+  shape_field?: string | null;
+  size_field?: string | null;
+  size_range?: [number, number];
+  size_scale?: 'Linear' | 'Area';
+}
+
+export interface PointMarker {
+  shape: MarkerShape;
+  size: number;
+}
+
+// From export_chart(chart_id, 'svg' | 'png', scale). content is SVG markup, or a PNG data: URL
+// (needs the engine's raster feature); width and height are in pixels after scaling
+export interface ChartExport {
+  chart_id: string;
+  format: 'Svg' | 'Png';
+  mime_type: string;
+  width: number;
+  height: number;
+  content: string;
+}
+
+// Stroke of a line or area series (ChartSeries.line_style). Rows whose value is null or
+// missing break the line unless gaps is 'Connect'
+export interface LineStyle {
+  dash?: number[] | null;
+  curve?: 'Linear' | 'Monotone' | 'CatmullRom' | 'StepBefore' | 'StepAfter';
+  gaps?: 'Break' | 'Connect';
+}
+
+// Client to document coordinates (set_input_transform), as the CSS matrix(a, b, c, d, e, f).
+// Fold device pixel ratio, zoom, scroll and container transforms into one matrix
+export interface InputTransform {
+  a: number;
+  b: number;
+  c: number;
+  d: number;
+  e: number;
+  f: number;
+}
+
+// Spaces convert_point moves between
+export type CoordinateSpace =
+  | 'Client'
+  | 'Document'
+  | 'Viewport'
+  | { Element: { element_id: string } };
+
+// Linked text frames (set_text_story). The story flows through its Text frames in order, split
+// into each frame's `columns` property; frames get text, text_lines and text_overflow properties
+export interface TextRun {
+  text: string;
+  font_size?: number | null;
+  font_weight?: string | null;
+  italic?: boolean;
+  color?: string | null;
+}
+
+export interface TextStory {
+  id: string;
+  runs: TextRun[];
+  frames: string[];
+  font_size?: number;
+  line_height?: number;
+  align?: 'Left' | 'Center' | 'Right' | 'Justify';
+  // Language of the hyphenation patterns, e.g. 'en'; see register_hyphenation_patterns
+  hyphenation?: string | null;
+  // 'Optimal' breaks whole paragraphs at once for more even spacing
+  line_breaking?: 'Greedy' | 'Optimal';
+  widows?: number;
+  orphans?: number;
+  // Base direction of each paragraph; 'Auto' follows its first strong character
+  direction?: 'Auto' | 'Ltr' | 'Rtl';
+}
+
+// From get_story_layout; x and y are relative to the frame, start and end are character offsets.
+// word_spacing is the extra width of each space on a justified line
+export interface FlowLine {
+  column: number;
+  y: number;
+  height: number;
+  spans: FlowSpan[];
+  word_spacing: number;
+  hyphenated: boolean;
+}
+
+// Spans are in visual order, x being the left edge; text is in logical order and drawn right to
+// left when rtl is set
+export interface FlowSpan {
+  run: number;
+  text: string;
+  x: number;
+  width: number;
+  rtl: boolean;
+  start: number;
+}
+
+export interface StoryLayout {
+  story_id: string;
+  frames: { frame_id: string; start: number; end: number; lines: FlowLine[] }[];
+  overflow: boolean;
+}
+
+// set_shaping_callback receives a ShapingRequest as JSON and answers with a JSON ShapedCluster[].
+// Cluster starts are character offsets into the request text, in logical order
+export interface ShapingRequest {
+  text: string;
+  font_size: number;
+  font_weight: string | null;
+  italic: boolean;
+  rtl: boolean;
+}
+
+export interface ShapedCluster {
+  start: number;
+  advance: number;
+}
+
+// Assessments (set_question, submit_answer). Answer keys stay in the engine; Question elements get
+// quiz_attempts, quiz_correct, quiz_score and quiz_closed properties. Answers and completion are
+// also emitted on the event bus as "quiz.answered" and "quiz.completed"
+export type QuestionKind =
+  | { MultipleChoice: { options: string[]; correct: number } }
+  | { MultiSelect: { options: string[]; correct: number[]; partial_credit?: boolean } }
+  | { Numeric: { answer: number; tolerance?: number } }
+  // Items in the correct order; the host shuffles them for display
+  | { Ordering: { items: string[]; partial_credit?: boolean } };
+
+export interface Question {
+  element_id: string;
+  prompt: string;
+  kind: QuestionKind;
+  points?: number;
+  max_attempts?: number;
+}
+
+// Option indices, or item indices in the learner's order
+export type QuestionAnswer =
+  | { Choice: number }
+  | { Choices: number[] }
+  | { Number: number }
+  | { Order: number[] };
+
+export interface QuestionAttempt {
+  element_id: string;
+  attempts: number;
+  answer: QuestionAnswer;
+  score: number;
+  correct: boolean;
+}
+
+export interface QuizScore {
+  score: number;
+  max_score: number;
+  answered: number;
+  total: number;
+  completed: boolean;
+}
+
+export type QuizEvent =
+  | { Answered: { element_id: string; correct: boolean; score: number; attempts: number; timestamp: number } }
+  | { Completed: { score: number; max_score: number; timestamp: number } };
+
+// Guided tours (define_tour, start_tour). The engine draws the dimming overlay, spotlight and
+// callout itself; steps whose target is hidden are passed over. Progress is emitted on the event
+// bus as "tour.step" {tour_id, step, step_count, target}, "tour.completed" and "tour.skipped"
+export type CalloutPlacement = 'Auto' | 'Top' | 'Bottom' | 'Left' | 'Right';
+
+export interface TourStep {
+  target: string;
+  title: string;
+  body?: string;
+  placement?: CalloutPlacement;
+}
+
+export interface Tour {
+  id: string;
+  steps: TourStep[];
+  dim_opacity?: number;
+  padding?: number;
+}
+
+export interface TourProgress {
+  tour_id: string;
+  step: number;
+  step_count: number;
+  target: string;
+}
+
+// Branching pages (set_pages, next_page, page_back). The first page is the start; next_page takes
+// the first route whose condition holds. Page changes are emitted on the event bus as
+// "page.changed" with {from, to, back, depth}
+export type Comparison = 'Equal' | 'NotEqual' | 'Less' | 'LessOrEqual' | 'Greater' | 'GreaterOrEqual';
+
+export type PageCondition =
+  | 'Always'
+  // Percentage of the quiz's maximum score, 0 to 100
+  | { QuizPercent: { comparison: Comparison; percent: number } }
+  | { Answered: { element_id: string } }
+  | { AnsweredCorrectly: { element_id: string } }
+  // A field of the source's first row, or of the source itself when its data is an object
+  | { Data: { source_id: string; field: string; comparison: Comparison; value: unknown } }
+  | { All: PageCondition[] }
+  | { Any: PageCondition[] }
+  | { Not: PageCondition };
+
+export interface PageRoute {
+  to: string;
+  when?: PageCondition;
+}
+
+export interface Page {
+  id: string;
+  elements: string[];
+  routes?: PageRoute[];
+  // An intended ending, not reported as a dead end
+  end?: boolean;
+}
+
+// Every route is assumed takeable; dead ends are reachable pages, other than endings, with no
+// unconditional route
+export interface PageReachability {
+  start: string | null;
+  reachable: string[];
+  unreachable: string[];
+  dead_ends: string[];
+}
+
+// Scroll, zoom, page and map chart views; navigate_back/navigate_forward move through the
+// history. encode_view_link gives e.g. "x=0&y=640&s=1.5&p=results&m.sites=-10,35,30,60",
+// which open_view_link accepts with or without a leading '#' or '?'
+export interface ViewState {
+  offset_x: number;
+  offset_y: number;
+  scale: number;
+  page: string | null;
+  map_views: Record<string, GeoBounds>;
+}
+
+// Scrolling updates the current entry; zooms, page changes, map views and push_view add one
+export interface ViewHistory {
+  entries: ViewState[];
+  index: number;
+}
+
+// Funnel and pyramid layout (ChartConfig.stages). Stage clicks are emitted on the event bus
+// as "chart.stage_click" with {chart_id, stage, label, value, percent_of_first, percent_of_previous}
+export interface StageOptions {
+  neck_width: number;
+  labels: 'Value' | 'PercentOfFirst' | 'PercentOfPrevious';
+}
+
+// Map markers (chart type "map", ChartConfig.map). Rows are placed by latitude and longitude in
+// the map's projection; set_map_view pans and zooms, fit_map_view zooms to all markers. Clicks are emitted on the event bus as
+// "chart.marker_click" {chart_id, row, latitude, longitude, label, data} and
+// "chart.cluster_click" {chart_id, rows, count}; the chart element then gets a redrawn svg_content
+export type Projection = 'Mercator' | 'Equirectangular';
+
+// Degrees; west < east and south < north
+export interface GeoBounds {
+  west: number;
+  south: number;
+  east: number;
+  north: number;
+}
+
+export interface MapOptions {
+  view: GeoBounds;
+  projection?: Projection;
+  latitude_field: string;
+  longitude_field: string;
+  label_field: string;
+  // Pixels; 0 turns clustering off
+  cluster_radius: number;
+  marker_radius: number;
+}
+
+// Custom chart type from register_custom_chart_type. Placeholders in the templates:
+// {field}, {field:x} / {field:y} (position), {field:w} / {field:h} (length),
+// {index}, {band}, {band_size}, {color}, {width}, {height}
+export interface DeclarativeChartType {
+  header?: string;
+  row: string;
+  footer?: string;
+}
+
+// Structured chart and vector output (render_chart_nodes, render_vector_nodes). Node ids are
+// stable across renders, e.g. "sales/rect[2]" or "sales/line.error-bar[0]"
+export interface SvgNode {
+  id: string;
+  tag: string;
+  attributes: [string, string][];
+  text?: string | null;
+  children: SvgNode[];
+}
+
+// From diff_svg_nodes; apply in order
+export type SvgPatch =
+  | { op: 'Replace'; id: string; node: SvgNode }
+  | { op: 'SetAttributes'; id: string; set: [string, string][]; removed: string[] }
+  | { op: 'SetText'; id: string; text?: string | null }
+  | { op: 'Insert'; parent_id: string; index: number; node: SvgNode }
+  | { op: 'Remove'; id: string };
+
 // Loader options
 export interface LoaderOptions {
   validateSignatures?: boolean;
@@ -1,6 +1,13 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+// The runtime's lint rules, compiled in so the editor reports exactly what a loaded document
+// would be warned about
+#[path = "../../interactive-engine/src/lint.rs"]
+pub mod lint;
+
+use lint::{LintCategory, LintDocument, LintElement, LintFinding, LintProfile, LintSeverity};
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global allocator
 #[cfg(feature = "wee_alloc")]
@@ -22,15 +29,33 @@ pub struct EditorState {
     pub history: EditHistory,
     pub validation_state: ValidationState,
     pub preview_mode: PreviewMode,
+    #[serde(default)]
+    pub playback: PlaybackState,
+    #[serde(default)]
+    pub journal: Journal,
+    // Charts being authored, not yet part of the document
+    #[serde(default)]
+    pub chart_drafts: HashMap<String, ChartDraft>,
+    // Lint rules validate_document runs
+    #[serde(default)]
+    pub lint_profile: LintProfile,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct DocumentState {
     pub elements: Vec<EditableElement>,
     pub styles: HashMap<String, StyleRule>,
     pub scripts: HashMap<String, ScriptModule>,
     pub assets: HashMap<String, AssetReference>,
     pub metadata: DocumentMetadata,
+    // Animation timelines keyed by element id
+    #[serde(default)]
+    pub timelines: HashMap<String, Timeline>,
+    #[serde(default)]
+    pub canvas: CanvasSettings,
+    // Data for authoring and previews, keyed by data source id; rows are JSON objects
+    #[serde(default)]
+    pub data_sources: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -45,7 +70,7 @@ pub struct EditableElement {
     pub bounds: BoundingBox,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum ElementType {
     Text,
     Image,
@@ -202,6 +227,7 @@ pub enum WarningType {
     Accessibility,
     Compatibility,
     BestPractice,
+    Security,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -243,6 +269,8 @@ pub struct ValidationReport {
     pub warnings: Vec<ValidationWarning>,
     pub performance_score: f64,
     pub accessibility_score: f64,
+    // Every lint finding with its rule id and fix; errors and warnings include them too
+    pub lint: Vec<LintFinding>,
 }
 
 // Render update for preview
@@ -315,6 +343,831 @@ pub enum AssetAction {
     Optimize,
 }
 
+// Animation authoring: one keyframe timeline per element, exported in the runtime animation format
+
+// Same variants as the interactive engine's AnimationType
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum AnimationType {
+    Transform,
+    Style,
+    Path,
+    Morph,
+}
+
+// Same variants as the interactive engine; Cubic holds CSS control points (x1, y1, x2, y2)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum EasingFunction {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    Cubic(f64, f64, f64, f64),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TimelineKeyframe {
+    pub time: f64, // milliseconds from the start
+    pub properties: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Timeline {
+    pub element_id: String,
+    pub animation_type: AnimationType,
+    pub duration: f64, // milliseconds
+    pub easing: EasingFunction,
+    pub keyframes: Vec<TimelineKeyframe>, // sorted by time
+    pub loop_count: i32, // -1 for infinite
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PlaybackState {
+    pub playing: bool,
+    pub position: f64, // milliseconds
+    pub last_tick: Option<f64>,
+}
+
+pub const EASING_PRESETS: [(&str, EasingFunction); 8] = [
+    ("linear", EasingFunction::Linear),
+    ("ease", EasingFunction::Cubic(0.25, 0.1, 0.25, 1.0)),
+    ("ease-in", EasingFunction::Cubic(0.42, 0.0, 1.0, 1.0)),
+    ("ease-out", EasingFunction::Cubic(0.0, 0.0, 0.58, 1.0)),
+    ("ease-in-out", EasingFunction::Cubic(0.42, 0.0, 0.58, 1.0)),
+    ("ease-in-back", EasingFunction::Cubic(0.36, 0.0, 0.66, -0.56)),
+    ("ease-out-back", EasingFunction::Cubic(0.34, 1.56, 0.64, 1.0)),
+    ("ease-in-out-cubic", EasingFunction::Cubic(0.65, 0.0, 0.35, 1.0)),
+];
+
+// Keyframes closer than this are the same keyframe
+const KEYFRAME_TIME_EPSILON: f64 = 0.5;
+
+impl EasingFunction {
+    pub fn preset(name: &str) -> Option<EasingFunction> {
+        EASING_PRESETS.iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, easing)| easing.clone())
+    }
+
+    // Eased progress; the named curves use the runtime's formulas so the preview matches playback
+    pub fn evaluate(&self, progress: f64) -> f64 {
+        let progress = progress.clamp(0.0, 1.0);
+        match self {
+            EasingFunction::Linear => progress,
+            EasingFunction::EaseIn => progress * progress,
+            EasingFunction::EaseOut => 1.0 - (1.0 - progress) * (1.0 - progress),
+            EasingFunction::EaseInOut => {
+                if progress < 0.5 {
+                    2.0 * progress * progress
+                } else {
+                    1.0 - 2.0 * (1.0 - progress) * (1.0 - progress)
+                }
+            }
+            EasingFunction::Cubic(x1, y1, x2, y2) => cubic_bezier(*x1, *y1, *x2, *y2, progress),
+        }
+    }
+
+    // Control points for the curve editor; EaseInOut is only approximated by a single cubic
+    pub fn control_points(&self) -> (Position, Position) {
+        let (x1, y1, x2, y2) = match self {
+            EasingFunction::Linear => (0.0, 0.0, 1.0, 1.0),
+            EasingFunction::EaseIn => (1.0 / 3.0, 0.0, 2.0 / 3.0, 1.0 / 3.0),
+            EasingFunction::EaseOut => (1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0, 1.0),
+            EasingFunction::EaseInOut => (0.5, 0.0, 0.5, 1.0),
+            EasingFunction::Cubic(x1, y1, x2, y2) => (*x1, *y1, *x2, *y2),
+        };
+        (Position { x: x1, y: y1 }, Position { x: x2, y: y2 })
+    }
+
+    // Dragging a handle turns any curve into a Cubic; x stays within 0-1, y may overshoot
+    pub fn with_control_point(&self, index: usize, point: Position) -> EasingFunction {
+        let (mut first, mut second) = self.control_points();
+        let point = Position { x: point.x.clamp(0.0, 1.0), y: point.y };
+        if index == 0 {
+            first = point;
+        } else {
+            second = point;
+        }
+        EasingFunction::Cubic(first.x, first.y, second.x, second.y)
+    }
+
+    // Points along the curve for drawing it, from progress 0 to 1
+    pub fn sample(&self, steps: usize) -> Vec<Position> {
+        let steps = steps.max(1);
+        (0..=steps)
+            .map(|step| {
+                let x = step as f64 / steps as f64;
+                Position { x, y: self.evaluate(x) }
+            })
+            .collect()
+    }
+}
+
+// y of the CSS cubic-bezier curve at x: solve x(t) with Newton's method, bisecting where it stalls
+fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64, x: f64) -> f64 {
+    let coordinate = |t: f64, p1: f64, p2: f64| {
+        let mt = 1.0 - t;
+        3.0 * mt * mt * t * p1 + 3.0 * mt * t * t * p2 + t * t * t
+    };
+    let slope = |t: f64, p1: f64, p2: f64| {
+        let mt = 1.0 - t;
+        3.0 * mt * mt * p1 + 6.0 * mt * t * (p2 - p1) + 3.0 * t * t * (1.0 - p2)
+    };
+    
+    let mut t = x;
+    for _ in 0..8 {
+        let error = coordinate(t, x1, x2) - x;
+        if error.abs() < 1e-7 {
+            return coordinate(t, y1, y2);
+        }
+        let derivative = slope(t, x1, x2);
+        if derivative.abs() < 1e-7 {
+            break;
+        }
+        t = (t - error / derivative).clamp(0.0, 1.0);
+    }
+    
+    let (mut low, mut high) = (0.0, 1.0);
+    t = x;
+    while high - low > 1e-7 {
+        if coordinate(t, x1, x2) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.0;
+    }
+    coordinate(t, y1, y2)
+}
+
+impl Timeline {
+    pub fn new(element_id: &str) -> Self {
+        Self {
+            element_id: element_id.to_string(),
+            animation_type: AnimationType::Style,
+            duration: 0.0,
+            easing: EasingFunction::Linear,
+            keyframes: Vec::new(),
+            loop_count: 1,
+        }
+    }
+
+    // Merges into an existing keyframe at this time, keeping that keyframe's time, otherwise
+    // inserts one in order
+    pub fn set_keyframe(&mut self, time: f64, properties: HashMap<String, serde_json::Value>) {
+        if let Some(keyframe) = self.keyframes.iter_mut().find(|k| (k.time - time).abs() < KEYFRAME_TIME_EPSILON) {
+            keyframe.properties.extend(properties);
+        } else {
+            let index = self.keyframes.iter().position(|k| k.time > time).unwrap_or(self.keyframes.len());
+            self.keyframes.insert(index, TimelineKeyframe { time, properties });
+            self.duration = self.duration.max(time);
+        }
+    }
+
+    pub fn remove_keyframe(&mut self, time: f64) -> bool {
+        let before = self.keyframes.len();
+        self.keyframes.retain(|k| (k.time - time).abs() >= KEYFRAME_TIME_EPSILON);
+        self.keyframes.len() != before
+    }
+
+    // Values at a position in milliseconds, computed the way the runtime plays the exported animation
+    pub fn values_at(&self, position: f64) -> HashMap<String, serde_json::Value> {
+        let mut values = HashMap::new();
+        let (Some(first), Some(last)) = (self.keyframes.first(), self.keyframes.last()) else {
+            return values;
+        };
+        let progress = if self.duration > 0.0 { position / self.duration } else { 1.0 };
+        let time = self.easing.evaluate(progress) * self.duration;
+        
+        let (prev, next) = self.keyframes.windows(2)
+            .find(|pair| pair[0].time <= time && pair[1].time >= time)
+            .map(|pair| (&pair[0], &pair[1]))
+            .unwrap_or((first, last));
+        let span = next.time - prev.time;
+        let local = if span > 0.0 { ((time - prev.time) / span).clamp(0.0, 1.0) } else { 0.0 };
+        
+        for (key, prev_value) in &prev.properties {
+            let value = match (prev_value.as_f64(), next.properties.get(key)) {
+                (Some(from), Some(to)) if to.is_number() => {
+                    serde_json::json!(from + (to.as_f64().unwrap_or(from) - from) * local)
+                }
+                (_, Some(to)) if local > 0.5 => to.clone(),
+                _ => prev_value.clone(),
+            };
+            values.insert(key.clone(), value);
+        }
+        values
+    }
+
+    // Serialized like the interactive engine's Animation: keyframe times are fractions of the duration
+    pub fn to_runtime_animation(&self) -> serde_json::Value {
+        let keyframes: Vec<serde_json::Value> = self.keyframes.iter()
+            .map(|keyframe| serde_json::json!({
+                "time": if self.duration > 0.0 { keyframe.time / self.duration } else { 0.0 },
+                "properties": keyframe.properties,
+            }))
+            .collect();
+        serde_json::json!({
+            "id": format!("{}_timeline", self.element_id),
+            "target_element": self.element_id,
+            "animation_type": self.animation_type,
+            "duration": self.duration,
+            "easing": self.easing,
+            "keyframes": keyframes,
+            "loop_count": self.loop_count,
+            "direction": "Normal",
+        })
+    }
+
+    // Style changes for the preview; transform timelines combine x, y, rotation and scale
+    fn style_changes_at(&self, position: f64) -> Vec<StyleChange> {
+        let selector = format!("#{}", self.element_id);
+        let mut values = self.values_at(position);
+        let mut changes = Vec::new();
+        
+        if let AnimationType::Transform = self.animation_type {
+            let mut take = |key: &str, default: f64| values.remove(key).and_then(|v| v.as_f64()).unwrap_or(default);
+            let transform = format!(
+                "translate({}px, {}px) rotate({}deg) scale({})",
+                take("x", 0.0), take("y", 0.0), take("rotation", 0.0), take("scale", 1.0)
+            );
+            changes.push(StyleChange { selector: selector.clone(), property: "transform".to_string(), value: transform, important: false });
+        }
+        let mut keys: Vec<String> = values.keys().cloned().collect();
+        keys.sort();
+        for key in keys {
+            let value = match &values[&key] {
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            changes.push(StyleChange { selector: selector.clone(), property: key, value, important: false });
+        }
+        changes
+    }
+}
+
+// Canvas chrome: grid, guides and rulers, saved with the document
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GridSettings {
+    pub size: f64, // pixels between major lines
+    pub subdivisions: u32,
+    pub visible: bool,
+    pub snap: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum GuideOrientation {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Guide {
+    pub id: String,
+    pub orientation: GuideOrientation,
+    pub position: f64, // y for horizontal guides, x for vertical ones
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum RulerUnit {
+    Pixels,
+    Points,
+    Millimeters,
+    Inches,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RulerSettings {
+    pub visible: bool,
+    pub unit: RulerUnit,
+    pub origin: Position,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CanvasSettings {
+    pub grid: GridSettings,
+    pub guides: Vec<Guide>,
+    pub rulers: RulerSettings,
+    pub snap_to_guides: bool,
+    pub snap_threshold: f64, // pixels
+}
+
+// Handle being dragged on the selection box
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum TransformHandle {
+    Move,
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+}
+
+impl CanvasSettings {
+    // Distance between the lines elements snap to
+    pub fn grid_step(&self) -> f64 {
+        self.grid.size / self.grid.subdivisions.max(1) as f64
+    }
+
+    // Adjustment that snaps the closest of `edges` on one axis: guides within the threshold win,
+    // otherwise the grid when snapping is on
+    pub fn snap_offset(&self, edges: &[f64], orientation: GuideOrientation) -> f64 {
+        if self.snap_to_guides {
+            let nearest_guide = self.guides.iter()
+                .filter(|guide| guide.orientation == orientation)
+                .flat_map(|guide| edges.iter().map(move |edge| guide.position - edge))
+                .filter(|offset| offset.abs() <= self.snap_threshold)
+                .min_by(|a, b| a.abs().total_cmp(&b.abs()));
+            if let Some(offset) = nearest_guide {
+                return offset;
+            }
+        }
+        let step = self.grid_step();
+        if !self.grid.snap || step <= 0.0 {
+            return 0.0;
+        }
+        edges.iter()
+            .map(|edge| (edge / step).round() * step - edge)
+            .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .unwrap_or(0.0)
+    }
+}
+
+// Bounds after dragging `handle` by (dx, dy), with the moved edges snapped
+fn transform_bounds(canvas: &CanvasSettings, bounds: &BoundingBox, handle: &TransformHandle, dx: f64, dy: f64) -> BoundingBox {
+    const MIN_SIZE: f64 = 1.0;
+    let (mut left, mut top) = (bounds.x, bounds.y);
+    let (mut right, mut bottom) = (bounds.x + bounds.width, bounds.y + bounds.height);
+    
+    if let TransformHandle::Move = handle {
+        let x_offset = canvas.snap_offset(&[left + dx, right + dx], GuideOrientation::Vertical);
+        let y_offset = canvas.snap_offset(&[top + dy, bottom + dy], GuideOrientation::Horizontal);
+        return BoundingBox { x: left + dx + x_offset, y: top + dy + y_offset, width: bounds.width, height: bounds.height };
+    }
+    
+    let moves_left = matches!(handle, TransformHandle::TopLeft | TransformHandle::Left | TransformHandle::BottomLeft);
+    let moves_right = matches!(handle, TransformHandle::TopRight | TransformHandle::Right | TransformHandle::BottomRight);
+    let moves_top = matches!(handle, TransformHandle::TopLeft | TransformHandle::Top | TransformHandle::TopRight);
+    let moves_bottom = matches!(handle, TransformHandle::BottomLeft | TransformHandle::Bottom | TransformHandle::BottomRight);
+    let snapped = |edge: f64, orientation: GuideOrientation| edge + canvas.snap_offset(&[edge], orientation);
+    if moves_left {
+        left = snapped(left + dx, GuideOrientation::Vertical).min(right - MIN_SIZE);
+    }
+    if moves_right {
+        right = snapped(right + dx, GuideOrientation::Vertical).max(left + MIN_SIZE);
+    }
+    if moves_top {
+        top = snapped(top + dy, GuideOrientation::Horizontal).min(bottom - MIN_SIZE);
+    }
+    if moves_bottom {
+        bottom = snapped(bottom + dy, GuideOrientation::Horizontal).max(top + MIN_SIZE);
+    }
+    BoundingBox { x: left, y: top, width: right - left, height: bottom - top }
+}
+
+// Chart builder: drafts previewed against sampled rows, then turned into runtime chart elements
+
+// Same variants as the interactive engine's ChartType
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ChartType {
+    Line,
+    Bar,
+    Pie,
+    Scatter,
+    Area,
+    Histogram,
+    Heatmap,
+    Treemap,
+    Sankey,
+    Radar,
+    Gauge,
+    Candlestick,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum ColumnType {
+    Number,
+    Text,
+    Date,
+    Boolean,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub column_type: ColumnType,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SeriesMapping {
+    pub field: String,
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChartDraft {
+    pub id: String,
+    pub chart_type: ChartType,
+    pub data_source_id: String,
+    pub x_field: Option<String>,
+    pub series: Vec<SeriesMapping>,
+    pub title: Option<String>,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChartSuggestion {
+    pub columns: Vec<ColumnInfo>,
+    pub chart_type: ChartType,
+    pub x_field: Option<String>,
+    pub series: Vec<SeriesMapping>,
+}
+
+// Rows previews and type inference look at
+const CHART_SAMPLE_ROWS: usize = 200;
+const MAX_SUGGESTED_SERIES: usize = 5;
+const CHART_PALETTE: [&str; 6] = ["#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948"];
+
+// Up to `max` rows spread evenly over the data
+fn sample_rows(data: &serde_json::Value, max: usize) -> Vec<&serde_json::Value> {
+    let rows = match data.as_array() {
+        Some(rows) => rows,
+        None => return Vec::new(),
+    };
+    let stride = (rows.len() + max - 1) / max.max(1);
+    rows.iter().step_by(stride.max(1)).take(max).collect()
+}
+
+fn looks_like_date(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() >= 10
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[5..7].iter().all(u8::is_ascii_digit)
+        && bytes[7] == b'-'
+        && bytes[8..10].iter().all(u8::is_ascii_digit)
+}
+
+// Column types by majority over the sampled non-null values, in first-seen column order
+fn infer_columns(rows: &[&serde_json::Value]) -> Vec<ColumnInfo> {
+    let mut names: Vec<String> = Vec::new();
+    let mut votes: HashMap<String, [usize; 4]> = HashMap::new();
+    for row in rows {
+        let Some(fields) = row.as_object() else { continue };
+        for (name, value) in fields {
+            let index = match value {
+                serde_json::Value::Number(_) => 0,
+                serde_json::Value::String(text) if looks_like_date(text) => 2,
+                serde_json::Value::String(text) if text.trim().parse::<f64>().is_ok() => 0,
+                serde_json::Value::String(_) => 1,
+                serde_json::Value::Bool(_) => 3,
+                _ => continue,
+            };
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+            votes.entry(name.clone()).or_insert([0; 4])[index] += 1;
+        }
+    }
+    names.into_iter()
+        .map(|name| {
+            let counts = votes[&name];
+            let winner = (0..4).max_by_key(|i| (counts[*i], 3 - *i)).unwrap_or(1);
+            let column_type = [ColumnType::Number, ColumnType::Text, ColumnType::Date, ColumnType::Boolean][winner].clone();
+            ColumnInfo { name, column_type }
+        })
+        .collect()
+}
+
+// Dates along x suggest a line chart, categories a bar chart, and two bare numbers a scatter plot
+fn suggest_chart(columns: Vec<ColumnInfo>) -> ChartSuggestion {
+    let x_column = columns.iter().find(|c| c.column_type == ColumnType::Date)
+        .or_else(|| columns.iter().find(|c| c.column_type == ColumnType::Text));
+    let numbers: Vec<&ColumnInfo> = columns.iter().filter(|c| c.column_type == ColumnType::Number).collect();
+    let (chart_type, x_field, values) = match x_column {
+        Some(x) if x.column_type == ColumnType::Date => (ChartType::Line, Some(x.name.clone()), &numbers[..]),
+        Some(x) => (ChartType::Bar, Some(x.name.clone()), &numbers[..]),
+        None if numbers.len() >= 2 => (ChartType::Scatter, Some(numbers[0].name.clone()), &numbers[1..]),
+        None => (ChartType::Bar, None, &numbers[..]),
+    };
+    let series = values.iter()
+        .take(MAX_SUGGESTED_SERIES)
+        .enumerate()
+        .map(|(i, column)| SeriesMapping {
+            field: column.name.clone(),
+            name: column.name.clone(),
+            color: CHART_PALETTE[i % CHART_PALETTE.len()].to_string(),
+        })
+        .collect();
+    ChartSuggestion { columns, chart_type, x_field, series }
+}
+
+fn numeric_value(row: &serde_json::Value, field: &str) -> Option<f64> {
+    match row.get(field)? {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+// Quick SVG of the draft for the preview pane; chart types without a preview of their own show as bars
+fn render_chart_preview(draft: &ChartDraft, rows: &[&serde_json::Value]) -> String {
+    let (width, height) = (draft.width.max(1.0), draft.height.max(1.0));
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", width, height, width, height);
+    let values: Vec<Vec<f64>> = draft.series.iter()
+        .map(|series| rows.iter().map(|row| numeric_value(row, &series.field).unwrap_or(0.0)).collect())
+        .collect();
+    
+    if draft.chart_type == ChartType::Pie {
+        let slices = values.first().cloned().unwrap_or_default();
+        let total: f64 = slices.iter().filter(|v| **v > 0.0).sum();
+        let (cx, cy, r) = (width / 2.0, height / 2.0, width.min(height) / 2.0);
+        let mut angle: f64 = 0.0;
+        for (i, value) in slices.iter().filter(|v| **v > 0.0).enumerate() {
+            let sweep = value / total.max(f64::EPSILON) * std::f64::consts::TAU;
+            let (x1, y1) = (cx + r * angle.sin(), cy - r * angle.cos());
+            angle += sweep;
+            let (x2, y2) = (cx + r * angle.sin(), cy - r * angle.cos());
+            let large = if sweep > std::f64::consts::PI { 1 } else { 0 };
+            svg.push_str(&format!(
+                "<path d=\"M{:.1},{:.1} L{:.1},{:.1} A{:.1},{:.1} 0 {} 1 {:.1},{:.1} Z\" fill=\"{}\"/>",
+                cx, cy, x1, y1, r, r, large, x2, y2, CHART_PALETTE[i % CHART_PALETTE.len()]
+            ));
+        }
+        svg.push_str("</svg>");
+        return svg;
+    }
+    
+    let all = values.iter().flatten();
+    let min = all.clone().fold(0.0_f64, |a, b| a.min(*b));
+    let max = all.fold(0.0_f64, |a, b| a.max(*b));
+    let span = (max - min).max(f64::EPSILON);
+    let count = rows.len().max(1);
+    let x_at = |i: usize| (i as f64 + 0.5) * width / count as f64;
+    let y_at = |v: f64| height - (v - min) / span * height;
+    
+    for (s, (series, points)) in draft.series.iter().zip(&values).enumerate() {
+        match draft.chart_type {
+            ChartType::Line | ChartType::Area => {
+                let path: Vec<String> = points.iter().enumerate().map(|(i, v)| format!("{:.1},{:.1}", x_at(i), y_at(*v))).collect();
+                if draft.chart_type == ChartType::Area {
+                    svg.push_str(&format!(
+                        "<polygon points=\"{:.1},{:.1} {} {:.1},{:.1}\" fill=\"{}\" fill-opacity=\"0.3\"/>",
+                        x_at(0), y_at(0.0), path.join(" "), x_at(points.len().saturating_sub(1)), y_at(0.0), series.color
+                    ));
+                }
+                svg.push_str(&format!("<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>", path.join(" "), series.color));
+            }
+            ChartType::Scatter => {
+                for (i, v) in points.iter().enumerate() {
+                    svg.push_str(&format!("<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"/>", x_at(i), y_at(*v), series.color));
+                }
+            }
+            _ => {
+                let slot = width / count as f64;
+                let bar = slot * 0.8 / draft.series.len().max(1) as f64;
+                for (i, v) in points.iter().enumerate() {
+                    let (top, bottom) = (y_at(v.max(0.0)), y_at(v.min(0.0)));
+                    svg.push_str(&format!(
+                        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+                        i as f64 * slot + slot * 0.1 + s as f64 * bar, top, bar, bottom - top, series.color
+                    ));
+                }
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+// Script modules: dependencies name other modules in DocumentState.scripts
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScriptBundle {
+    // Dependencies before their dependents
+    pub order: Vec<String>,
+    // JavaScript and TypeScript modules in order; WASM modules are loaded separately
+    pub content: String,
+    // Exports kept per module
+    pub exports: HashMap<String, Vec<String>>,
+    // Exports no dependent uses
+    pub pruned_exports: HashMap<String, Vec<String>>,
+}
+
+// (module, dependency) pairs naming modules that don't exist
+fn missing_script_dependencies(scripts: &HashMap<String, ScriptModule>) -> Vec<(String, String)> {
+    let mut names: Vec<&String> = scripts.keys().collect();
+    names.sort();
+    names.into_iter()
+        .flat_map(|name| scripts[name].dependencies.iter()
+            .filter(|dependency| !scripts.contains_key(*dependency))
+            .map(move |dependency| (name.clone(), dependency.clone())))
+        .collect()
+}
+
+// Modules with dependencies first; Err holds a cycle as the path that closes it
+fn script_order(scripts: &HashMap<String, ScriptModule>) -> Result<Vec<String>, Vec<String>> {
+    fn visit<'a>(
+        name: &'a str,
+        scripts: &'a HashMap<String, ScriptModule>,
+        done: &mut HashSet<&'a str>,
+        path: &mut Vec<&'a str>,
+        order: &mut Vec<String>,
+    ) -> Result<(), Vec<String>> {
+        if done.contains(name) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|visiting| *visiting == name) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|n| n.to_string()).collect();
+            cycle.push(name.to_string());
+            return Err(cycle);
+        }
+        // Missing dependencies are reported separately
+        let Some(module) = scripts.get(name) else {
+            return Ok(());
+        };
+        path.push(name);
+        for dependency in &module.dependencies {
+            visit(dependency, scripts, done, path, order)?;
+        }
+        path.pop();
+        done.insert(name);
+        order.push(name.to_string());
+        Ok(())
+    }
+    
+    let mut names: Vec<&String> = scripts.keys().collect();
+    names.sort();
+    let mut done = HashSet::new();
+    let mut order = Vec::new();
+    for name in names {
+        visit(name, scripts, &mut done, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+fn mentions_identifier(content: &str, identifier: &str) -> bool {
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    content.match_indices(identifier).any(|(index, _)| {
+        let before = content[..index].chars().next_back();
+        let after = content[index + identifier.len()..].chars().next();
+        !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char)
+    })
+}
+
+// Exports of each module that none of its dependents mention. Modules nothing depends on are
+// entry points, so all their exports stay
+fn unused_script_exports(scripts: &HashMap<String, ScriptModule>) -> HashMap<String, Vec<String>> {
+    let mut unused = HashMap::new();
+    for (name, module) in scripts {
+        let dependents: Vec<&ScriptModule> = scripts.values()
+            .filter(|other| other.dependencies.contains(name))
+            .collect();
+        if dependents.is_empty() {
+            continue;
+        }
+        let dead: Vec<String> = module.exports.iter()
+            .filter(|export| !dependents.iter().any(|dependent| mentions_identifier(&dependent.content, export)))
+            .cloned()
+            .collect();
+        if !dead.is_empty() {
+            unused.insert(name.clone(), dead);
+        }
+    }
+    unused
+}
+
+// Autosave journal: every edit as the resulting state of what it touched, replayable onto the last save
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind")]
+pub enum JournalEntry {
+    PutElement { element: EditableElement, timeline: Option<Timeline> },
+    RemoveElement { element_id: String },
+    PutCanvas { canvas: CanvasSettings },
+    PutAsset { asset: AssetReference },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JournalRecord {
+    pub sequence: u64,
+    pub timestamp: f64,
+    pub entry: JournalEntry,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Journal {
+    // Records not yet taken by the host
+    pub pending: Vec<JournalRecord>,
+    pub next_sequence: u64,
+}
+
+impl JournalEntry {
+    // Later entries with the same key supersede earlier ones
+    fn key(&self) -> String {
+        match self {
+            JournalEntry::PutElement { element, .. } => format!("element:{}", element.id),
+            JournalEntry::RemoveElement { element_id } => format!("element:{}", element_id),
+            JournalEntry::PutCanvas { .. } => "canvas".to_string(),
+            JournalEntry::PutAsset { asset } => format!("asset:{}", asset.id),
+        }
+    }
+}
+
+// Keeps the last record per element, asset and canvas, in sequence order
+pub fn compact_records(records: Vec<JournalRecord>) -> Vec<JournalRecord> {
+    let mut latest: HashMap<String, JournalRecord> = HashMap::new();
+    for record in records {
+        let key = record.entry.key();
+        if latest.get(&key).map_or(true, |existing| existing.sequence <= record.sequence) {
+            latest.insert(key, record);
+        }
+    }
+    let mut records: Vec<JournalRecord> = latest.into_values().collect();
+    records.sort_by_key(|record| record.sequence);
+    records
+}
+
+// The journal blob is one JSON record per line, so the host can append deltas as they come.
+// Unreadable lines, such as one cut short by a crash, are skipped and counted
+pub fn parse_journal(blob: &str) -> (Vec<JournalRecord>, usize) {
+    let mut records = Vec::new();
+    let mut skipped = 0;
+    for line in blob.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(_) => skipped += 1,
+        }
+    }
+    (records, skipped)
+}
+
+fn journal_blob(records: &[JournalRecord]) -> String {
+    records.iter()
+        .filter_map(|record| serde_json::to_string(record).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+// Multi-document workspace
+
+pub const CLIPBOARD_FORMAT: &str = "application/x-liv-fragment+json";
+
+// Elements copied with their descendants, the assets they use and their timelines;
+// also what the host puts on the system clipboard
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClipboardFragment {
+    pub format: String,
+    pub source_document: String,
+    // Copied roots and their descendants, parents before children
+    pub elements: Vec<EditableElement>,
+    pub assets: Vec<AssetReference>,
+    pub timelines: Vec<Timeline>,
+}
+
+// Open documents and the asset library they share. The active document's state lives in
+// EditorEngine.state; the others wait here with their own selection and history
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Workspace {
+    pub active_document: String,
+    // Open documents in tab order, including the active one
+    pub order: Vec<String>,
+    pub documents: HashMap<String, EditorState>,
+    pub assets: HashMap<String, AssetReference>,
+    pub clipboard: Option<ClipboardFragment>,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self {
+            active_document: "document_1".to_string(),
+            order: vec!["document_1".to_string()],
+            documents: HashMap::new(),
+            assets: HashMap::new(),
+            clipboard: None,
+        }
+    }
+
+    fn next_document_id(&self) -> String {
+        let mut n = self.order.len() + 1;
+        while self.order.contains(&format!("document_{}", n)) {
+            n += 1;
+        }
+        format!("document_{}", n)
+    }
+}
+
 // Default implementations
 impl Default for EditorState {
     fn default() -> Self {
@@ -324,18 +1177,31 @@ impl Default for EditorState {
             history: EditHistory::default(),
             validation_state: ValidationState::default(),
             preview_mode: PreviewMode::Design,
+            playback: PlaybackState::default(),
+            journal: Journal::default(),
+            chart_drafts: HashMap::new(),
+            lint_profile: LintProfile::recommended(),
         }
     }
 }
 
-impl Default for DocumentState {
+impl Default for CanvasSettings {
     fn default() -> Self {
         Self {
-            elements: Vec::new(),
-            styles: HashMap::new(),
-            scripts: HashMap::new(),
-            assets: HashMap::new(),
-            metadata: DocumentMetadata::default(),
+            grid: GridSettings {
+                size: 40.0,
+                subdivisions: 4,
+                visible: false,
+                snap: false,
+            },
+            guides: Vec::new(),
+            rulers: RulerSettings {
+                visible: true,
+                unit: RulerUnit::Pixels,
+                origin: Position { x: 0.0, y: 0.0 },
+            },
+            snap_to_guides: true,
+            snap_threshold: 6.0,
         }
     }
 }
@@ -393,22 +1259,39 @@ extern "C" {
     fn log(s: &str);
 }
 
-// Global editor state
+// Global editor state: the active document, and the workspace around it
 static mut EDITOR_STATE: Option<EditorState> = None;
+static mut EDITOR_WORKSPACE: Option<Workspace> = None;
 
 // Core Editor Engine Implementation
 pub struct EditorEngine {
     state: EditorState,
+    workspace: Workspace,
+}
+
+impl Default for EditorEngine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl EditorEngine {
     pub fn new() -> Self {
         Self {
             state: EditorState::default(),
+            workspace: Workspace::new(),
         }
     }
 
-    pub fn load_document(&mut self, document_json: &str) -> EditorResult {
+    // For operations on the active document that don't touch the workspace
+    pub fn with_state(state: EditorState) -> Self {
+        Self {
+            state,
+            workspace: Workspace::new(),
+        }
+    }
+
+    pub fn load_document(&mut self, document_json: &str) -> EditorResult {
         match serde_json::from_str::<serde_json::Value>(document_json) {
             Ok(doc_value) => {
                 // Convert LIV document to editor document state
@@ -457,6 +1340,10 @@ impl EditorEngine {
     }
 
     pub fn create_element(&mut self, element_type: ElementType, properties: HashMap<String, serde_json::Value>) -> EditorResult {
+        self.create_element_with_bounds(element_type, properties, BoundingBox { x: 0.0, y: 0.0, width: 100.0, height: 100.0 })
+    }
+
+    fn create_element_with_bounds(&mut self, element_type: ElementType, properties: HashMap<String, serde_json::Value>, bounds: BoundingBox) -> EditorResult {
         let element_id = format!("element_{}", self.state.document.elements.len());
         
         let element = EditableElement {
@@ -467,7 +1354,7 @@ impl EditorEngine {
             parent: None,
             locked: false,
             visible: true,
-            bounds: BoundingBox { x: 0.0, y: 0.0, width: 100.0, height: 100.0 },
+            bounds,
         };
 
         self.state.document.elements.push(element);
@@ -475,7 +1362,8 @@ impl EditorEngine {
         // Add to history
         self.add_to_history(OperationType::Create, serde_json::json!({
             "element_id": element_id,
-            "element": self.state.document.elements.last().unwrap()
+            "element": self.state.document.elements.last().unwrap(),
+            "position": self.state.document.elements.len() - 1
         }));
 
         EditorResult {
@@ -495,10 +1383,11 @@ impl EditorEngine {
             }
 
             // Add to history
+            let new_properties = element.properties.clone();
             self.add_to_history(OperationType::Update, serde_json::json!({
                 "element_id": element_id,
                 "old_properties": old_properties,
-                "new_properties": element.properties
+                "new_properties": new_properties
             }));
 
             EditorResult {
@@ -520,12 +1409,14 @@ impl EditorEngine {
     pub fn delete_element(&mut self, element_id: &str) -> EditorResult {
         if let Some(pos) = self.state.document.elements.iter().position(|e| e.id == element_id) {
             let element = self.state.document.elements.remove(pos);
+            let timeline = self.state.document.timelines.remove(element_id);
             
             // Add to history
             self.add_to_history(OperationType::Delete, serde_json::json!({
                 "element_id": element_id,
                 "element": element,
-                "position": pos
+                "position": pos,
+                "timeline": timeline
             }));
 
             EditorResult {
@@ -568,15 +1459,15 @@ impl EditorEngine {
     pub fn undo(&mut self) -> EditorResult {
         if self.state.history.current_index > 0 {
             self.state.history.current_index -= 1;
-            let operation = &self.state.history.operations[self.state.history.current_index];
+            let operation = self.state.history.operations[self.state.history.current_index].clone();
             
             // Apply inverse operation
-            self.apply_inverse_operation(operation);
+            self.apply_inverse_operation(&operation);
             
             EditorResult {
                 success: true,
                 message: Some("Undo successful".to_string()),
-                data: None,
+                data: Some(serde_json::json!({"element_ids": affected_element_ids(&operation.data)})),
                 errors: Vec::new(),
             }
         } else {
@@ -591,16 +1482,16 @@ impl EditorEngine {
 
     pub fn redo(&mut self) -> EditorResult {
         if self.state.history.current_index < self.state.history.operations.len() {
-            let operation = &self.state.history.operations[self.state.history.current_index];
+            let operation = self.state.history.operations[self.state.history.current_index].clone();
             
             // Apply operation
-            self.apply_operation(operation);
+            self.apply_operation(&operation);
             self.state.history.current_index += 1;
             
             EditorResult {
                 success: true,
                 message: Some("Redo successful".to_string()),
-                data: None,
+                data: Some(serde_json::json!({"element_ids": affected_element_ids(&operation.data)})),
                 errors: Vec::new(),
             }
         } else {
@@ -621,39 +1512,86 @@ impl EditorEngine {
         for element in &self.state.document.elements {
             // Check for required properties
             match element.element_type {
-                ElementType::Text => {
-                    if !element.properties.contains_key("content") {
-                        errors.push(ValidationError {
-                            element_id: Some(element.id.clone()),
-                            error_type: ErrorType::Semantic,
-                            message: "Text element missing content property".to_string(),
-                            line: None,
-                            column: None,
-                        });
-                    }
+                ElementType::Text if !element.properties.contains_key("content") => {
+                    errors.push(ValidationError {
+                        element_id: Some(element.id.clone()),
+                        error_type: ErrorType::Semantic,
+                        message: "Text element missing content property".to_string(),
+                        line: None,
+                        column: None,
+                    });
                 }
-                ElementType::Image => {
-                    if !element.properties.contains_key("src") {
-                        errors.push(ValidationError {
-                            element_id: Some(element.id.clone()),
-                            error_type: ErrorType::Semantic,
-                            message: "Image element missing src property".to_string(),
-                            line: None,
-                            column: None,
-                        });
-                    }
+                ElementType::Image if !element.properties.contains_key("src") => {
+                    errors.push(ValidationError {
+                        element_id: Some(element.id.clone()),
+                        error_type: ErrorType::Semantic,
+                        message: "Image element missing src property".to_string(),
+                        line: None,
+                        column: None,
+                    });
                 }
                 _ => {}
             }
+        }
+
+        // Script modules
+        let scripts = &self.state.document.scripts;
+        for (module, dependency) in missing_script_dependencies(scripts) {
+            errors.push(ValidationError {
+                element_id: None,
+                error_type: ErrorType::Semantic,
+                message: format!("Script module {} depends on missing module {}", module, dependency),
+                line: None,
+                column: None,
+            });
+        }
+        if let Err(cycle) = script_order(scripts) {
+            errors.push(ValidationError {
+                element_id: None,
+                error_type: ErrorType::Semantic,
+                message: format!("Script modules depend on each other in a cycle: {}", cycle.join(" -> ")),
+                line: None,
+                column: None,
+            });
+        }
+        let mut unused: Vec<(String, Vec<String>)> = unused_script_exports(scripts).into_iter().collect();
+        unused.sort();
+        for (module, exports) in unused {
+            warnings.push(ValidationWarning {
+                element_id: None,
+                warning_type: WarningType::BestPractice,
+                message: format!("Script module {} exports {} but no dependent uses them", module, exports.join(", ")),
+                suggestion: Some("Remove the unused exports".to_string()),
+            });
+        }
 
-            // Check accessibility
-            if element.element_type == ElementType::Image && !element.properties.contains_key("alt") {
-                warnings.push(ValidationWarning {
-                    element_id: Some(element.id.clone()),
-                    warning_type: WarningType::Accessibility,
-                    message: "Image element missing alt text".to_string(),
-                    suggestion: Some("Add alt text for accessibility".to_string()),
-                });
+        let lint = self.lint_document(&self.state.lint_profile);
+        for finding in &lint {
+            let message = format!("{} ({})", finding.message, finding.rule_id);
+            match finding.severity {
+                LintSeverity::Error => errors.push(ValidationError {
+                    element_id: finding.element_id.clone(),
+                    error_type: match finding.category {
+                        LintCategory::Performance => ErrorType::Performance,
+                        LintCategory::Accessibility => ErrorType::Accessibility,
+                        LintCategory::Security => ErrorType::Security,
+                        LintCategory::Style => ErrorType::Semantic,
+                    },
+                    message,
+                    line: None,
+                    column: None,
+                }),
+                _ => warnings.push(ValidationWarning {
+                    element_id: finding.element_id.clone(),
+                    warning_type: match finding.category {
+                        LintCategory::Performance => WarningType::Performance,
+                        LintCategory::Accessibility => WarningType::Accessibility,
+                        LintCategory::Security => WarningType::Security,
+                        LintCategory::Style => WarningType::BestPractice,
+                    },
+                    message,
+                    suggestion: finding.fix.as_ref().map(|fix| fix.description.clone()),
+                }),
             }
         }
 
@@ -664,15 +1602,61 @@ impl EditorEngine {
             errors: errors.clone(),
             warnings: warnings.clone(),
             is_valid,
-            last_validated: js_sys::Date::now(),
+            last_validated: now_ms(),
         };
 
         ValidationReport {
             is_valid,
             errors,
+            accessibility_score: if warnings.is_empty() { 100.0 } else { 75.0 },
             warnings,
             performance_score: 85.0, // Placeholder
-            accessibility_score: if warnings.is_empty() { 100.0 } else { 75.0 },
+            lint,
+        }
+    }
+
+    // Findings under `profile`; the runtime warns about the same ones when the document loads
+    pub fn lint_document(&self, profile: &LintProfile) -> Vec<LintFinding> {
+        let document = &self.state.document;
+        let data_sizes = document.data_sources.iter()
+            .map(|(source_id, data)| (source_id.as_str(), serde_json::to_vec(data).map_or(0, |bytes| bytes.len())))
+            .collect();
+        // Tokens are custom properties declared in the document's style rules
+        let tokens = document.styles.values()
+            .flat_map(|rule| rule.properties.iter())
+            .filter(|(name, _)| name.starts_with("--"))
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        let lint_document = LintDocument {
+            elements: document.elements.iter()
+                .map(|element| LintElement {
+                    id: &element.id,
+                    element_type: format!("{:?}", element.element_type),
+                    properties: &element.properties,
+                    children: &element.children,
+                })
+                .collect(),
+            data_sizes,
+            tokens,
+        };
+        lint::lint(&lint_document, profile)
+    }
+
+    pub fn set_lint_profile(&mut self, profile: LintProfile) -> EditorResult {
+        if let Err(reason) = profile.validate() {
+            return EditorResult {
+                success: false,
+                message: Some(format!("Invalid lint profile: {}", reason)),
+                data: None,
+                errors: vec![reason],
+            };
+        }
+        self.state.lint_profile = profile;
+        EditorResult {
+            success: true,
+            message: Some("Lint profile updated".to_string()),
+            data: None,
+            errors: Vec::new(),
         }
     }
 
@@ -715,222 +1699,1486 @@ impl EditorEngine {
         }
     }
 
-    // Helper methods
-    fn convert_liv_to_editor_document(&self, doc_value: &serde_json::Value) -> DocumentState {
-        let mut document_state = DocumentState::default();
-        
-        // Extract metadata
-        if let Some(metadata) = doc_value.get("metadata") {
-            if let Some(title) = metadata.get("title").and_then(|v| v.as_str()) {
-                document_state.metadata.title = title.to_string();
-            }
-            if let Some(author) = metadata.get("author").and_then(|v| v.as_str()) {
-                document_state.metadata.author = author.to_string();
-            }
+    pub fn set_keyframe(&mut self, element_id: &str, time: f64, properties: HashMap<String, serde_json::Value>) -> EditorResult {
+        if !self.state.document.elements.iter().any(|e| e.id == element_id) {
+            return EditorResult {
+                success: false,
+                message: Some("Element not found".to_string()),
+                data: None,
+                errors: vec!["Element not found".to_string()],
+            };
         }
-
-        // Parse HTML content into elements (simplified)
-        if let Some(content) = doc_value.get("content") {
-            if let Some(html) = content.get("html").and_then(|v| v.as_str()) {
-                // Simple HTML parsing - in a real implementation, this would be more sophisticated
-                let element = EditableElement {
-                    id: "root".to_string(),
-                    element_type: ElementType::Container,
-                    properties: [("innerHTML".to_string(), serde_json::Value::String(html.to_string()))].into_iter().collect(),
-                    children: Vec::new(),
-                    parent: None,
-                    locked: false,
-                    visible: true,
-                    bounds: BoundingBox { x: 0.0, y: 0.0, width: 800.0, height: 600.0 },
-                };
-                document_state.elements.push(element);
-            }
+        if !time.is_finite() || time < 0.0 {
+            return EditorResult {
+                success: false,
+                message: Some("Keyframe time must be a non-negative number of milliseconds".to_string()),
+                data: None,
+                errors: vec!["Invalid keyframe time".to_string()],
+            };
+        }
+        
+        let old_timeline = self.state.document.timelines.get(element_id).cloned();
+        let timeline = self.state.document.timelines.entry(element_id.to_string())
+            .or_insert_with(|| Timeline::new(element_id));
+        timeline.set_keyframe(time, properties);
+        let timeline = serde_json::json!(timeline);
+        self.add_to_history(OperationType::Update, serde_json::json!({
+            "element_id": element_id,
+            "old_timeline": old_timeline,
+            "new_timeline": timeline
+        }));
+        
+        EditorResult {
+            success: true,
+            message: Some("Keyframe set".to_string()),
+            data: Some(timeline),
+            errors: Vec::new(),
         }
-
-        document_state
     }
 
-    fn convert_editor_to_liv_document(&self) -> serde_json::Value {
-        let mut content_html = String::new();
+    pub fn remove_keyframe(&mut self, element_id: &str, time: f64) -> EditorResult {
+        let old_timeline = self.state.document.timelines.get(element_id).cloned();
+        let removed = self.state.document.timelines.get_mut(element_id)
+            .is_some_and(|timeline| timeline.remove_keyframe(time));
+        if !removed {
+            return EditorResult {
+                success: false,
+                message: Some("Keyframe not found".to_string()),
+                data: None,
+                errors: vec!["Keyframe not found".to_string()],
+            };
+        }
         
-        // Convert elements back to HTML (simplified)
-        for element in &self.state.document.elements {
-            if let Some(html) = element.properties.get("innerHTML").and_then(|v| v.as_str()) {
-                content_html.push_str(html);
-            }
+        let timeline = serde_json::json!(self.state.document.timelines[element_id]);
+        self.add_to_history(OperationType::Update, serde_json::json!({
+            "element_id": element_id,
+            "old_timeline": old_timeline,
+            "new_timeline": timeline
+        }));
+        EditorResult {
+            success: true,
+            message: Some("Keyframe removed".to_string()),
+            data: Some(timeline),
+            errors: Vec::new(),
         }
+    }
 
-        serde_json::json!({
-            "metadata": {
-                "title": self.state.document.metadata.title,
-                "author": self.state.document.metadata.author,
-                "description": self.state.document.metadata.description,
-                "version": self.state.document.metadata.version,
-                "created": self.state.document.metadata.created,
-                "modified": js_sys::Date::new_0().to_iso_string()
-            },
-            "content": {
-                "html": content_html,
-                "css": "",
-                "interactiveSpec": "",
-                "staticFallback": content_html
+    pub fn set_timeline_easing(&mut self, element_id: &str, easing: EasingFunction) -> EditorResult {
+        if let Some(timeline) = self.state.document.timelines.get_mut(element_id) {
+            timeline.easing = easing;
+            self.journal_element(element_id);
+            EditorResult {
+                success: true,
+                message: Some("Easing updated".to_string()),
+                data: None,
+                errors: Vec::new(),
             }
-        })
+        } else {
+            EditorResult {
+                success: false,
+                message: Some("Timeline not found".to_string()),
+                data: None,
+                errors: vec!["Timeline not found".to_string()],
+            }
+        }
     }
 
-    fn add_to_history(&mut self, operation_type: OperationType, data: serde_json::Value) {
-        let operation = EditOperation {
-            id: format!("op_{}", self.state.history.operations.len()),
-            operation_type,
-            timestamp: js_sys::Date::now(),
-            data: data.clone(),
-            inverse_data: data, // Simplified - should be actual inverse
-        };
-
-        // Remove operations after current index (for redo)
-        self.state.history.operations.truncate(self.state.history.current_index);
+    // Every timeline in the interactive engine's Animation format
+    pub fn export_animations(&self) -> EditorResult {
+        let mut timelines: Vec<&Timeline> = self.state.document.timelines.values().collect();
+        timelines.sort_by(|a, b| a.element_id.cmp(&b.element_id));
+        let animations: Vec<serde_json::Value> = timelines.iter()
+            .map(|timeline| timeline.to_runtime_animation())
+            .collect();
         
-        // Add new operation
-        self.state.history.operations.push(operation);
-        self.state.history.current_index = self.state.history.operations.len();
-
-        // Limit history size
-        if self.state.history.operations.len() > self.state.history.max_operations {
-            self.state.history.operations.remove(0);
-            self.state.history.current_index -= 1;
+        EditorResult {
+            success: true,
+            message: None,
+            data: Some(serde_json::Value::Array(animations)),
+            errors: Vec::new(),
         }
     }
 
-    fn apply_operation(&mut self, _operation: &EditOperation) {
-        // Implementation would apply the operation
+    pub fn play_preview(&mut self) {
+        self.state.playback.playing = true;
+        self.state.playback.last_tick = None;
     }
 
-    fn apply_inverse_operation(&mut self, _operation: &EditOperation) {
-        // Implementation would apply the inverse operation
+    pub fn pause_preview(&mut self) {
+        self.state.playback.playing = false;
     }
-}
 
-// WASM bindings
-#[wasm_bindgen]
-pub fn init_editor_engine() {
-    log("LIV Editor Engine initialized");
-    unsafe {
-        EDITOR_STATE = Some(EditorState::default());
+    pub fn seek_preview(&mut self, position: f64) -> RenderUpdate {
+        self.state.playback.position = position.max(0.0);
+        self.preview_update()
     }
-}
 
-#[wasm_bindgen]
-pub fn load_document(document_json: &str) -> String {
-    let mut engine = EditorEngine::new();
-    let result = engine.load_document(document_json);
-    
-    unsafe {
-        EDITOR_STATE = Some(engine.state);
+    // Advances a playing preview to the host's frame timestamp; the preview loops over the longest timeline
+    pub fn tick_preview(&mut self, timestamp: f64) -> RenderUpdate {
+        let playback = &mut self.state.playback;
+        if playback.playing {
+            let elapsed = playback.last_tick.map_or(0.0, |last| (timestamp - last).max(0.0));
+            playback.last_tick = Some(timestamp);
+            let length = self.state.document.timelines.values().map(|t| t.duration).fold(0.0, f64::max);
+            playback.position = if length > 0.0 { (playback.position + elapsed) % length } else { 0.0 };
+        }
+        self.preview_update()
     }
-    
-    serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
-}
 
-#[wasm_bindgen]
-pub fn save_document() -> String {
-    unsafe {
-        if let Some(ref state) = EDITOR_STATE {
-            let engine = EditorEngine { state: state.clone() };
-            let result = engine.save_document();
-            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
-        } else {
-            serde_json::to_string(&EditorResult {
-                success: false,
-                message: Some("Editor not initialized".to_string()),
-                data: None,
-                errors: vec!["Editor not initialized".to_string()],
-            }).unwrap_or_else(|_| "{}".to_string())
+    fn preview_update(&self) -> RenderUpdate {
+        let mut timelines: Vec<&Timeline> = self.state.document.timelines.values().collect();
+        timelines.sort_by(|a, b| a.element_id.cmp(&b.element_id));
+        let position = self.state.playback.position;
+        
+        RenderUpdate {
+            dom_operations: Vec::new(),
+            style_changes: timelines.iter()
+                .flat_map(|timeline| timeline.style_changes_at(position.min(timeline.duration)))
+                .collect(),
+            script_updates: Vec::new(),
+            asset_updates: Vec::new(),
         }
     }
-}
 
-#[wasm_bindgen]
-pub fn create_element(element_type: &str, properties_json: &str) -> String {
-    unsafe {
-        if let Some(ref mut state) = EDITOR_STATE {
-            let mut engine = EditorEngine { state: state.clone() };
-            
-            let element_type = match element_type {
-                "text" => ElementType::Text,
-                "image" => ElementType::Image,
-                "chart" => ElementType::Chart,
-                "container" => ElementType::Container,
-                _ => ElementType::Container,
+    pub fn set_grid(&mut self, grid: GridSettings) -> EditorResult {
+        if !grid.size.is_finite() || grid.size <= 0.0 {
+            return EditorResult {
+                success: false,
+                message: Some("Grid size must be positive".to_string()),
+                data: None,
+                errors: vec!["Invalid grid size".to_string()],
             };
-
-            let properties: HashMap<String, serde_json::Value> = 
-                serde_json::from_str(properties_json).unwrap_or_default();
-
-            let result = engine.create_element(element_type, properties);
-            *state = engine.state;
-            
-            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
-        } else {
-            "{}".to_string()
+        }
+        self.state.document.canvas.grid = grid;
+        self.journal_canvas();
+        EditorResult {
+            success: true,
+            message: Some("Grid updated".to_string()),
+            data: None,
+            errors: Vec::new(),
         }
     }
-}
-
-#[wasm_bindgen]
-pub fn update_element(element_id: &str, properties_json: &str) -> String {
-    unsafe {
-        if let Some(ref mut state) = EDITOR_STATE {
-            let mut engine = EditorEngine { state: state.clone() };
-            
-            let properties: HashMap<String, serde_json::Value> = 
-                serde_json::from_str(properties_json).unwrap_or_default();
 
-            let result = engine.update_element(element_id, properties);
-            *state = engine.state;
-            
-            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
-        } else {
-            "{}".to_string()
+    pub fn set_rulers(&mut self, rulers: RulerSettings) -> EditorResult {
+        self.state.document.canvas.rulers = rulers;
+        self.journal_canvas();
+        EditorResult {
+            success: true,
+            message: Some("Rulers updated".to_string()),
+            data: None,
+            errors: Vec::new(),
         }
     }
-}
 
-#[wasm_bindgen]
-pub fn delete_element(element_id: &str) -> String {
-    unsafe {
-        if let Some(ref mut state) = EDITOR_STATE {
-            let mut engine = EditorEngine { state: state.clone() };
-            let result = engine.delete_element(element_id);
-            *state = engine.state;
-            
-            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
-        } else {
-            "{}".to_string()
+    pub fn add_guide(&mut self, orientation: GuideOrientation, position: f64) -> EditorResult {
+        let guides = &mut self.state.document.canvas.guides;
+        let mut n = guides.len() + 1;
+        while guides.iter().any(|guide| guide.id == format!("guide_{}", n)) {
+            n += 1;
+        }
+        let guide_id = format!("guide_{}", n);
+        guides.push(Guide { id: guide_id.clone(), orientation, position });
+        self.journal_canvas();
+        
+        EditorResult {
+            success: true,
+            message: Some("Guide added".to_string()),
+            data: Some(serde_json::json!({"guide_id": guide_id})),
+            errors: Vec::new(),
         }
     }
-}
 
-#[wasm_bindgen]
-pub fn select_element(element_id: &str) -> String {
-    unsafe {
-        if let Some(ref mut state) = EDITOR_STATE {
-            let mut engine = EditorEngine { state: state.clone() };
-            let result = engine.select_element(element_id);
-            *state = engine.state;
-            
-            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    // Dragging a guide off the canvas removes it; the host calls remove_guide for that
+    pub fn move_guide(&mut self, guide_id: &str, position: f64) -> EditorResult {
+        if let Some(guide) = self.state.document.canvas.guides.iter_mut().find(|g| g.id == guide_id) {
+            guide.position = position;
+            self.journal_canvas();
+            EditorResult {
+                success: true,
+                message: Some("Guide moved".to_string()),
+                data: None,
+                errors: Vec::new(),
+            }
         } else {
-            "{}".to_string()
+            EditorResult {
+                success: false,
+                message: Some("Guide not found".to_string()),
+                data: None,
+                errors: vec!["Guide not found".to_string()],
+            }
         }
     }
-}
 
-#[wasm_bindgen]
-pub fn undo() -> String {
-    unsafe {
+    pub fn remove_guide(&mut self, guide_id: &str) -> EditorResult {
+        let guides = &mut self.state.document.canvas.guides;
+        let before = guides.len();
+        guides.retain(|g| g.id != guide_id);
+        if guides.len() == before {
+            return EditorResult {
+                success: false,
+                message: Some("Guide not found".to_string()),
+                data: None,
+                errors: vec!["Guide not found".to_string()],
+            };
+        }
+        self.journal_canvas();
+        EditorResult {
+            success: true,
+            message: Some("Guide removed".to_string()),
+            data: None,
+            errors: Vec::new(),
+        }
+    }
+
+    // Applies a handle drag of (dx, dy) from the element's current bounds, snapping to guides and grid
+    pub fn transform_element(&mut self, element_id: &str, handle: TransformHandle, dx: f64, dy: f64) -> EditorResult {
+        let canvas = &self.state.document.canvas;
+        let Some(element) = self.state.document.elements.iter_mut().find(|e| e.id == element_id) else {
+            return EditorResult {
+                success: false,
+                message: Some("Element not found".to_string()),
+                data: None,
+                errors: vec!["Element not found".to_string()],
+            };
+        };
+        if element.locked {
+            return EditorResult {
+                success: false,
+                message: Some("Element is locked".to_string()),
+                data: None,
+                errors: vec!["Element is locked".to_string()],
+            };
+        }
+        
+        let old_bounds = element.bounds.clone();
+        element.bounds = transform_bounds(canvas, &old_bounds, &handle, dx, dy);
+        let new_bounds = element.bounds.clone();
+        self.add_to_history(OperationType::Transform, serde_json::json!({
+            "element_id": element_id,
+            "old_bounds": old_bounds,
+            "new_bounds": new_bounds
+        }));
+        
+        EditorResult {
+            success: true,
+            message: Some("Element transformed".to_string()),
+            data: Some(serde_json::json!({"bounds": new_bounds})),
+            errors: Vec::new(),
+        }
+    }
+
+    // Opens a document next to the current ones and makes it active
+    pub fn open_document(&mut self, document_json: &str) -> EditorResult {
+        let previous = std::mem::take(&mut self.state);
+        let result = self.load_document(document_json);
+        if !result.success {
+            self.state = previous;
+            return result;
+        }
+        
+        let document_id = self.workspace.next_document_id();
+        let previous_id = std::mem::replace(&mut self.workspace.active_document, document_id.clone());
+        self.workspace.documents.insert(previous_id, previous);
+        self.workspace.order.push(document_id.clone());
+        for asset in self.state.document.assets.values() {
+            self.workspace.assets.entry(asset.id.clone()).or_insert_with(|| asset.clone());
+        }
+        
+        EditorResult {
+            success: true,
+            message: Some("Document opened".to_string()),
+            data: Some(serde_json::json!({"document_id": document_id})),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn switch_document(&mut self, document_id: &str) -> EditorResult {
+        if document_id != self.workspace.active_document {
+            let Some(state) = self.workspace.documents.remove(document_id) else {
+                return EditorResult {
+                    success: false,
+                    message: Some("Document not open".to_string()),
+                    data: None,
+                    errors: vec!["Document not open".to_string()],
+                };
+            };
+            let previous = std::mem::replace(&mut self.state, state);
+            let previous_id = std::mem::replace(&mut self.workspace.active_document, document_id.to_string());
+            self.workspace.documents.insert(previous_id, previous);
+        }
+        
+        EditorResult {
+            success: true,
+            message: Some("Document switched".to_string()),
+            data: Some(serde_json::json!({"document_id": document_id})),
+            errors: Vec::new(),
+        }
+    }
+
+    // Closing the active document activates its neighbour; closing the last one leaves an empty document
+    pub fn close_document(&mut self, document_id: &str) -> EditorResult {
+        let Some(index) = self.workspace.order.iter().position(|id| id == document_id) else {
+            return EditorResult {
+                success: false,
+                message: Some("Document not open".to_string()),
+                data: None,
+                errors: vec!["Document not open".to_string()],
+            };
+        };
+        
+        if document_id == self.workspace.active_document {
+            if self.workspace.order.len() == 1 {
+                self.state = EditorState::default();
+                self.workspace.order[0] = self.workspace.next_document_id();
+                self.workspace.active_document = self.workspace.order[0].clone();
+            } else {
+                let neighbour = self.workspace.order[if index + 1 < self.workspace.order.len() { index + 1 } else { index - 1 }].clone();
+                self.switch_document(&neighbour);
+                self.workspace.documents.remove(document_id);
+                self.workspace.order.remove(index);
+            }
+        } else {
+            self.workspace.documents.remove(document_id);
+            self.workspace.order.remove(index);
+        }
+        
+        EditorResult {
+            success: true,
+            message: Some("Document closed".to_string()),
+            data: Some(serde_json::json!({"active_document": self.workspace.active_document})),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn list_documents(&self) -> EditorResult {
+        let documents: Vec<serde_json::Value> = self.workspace.order.iter()
+            .map(|id| {
+                let title = if *id == self.workspace.active_document {
+                    &self.state.document.metadata.title
+                } else {
+                    &self.workspace.documents[id].document.metadata.title
+                };
+                serde_json::json!({"document_id": id, "title": title, "active": *id == self.workspace.active_document})
+            })
+            .collect();
+        
+        EditorResult {
+            success: true,
+            message: None,
+            data: Some(serde_json::Value::Array(documents)),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn add_library_asset(&mut self, asset: AssetReference) -> EditorResult {
+        self.workspace.assets.insert(asset.id.clone(), asset);
+        EditorResult {
+            success: true,
+            message: Some("Asset added to library".to_string()),
+            data: None,
+            errors: Vec::new(),
+        }
+    }
+
+    // Makes a library asset available to the active document
+    pub fn use_library_asset(&mut self, asset_id: &str) -> EditorResult {
+        if let Some(asset) = self.workspace.assets.get(asset_id).cloned() {
+            self.state.document.assets.insert(asset.id.clone(), asset.clone());
+            self.journal(JournalEntry::PutAsset { asset });
+            EditorResult {
+                success: true,
+                message: Some("Asset added to document".to_string()),
+                data: None,
+                errors: Vec::new(),
+            }
+        } else {
+            EditorResult {
+                success: false,
+                message: Some("Asset not found".to_string()),
+                data: None,
+                errors: vec!["Asset not found".to_string()],
+            }
+        }
+    }
+
+    // Copies the elements with their descendants; the fragment is also returned for the system clipboard
+    pub fn copy_elements(&mut self, element_ids: &[String]) -> EditorResult {
+        let document = &self.state.document;
+        let mut elements: Vec<EditableElement> = Vec::new();
+        let mut pending: Vec<String> = element_ids.to_vec();
+        while !pending.is_empty() {
+            let id = pending.remove(0);
+            if elements.iter().any(|e| e.id == id) {
+                continue;
+            }
+            let Some(element) = document.elements.iter().find(|e| e.id == id) else {
+                return EditorResult {
+                    success: false,
+                    message: Some(format!("Element {} not found", id)),
+                    data: None,
+                    errors: vec!["Element not found".to_string()],
+                };
+            };
+            pending.extend(element.children.iter().cloned());
+            elements.push(element.clone());
+        }
+        
+        // Assets are referenced by id from element properties
+        let assets = document.assets.values()
+            .filter(|asset| elements.iter().any(|e| e.properties.values().any(|v| v.as_str() == Some(asset.id.as_str()))))
+            .cloned()
+            .collect();
+        let timelines = elements.iter()
+            .filter_map(|e| document.timelines.get(&e.id).cloned())
+            .collect();
+        let fragment = ClipboardFragment {
+            format: CLIPBOARD_FORMAT.to_string(),
+            source_document: self.workspace.active_document.clone(),
+            elements,
+            assets,
+            timelines,
+        };
+        let data = serde_json::json!(fragment);
+        self.workspace.clipboard = Some(fragment);
+        
+        EditorResult {
+            success: true,
+            message: Some("Elements copied".to_string()),
+            data: Some(data),
+            errors: Vec::new(),
+        }
+    }
+
+    // Pastes a fragment (or the workspace clipboard) into the active document under fresh ids
+    pub fn paste_fragment(&mut self, fragment: Option<ClipboardFragment>) -> EditorResult {
+        let Some(fragment) = fragment.or_else(|| self.workspace.clipboard.clone()) else {
+            return EditorResult {
+                success: false,
+                message: Some("Clipboard is empty".to_string()),
+                data: None,
+                errors: vec!["Clipboard is empty".to_string()],
+            };
+        };
+        if fragment.format != CLIPBOARD_FORMAT {
+            return EditorResult {
+                success: false,
+                message: Some(format!("Unsupported clipboard format {}", fragment.format)),
+                data: None,
+                errors: vec!["Unsupported clipboard format".to_string()],
+            };
+        }
+        
+        let mut ids: HashMap<String, String> = HashMap::new();
+        let mut n = self.state.document.elements.len();
+        for element in &fragment.elements {
+            while self.state.document.elements.iter().any(|e| e.id == format!("element_{}", n))
+                || ids.values().any(|id| *id == format!("element_{}", n)) {
+                n += 1;
+            }
+            ids.insert(element.id.clone(), format!("element_{}", n));
+        }
+        // Pasting into the source document offsets the copies so they don't cover the originals
+        let offset = if fragment.source_document == self.workspace.active_document { 10.0 } else { 0.0 };
+        
+        for asset in fragment.assets {
+            self.workspace.assets.entry(asset.id.clone()).or_insert_with(|| asset.clone());
+            if !self.state.document.assets.contains_key(&asset.id) {
+                self.state.document.assets.insert(asset.id.clone(), asset.clone());
+                self.journal(JournalEntry::PutAsset { asset });
+            }
+        }
+        for mut timeline in fragment.timelines {
+            if let Some(id) = ids.get(&timeline.element_id) {
+                timeline.element_id = id.clone();
+                self.state.document.timelines.insert(id.clone(), timeline);
+            }
+        }
+        let mut pasted = Vec::new();
+        let mut creates = Vec::new();
+        for mut element in fragment.elements {
+            element.id = ids[&element.id].clone();
+            element.children = element.children.iter().filter_map(|child| ids.get(child).cloned()).collect();
+            element.parent = element.parent.as_ref().and_then(|parent| ids.get(parent).cloned());
+            if element.parent.is_none() {
+                element.bounds.x += offset;
+                element.bounds.y += offset;
+            }
+            pasted.push(element.id.clone());
+            creates.push(serde_json::json!({
+                "operation_type": OperationType::Create,
+                "data": {
+                    "element_id": element.id,
+                    "element": element,
+                    "position": self.state.document.elements.len(),
+                    "timeline": self.state.document.timelines.get(&element.id)
+                }
+            }));
+            self.state.document.elements.push(element);
+        }
+        
+        self.add_to_history(OperationType::Batch, serde_json::json!({
+            "element_ids": pasted,
+            "operations": creates
+        }));
+        self.state.selection.selected_elements = pasted.clone();
+        self.state.selection.selection_type = if pasted.len() == 1 { SelectionType::Single } else { SelectionType::Multiple };
+        
+        EditorResult {
+            success: true,
+            message: Some("Elements pasted".to_string()),
+            data: Some(serde_json::json!({"element_ids": pasted})),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn set_data_source(&mut self, data_source_id: &str, data: serde_json::Value) -> EditorResult {
+        self.state.document.data_sources.insert(data_source_id.to_string(), data);
+        EditorResult {
+            success: true,
+            message: Some("Data source updated".to_string()),
+            data: None,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn suggest_chart(&self, data_source_id: &str) -> EditorResult {
+        if let Some(data) = self.state.document.data_sources.get(data_source_id) {
+            let suggestion = suggest_chart(infer_columns(&sample_rows(data, CHART_SAMPLE_ROWS)));
+            EditorResult {
+                success: true,
+                message: None,
+                data: Some(serde_json::json!(suggestion)),
+                errors: Vec::new(),
+            }
+        } else {
+            EditorResult {
+                success: false,
+                message: Some("Data source not found".to_string()),
+                data: None,
+                errors: vec!["Data source not found".to_string()],
+            }
+        }
+    }
+
+    // Starts a draft from the suggested chart type and field mapping
+    pub fn create_chart_draft(&mut self, data_source_id: &str) -> EditorResult {
+        let Some(data) = self.state.document.data_sources.get(data_source_id) else {
+            return EditorResult {
+                success: false,
+                message: Some("Data source not found".to_string()),
+                data: None,
+                errors: vec!["Data source not found".to_string()],
+            };
+        };
+        let suggestion = suggest_chart(infer_columns(&sample_rows(data, CHART_SAMPLE_ROWS)));
+        let mut n = self.state.chart_drafts.len() + 1;
+        while self.state.chart_drafts.contains_key(&format!("chart_draft_{}", n)) {
+            n += 1;
+        }
+        let draft = ChartDraft {
+            id: format!("chart_draft_{}", n),
+            chart_type: suggestion.chart_type,
+            data_source_id: data_source_id.to_string(),
+            x_field: suggestion.x_field,
+            series: suggestion.series,
+            title: None,
+            width: 400.0,
+            height: 300.0,
+        };
+        let data = serde_json::json!(draft);
+        self.state.chart_drafts.insert(draft.id.clone(), draft);
+        
+        EditorResult {
+            success: true,
+            message: Some("Chart draft created".to_string()),
+            data: Some(data),
+            errors: Vec::new(),
+        }
+    }
+
+    // Replaces a draft; fields must exist in the data source
+    pub fn update_chart_draft(&mut self, draft: ChartDraft) -> EditorResult {
+        let Some(data) = self.state.document.data_sources.get(&draft.data_source_id) else {
+            return EditorResult {
+                success: false,
+                message: Some("Data source not found".to_string()),
+                data: None,
+                errors: vec!["Data source not found".to_string()],
+            };
+        };
+        let columns = infer_columns(&sample_rows(data, CHART_SAMPLE_ROWS));
+        let errors: Vec<String> = draft.x_field.iter()
+            .chain(draft.series.iter().map(|series| &series.field))
+            .filter(|field| !columns.iter().any(|c| c.name == **field))
+            .map(|field| format!("Field {} not found in {}", field, draft.data_source_id))
+            .collect();
+        if !errors.is_empty() || !self.state.chart_drafts.contains_key(&draft.id) {
+            return EditorResult {
+                success: false,
+                message: Some("Chart draft not updated".to_string()),
+                data: None,
+                errors: if errors.is_empty() { vec!["Chart draft not found".to_string()] } else { errors },
+            };
+        }
+        self.state.chart_drafts.insert(draft.id.clone(), draft);
+        
+        EditorResult {
+            success: true,
+            message: Some("Chart draft updated".to_string()),
+            data: None,
+            errors: Vec::new(),
+        }
+    }
+
+    // Preview of a draft as content for the "<draft id>_preview" element
+    pub fn preview_chart_draft(&self, draft_id: &str) -> RenderUpdate {
+        let mut dom_operations = Vec::new();
+        if let Some(draft) = self.state.chart_drafts.get(draft_id) {
+            let rows = self.state.document.data_sources.get(&draft.data_source_id)
+                .map(|data| sample_rows(data, CHART_SAMPLE_ROWS))
+                .unwrap_or_default();
+            dom_operations.push(DOMOperation::Update {
+                element_id: format!("{}_preview", draft.id),
+                attributes: [("data-chart-type".to_string(), format!("{:?}", draft.chart_type))].into_iter().collect(),
+                content: Some(render_chart_preview(draft, &rows)),
+            });
+        }
+        
+        RenderUpdate {
+            dom_operations,
+            style_changes: Vec::new(),
+            script_updates: Vec::new(),
+            asset_updates: Vec::new(),
+        }
+    }
+
+    // Turns a draft into a Chart element whose "chart" property uses the runtime chart format
+    pub fn commit_chart_draft(&mut self, draft_id: &str) -> EditorResult {
+        let Some(draft) = self.state.chart_drafts.remove(draft_id) else {
+            return EditorResult {
+                success: false,
+                message: Some("Chart draft not found".to_string()),
+                data: None,
+                errors: vec!["Chart draft not found".to_string()],
+            };
+        };
+        let series: Vec<serde_json::Value> = draft.series.iter()
+            .map(|series| serde_json::json!({
+                "id": series.field,
+                "name": series.name,
+                "data_field": series.field,
+                "color": series.color,
+                "line_width": null,
+                "fill_opacity": null,
+                "marker_size": null,
+                "marker_shape": null,
+                "visible": true,
+                "y_axis": "Primary",
+            }))
+            .collect();
+        let chart_id = format!("{}_chart", draft.id);
+        let properties = [
+            ("chart_id".to_string(), serde_json::json!(chart_id)),
+            ("data_source".to_string(), serde_json::json!(draft.data_source_id)),
+            ("width".to_string(), serde_json::json!(draft.width)),
+            ("height".to_string(), serde_json::json!(draft.height)),
+            ("chart".to_string(), serde_json::json!({
+                "id": chart_id,
+                "chart_type": draft.chart_type,
+                "data_source_id": draft.data_source_id,
+                "x_field": draft.x_field,
+                "title": draft.title,
+                "series": series,
+            })),
+        ].into_iter().collect();
+        
+        let bounds = BoundingBox { x: 0.0, y: 0.0, width: draft.width, height: draft.height };
+        let mut result = self.create_element_with_bounds(ElementType::Chart, properties, bounds);
+        result.message = Some("Chart element created".to_string());
+        result
+    }
+
+    // Bundles the script modules in dependency order, leaving out exports nothing uses
+    pub fn bundle_scripts(&self) -> EditorResult {
+        let scripts = &self.state.document.scripts;
+        let mut errors: Vec<String> = missing_script_dependencies(scripts).into_iter()
+            .map(|(module, dependency)| format!("Script module {} depends on missing module {}", module, dependency))
+            .collect();
+        let order = match script_order(scripts) {
+            Ok(order) => order,
+            Err(cycle) => {
+                errors.push(format!("Script modules depend on each other in a cycle: {}", cycle.join(" -> ")));
+                Vec::new()
+            }
+        };
+        if !errors.is_empty() {
+            return EditorResult {
+                success: false,
+                message: Some("Script modules cannot be bundled".to_string()),
+                data: None,
+                errors,
+            };
+        }
+        
+        let pruned_exports = unused_script_exports(scripts);
+        let mut content = String::new();
+        let mut exports = HashMap::new();
+        for name in &order {
+            let module = &scripts[name];
+            let pruned = pruned_exports.get(name);
+            exports.insert(name.clone(), module.exports.iter()
+                .filter(|export| !pruned.is_some_and(|pruned| pruned.contains(export)))
+                .cloned()
+                .collect());
+            if !matches!(module.module_type, ScriptType::WASM) {
+                content.push_str(&format!("// module: {}\n{}\n", name, module.content));
+            }
+        }
+        let bundle = ScriptBundle { order, content, exports, pruned_exports };
+        
+        EditorResult {
+            success: true,
+            message: Some("Scripts bundled".to_string()),
+            data: Some(serde_json::json!(bundle)),
+            errors: Vec::new(),
+        }
+    }
+
+    // Journal records since the last call, compacted, as lines to append to the host's journal blob
+    pub fn take_autosave_delta(&mut self) -> String {
+        let pending = std::mem::take(&mut self.state.journal.pending);
+        journal_blob(&compact_records(pending))
+    }
+
+    // After the host saved the document, earlier records are no longer needed; the host starts a new blob too
+    pub fn mark_saved(&mut self) {
+        self.state.journal.pending.clear();
+    }
+
+    // Loads the last saved document and replays the journal written since
+    pub fn recover_from_journal(&mut self, base_document_json: &str, journal: &str) -> EditorResult {
+        let result = self.load_document(base_document_json);
+        if !result.success {
+            return result;
+        }
+        
+        let (records, skipped) = parse_journal(journal);
+        let records = compact_records(records);
+        let next_sequence = records.last().map_or(0, |record| record.sequence + 1);
+        let applied = records.len();
+        for record in records {
+            self.apply_journal_entry(record.entry);
+        }
+        self.state.journal.next_sequence = next_sequence;
+        
+        EditorResult {
+            success: true,
+            message: Some(format!("Recovered {} changes", applied)),
+            data: Some(serde_json::json!({"applied": applied, "skipped": skipped})),
+            errors: if skipped > 0 { vec![format!("{} unreadable journal lines skipped", skipped)] } else { Vec::new() },
+        }
+    }
+
+    fn apply_journal_entry(&mut self, entry: JournalEntry) {
+        let document = &mut self.state.document;
+        match entry {
+            JournalEntry::PutElement { element, timeline } => {
+                match timeline {
+                    Some(timeline) => document.timelines.insert(element.id.clone(), timeline),
+                    None => document.timelines.remove(&element.id),
+                };
+                if let Some(existing) = document.elements.iter_mut().find(|e| e.id == element.id) {
+                    *existing = element;
+                } else {
+                    document.elements.push(element);
+                }
+            }
+            JournalEntry::RemoveElement { element_id } => {
+                document.elements.retain(|e| e.id != element_id);
+                document.timelines.remove(&element_id);
+            }
+            JournalEntry::PutCanvas { canvas } => document.canvas = canvas,
+            JournalEntry::PutAsset { asset } => {
+                document.assets.insert(asset.id.clone(), asset);
+            }
+        }
+    }
+
+    fn journal(&mut self, entry: JournalEntry) {
+        let journal = &mut self.state.journal;
+        journal.pending.push(JournalRecord {
+            sequence: journal.next_sequence,
+            timestamp: now_ms(),
+            entry,
+        });
+        journal.next_sequence += 1;
+    }
+
+    // Records the element as it is now, or its removal
+    fn journal_element(&mut self, element_id: &str) {
+        let document = &self.state.document;
+        let entry = match document.elements.iter().find(|e| e.id == element_id) {
+            Some(element) => JournalEntry::PutElement {
+                element: element.clone(),
+                timeline: document.timelines.get(element_id).cloned(),
+            },
+            None => JournalEntry::RemoveElement { element_id: element_id.to_string() },
+        };
+        self.journal(entry);
+    }
+
+    fn journal_canvas(&mut self) {
+        let canvas = self.state.document.canvas.clone();
+        self.journal(JournalEntry::PutCanvas { canvas });
+    }
+
+    // Helper methods
+    fn convert_liv_to_editor_document(&self, doc_value: &serde_json::Value) -> DocumentState {
+        let mut document_state = DocumentState::default();
+        
+        // Extract metadata
+        if let Some(metadata) = doc_value.get("metadata") {
+            if let Some(title) = metadata.get("title").and_then(|v| v.as_str()) {
+                document_state.metadata.title = title.to_string();
+            }
+            if let Some(author) = metadata.get("author").and_then(|v| v.as_str()) {
+                document_state.metadata.author = author.to_string();
+            }
+        }
+
+        // Canvas chrome saved by the editor
+        if let Some(canvas) = doc_value.get("editor").and_then(|editor| editor.get("canvas")) {
+            if let Ok(canvas) = serde_json::from_value(canvas.clone()) {
+                document_state.canvas = canvas;
+            }
+        }
+
+        // Parse HTML content into elements (simplified)
+        if let Some(content) = doc_value.get("content") {
+            if let Some(html) = content.get("html").and_then(|v| v.as_str()) {
+                // Simple HTML parsing - in a real implementation, this would be more sophisticated
+                let element = EditableElement {
+                    id: "root".to_string(),
+                    element_type: ElementType::Container,
+                    properties: [("innerHTML".to_string(), serde_json::Value::String(html.to_string()))].into_iter().collect(),
+                    children: Vec::new(),
+                    parent: None,
+                    locked: false,
+                    visible: true,
+                    bounds: BoundingBox { x: 0.0, y: 0.0, width: 800.0, height: 600.0 },
+                };
+                document_state.elements.push(element);
+            }
+        }
+
+        document_state
+    }
+
+    fn convert_editor_to_liv_document(&self) -> serde_json::Value {
+        let mut content_html = String::new();
+        
+        // Convert elements back to HTML (simplified)
+        for element in &self.state.document.elements {
+            if let Some(html) = element.properties.get("innerHTML").and_then(|v| v.as_str()) {
+                content_html.push_str(html);
+            }
+        }
+
+        serde_json::json!({
+            "metadata": {
+                "title": self.state.document.metadata.title,
+                "author": self.state.document.metadata.author,
+                "description": self.state.document.metadata.description,
+                "version": self.state.document.metadata.version,
+                "created": self.state.document.metadata.created,
+                "modified": String::from(js_sys::Date::new_0().to_iso_string())
+            },
+            "content": {
+                "html": content_html,
+                "css": "",
+                "interactiveSpec": "",
+                "staticFallback": content_html
+            },
+            "editor": {
+                "canvas": self.state.document.canvas
+            }
+        })
+    }
+
+    // Records an edit that has already been made. An Update or Transform of the same target
+    // within HISTORY_COALESCE_MS of the previous one extends that entry, so a burst of typing or a
+    // drag undoes in one step.
+    fn add_to_history(&mut self, operation_type: OperationType, data: serde_json::Value) {
+        let timestamp = now_ms();
+        
+        // Every recorded edit also goes to the autosave journal
+        for element_id in affected_element_ids(&data) {
+            self.journal_element(&element_id);
+        }
+
+        let history = &mut self.state.history;
+        if history.current_index == history.operations.len() {
+            if let Some(last) = history.operations.last_mut() {
+                if timestamp - last.timestamp <= HISTORY_COALESCE_MS {
+                    if let Some(data) = coalesce(&last.operation_type, &last.data, &operation_type, &data) {
+                        last.inverse_data = inverse_data(&last.operation_type, &data);
+                        last.data = data;
+                        last.timestamp = timestamp;
+                        return;
+                    }
+                }
+            }
+        }
+
+        let operation = EditOperation {
+            id: format!("op_{}", history.operations.len()),
+            inverse_data: inverse_data(&operation_type, &data),
+            operation_type,
+            timestamp,
+            data,
+        };
+
+        // Remove operations after current index (for redo)
+        history.operations.truncate(history.current_index);
+        
+        // Add new operation
+        history.operations.push(operation);
+        history.current_index = history.operations.len();
+
+        // Limit history size
+        if history.operations.len() > history.max_operations {
+            history.operations.remove(0);
+            history.current_index -= 1;
+        }
+    }
+
+    fn apply_operation(&mut self, operation: &EditOperation) {
+        self.execute(&operation.operation_type, &operation.data);
+    }
+
+    fn apply_inverse_operation(&mut self, operation: &EditOperation) {
+        self.execute(&inverse_type(&operation.operation_type), &operation.inverse_data);
+    }
+
+    // Makes the change a history payload describes and journals the elements it touched.
+    // Payloads that don't match the document (a stale element id) change nothing.
+    fn execute(&mut self, operation_type: &OperationType, data: &serde_json::Value) {
+        let document = &mut self.state.document;
+        let element_id = data.get("element_id").and_then(|v| v.as_str()).unwrap_or_default();
+        match operation_type {
+            OperationType::Create => {
+                let Some(element) = data.get("element").and_then(|v| serde_json::from_value::<EditableElement>(v.clone()).ok()) else { return };
+                document.elements.retain(|e| e.id != element.id);
+                let position = data.get("position").and_then(|v| v.as_u64())
+                    .map_or(document.elements.len(), |position| (position as usize).min(document.elements.len()));
+                if let Some(timeline) = data.get("timeline").and_then(|v| serde_json::from_value::<Timeline>(v.clone()).ok()) {
+                    document.timelines.insert(element.id.clone(), timeline);
+                }
+                document.elements.insert(position, element);
+            }
+            OperationType::Delete => {
+                document.elements.retain(|e| e.id != element_id);
+                document.timelines.remove(element_id);
+                self.state.selection.selected_elements.retain(|id| id != element_id);
+            }
+            OperationType::Update => {
+                if let Some(properties) = data.get("new_properties").and_then(|v| serde_json::from_value(v.clone()).ok()) {
+                    if let Some(element) = document.elements.iter_mut().find(|e| e.id == element_id) {
+                        element.properties = properties;
+                    }
+                }
+                if let Some(timeline) = data.get("new_timeline") {
+                    match serde_json::from_value::<Timeline>(timeline.clone()) {
+                        Ok(timeline) => document.timelines.insert(element_id.to_string(), timeline),
+                        Err(_) => document.timelines.remove(element_id),
+                    };
+                }
+            }
+            OperationType::Transform => {
+                let bounds = data.get("new_bounds").and_then(|v| serde_json::from_value::<BoundingBox>(v.clone()).ok());
+                if let (Some(bounds), Some(element)) = (bounds, document.elements.iter_mut().find(|e| e.id == element_id)) {
+                    element.bounds = bounds;
+                }
+            }
+            // Nothing records moves or style edits yet
+            OperationType::Move | OperationType::Style => return,
+            OperationType::Batch => {
+                for step in data.get("operations").and_then(|v| v.as_array()).into_iter().flatten() {
+                    let Some(step_type) = step.get("operation_type").and_then(|v| serde_json::from_value(v.clone()).ok()) else { continue };
+                    self.execute(&step_type, &step["data"]);
+                }
+                return;
+            }
+        }
+        for element_id in affected_element_ids(data) {
+            self.journal_element(&element_id);
+        }
+    }
+}
+
+// Milliseconds since the epoch; js_sys's clock only exists in the browser
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as f64
+}
+
+// Edits to the same target closer together than this share a history entry
+const HISTORY_COALESCE_MS: f64 = 1000.0;
+
+// The elements a history payload changes: its element or a pasted set
+fn affected_element_ids(data: &serde_json::Value) -> Vec<String> {
+    let mut ids: Vec<String> = data.get("element_ids")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    if let Some(id) = data.get("element_id").and_then(|v| v.as_str()) {
+        if !ids.iter().any(|existing| existing == id) {
+            ids.push(id.to_string());
+        }
+    }
+    ids
+}
+
+// Creating and deleting undo each other; every other kind is its own inverse with the states swapped
+fn inverse_type(operation_type: &OperationType) -> OperationType {
+    match operation_type {
+        OperationType::Create => OperationType::Delete,
+        OperationType::Delete => OperationType::Create,
+        other => other.clone(),
+    }
+}
+
+// The payload that reverts `data`. Create and Delete carry the whole element either way; the
+// rest swap each "old_" field with its "new_" one. A batch reverts its steps in reverse order.
+fn inverse_data(operation_type: &OperationType, data: &serde_json::Value) -> serde_json::Value {
+    match operation_type {
+        OperationType::Create | OperationType::Delete => data.clone(),
+        OperationType::Batch => {
+            let steps: Vec<serde_json::Value> = data.get("operations").and_then(|v| v.as_array()).into_iter().flatten()
+                .rev()
+                .filter_map(|step| {
+                    let step_type: OperationType = serde_json::from_value(step.get("operation_type")?.clone()).ok()?;
+                    Some(serde_json::json!({
+                        "operation_type": inverse_type(&step_type),
+                        "data": inverse_data(&step_type, &step["data"]),
+                    }))
+                })
+                .collect();
+            serde_json::json!({
+                "element_ids": data.get("element_ids").cloned().unwrap_or_else(|| serde_json::json!([])),
+                "operations": steps,
+            })
+        }
+        _ => {
+            let Some(fields) = data.as_object() else { return data.clone() };
+            let mut inverse = fields.clone();
+            for (key, value) in fields {
+                if let Some(field) = key.strip_prefix("old_") {
+                    inverse.insert(format!("new_{}", field), value.clone());
+                } else if let Some(field) = key.strip_prefix("new_") {
+                    inverse.insert(format!("old_{}", field), value.clone());
+                }
+            }
+            serde_json::Value::Object(inverse)
+        }
+    }
+}
+
+// `next` folded into `last` when both change the same thing the same way: the earlier "old_"
+// states with the later "new_" ones. None when they can't be merged.
+fn coalesce(last_type: &OperationType, last: &serde_json::Value, next_type: &OperationType, next: &serde_json::Value) -> Option<serde_json::Value> {
+    let same_kind = matches!(
+        (last_type, next_type),
+        (OperationType::Update, OperationType::Update) | (OperationType::Transform, OperationType::Transform)
+    );
+    let (last, next) = (last.as_object()?, next.as_object()?);
+    let same_target = last.get("element_id") == next.get("element_id");
+    let same_fields = last.keys().eq(next.keys());
+    if !same_kind || !same_target || !same_fields {
+        return None;
+    }
+    let mut merged = next.clone();
+    for (key, value) in last {
+        if key.starts_with("old_") {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    Some(serde_json::Value::Object(merged))
+}
+
+// The editor state and workspace the workspace bindings share, created on first use.
+// Callers must not hold on to them across bindings.
+unsafe fn editor_globals() -> (&'static mut EditorState, &'static mut Workspace) {
+    let state = (*std::ptr::addr_of_mut!(EDITOR_STATE)).get_or_insert_with(EditorState::default);
+    let workspace = (*std::ptr::addr_of_mut!(EDITOR_WORKSPACE)).get_or_insert_with(Workspace::new);
+    (state, workspace)
+}
+
+// WASM bindings
+#[wasm_bindgen]
+pub fn init_editor_engine() {
+    log("LIV Editor Engine initialized");
+    unsafe {
+        EDITOR_STATE = Some(EditorState::default());
+        EDITOR_WORKSPACE = Some(Workspace::new());
+    }
+}
+
+#[wasm_bindgen]
+pub fn load_document(document_json: &str) -> String {
+    let mut engine = EditorEngine::new();
+    let result = engine.load_document(document_json);
+    
+    unsafe {
+        EDITOR_STATE = Some(engine.state);
+    }
+    
+    serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+}
+
+#[wasm_bindgen]
+pub fn save_document() -> String {
+    unsafe {
+        if let Some(ref state) = EDITOR_STATE {
+            let engine = EditorEngine::with_state(state.clone());
+            let result = engine.save_document();
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            serde_json::to_string(&EditorResult {
+                success: false,
+                message: Some("Editor not initialized".to_string()),
+                data: None,
+                errors: vec!["Editor not initialized".to_string()],
+            }).unwrap_or_else(|_| "{}".to_string())
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_element(element_type: &str, properties_json: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let element_type = match element_type {
+                "text" => ElementType::Text,
+                "image" => ElementType::Image,
+                "chart" => ElementType::Chart,
+                "container" => ElementType::Container,
+                _ => ElementType::Container,
+            };
+
+            let properties: HashMap<String, serde_json::Value> = 
+                serde_json::from_str(properties_json).unwrap_or_default();
+
+            let result = engine.create_element(element_type, properties);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn update_element(element_id: &str, properties_json: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let properties: HashMap<String, serde_json::Value> = 
+                serde_json::from_str(properties_json).unwrap_or_default();
+
+            let result = engine.update_element(element_id, properties);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn delete_element(element_id: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.delete_element(element_id);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn select_element(element_id: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.select_element(element_id);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn editor_undo() -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.undo();
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn editor_redo() -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.redo();
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+// Older names for editor_undo and editor_redo
+#[wasm_bindgen]
+pub fn undo() -> String {
+    editor_undo()
+}
+
+#[wasm_bindgen]
+pub fn redo() -> String {
+    editor_redo()
+}
+
+#[wasm_bindgen]
+pub fn validate_document() -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.validate_document();
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+// Every lint rule with its category, default severity and whether it has fixes
+#[wasm_bindgen]
+pub fn get_lint_rules() -> String {
+    serde_json::to_string(&lint::rules()).unwrap_or_else(|_| "[]".to_string())
+}
+
+#[wasm_bindgen]
+pub fn get_lint_profiles() -> String {
+    serde_json::to_string(&LintProfile::built_in()).unwrap_or_else(|_| "[]".to_string())
+}
+
+// A LintProfile as JSON, or the name of a built-in one such as "strict"
+#[wasm_bindgen]
+pub fn set_lint_profile(profile: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let parsed = if profile.trim_start().starts_with('{') {
+                serde_json::from_str::<LintProfile>(profile).map_err(|e| e.to_string())
+            } else {
+                LintProfile::named(profile).ok_or_else(|| format!("unknown profile {}", profile))
+            };
+            let result = match parsed {
+                Ok(profile) => engine.set_lint_profile(profile),
+                Err(e) => EditorResult {
+                    success: false,
+                    message: Some(format!("Failed to parse lint profile: {}", e)),
+                    data: None,
+                    errors: vec![e],
+                },
+            };
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_render_update() -> String {
+    unsafe {
+        if let Some(ref state) = EDITOR_STATE {
+            let engine = EditorEngine::with_state(state.clone());
+            let result = engine.get_render_update();
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+#[wasm_bindgen]
+pub fn set_keyframe(element_id: &str, time: f64, properties_json: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let properties: HashMap<String, serde_json::Value> = 
+                serde_json::from_str(properties_json).unwrap_or_default();
+
+            let result = engine.set_keyframe(element_id, time, properties);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_keyframe(element_id: &str, time: f64) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.remove_keyframe(element_id, time);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+// Accepts a preset name from EASING_PRESETS or a serialized EasingFunction
+#[wasm_bindgen]
+pub fn set_timeline_easing(element_id: &str, easing: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let easing = EasingFunction::preset(easing)
+                .or_else(|| serde_json::from_str(easing).ok())
+                .unwrap_or(EasingFunction::Linear);
+
+            let result = engine.set_timeline_easing(element_id, easing);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_easing_presets() -> String {
+    let presets: HashMap<&str, &EasingFunction> = EASING_PRESETS.iter()
+        .map(|(name, easing)| (*name, easing))
+        .collect();
+    serde_json::to_string(&presets).unwrap_or_else(|_| "{}".to_string())
+}
+
+// Curve points and control points for drawing an easing in the curve editor
+#[wasm_bindgen]
+pub fn sample_easing(easing_json: &str, steps: usize) -> String {
+    let easing: EasingFunction = serde_json::from_str(easing_json).unwrap_or(EasingFunction::Linear);
+    let (first, second) = easing.control_points();
+    serde_json::to_string(&serde_json::json!({
+        "points": easing.sample(steps),
+        "control_points": [first, second],
+    })).unwrap_or_else(|_| "{}".to_string())
+}
+
+#[wasm_bindgen]
+pub fn export_animations() -> String {
+    unsafe {
+        if let Some(ref state) = EDITOR_STATE {
+            let engine = EditorEngine::with_state(state.clone());
+            let result = engine.export_animations();
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn play_preview() {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            engine.play_preview();
+            *state = engine.state;
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn pause_preview() {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            engine.pause_preview();
+            *state = engine.state;
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn seek_preview(position: f64) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.seek_preview(position);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+// Call once per animation frame while previewing; returns the style changes for the preview pane
+#[wasm_bindgen]
+pub fn tick_preview(timestamp: f64) -> String {
+    unsafe {
         if let Some(ref mut state) = EDITOR_STATE {
-            let mut engine = EditorEngine { state: state.clone() };
-            let result = engine.undo();
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.tick_preview(timestamp);
             *state = engine.state;
             
             serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
@@ -941,11 +3189,20 @@ pub fn undo() -> String {
 }
 
 #[wasm_bindgen]
-pub fn redo() -> String {
+pub fn set_grid(grid_json: &str) -> String {
     unsafe {
         if let Some(ref mut state) = EDITOR_STATE {
-            let mut engine = EditorEngine { state: state.clone() };
-            let result = engine.redo();
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let result = match serde_json::from_str::<GridSettings>(grid_json) {
+                Ok(grid) => engine.set_grid(grid),
+                Err(e) => EditorResult {
+                    success: false,
+                    message: Some(format!("Failed to parse grid settings: {}", e)),
+                    data: None,
+                    errors: vec![e.to_string()],
+                },
+            };
             *state = engine.state;
             
             serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
@@ -956,11 +3213,20 @@ pub fn redo() -> String {
 }
 
 #[wasm_bindgen]
-pub fn validate_document() -> String {
+pub fn set_rulers(rulers_json: &str) -> String {
     unsafe {
         if let Some(ref mut state) = EDITOR_STATE {
-            let mut engine = EditorEngine { state: state.clone() };
-            let result = engine.validate_document();
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let result = match serde_json::from_str::<RulerSettings>(rulers_json) {
+                Ok(rulers) => engine.set_rulers(rulers),
+                Err(e) => EditorResult {
+                    success: false,
+                    message: Some(format!("Failed to parse ruler settings: {}", e)),
+                    data: None,
+                    errors: vec![e.to_string()],
+                },
+            };
             *state = engine.state;
             
             serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
@@ -971,15 +3237,385 @@ pub fn validate_document() -> String {
 }
 
 #[wasm_bindgen]
-pub fn get_render_update() -> String {
+pub fn add_guide(orientation: &str, position: f64) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let orientation = match orientation {
+                "horizontal" => GuideOrientation::Horizontal,
+                _ => GuideOrientation::Vertical,
+            };
+
+            let result = engine.add_guide(orientation, position);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn move_guide(guide_id: &str, position: f64) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.move_guide(guide_id, position);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_guide(guide_id: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.remove_guide(guide_id);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_canvas_settings() -> String {
     unsafe {
         if let Some(ref state) = EDITOR_STATE {
-            let engine = EditorEngine { state: state.clone() };
-            let result = engine.get_render_update();
+            serde_json::to_string(&state.document.canvas).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn transform_element(element_id: &str, handle: &str, dx: f64, dy: f64) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let handle = match handle {
+                "top-left" => TransformHandle::TopLeft,
+                "top" => TransformHandle::Top,
+                "top-right" => TransformHandle::TopRight,
+                "right" => TransformHandle::Right,
+                "bottom-right" => TransformHandle::BottomRight,
+                "bottom" => TransformHandle::Bottom,
+                "bottom-left" => TransformHandle::BottomLeft,
+                "left" => TransformHandle::Left,
+                _ => TransformHandle::Move,
+            };
+
+            let result = engine.transform_element(element_id, handle, dx, dy);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn open_document(document_json: &str) -> String {
+    unsafe {
+        let (state, workspace) = editor_globals();
+        let mut engine = EditorEngine { state: state.clone(), workspace: workspace.clone() };
+        let result = engine.open_document(document_json);
+        *state = engine.state;
+        *workspace = engine.workspace;
+        
+        serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[wasm_bindgen]
+pub fn switch_document(document_id: &str) -> String {
+    unsafe {
+        let (state, workspace) = editor_globals();
+        let mut engine = EditorEngine { state: state.clone(), workspace: workspace.clone() };
+        let result = engine.switch_document(document_id);
+        *state = engine.state;
+        *workspace = engine.workspace;
+        
+        serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[wasm_bindgen]
+pub fn close_document(document_id: &str) -> String {
+    unsafe {
+        let (state, workspace) = editor_globals();
+        let mut engine = EditorEngine { state: state.clone(), workspace: workspace.clone() };
+        let result = engine.close_document(document_id);
+        *state = engine.state;
+        *workspace = engine.workspace;
+        
+        serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[wasm_bindgen]
+pub fn list_documents() -> String {
+    unsafe {
+        let (state, workspace) = editor_globals();
+        let engine = EditorEngine { state: state.clone(), workspace: workspace.clone() };
+        let result = engine.list_documents();
+        
+        serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[wasm_bindgen]
+pub fn add_library_asset(asset_json: &str) -> String {
+    unsafe {
+        let (state, workspace) = editor_globals();
+        let mut engine = EditorEngine { state: state.clone(), workspace: workspace.clone() };
+        
+        let result = match serde_json::from_str::<AssetReference>(asset_json) {
+            Ok(asset) => engine.add_library_asset(asset),
+            Err(e) => EditorResult {
+                success: false,
+                message: Some(format!("Failed to parse asset: {}", e)),
+                data: None,
+                errors: vec![e.to_string()],
+            },
+        };
+        *workspace = engine.workspace;
+        
+        serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[wasm_bindgen]
+pub fn use_library_asset(asset_id: &str) -> String {
+    unsafe {
+        let (state, workspace) = editor_globals();
+        let mut engine = EditorEngine { state: state.clone(), workspace: workspace.clone() };
+        let result = engine.use_library_asset(asset_id);
+        *state = engine.state;
+        
+        serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[wasm_bindgen]
+pub fn copy_elements(element_ids_json: &str) -> String {
+    unsafe {
+        let (state, workspace) = editor_globals();
+        let mut engine = EditorEngine { state: state.clone(), workspace: workspace.clone() };
+        
+        let element_ids: Vec<String> = serde_json::from_str(element_ids_json).unwrap_or_default();
+        let result = engine.copy_elements(&element_ids);
+        *workspace = engine.workspace;
+        
+        serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+// Pastes the fragment in fragment_json, or the workspace clipboard when it is empty
+#[wasm_bindgen]
+pub fn paste_fragment(fragment_json: &str) -> String {
+    unsafe {
+        let (state, workspace) = editor_globals();
+        let mut engine = EditorEngine { state: state.clone(), workspace: workspace.clone() };
+        
+        let result = if fragment_json.trim().is_empty() {
+            engine.paste_fragment(None)
+        } else {
+            match serde_json::from_str::<ClipboardFragment>(fragment_json) {
+                Ok(fragment) => engine.paste_fragment(Some(fragment)),
+                Err(e) => EditorResult {
+                    success: false,
+                    message: Some(format!("Failed to parse clipboard fragment: {}", e)),
+                    data: None,
+                    errors: vec![e.to_string()],
+                },
+            }
+        };
+        *state = engine.state;
+        *workspace = engine.workspace;
+        
+        serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+// Returns journal lines to append to the autosave blob; empty when nothing changed
+#[wasm_bindgen]
+pub fn take_autosave_delta() -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let delta = engine.take_autosave_delta();
+            *state = engine.state;
+            delta
+        } else {
+            String::new()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn mark_saved() {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            engine.mark_saved();
+            *state = engine.state;
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn recover_from_journal(base_document_json: &str, journal: &str) -> String {
+    let mut engine = EditorEngine::new();
+    let result = engine.recover_from_journal(base_document_json, journal);
+    
+    unsafe {
+        if result.success {
+            EDITOR_STATE = Some(engine.state);
+        }
+    }
+    
+    serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+}
+
+// Compacts a journal blob so it holds one record per element, asset and the canvas
+#[wasm_bindgen]
+pub fn compact_journal(journal: &str) -> String {
+    let (records, _) = parse_journal(journal);
+    journal_blob(&compact_records(records))
+}
+
+#[wasm_bindgen]
+pub fn bundle_scripts() -> String {
+    unsafe {
+        if let Some(ref state) = EDITOR_STATE {
+            let engine = EditorEngine::with_state(state.clone());
+            let result = engine.bundle_scripts();
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_data_source(data_source_id: &str, data_json: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let result = match serde_json::from_str::<serde_json::Value>(data_json) {
+                Ok(data) => engine.set_data_source(data_source_id, data),
+                Err(e) => EditorResult {
+                    success: false,
+                    message: Some(format!("Failed to parse data: {}", e)),
+                    data: None,
+                    errors: vec![e.to_string()],
+                },
+            };
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn suggest_chart_for(data_source_id: &str) -> String {
+    unsafe {
+        if let Some(ref state) = EDITOR_STATE {
+            let engine = EditorEngine::with_state(state.clone());
+            let result = engine.suggest_chart(data_source_id);
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_chart_draft(data_source_id: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.create_chart_draft(data_source_id);
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn update_chart_draft(draft_json: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            
+            let result = match serde_json::from_str::<ChartDraft>(draft_json) {
+                Ok(draft) => engine.update_chart_draft(draft),
+                Err(e) => EditorResult {
+                    success: false,
+                    message: Some(format!("Failed to parse chart draft: {}", e)),
+                    data: None,
+                    errors: vec![e.to_string()],
+                },
+            };
+            *state = engine.state;
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn preview_chart_draft(draft_id: &str) -> String {
+    unsafe {
+        if let Some(ref state) = EDITOR_STATE {
+            let engine = EditorEngine::with_state(state.clone());
+            let result = engine.preview_chart_draft(draft_id);
+            
+            serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
+        } else {
+            "{}".to_string()
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn commit_chart_draft(draft_id: &str) -> String {
+    unsafe {
+        if let Some(ref mut state) = EDITOR_STATE {
+            let mut engine = EditorEngine::with_state(state.clone());
+            let result = engine.commit_chart_draft(draft_id);
+            *state = engine.state;
             
             serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string())
         } else {
             "{}".to_string()
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests;
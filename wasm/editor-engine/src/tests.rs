@@ -0,0 +1,884 @@
+use super::*;
+use wasm_bindgen_test::*;
+// Native `cargo test` runs the same tests as plain #[test]s
+#[cfg(not(target_family = "wasm"))]
+use std::prelude::v1::test as wasm_bindgen_test;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn properties(pairs: &[(&str, serde_json::Value)]) -> HashMap<String, serde_json::Value> {
+    pairs.iter().map(|(key, value)| (key.to_string(), value.clone())).collect()
+}
+
+// The document as JSON, so states can be compared whole
+fn snapshot(engine: &EditorEngine) -> serde_json::Value {
+    serde_json::json!({
+        "elements": engine.state.document.elements,
+        "timelines": engine.state.document.timelines,
+    })
+}
+
+fn created_id(result: &EditorResult) -> String {
+    result.data.as_ref().unwrap()["element_id"].as_str().unwrap().to_string()
+}
+
+// Moves the last history entry out of the coalescing window
+fn age_last_entry(engine: &mut EditorEngine) {
+    let last = engine.state.history.operations.last_mut().unwrap();
+    last.timestamp -= HISTORY_COALESCE_MS + 1.0;
+}
+
+// Undoes the last edit and checks the document is back to `before`, then redoes it and checks
+// it matches `after` again
+fn assert_round_trip(engine: &mut EditorEngine, before: &serde_json::Value) {
+    let after = snapshot(engine);
+    assert!(engine.undo().success);
+    assert_eq!(snapshot(engine), *before);
+    assert!(engine.redo().success);
+    assert_eq!(snapshot(engine), after);
+}
+
+#[wasm_bindgen_test]
+fn test_undo_redo_create_and_delete() {
+    let mut engine = EditorEngine::new();
+    let before = snapshot(&engine);
+    let id = created_id(&engine.create_element(ElementType::Text, properties(&[("text", serde_json::json!("Hello"))])));
+    assert_round_trip(&mut engine, &before);
+
+    engine.create_element(ElementType::Image, HashMap::new());
+    age_last_entry(&mut engine);
+    engine.set_keyframe(&id, 0.0, properties(&[("opacity", serde_json::json!(0.0))]));
+    age_last_entry(&mut engine);
+    let before = snapshot(&engine);
+    assert!(engine.delete_element(&id).success);
+    assert!(engine.state.document.timelines.is_empty());
+    assert_round_trip(&mut engine, &before);
+
+    // Undo puts the element back where it was, with its timeline
+    engine.undo();
+    assert_eq!(engine.state.document.elements[0].id, id);
+    assert!(engine.state.document.timelines.contains_key(&id));
+}
+
+#[wasm_bindgen_test]
+fn test_undo_redo_update_and_transform() {
+    let mut engine = EditorEngine::new();
+    let id = created_id(&engine.create_element(ElementType::Text, properties(&[("text", serde_json::json!("Hello"))])));
+    age_last_entry(&mut engine);
+
+    let before = snapshot(&engine);
+    engine.update_element(&id, properties(&[("text", serde_json::json!("Goodbye")), ("color", serde_json::json!("red"))]));
+    assert_round_trip(&mut engine, &before);
+    age_last_entry(&mut engine);
+
+    let before = snapshot(&engine);
+    let result = engine.transform_element(&id, TransformHandle::BottomRight, 40.0, 20.0);
+    assert!(result.success);
+    assert_ne!(snapshot(&engine), before);
+    assert_round_trip(&mut engine, &before);
+
+    let undone = engine.undo();
+    assert_eq!(undone.data.unwrap()["element_ids"], serde_json::json!([id]));
+}
+
+#[wasm_bindgen_test]
+fn test_undo_redo_keyframes() {
+    let mut engine = EditorEngine::new();
+    let id = created_id(&engine.create_element(ElementType::Animation, HashMap::new()));
+    age_last_entry(&mut engine);
+
+    // The first keyframe creates the timeline, so undoing it removes the timeline again
+    let before = snapshot(&engine);
+    assert!(engine.set_keyframe(&id, 0.0, properties(&[("opacity", serde_json::json!(0.0))])).success);
+    assert_round_trip(&mut engine, &before);
+    age_last_entry(&mut engine);
+
+    engine.set_keyframe(&id, 500.0, properties(&[("opacity", serde_json::json!(1.0))]));
+    age_last_entry(&mut engine);
+    let before = snapshot(&engine);
+    assert!(engine.remove_keyframe(&id, 500.0).success);
+    assert_round_trip(&mut engine, &before);
+    assert!(!engine.remove_keyframe(&id, 500.0).success);
+}
+
+#[wasm_bindgen_test]
+fn test_paste_undoes_as_one_batch() {
+    let mut engine = EditorEngine::new();
+    let first = created_id(&engine.create_element(ElementType::Text, HashMap::new()));
+    let second = created_id(&engine.create_element(ElementType::Image, HashMap::new()));
+    engine.set_keyframe(&first, 0.0, properties(&[("opacity", serde_json::json!(0.5))]));
+    assert!(engine.copy_elements(&[first.clone(), second.clone()]).success);
+
+    let history_len = engine.state.history.operations.len();
+    let before = snapshot(&engine);
+    let pasted = engine.paste_fragment(None);
+    assert!(pasted.success);
+    let ids: Vec<String> = serde_json::from_value(pasted.data.unwrap()["element_ids"].clone()).unwrap();
+    assert_eq!(ids.len(), 2);
+    assert!(!ids.contains(&first) && !ids.contains(&second));
+    assert_eq!(engine.state.history.operations.len(), history_len + 1);
+    assert!(matches!(engine.state.history.operations.last().unwrap().operation_type, OperationType::Batch));
+    assert!(engine.state.document.timelines.contains_key(&ids[0]));
+
+    // One undo removes every pasted element and the copied timeline
+    let undone = engine.undo();
+    assert_eq!(undone.data.unwrap()["element_ids"], serde_json::json!(ids));
+    assert_eq!(snapshot(&engine), before);
+    engine.redo();
+    assert_eq!(engine.state.document.elements.len(), 4);
+    assert!(engine.state.document.timelines.contains_key(&ids[0]));
+}
+
+#[wasm_bindgen_test]
+fn test_history_coalescing() {
+    let mut engine = EditorEngine::new();
+    let id = created_id(&engine.create_element(ElementType::Text, properties(&[("text", serde_json::json!("H"))])));
+    age_last_entry(&mut engine);
+    let typed = snapshot(&engine);
+
+    // Updates to one element inside the window share an entry that undoes to the first state
+    engine.update_element(&id, properties(&[("text", serde_json::json!("He"))]));
+    engine.update_element(&id, properties(&[("text", serde_json::json!("Hel"))]));
+    engine.update_element(&id, properties(&[("text", serde_json::json!("Hell"))]));
+    assert_eq!(engine.state.history.operations.len(), 2);
+    engine.undo();
+    assert_eq!(snapshot(&engine), typed);
+    engine.redo();
+    assert_eq!(engine.state.document.elements[0].properties["text"], "Hell");
+
+    // Outside the window the next update is its own entry
+    age_last_entry(&mut engine);
+    engine.update_element(&id, properties(&[("text", serde_json::json!("Hello"))]));
+    assert_eq!(engine.state.history.operations.len(), 3);
+    engine.undo();
+    assert_eq!(engine.state.document.elements[0].properties["text"], "Hell");
+    engine.redo();
+
+    // A drag coalesces too, but not with an edit of another kind or element
+    engine.transform_element(&id, TransformHandle::Move, 10.0, 0.0);
+    engine.transform_element(&id, TransformHandle::Move, 10.0, 0.0);
+    assert_eq!(engine.state.history.operations.len(), 4);
+    let other = created_id(&engine.create_element(ElementType::Image, HashMap::new()));
+    engine.transform_element(&other, TransformHandle::Move, 10.0, 0.0);
+    engine.transform_element(&id, TransformHandle::Move, 10.0, 0.0);
+    assert_eq!(engine.state.history.operations.len(), 7);
+}
+
+#[wasm_bindgen_test]
+fn test_new_edit_discards_redo_history() {
+    let mut engine = EditorEngine::new();
+    let id = created_id(&engine.create_element(ElementType::Text, HashMap::new()));
+    age_last_entry(&mut engine);
+    engine.update_element(&id, properties(&[("text", serde_json::json!("first"))]));
+    age_last_entry(&mut engine);
+    engine.update_element(&id, properties(&[("text", serde_json::json!("second"))]));
+    age_last_entry(&mut engine);
+
+    engine.undo();
+    engine.undo();
+    assert_eq!(engine.state.history.current_index, 1);
+
+    // An undone entry is never extended, even inside the window
+    engine.update_element(&id, properties(&[("text", serde_json::json!("other"))]));
+    assert_eq!(engine.state.history.operations.len(), 2);
+    assert_eq!(engine.state.history.current_index, 2);
+    assert!(!engine.redo().success);
+    engine.undo();
+    assert!(!engine.state.document.elements[0].properties.contains_key("text"));
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-3, "expected {} but got {}", expected, actual);
+}
+
+#[wasm_bindgen_test]
+fn test_easing_curves() {
+    for (_, easing) in EASING_PRESETS.iter() {
+        assert_close(easing.evaluate(0.0), 0.0);
+        assert_close(easing.evaluate(1.0), 1.0);
+    }
+    assert_close(EasingFunction::Linear.evaluate(0.3), 0.3);
+    assert_close(EasingFunction::EaseIn.evaluate(0.5), 0.25);
+    assert_close(EasingFunction::EaseOut.evaluate(0.5), 0.75);
+    assert_close(EasingFunction::EaseInOut.evaluate(0.25), 0.125);
+    // Progress outside 0-1 is clamped
+    assert_close(EasingFunction::Linear.evaluate(1.5), 1.0);
+
+    // A cubic along the diagonal is linear, and ease-in-out is symmetric about the midpoint
+    let diagonal = EasingFunction::Cubic(0.25, 0.25, 0.75, 0.75);
+    assert_close(diagonal.evaluate(0.4), 0.4);
+    let ease_in_out = EasingFunction::preset("ease-in-out").unwrap();
+    assert_close(ease_in_out.evaluate(0.5), 0.5);
+    assert_close(ease_in_out.evaluate(0.2) + ease_in_out.evaluate(0.8), 1.0);
+    // Back curves overshoot
+    assert!(EasingFunction::preset("ease-out-back").unwrap().evaluate(0.6) > 1.0);
+    assert!(EasingFunction::preset("bounce").is_none());
+
+    let samples = EasingFunction::EaseIn.sample(4);
+    assert_eq!(samples.len(), 5);
+    assert_close(samples[2].x, 0.5);
+    assert_close(samples[2].y, 0.25);
+    assert_eq!(EasingFunction::Linear.sample(0).len(), 2);
+}
+
+#[wasm_bindgen_test]
+fn test_easing_control_points() {
+    let (first, second) = EasingFunction::preset("ease").unwrap().control_points();
+    assert_close(first.x, 0.25);
+    assert_close(second.y, 1.0);
+
+    // Dragging a handle makes a cubic, keeping x in 0-1 but letting y overshoot
+    let dragged = EasingFunction::Linear.with_control_point(1, Position { x: 1.4, y: 1.6 });
+    match dragged {
+        EasingFunction::Cubic(x1, y1, x2, y2) => {
+            assert_eq!((x1, y1, x2, y2), (0.0, 0.0, 1.0, 1.6));
+        }
+        other => panic!("expected a cubic, got {:?}", other),
+    }
+    let dragged = EasingFunction::EaseIn.with_control_point(0, Position { x: -0.5, y: 0.2 });
+    assert!(matches!(dragged, EasingFunction::Cubic(x1, y1, _, _) if x1 == 0.0 && y1 == 0.2));
+}
+
+#[wasm_bindgen_test]
+fn test_timeline_keyframes() {
+    let mut timeline = Timeline::new("element_0");
+    timeline.set_keyframe(1000.0, properties(&[("opacity", serde_json::json!(1.0))]));
+    timeline.set_keyframe(0.0, properties(&[("opacity", serde_json::json!(0.0)), ("color", serde_json::json!("red"))]));
+    // Within KEYFRAME_TIME_EPSILON of an existing keyframe merges into it
+    timeline.set_keyframe(1000.2, properties(&[("color", serde_json::json!("blue"))]));
+
+    let times: Vec<f64> = timeline.keyframes.iter().map(|k| k.time).collect();
+    assert_eq!(times, vec![0.0, 1000.0]);
+    assert_eq!(timeline.duration, 1000.0);
+    assert_eq!(timeline.keyframes[1].properties["color"], "blue");
+
+    // Numbers interpolate; other values switch halfway through
+    let values = timeline.values_at(250.0);
+    assert_close(values["opacity"].as_f64().unwrap(), 0.25);
+    assert_eq!(values["color"], "red");
+    assert_eq!(timeline.values_at(750.0)["color"], "blue");
+
+    // Easing shapes time the way the runtime does
+    timeline.easing = EasingFunction::EaseIn;
+    assert_close(timeline.values_at(500.0)["opacity"].as_f64().unwrap(), 0.25);
+
+    let runtime = timeline.to_runtime_animation();
+    assert_eq!(runtime["target_element"], "element_0");
+    assert_eq!(runtime["keyframes"][1]["time"], 1.0);
+    assert_eq!(runtime["duration"], 1000.0);
+
+    assert!(timeline.remove_keyframe(999.8));
+    assert!(!timeline.remove_keyframe(500.0));
+    assert_eq!(timeline.keyframes.len(), 1);
+    assert!(Timeline::new("empty").values_at(0.0).is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_editor_keyframes_and_export() {
+    let mut engine = EditorEngine::new();
+    let id = created_id(&engine.create_element(ElementType::Animation, HashMap::new()));
+    let other = created_id(&engine.create_element(ElementType::Text, HashMap::new()));
+
+    assert!(!engine.set_keyframe("missing", 0.0, HashMap::new()).success);
+    assert!(!engine.set_keyframe(&id, -1.0, HashMap::new()).success);
+    assert!(!engine.set_keyframe(&id, f64::NAN, HashMap::new()).success);
+    assert!(!engine.set_timeline_easing(&id, EasingFunction::EaseOut).success);
+
+    engine.set_keyframe(&other, 200.0, properties(&[("opacity", serde_json::json!(1.0))]));
+    engine.set_keyframe(&id, 400.0, properties(&[("opacity", serde_json::json!(1.0))]));
+    assert!(engine.set_timeline_easing(&id, EasingFunction::EaseOut).success);
+
+    // Exported in element order, with the easing that was set
+    let exported = engine.export_animations().data.unwrap();
+    let targets: Vec<&str> = exported.as_array().unwrap().iter().map(|a| a["target_element"].as_str().unwrap()).collect();
+    assert_eq!(targets, vec![id.as_str(), other.as_str()]);
+    assert_eq!(exported[0]["easing"], "EaseOut");
+}
+
+#[wasm_bindgen_test]
+fn test_animation_preview() {
+    let mut engine = EditorEngine::new();
+    let id = created_id(&engine.create_element(ElementType::Animation, HashMap::new()));
+    engine.set_keyframe(&id, 0.0, properties(&[("x", serde_json::json!(0.0)), ("opacity", serde_json::json!(0.0))]));
+    engine.set_keyframe(&id, 1000.0, properties(&[("x", serde_json::json!(100.0)), ("opacity", serde_json::json!(1.0))]));
+    engine.state.document.timelines.get_mut(&id).unwrap().animation_type = AnimationType::Transform;
+
+    // Transform timelines fold x, y, rotation and scale into one transform
+    let update = engine.seek_preview(500.0);
+    let properties: Vec<&str> = update.style_changes.iter().map(|c| c.property.as_str()).collect();
+    assert_eq!(properties, vec!["transform", "opacity"]);
+    assert_eq!(update.style_changes[0].selector, format!("#{}", id));
+    assert_eq!(update.style_changes[0].value, "translate(50px, 0px) rotate(0deg) scale(1)");
+    assert_eq!(update.style_changes[1].value, "0.5");
+
+    // A paused preview doesn't move; a playing one advances by frame time and loops
+    engine.tick_preview(10_000.0);
+    assert_eq!(engine.state.playback.position, 500.0);
+    engine.play_preview();
+    engine.tick_preview(10_000.0);
+    assert_eq!(engine.state.playback.position, 500.0);
+    engine.tick_preview(10_700.0);
+    assert_close(engine.state.playback.position, 200.0);
+    engine.pause_preview();
+    engine.tick_preview(20_000.0);
+    assert_close(engine.state.playback.position, 200.0);
+
+    // Seeking clamps to the start
+    engine.seek_preview(-50.0);
+    assert_eq!(engine.state.playback.position, 0.0);
+}
+
+fn bounds(x: f64, y: f64, width: f64, height: f64) -> BoundingBox {
+    BoundingBox { x, y, width, height }
+}
+
+fn assert_bounds(actual: &BoundingBox, expected: (f64, f64, f64, f64)) {
+    assert_eq!((actual.x, actual.y, actual.width, actual.height), expected);
+}
+
+#[wasm_bindgen_test]
+fn test_grid_snapping() {
+    let mut canvas = CanvasSettings::default();
+    assert_eq!(canvas.grid_step(), 10.0);
+    // Snapping is off by default
+    assert_eq!(canvas.snap_offset(&[13.0], GuideOrientation::Vertical), 0.0);
+
+    canvas.grid.snap = true;
+    assert_eq!(canvas.snap_offset(&[13.0], GuideOrientation::Vertical), -3.0);
+    // The edge closest to a line decides
+    assert_eq!(canvas.snap_offset(&[13.0, 119.0], GuideOrientation::Vertical), 1.0);
+    canvas.grid.subdivisions = 0;
+    assert_eq!(canvas.grid_step(), 40.0);
+
+    // A move keeps the size; a handle drag snaps only the edges it moves
+    canvas.grid.subdivisions = 4;
+    let moved = transform_bounds(&canvas, &bounds(0.0, 0.0, 100.0, 50.0), &TransformHandle::Move, 13.0, 27.0);
+    assert_bounds(&moved, (10.0, 30.0, 100.0, 50.0));
+    let resized = transform_bounds(&canvas, &bounds(0.0, 0.0, 100.0, 50.0), &TransformHandle::BottomRight, 13.0, 27.0);
+    assert_bounds(&resized, (0.0, 0.0, 110.0, 80.0));
+    let resized = transform_bounds(&canvas, &bounds(0.0, 0.0, 100.0, 50.0), &TransformHandle::Top, 5.0, 8.0);
+    assert_bounds(&resized, (0.0, 10.0, 100.0, 40.0));
+
+    // Edges can't cross: the box keeps a minimum size
+    canvas.grid.snap = false;
+    let collapsed = transform_bounds(&canvas, &bounds(0.0, 0.0, 100.0, 50.0), &TransformHandle::Left, 150.0, 0.0);
+    assert_bounds(&collapsed, (99.0, 0.0, 1.0, 50.0));
+}
+
+#[wasm_bindgen_test]
+fn test_guide_snapping() {
+    let mut canvas = CanvasSettings::default();
+    canvas.grid.snap = true;
+    canvas.guides.push(Guide { id: "guide_1".to_string(), orientation: GuideOrientation::Vertical, position: 104.0 });
+    canvas.guides.push(Guide { id: "guide_2".to_string(), orientation: GuideOrientation::Horizontal, position: 52.0 });
+
+    // A guide within the threshold wins over the grid, on its own axis only
+    assert_eq!(canvas.snap_offset(&[100.0], GuideOrientation::Vertical), 4.0);
+    assert_eq!(canvas.snap_offset(&[100.0], GuideOrientation::Horizontal), 0.0);
+    assert_eq!(canvas.snap_offset(&[49.0], GuideOrientation::Horizontal), 3.0);
+    // Out of reach, the grid applies
+    assert_eq!(canvas.snap_offset(&[113.0], GuideOrientation::Vertical), -3.0);
+
+    // A moved box's right edge lands on the guide
+    let moved = transform_bounds(&canvas, &bounds(0.0, 0.0, 50.0, 20.0), &TransformHandle::Move, 52.0, 0.0);
+    assert_bounds(&moved, (54.0, 0.0, 50.0, 20.0));
+
+    canvas.snap_to_guides = false;
+    assert_eq!(canvas.snap_offset(&[100.0], GuideOrientation::Vertical), 0.0);
+}
+
+#[wasm_bindgen_test]
+fn test_editor_canvas_settings() {
+    let mut engine = EditorEngine::new();
+    let mut grid = engine.state.document.canvas.grid.clone();
+    grid.size = 0.0;
+    assert!(!engine.set_grid(grid.clone()).success);
+    grid.size = f64::INFINITY;
+    assert!(!engine.set_grid(grid.clone()).success);
+    grid.size = 20.0;
+    grid.snap = true;
+    assert!(engine.set_grid(grid).success);
+    assert_eq!(engine.state.document.canvas.grid_step(), 5.0);
+
+    engine.set_rulers(RulerSettings { visible: false, unit: RulerUnit::Millimeters, origin: Position { x: 10.0, y: 10.0 } });
+    assert!(!engine.state.document.canvas.rulers.visible);
+
+    let first = engine.add_guide(GuideOrientation::Vertical, 100.0).data.unwrap()["guide_id"].as_str().unwrap().to_string();
+    let second = engine.add_guide(GuideOrientation::Horizontal, 50.0).data.unwrap()["guide_id"].as_str().unwrap().to_string();
+    assert_ne!(first, second);
+    assert!(engine.move_guide(&first, 120.0).success);
+    assert!(!engine.move_guide("guide_9", 0.0).success);
+    assert!(engine.remove_guide(&first).success);
+    assert!(!engine.remove_guide(&first).success);
+    // Ids stay unique after a removal
+    let third = engine.add_guide(GuideOrientation::Vertical, 10.0).data.unwrap()["guide_id"].as_str().unwrap().to_string();
+    assert_ne!(third, second);
+    assert_eq!(engine.state.document.canvas.guides.len(), 2);
+
+    // Handle drags snap on the document's canvas and refuse locked elements
+    let id = created_id(&engine.create_element(ElementType::Container, HashMap::new()));
+    let result = engine.transform_element(&id, TransformHandle::Move, 12.0, 0.0);
+    assert_eq!(result.data.unwrap()["bounds"]["x"], 10.0);
+    engine.state.document.elements[0].locked = true;
+    assert!(!engine.transform_element(&id, TransformHandle::Move, 12.0, 0.0).success);
+    assert!(!engine.transform_element("missing", TransformHandle::Move, 12.0, 0.0).success);
+}
+
+fn asset(id: &str) -> AssetReference {
+    AssetReference {
+        id: id.to_string(),
+        name: format!("{}.png", id),
+        asset_type: AssetType::Image,
+        size: 1024,
+        hash: "abc".to_string(),
+        url: None,
+    }
+}
+
+fn opened_id(result: &EditorResult) -> String {
+    result.data.as_ref().unwrap()["document_id"].as_str().unwrap().to_string()
+}
+
+#[wasm_bindgen_test]
+fn test_workspace_documents() {
+    let mut engine = EditorEngine::new();
+    engine.state.document.metadata.title = "First".to_string();
+    let first = created_id(&engine.create_element(ElementType::Text, HashMap::new()));
+
+    assert!(!engine.open_document("not json").success);
+    assert_eq!(engine.workspace.order, vec!["document_1"]);
+    assert_eq!(engine.state.document.elements.len(), 1);
+
+    let second = opened_id(&engine.open_document(r#"{"metadata": {"title": "Second"}, "content": {"html": "<p>Hi</p>"}}"#));
+    assert_eq!(engine.workspace.active_document, second);
+    assert_eq!(engine.state.document.elements[0].id, "root");
+    let listed = engine.list_documents().data.unwrap();
+    assert_eq!(listed[0]["title"], "First");
+    assert_eq!(listed[1]["title"], "Second");
+    assert_eq!(listed[1]["active"], true);
+
+    // Each document keeps its own elements and history
+    engine.update_element("root", properties(&[("color", serde_json::json!("red"))]));
+    assert!(engine.switch_document("document_1").success);
+    assert_eq!(engine.state.document.elements[0].id, first);
+    assert_eq!(engine.state.history.operations.len(), 1);
+    assert!(!engine.switch_document("document_9").success);
+    engine.switch_document(&second);
+    engine.undo();
+    assert!(!engine.state.document.elements[0].properties.contains_key("color"));
+
+    // Closing the active document activates its neighbour
+    let third = opened_id(&engine.open_document("{}"));
+    engine.switch_document(&second);
+    let closed = engine.close_document(&second);
+    assert_eq!(closed.data.unwrap()["active_document"], serde_json::json!(third));
+    assert_eq!(engine.workspace.order, vec!["document_1".to_string(), third.clone()]);
+    assert!(!engine.close_document(&second).success);
+
+    // Closing a background document leaves the active one alone, and the last one is replaced
+    engine.close_document("document_1");
+    assert_eq!(engine.workspace.active_document, third);
+    engine.close_document(&third);
+    assert_eq!(engine.workspace.order.len(), 1);
+    assert_ne!(engine.workspace.active_document, third);
+    assert!(engine.state.document.elements.is_empty());
+    assert!(engine.workspace.documents.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_workspace_asset_library() {
+    let mut engine = EditorEngine::new();
+    engine.add_library_asset(asset("logo"));
+    assert!(engine.state.document.assets.is_empty());
+    assert!(engine.use_library_asset("logo").success);
+    assert!(engine.state.document.assets.contains_key("logo"));
+    assert!(!engine.use_library_asset("missing").success);
+
+    // Another document can use the same library asset
+    engine.open_document("{}");
+    assert!(engine.state.document.assets.is_empty());
+    assert!(engine.use_library_asset("logo").success);
+}
+
+#[wasm_bindgen_test]
+fn test_copy_and_paste_between_documents() {
+    let mut engine = EditorEngine::new();
+    engine.add_library_asset(asset("logo"));
+    engine.add_library_asset(asset("unused"));
+    engine.use_library_asset("logo");
+    engine.use_library_asset("unused");
+    let parent = created_id(&engine.create_element(ElementType::Container, HashMap::new()));
+    let child = created_id(&engine.create_element(ElementType::Image, properties(&[("src", serde_json::json!("logo"))])));
+    engine.state.document.elements[0].children = vec![child.clone()];
+    engine.state.document.elements[1].parent = Some(parent.clone());
+    engine.set_keyframe(&child, 0.0, properties(&[("opacity", serde_json::json!(0.0))]));
+
+    assert!(!engine.copy_elements(&["missing".to_string()]).success);
+    // Copying the parent brings its child, the asset the child uses and the child's timeline
+    let copied = engine.copy_elements(std::slice::from_ref(&parent));
+    let fragment: ClipboardFragment = serde_json::from_value(copied.data.unwrap()).unwrap();
+    assert_eq!(fragment.format, CLIPBOARD_FORMAT);
+    assert_eq!(fragment.elements.len(), 2);
+    assert_eq!(fragment.assets.len(), 1);
+    assert_eq!(fragment.assets[0].id, "logo");
+    assert_eq!(fragment.timelines.len(), 1);
+
+    // Pasting into the same document offsets the root and gives fresh ids
+    let pasted = engine.paste_fragment(None).data.unwrap();
+    let ids: Vec<String> = serde_json::from_value(pasted["element_ids"].clone()).unwrap();
+    assert_eq!(ids, vec!["element_2".to_string(), "element_3".to_string()]);
+    let root = &engine.state.document.elements[2];
+    assert_eq!((root.bounds.x, root.bounds.y), (10.0, 10.0));
+    assert_eq!(root.children, vec!["element_3".to_string()]);
+    assert_eq!(engine.state.document.elements[3].parent.as_deref(), Some("element_2"));
+    assert!(engine.state.document.timelines.contains_key("element_3"));
+    assert_eq!(engine.state.selection.selected_elements, ids);
+
+    // Another document gets the copies where they were, and the assets they need
+    engine.open_document("{}");
+    assert!(engine.paste_fragment(None).success);
+    assert_eq!(engine.state.document.elements[0].bounds.x, 0.0);
+    assert!(engine.state.document.assets.contains_key("logo"));
+    assert!(!engine.state.document.assets.contains_key("unused"));
+
+    let mut foreign = fragment.clone();
+    foreign.format = "text/plain".to_string();
+    assert!(!engine.paste_fragment(Some(foreign)).success);
+    engine.workspace.clipboard = None;
+    assert!(!engine.paste_fragment(None).success);
+    // A fragment from the system clipboard pastes without the workspace clipboard
+    assert!(engine.paste_fragment(Some(fragment)).success);
+}
+
+fn record(sequence: u64, entry: JournalEntry) -> JournalRecord {
+    JournalRecord { sequence, timestamp: 0.0, entry }
+}
+
+#[wasm_bindgen_test]
+fn test_journal_compaction() {
+    let remove = |id: &str| JournalEntry::RemoveElement { element_id: id.to_string() };
+    let records = vec![
+        record(0, remove("element_0")),
+        record(3, remove("element_1")),
+        record(1, JournalEntry::PutAsset { asset: asset("logo") }),
+        record(2, remove("element_0")),
+        record(4, JournalEntry::PutCanvas { canvas: CanvasSettings::default() }),
+    ];
+    // One record per element, asset and canvas: the latest, in sequence order
+    let sequences: Vec<u64> = compact_records(records).iter().map(|r| r.sequence).collect();
+    assert_eq!(sequences, vec![1, 2, 3, 4]);
+
+    // Blank and unreadable lines are skipped and counted
+    let blob = journal_blob(&[record(0, remove("element_0")), record(1, remove("element_1"))]);
+    assert_eq!(blob.lines().count(), 2);
+    let (parsed, skipped) = parse_journal(&format!("{}\n{{\"sequence\": 2, \"timest", blob));
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(skipped, 1);
+    assert!(matches!(&parsed[1].entry, JournalEntry::RemoveElement { element_id } if element_id == "element_1"));
+}
+
+#[wasm_bindgen_test]
+fn test_autosave_delta() {
+    let mut engine = EditorEngine::new();
+    let id = created_id(&engine.create_element(ElementType::Text, HashMap::new()));
+    engine.update_element(&id, properties(&[("text", serde_json::json!("a"))]));
+    engine.update_element(&id, properties(&[("text", serde_json::json!("ab"))]));
+    engine.add_guide(GuideOrientation::Vertical, 40.0);
+
+    // Three element records compact to the latest one
+    let delta = engine.take_autosave_delta();
+    let (records, _) = parse_journal(&delta);
+    assert_eq!(records.len(), 2);
+    match &records[0].entry {
+        JournalEntry::PutElement { element, .. } => assert_eq!(element.properties["text"], "ab"),
+        other => panic!("expected the element, got {:?}", other),
+    }
+    assert!(matches!(records[1].entry, JournalEntry::PutCanvas { .. }));
+    assert!(engine.take_autosave_delta().is_empty());
+
+    // Undo is journaled like any other edit; saving drops what is pending
+    engine.undo();
+    assert_eq!(engine.state.journal.pending.len(), 1);
+    engine.mark_saved();
+    assert!(engine.take_autosave_delta().is_empty());
+    assert_eq!(engine.state.journal.next_sequence, 5);
+}
+
+#[wasm_bindgen_test]
+fn test_recover_from_journal() {
+    let base = r#"{"metadata": {"title": "Saved"}, "content": {"html": "<p>Hi</p>"}}"#;
+    let mut engine = EditorEngine::new();
+    engine.load_document(base);
+    engine.mark_saved();
+    engine.update_element("root", properties(&[("color", serde_json::json!("red"))]));
+    let id = created_id(&engine.create_element(ElementType::Animation, HashMap::new()));
+    engine.set_keyframe(&id, 100.0, properties(&[("opacity", serde_json::json!(1.0))]));
+    let removed = created_id(&engine.create_element(ElementType::Text, HashMap::new()));
+    engine.delete_element(&removed);
+    engine.add_library_asset(asset("logo"));
+    engine.use_library_asset("logo");
+    engine.add_guide(GuideOrientation::Horizontal, 25.0);
+    // The crash cut the last line short
+    let journal = engine.take_autosave_delta() + "{\"sequence\": 99";
+    let expected = snapshot(&engine);
+
+    let mut recovered = EditorEngine::new();
+    assert!(!recovered.recover_from_journal("not json", &journal).success);
+    let result = recovered.recover_from_journal(base, &journal);
+    assert!(result.success);
+    assert_eq!(result.data.unwrap()["skipped"], 1);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(snapshot(&recovered), expected);
+    assert_eq!(recovered.state.document.metadata.title, "Saved");
+    assert!(recovered.state.document.assets.contains_key("logo"));
+    assert_eq!(recovered.state.document.canvas.guides.len(), 1);
+
+    // New records continue the recovered sequence
+    recovered.update_element("root", properties(&[("color", serde_json::json!("blue"))]));
+    let (records, _) = parse_journal(&journal);
+    let last = records.iter().map(|r| r.sequence).max().unwrap();
+    assert_eq!(recovered.state.journal.pending[0].sequence, last + 1);
+}
+
+fn script(name: &str, content: &str, dependencies: &[&str], exports: &[&str]) -> (String, ScriptModule) {
+    (name.to_string(), ScriptModule {
+        name: name.to_string(),
+        content: content.to_string(),
+        module_type: ScriptType::JavaScript,
+        dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+        exports: exports.iter().map(|e| e.to_string()).collect(),
+    })
+}
+
+#[wasm_bindgen_test]
+fn test_script_dependency_order() {
+    let mut scripts: HashMap<String, ScriptModule> = [
+        script("app", "", &["charts", "util"], &[]),
+        script("charts", "", &["util"], &[]),
+        script("util", "", &[], &[]),
+    ].into_iter().collect();
+    assert_eq!(script_order(&scripts).unwrap(), vec!["util", "charts", "app"]);
+    assert!(missing_script_dependencies(&scripts).is_empty());
+
+    // Missing modules are reported per dependent and don't stop ordering
+    let (name, module) = script("extra", "", &["gone", "util"], &[]);
+    scripts.insert(name, module);
+    assert_eq!(missing_script_dependencies(&scripts), vec![("extra".to_string(), "gone".to_string())]);
+    assert_eq!(script_order(&scripts).unwrap().last().unwrap(), "extra");
+
+    // A cycle comes back as the path that closes it
+    scripts.get_mut("util").unwrap().dependencies = vec!["app".to_string()];
+    assert_eq!(script_order(&scripts).unwrap_err(), vec!["app", "charts", "util", "app"]);
+}
+
+#[wasm_bindgen_test]
+fn test_unused_script_exports() {
+    assert!(mentions_identifier("format(value)", "format"));
+    assert!(mentions_identifier("x = $format;", "$format"));
+    assert!(!mentions_identifier("formatDate(value)", "format"));
+    assert!(!mentions_identifier("my_format(value)", "format"));
+
+    let scripts: HashMap<String, ScriptModule> = [
+        script("app", "render(formatDate(now))", &["util"], &["main"]),
+        script("util", "", &[], &["formatDate", "format", "parse"]),
+    ].into_iter().collect();
+    // Entry points keep every export
+    let unused = unused_script_exports(&scripts);
+    assert_eq!(unused.len(), 1);
+    assert_eq!(unused["util"], vec!["format", "parse"]);
+}
+
+#[wasm_bindgen_test]
+fn test_bundle_scripts() {
+    let mut engine = EditorEngine::new();
+    engine.state.document.scripts = [
+        script("app", "util.formatDate(now)", &["util"], &["main"]),
+        script("util", "export function formatDate() {}", &[], &["formatDate", "parse"]),
+        script("engine", "", &[], &[]),
+    ].into_iter().collect();
+    engine.state.document.scripts.get_mut("engine").unwrap().module_type = ScriptType::WASM;
+
+    let bundle: ScriptBundle = serde_json::from_value(engine.bundle_scripts().data.unwrap()).unwrap();
+    assert_eq!(bundle.order, vec!["util", "app", "engine"]);
+    // WASM modules are loaded separately, so only the script modules are in the content, in order
+    assert_eq!(bundle.content, "// module: util\nexport function formatDate() {}\n// module: app\nutil.formatDate(now)\n");
+    assert_eq!(bundle.exports["util"], vec!["formatDate"]);
+    assert_eq!(bundle.exports["app"], vec!["main"]);
+    assert_eq!(bundle.pruned_exports["util"], vec!["parse"]);
+
+    // Missing and cyclic dependencies fail the bundle and show up in validation
+    engine.state.document.scripts.get_mut("util").unwrap().dependencies = vec!["app".to_string(), "gone".to_string()];
+    let result = engine.bundle_scripts();
+    assert!(!result.success);
+    assert_eq!(result.errors.len(), 2);
+    assert!(result.errors[0].contains("missing module gone"));
+    assert!(result.errors[1].contains("app -> util -> app"));
+    let report = engine.validate_document();
+    assert!(!report.is_valid);
+    assert!(report.errors.iter().any(|e| e.message.contains("cycle")));
+}
+
+fn column_types(data: &serde_json::Value) -> Vec<(String, ColumnType)> {
+    infer_columns(&sample_rows(data, CHART_SAMPLE_ROWS)).into_iter().map(|c| (c.name, c.column_type)).collect()
+}
+
+#[wasm_bindgen_test]
+fn test_chart_column_inference() {
+    // Sampling spreads over the data and never returns more than asked
+    let rows = serde_json::json!((0..1000).collect::<Vec<i32>>());
+    let sampled = sample_rows(&rows, 200);
+    assert_eq!(sampled.len(), 200);
+    assert_eq!(sampled[1], 5);
+    assert_eq!(sample_rows(&serde_json::json!([1, 2]), 200).len(), 2);
+    assert!(sample_rows(&serde_json::json!({"rows": []}), 200).is_empty());
+
+    assert!(looks_like_date("2024-03-01"));
+    assert!(looks_like_date("2024-03-01T10:00:00Z"));
+    assert!(!looks_like_date("2024-3-1"));
+    assert!(!looks_like_date("March 2024"));
+
+    // Majority wins, numeric strings count as numbers, and nulls don't vote, so a column is
+    // placed where its first value appears
+    let data = serde_json::json!([
+        {"day": "2024-03-01", "sales": 10, "region": "north", "open": true, "note": null},
+        {"day": "2024-03-02", "sales": "12.5", "region": "south", "open": false, "note": null},
+        {"day": "2024-03-03", "sales": "n/a", "region": "east", "open": true, "note": "late"},
+    ]);
+    assert_eq!(column_types(&data), vec![
+        ("day".to_string(), ColumnType::Date),
+        ("open".to_string(), ColumnType::Boolean),
+        ("region".to_string(), ColumnType::Text),
+        ("sales".to_string(), ColumnType::Number),
+        ("note".to_string(), ColumnType::Text),
+    ]);
+}
+
+#[wasm_bindgen_test]
+fn test_chart_suggestions() {
+    let suggest = |data: serde_json::Value| suggest_chart(infer_columns(&sample_rows(&data, CHART_SAMPLE_ROWS)));
+
+    let line = suggest(serde_json::json!([{"day": "2024-03-01", "region": "north", "sales": 1, "costs": 2}]));
+    assert_eq!(line.chart_type, ChartType::Line);
+    assert_eq!(line.x_field.as_deref(), Some("day"));
+    let fields: Vec<&str> = line.series.iter().map(|s| s.field.as_str()).collect();
+    assert_eq!(fields, vec!["costs", "sales"]);
+    assert_eq!(line.series[0].color, CHART_PALETTE[0]);
+
+    let bar = suggest(serde_json::json!([{"region": "north", "sales": 1}]));
+    assert_eq!(bar.chart_type, ChartType::Bar);
+    assert_eq!(bar.x_field.as_deref(), Some("region"));
+
+    let scatter = suggest(serde_json::json!([{"height": 1.8, "weight": 80}]));
+    assert_eq!(scatter.chart_type, ChartType::Scatter);
+    assert_eq!(scatter.x_field.as_deref(), Some("height"));
+    assert_eq!(scatter.series.len(), 1);
+
+    let single = suggest(serde_json::json!([{"value": 3}]));
+    assert_eq!((single.chart_type, single.x_field, single.series.len()), (ChartType::Bar, None, 1));
+
+    // Wide data is capped at MAX_SUGGESTED_SERIES
+    let wide: serde_json::Map<String, serde_json::Value> = (0..8).map(|i| (format!("v{}", i), serde_json::json!(i))).collect();
+    let wide = suggest(serde_json::json!([{"label": "a"}, wide]));
+    assert_eq!(wide.series.len(), MAX_SUGGESTED_SERIES);
+}
+
+#[wasm_bindgen_test]
+fn test_chart_preview_svg() {
+    let rows = [serde_json::json!({"a": 1, "b": 3}), serde_json::json!({"a": "2", "b": -1})];
+    let rows: Vec<&serde_json::Value> = rows.iter().collect();
+    let mut draft = ChartDraft {
+        id: "chart_draft_1".to_string(),
+        chart_type: ChartType::Bar,
+        data_source_id: "sales".to_string(),
+        x_field: None,
+        series: vec![
+            SeriesMapping { field: "a".to_string(), name: "A".to_string(), color: "#111111".to_string() },
+            SeriesMapping { field: "b".to_string(), name: "B".to_string(), color: "#222222".to_string() },
+        ],
+        title: None,
+        width: 200.0,
+        height: 100.0,
+    };
+
+    let bars = render_chart_preview(&draft, &rows);
+    assert!(bars.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"200\" height=\"100\""));
+    assert_eq!(bars.matches("<rect").count(), 4);
+    assert_eq!(bars.matches("fill=\"#222222\"").count(), 2);
+
+    draft.chart_type = ChartType::Line;
+    assert_eq!(render_chart_preview(&draft, &rows).matches("<polyline").count(), 2);
+    draft.chart_type = ChartType::Area;
+    let area = render_chart_preview(&draft, &rows);
+    assert_eq!(area.matches("<polygon").count(), 2);
+    draft.chart_type = ChartType::Scatter;
+    assert_eq!(render_chart_preview(&draft, &rows).matches("<circle").count(), 4);
+
+    // Pies use the first series and skip values that aren't positive
+    draft.chart_type = ChartType::Pie;
+    let pie = render_chart_preview(&draft, &rows);
+    assert_eq!(pie.matches("<path").count(), 2);
+    // Types without a preview of their own show as bars
+    draft.chart_type = ChartType::Sankey;
+    assert_eq!(render_chart_preview(&draft, &rows).matches("<rect").count(), 4);
+}
+
+#[wasm_bindgen_test]
+fn test_chart_drafts() {
+    let mut engine = EditorEngine::new();
+    assert!(!engine.suggest_chart("sales").success);
+    assert!(!engine.create_chart_draft("sales").success);
+    engine.set_data_source("sales", serde_json::json!([
+        {"month": "2024-01-01", "revenue": 10, "costs": 4},
+        {"month": "2024-02-01", "revenue": 14, "costs": 5},
+    ]));
+    assert_eq!(engine.suggest_chart("sales").data.unwrap()["chart_type"], "Line");
+
+    let mut draft: ChartDraft = serde_json::from_value(engine.create_chart_draft("sales").data.unwrap()).unwrap();
+    assert_eq!(draft.id, "chart_draft_1");
+    assert_eq!(draft.x_field.as_deref(), Some("month"));
+    let second: ChartDraft = serde_json::from_value(engine.create_chart_draft("sales").data.unwrap()).unwrap();
+    assert_ne!(second.id, draft.id);
+
+    // Updates must name fields the data source has
+    draft.series.truncate(1);
+    draft.series[0].field = "profit".to_string();
+    let rejected = engine.update_chart_draft(draft.clone());
+    assert_eq!(rejected.errors, vec!["Field profit not found in sales".to_string()]);
+    draft.series[0].field = "revenue".to_string();
+    draft.chart_type = ChartType::Bar;
+    draft.width = 640.0;
+    draft.title = Some("Revenue".to_string());
+    assert!(engine.update_chart_draft(draft.clone()).success);
+    let mut unknown = draft.clone();
+    unknown.id = "chart_draft_9".to_string();
+    assert_eq!(engine.update_chart_draft(unknown).errors, vec!["Chart draft not found".to_string()]);
+
+    let preview = engine.preview_chart_draft(&draft.id);
+    match &preview.dom_operations[0] {
+        DOMOperation::Update { element_id, attributes, content } => {
+            assert_eq!(element_id, "chart_draft_1_preview");
+            assert_eq!(attributes["data-chart-type"], "Bar");
+            assert_eq!(content.as_ref().unwrap().matches("<rect").count(), 2);
+        }
+        other => panic!("expected an update, got {:?}", other),
+    }
+    assert!(engine.preview_chart_draft("chart_draft_9").dom_operations.is_empty());
+
+    // Committing makes a chart element at the draft's size in the runtime chart format
+    let id = created_id(&engine.commit_chart_draft(&draft.id));
+    assert!(!engine.state.chart_drafts.contains_key(&draft.id));
+    assert!(!engine.commit_chart_draft(&draft.id).success);
+    let element = &engine.state.document.elements[0];
+    assert_eq!((element.bounds.width, element.bounds.height), (640.0, 300.0));
+    let chart = &element.properties["chart"];
+    assert_eq!(chart["id"], "chart_draft_1_chart");
+    assert_eq!(chart["title"], "Revenue");
+    assert_eq!(chart["series"][0]["data_field"], "revenue");
+
+    // Undo and redo bring the element back at the same size
+    engine.undo();
+    assert!(engine.state.document.elements.is_empty());
+    engine.redo();
+    assert_eq!(engine.state.document.elements[0].id, id);
+    assert_eq!(engine.state.document.elements[0].bounds.width, 640.0);
+}
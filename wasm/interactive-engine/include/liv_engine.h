@@ -0,0 +1,50 @@
+#ifndef LIV_ENGINE_H
+#define LIV_ENGINE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct InteractiveEngine InteractiveEngine;
+
+// Bytes owned by the engine library until passed to liv_buffer_free. Functions returning render
+// updates fill it with a liv.RenderUpdate flatbuffer, see liv_update.fbs.
+typedef struct LivBuffer {
+  uint8_t *data;
+  size_t len;
+} LivBuffer;
+
+// Message of the last failed call on this thread, or null; valid until the next failure. A call
+// that panicked inside the engine fails with "Engine panicked: ..."; free that engine, as its
+// state may be incomplete.
+const char *liv_last_error(void);
+
+// A new engine from WASMPermissions JSON; free it with liv_engine_free
+InteractiveEngine *liv_engine_new(const char *permissions_json);
+
+void liv_engine_free(InteractiveEngine *engine);
+
+// 0 once the whole document is loaded, -1 on failure
+int32_t liv_engine_load_document(InteractiveEngine *engine, const char *document_json);
+
+// Takes an InteractionEvent as JSON
+LivBuffer liv_engine_process_interaction(InteractiveEngine *engine, const char *event_json);
+
+LivBuffer liv_engine_render_frame(InteractiveEngine *engine, double timestamp);
+
+// Publishes on the event bus, as from the page's scripts; `source` may be null
+LivBuffer liv_engine_emit_event(InteractiveEngine *engine,
+                                const char *event_name,
+                                const char *payload_json,
+                                const char *source);
+
+// 0 once the source has the new data, -1 on failure
+int32_t liv_engine_update_data_source(InteractiveEngine *engine,
+                                      const char *data_source_id,
+                                      const char *data_json);
+
+// The event bus log as a JSON array of BusEventRecord
+LivBuffer liv_engine_event_log(InteractiveEngine *engine);
+
+void liv_buffer_free(LivBuffer buffer);
+
+#endif  // LIV_ENGINE_H
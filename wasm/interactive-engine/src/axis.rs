@@ -0,0 +1,222 @@
+// Ranges and ticks for chart axes. An axis runs from its min_value to its max_value when they are
+// set, and otherwise over the data, widened out to the nearest ticks. Linear ticks step by 1, 2,
+// 2.5 or 5 times a power of ten, about tick_count of them; logarithmic axes cover whole decades
+// of the positive values; time axes take epoch milliseconds and step by seconds, minutes, hours,
+// days or weeks. A category axis has one slot per label: rows are numbered from 0 and sit in the
+// middle of their slot, so the range runs from -0.5 to the row count less a half.
+//
+// Labels follow the axis' format: "percent" (fractions shown as percentages), "compact" (1.2K,
+// 3.4M) or plain numbers with as many decimals as the tick step needs.
+
+use super::{format_date, group_thousands, ChartAxis, ScaleType};
+
+const SECOND: f64 = 1000.0;
+const MINUTE: f64 = 60.0 * SECOND;
+const HOUR: f64 = 60.0 * MINUTE;
+const DAY: f64 = 24.0 * HOUR;
+const WEEK: f64 = 7.0 * DAY;
+
+// Time steps in milliseconds; longer ranges step by whole weeks
+const TIME_STEPS: [f64; 15] = [
+    SECOND, 5.0 * SECOND, 15.0 * SECOND, 30.0 * SECOND,
+    MINUTE, 5.0 * MINUTE, 15.0 * MINUTE, 30.0 * MINUTE,
+    HOUR, 3.0 * HOUR, 6.0 * HOUR, 12.0 * HOUR,
+    DAY, 2.0 * DAY, WEEK,
+];
+
+// Ticks past this many are dropped, whatever tick_count asks for
+pub const MAX_TICKS: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AxisTick {
+    pub value: f64,
+    pub label: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AxisScale {
+    pub scale_type: ScaleType,
+    // min < max
+    pub min: f64,
+    pub max: f64,
+    pub ticks: Vec<AxisTick>,
+}
+
+impl AxisScale {
+    // The axis over `values`, leaving out values that aren't finite, and values a logarithmic
+    // axis can't show. `include_zero` keeps 0 in a linear range, for bars and areas. Category
+    // axes are fitted like linear ones; see `categories` for slots.
+    pub fn fit(axis: &ChartAxis, values: &[f64], include_zero: bool) -> Self {
+        let count = axis.tick_count.unwrap_or(5).clamp(2, MAX_TICKS as u32) as f64;
+        let values = values.iter().copied().filter(|value| value.is_finite());
+        match axis.scale_type {
+            ScaleType::Logarithmic => Self::logarithmic(axis, values.filter(|value| *value > 0.0), count),
+            ScaleType::Time => Self::time(axis, values, count),
+            ScaleType::Linear | ScaleType::Category => Self::linear(axis, values.chain(include_zero.then_some(0.0)), count),
+        }
+    }
+
+    // One slot per label, with a tick in the middle of each
+    pub fn categories(labels: &[String]) -> Self {
+        Self {
+            scale_type: ScaleType::Category,
+            min: -0.5,
+            max: labels.len().max(1) as f64 - 0.5,
+            ticks: labels.iter().enumerate().map(|(index, label)| AxisTick { value: index as f64, label: label.clone() }).collect(),
+        }
+    }
+
+    // Where `value` sits along the axis: 0 at min and 1 at max, outside 0..1 for values outside
+    // the range. Values a logarithmic axis can't show sit at its min.
+    pub fn fraction(&self, value: f64) -> f64 {
+        match self.scale_type {
+            ScaleType::Logarithmic => {
+                let value = if value > 0.0 { value } else { self.min };
+                (value.log10() - self.min.log10()) / (self.max.log10() - self.min.log10())
+            }
+            _ => (value - self.min) / (self.max - self.min),
+        }
+    }
+
+    // Where bars and areas start: 0 kept inside the range, or the min of a logarithmic axis
+    pub fn baseline(&self) -> f64 {
+        match self.scale_type {
+            ScaleType::Logarithmic => self.min,
+            _ => 0.0_f64.clamp(self.min, self.max),
+        }
+    }
+
+    fn linear(axis: &ChartAxis, values: impl Iterator<Item = f64>, count: f64) -> Self {
+        let (low, high) = widen(axis, data_range(values, bounds(axis, |_| true), (0.0, 1.0)), |value| if value == 0.0 { 1.0 } else { value.abs() / 10.0 });
+        let step = nice_step((high - low) / (count - 1.0));
+        let (min, max) = snap(axis, low, high, step);
+        let ticks = stepped(min, max, step).map(|value| AxisTick { value, label: format_number(axis, value, step) }).collect();
+        Self { scale_type: ScaleType::Linear, min, max, ticks }
+    }
+
+    fn logarithmic(axis: &ChartAxis, values: impl Iterator<Item = f64>, count: f64) -> Self {
+        let (low, high) = data_range(values, bounds(axis, |value| value > 0.0), (1.0, 10.0));
+        let (low_decade, mut high_decade) = (low.log10().floor(), high.log10().ceil());
+        if high_decade <= low_decade {
+            high_decade = low_decade + 1.0;
+        }
+        let (min, max) = bounds(axis, |value| value > 0.0);
+        let min = min.unwrap_or(10_f64.powf(low_decade));
+        let max = max.filter(|max| *max > min).unwrap_or(10_f64.powf(high_decade).max(min * 10.0));
+
+        // Every decade, or every second, third... when there are more decades than ticks
+        let stride = ((high_decade - low_decade) / (count - 1.0)).ceil().max(1.0);
+        let ticks = stepped(low_decade, high_decade, stride)
+            .map(|decade| 10_f64.powf(decade))
+            .filter(|value| (min..=max).contains(value))
+            .map(|value| AxisTick { value, label: format_number(axis, value, value) })
+            .collect();
+        Self { scale_type: ScaleType::Logarithmic, min, max, ticks }
+    }
+
+    fn time(axis: &ChartAxis, values: impl Iterator<Item = f64>, count: f64) -> Self {
+        let (low, high) = widen(axis, data_range(values, bounds(axis, |_| true), (0.0, DAY)), |_| DAY);
+        let raw = (high - low) / (count - 1.0);
+        let step = TIME_STEPS.into_iter().find(|step| *step >= raw).unwrap_or_else(|| (raw / WEEK).ceil() * WEEK);
+        let (min, max) = snap(axis, low, high, step);
+        let ticks = stepped(min, max, step)
+            .map(|value| {
+                let time_of_day = value.rem_euclid(DAY);
+                let (hours, minutes, seconds) = ((time_of_day / HOUR) as u32, (time_of_day % HOUR / MINUTE) as u32, (time_of_day % MINUTE / SECOND) as u32);
+                let label = match step {
+                    step if step >= DAY => format_date(value),
+                    step if step >= MINUTE => format!("{:02}:{:02}", hours, minutes),
+                    _ => format!("{:02}:{:02}:{:02}", hours, minutes, seconds),
+                };
+                AxisTick { value, label }
+            })
+            .collect();
+        Self { scale_type: ScaleType::Time, min, max, ticks }
+    }
+}
+
+// The axis' finite min_value and max_value that pass `valid`
+fn bounds(axis: &ChartAxis, valid: impl Fn(f64) -> bool) -> (Option<f64>, Option<f64>) {
+    let valid = |value: &f64| value.is_finite() && valid(*value);
+    (axis.min_value.filter(valid), axis.max_value.filter(valid))
+}
+
+fn set(bound: Option<f64>) -> bool {
+    bound.is_some_and(f64::is_finite)
+}
+
+// Lowest and highest value with the bounds applied; `empty` when there are no values
+fn data_range(values: impl Iterator<Item = f64>, bounds: (Option<f64>, Option<f64>), empty: (f64, f64)) -> (f64, f64) {
+    let (low, high) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| (low.min(value), high.max(value)));
+    let (low, high) = if low <= high { (low, high) } else { empty };
+    let (low, high) = (bounds.0.unwrap_or(low), bounds.1.unwrap_or(high));
+    (low.min(high), low.max(high))
+}
+
+// A range of one value grows by `pad` of it on the sides the axis leaves open
+fn widen(axis: &ChartAxis, (low, high): (f64, f64), pad: impl Fn(f64) -> f64) -> (f64, f64) {
+    if low < high {
+        return (low, high);
+    }
+    let pad = pad(low);
+    match (set(axis.min_value), set(axis.max_value)) {
+        (false, true) => (low - pad, high),
+        (false, false) => (low - pad, high + pad),
+        (true, _) => (low, high + pad),
+    }
+}
+
+// Open ends out to the next multiple of `step`; set ones stay where they are
+fn snap(axis: &ChartAxis, low: f64, high: f64, step: f64) -> (f64, f64) {
+    let min = if set(axis.min_value) { low } else { (low / step).floor() * step };
+    let max = if set(axis.max_value) { high } else { (high / step).ceil() * step };
+    (min, max)
+}
+
+// Multiples of `step` from `low` to `high`
+fn stepped(low: f64, high: f64, step: f64) -> impl Iterator<Item = f64> {
+    let first = (low / step - 1e-9).ceil();
+    (0..MAX_TICKS)
+        .map(move |index| (first + index as f64) * step + 0.0)
+        .take_while(move |value| *value <= high + step * 1e-9)
+}
+
+// The smallest of 1, 2, 2.5, 5 and 10 times a power of ten at least `raw`
+fn nice_step(raw: f64) -> f64 {
+    if !(raw.is_finite() && raw > 0.0) {
+        return 1.0;
+    }
+    let magnitude = 10_f64.powf(raw.log10().floor());
+    [1.0, 2.0, 2.5, 5.0, 10.0].into_iter()
+        .map(|multiple| multiple * magnitude)
+        .find(|step| *step >= raw * (1.0 - 1e-9))
+        .unwrap_or(10.0 * magnitude)
+}
+
+// Decimals that show multiples of `step` exactly
+fn decimals(step: f64) -> usize {
+    (0..=10)
+        .find(|decimals| {
+            let scaled = step.abs() * 10_f64.powi(*decimals as i32);
+            (scaled - scaled.round()).abs() < 1e-6 * scaled.max(1.0)
+        })
+        .unwrap_or(10)
+}
+
+// A tick value as the axis' format shows it, with the decimals `step` needs; compact values
+// take the decimals of their own scaled value
+fn format_number(axis: &ChartAxis, value: f64, step: f64) -> String {
+    let sign = if value < 0.0 { "-" } else { "" };
+    let magnitude = value.abs();
+    let number = match axis.format.as_deref() {
+        Some("percent") => format!("{}%", group_thousands(&format!("{:.*}", decimals(step * 100.0), magnitude * 100.0))),
+        Some("compact") => {
+            let (scale, unit) = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")].into_iter()
+                .find(|(scale, _)| magnitude >= *scale)
+                .unwrap_or((1.0, ""));
+            format!("{:.*}{}", decimals(magnitude / scale).min(2), magnitude / scale, unit)
+        }
+        _ => group_thousands(&format!("{:.*}", decimals(step), magnitude)),
+    };
+    format!("{}{}", sign, number)
+}
@@ -0,0 +1,3550 @@
+// wasm-bindgen exports: the JSON string API over the global engine instance, and host callbacks.
+// Left out of native builds, which use InteractiveEngine directly.
+
+use super::*;
+use interpolate::InterpolatorKind;
+
+// Error for bindings whose subsystem was left out of the build
+#[cfg(not(feature = "editor-interop"))]
+fn feature_disabled(feature: &str) -> JsValue {
+    JsValue::from_str(&WASMError::from(FeatureError::Disabled { feature: feature.to_string() }).message)
+}
+
+// Export types for JavaScript interop
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console)]
+    fn log(s: &str);
+}
+
+// WASM-bindgen interface functions
+#[wasm_bindgen]
+pub fn init_interactive_engine(permissions_json: &str) -> Result<(), JsValue> {
+    let permissions: WASMPermissions = serde_json::from_str(permissions_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse permissions: {}", e)))?;
+    
+    let engine = InteractiveEngine::new(permissions)
+        .map_err(|e| JsValue::from_str(&format!("Failed to create engine: {}", e.message)))?;
+    install_engine(engine)
+        .map_err(|e| JsValue::from_str(&format!("Failed to create engine: {}", e.message)))?;
+    
+    log("LIV Interactive Engine initialized with security context");
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn process_interaction(event_json: &str) -> Result<String, JsValue> {
+    let event: InteractionEvent = serde_json::from_str(event_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse event: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.process_interaction(event)
+            .map_err(|e| JsValue::from_str(&format!("Interaction failed: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Queues the event for the next render_frame, which delivers queued events in timestamp order
+#[wasm_bindgen]
+pub fn queue_interaction(event_json: &str) -> Result<(), JsValue> {
+    let event: InteractionEvent = serde_json::from_str(event_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse event: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.queue_interaction(event)
+            .map_err(|e| JsValue::from_str(&format!("Interaction failed: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_event_queue_config(config_json: &str) -> Result<(), JsValue> {
+    let config: EventQueueConfig = serde_json::from_str(config_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse event queue config: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_event_queue_config(config);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn render_frame(timestamp: f64) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let result = engine.render_frame(timestamp);
+        deliver_telemetry(engine);
+        let render_update = result
+            .map_err(|e| JsValue::from_str(&format!("Render failed: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Confirms every update up to `sequence` was applied
+#[wasm_bindgen]
+pub fn ack(sequence: u64) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.ack_update(sequence)
+            .map_err(|e| JsValue::from_str(&format!("Ack failed: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_sync_state() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_sync_state())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize sync state: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a RenderUpdate with full_resync set that rebuilds the whole DOM
+#[wasm_bindgen]
+pub fn request_full_resync() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        serde_json::to_string(&engine.request_full_resync())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn update_data(data_source_id: &str, data: &[u8]) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.update_data(data_source_id, data)
+            .map_err(|e| JsValue::from_str(&format!("Data update failed: {}", e.message)))?;
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn update_data_f64(data_source_id: &str, values: &[f64]) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.update_data_f64(data_source_id, values)
+            .map_err(|e| JsValue::from_str(&format!("Data update failed: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Columnar batch: `values` holds each named column back to back, all of equal length
+#[wasm_bindgen]
+pub fn update_data_columns(data_source_id: &str, column_names: Vec<String>, values: &[f64]) -> Result<(), JsValue> {
+    if column_names.is_empty() || values.len() % column_names.len() != 0 {
+        return Err(JsValue::from_str("Column data length must be a multiple of the column count"));
+    }
+    
+    let row_count = values.len() / column_names.len();
+    let batch: Vec<(String, &[f64])> = column_names.into_iter()
+        .enumerate()
+        .map(|(i, name)| (name, &values[i * row_count..(i + 1) * row_count]))
+        .collect();
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.update_data_columns(data_source_id, &batch)
+            .map_err(|e| JsValue::from_str(&format!("Data update failed: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_performance_stats() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let stats = engine.get_performance_stats();
+        serde_json::to_string(&stats)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize stats: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// MetricsSnapshot JSON; its schema is get_json_schema("MetricsSnapshot")
+#[wasm_bindgen]
+pub fn get_metrics() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_metrics())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize metrics: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn reset_metrics() -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.reset_metrics();
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// JSON array of SecurityDenial, oldest first
+#[wasm_bindgen]
+pub fn get_security_audit() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_security_audit())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize security audit: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn clear_security_audit() -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.clear_security_audit();
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// ContentProtection JSON from the host; returns the RenderUpdate drawing the watermark. Later
+// calls can only tighten it.
+#[wasm_bindgen]
+pub fn set_content_protection(protection_json: &str) -> Result<String, JsValue> {
+    let protection: ContentProtection = serde_json::from_str(protection_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse content protection: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.set_content_protection(protection)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set content protection: {}", e.message)))?;
+        *CONTENT_PROTECTION.lock().unwrap() = Some(engine.get_content_protection());
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_content_protection() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_content_protection())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize content protection: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// The callback receives each SecurityDenial as JSON when it is recorded; it must not call back
+// into the engine. Pass null to stop.
+#[wasm_bindgen]
+pub fn set_security_audit_callback(callback: Option<js_sys::Function>) {
+    SECURITY_AUDIT_CALLBACK.with(|slot| *slot.borrow_mut() = callback);
+}
+
+#[wasm_bindgen]
+pub fn get_frame_report() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_frame_report())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize frame report: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_quality_tier() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_quality_state())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize quality state: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Pins a quality tier ("High", "Medium", "Low", "Minimal"), or restores automatic scaling when omitted
+#[wasm_bindgen]
+pub fn set_quality_tier(tier: Option<String>) -> Result<(), JsValue> {
+    let tier = match tier.as_deref() {
+        None => None,
+        Some("High") => Some(QualityTier::High),
+        Some("Medium") => Some(QualityTier::Medium),
+        Some("Low") => Some(QualityTier::Low),
+        Some("Minimal") => Some(QualityTier::Minimal),
+        Some(_) => return Err(JsValue::from_str("Invalid quality tier")),
+    };
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_quality_tier(tier);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_frame_overlay(enabled: bool) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_frame_overlay(enabled);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_element(element_type: &str, properties_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let element_type = match element_type {
+            "chart" => ElementType::Chart,
+            "animation" => ElementType::Animation,
+            "interactive" => ElementType::Interactive,
+            "vector" => ElementType::Vector,
+            "text" => ElementType::Text,
+            "image" => ElementType::Image,
+            "container" => ElementType::Container,
+            "embed" => ElementType::Embed,
+            "question" => ElementType::Question,
+            "kpi" => ElementType::Kpi,
+            _ => return Err(JsValue::from_str("Invalid element type")),
+        };
+        
+        let properties: HashMap<String, serde_json::Value> = serde_json::from_str(properties_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse properties: {}", e)))?;
+        
+        engine.create_element(element_type, properties)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create element: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn update_element(element_id: &str, properties_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let properties: HashMap<String, serde_json::Value> = serde_json::from_str(properties_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse properties: {}", e)))?;
+        
+        engine.update_element_properties(element_id, properties)
+            .map_err(|e| JsValue::from_str(&format!("Failed to update element: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of PropertySchema for the element type
+#[cfg(not(feature = "editor-interop"))]
+#[wasm_bindgen]
+pub fn get_property_schema(_element_type: &str) -> Result<String, JsValue> {
+    Err(feature_disabled("editor-interop"))
+}
+
+#[cfg(feature = "editor-interop")]
+#[wasm_bindgen]
+pub fn get_property_schema(element_type: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let element_type = match element_type {
+            "chart" => ElementType::Chart,
+            "animation" => ElementType::Animation,
+            "interactive" => ElementType::Interactive,
+            "vector" => ElementType::Vector,
+            "text" => ElementType::Text,
+            "image" => ElementType::Image,
+            "container" => ElementType::Container,
+            "embed" => ElementType::Embed,
+            "question" => ElementType::Question,
+            "kpi" => ElementType::Kpi,
+            _ => return Err(JsValue::from_str("Invalid element type")),
+        };
+        serde_json::to_string(&engine.get_property_schema(&element_type))
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize schema: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// "off", "warn" or "strict"
+#[cfg(not(feature = "editor-interop"))]
+#[wasm_bindgen]
+pub fn set_property_validation(_mode: &str) -> Result<(), JsValue> {
+    Err(feature_disabled("editor-interop"))
+}
+
+#[cfg(feature = "editor-interop")]
+#[wasm_bindgen]
+pub fn set_property_validation(mode: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let mode = match mode {
+            "off" => PropertyValidation::Off,
+            "warn" => PropertyValidation::Warn,
+            "strict" => PropertyValidation::Strict,
+            _ => return Err(JsValue::from_str("Invalid validation mode")),
+        };
+        engine.set_property_validation(mode);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of PropertyIssue collected in warn mode
+#[cfg(not(feature = "editor-interop"))]
+#[wasm_bindgen]
+pub fn take_property_warnings() -> Result<String, JsValue> {
+    Err(feature_disabled("editor-interop"))
+}
+
+#[cfg(feature = "editor-interop")]
+#[wasm_bindgen]
+pub fn take_property_warnings() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        serde_json::to_string(&engine.take_property_warnings())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize warnings: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// A LintProfile as JSON, or the name of a built-in one ("recommended", "strict", "accessibility",
+// "performance", "security", "style")
+fn parse_lint_profile(profile: &str) -> Result<LintProfile, JsValue> {
+    if profile.trim_start().starts_with('{') {
+        return serde_json::from_str(profile)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse lint profile: {}", e)));
+    }
+    LintProfile::named(profile).ok_or_else(|| JsValue::from_str(&format!("Unknown lint profile: {}", profile)))
+}
+
+// Returns a JSON array of LintRule: every rule with its category and default severity
+#[wasm_bindgen]
+pub fn get_lint_rules() -> Result<String, JsValue> {
+    serde_json::to_string(&lint::rules())
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize lint rules: {}", e)))
+}
+
+#[wasm_bindgen]
+pub fn get_lint_profiles() -> Result<String, JsValue> {
+    serde_json::to_string(&LintProfile::built_in())
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize lint profiles: {}", e)))
+}
+
+// Profile for documents loaded from now on
+#[wasm_bindgen]
+pub fn set_lint_profile(profile: &str) -> Result<(), JsValue> {
+    let profile = parse_lint_profile(profile)?;
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_lint_profile(profile)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set lint profile: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of LintFinding for the current document; an empty profile uses the engine's
+#[wasm_bindgen]
+pub fn lint_document(profile: &str) -> Result<String, JsValue> {
+    let profile = if profile.is_empty() { None } else { Some(parse_lint_profile(profile)?) };
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let findings = engine.lint_document(profile.as_ref())
+            .map_err(|e| JsValue::from_str(&format!("Failed to lint document: {}", e.message)))?;
+        serde_json::to_string(&findings)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize lint findings: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of LintFinding from the last document load
+#[wasm_bindgen]
+pub fn take_lint_findings() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        serde_json::to_string(&engine.take_lint_findings())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize lint findings: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of NumericIssue: NaN or infinite values the engine replaced, by site
+#[wasm_bindgen]
+pub fn take_numeric_issues() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        serde_json::to_string(&engine.take_numeric_issues())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize issues: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of AnimationConflict: properties several animations wrote in the last frame
+#[wasm_bindgen]
+pub fn get_animation_conflicts() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_animation_conflicts())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize conflicts: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of AnimationConflict detected since the last call
+#[wasm_bindgen]
+pub fn take_animation_conflicts() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        serde_json::to_string(&engine.take_animation_conflicts())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize conflicts: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn delete_element(element_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.delete_element(element_id)
+            .map(|_| ())
+            .map_err(|e| JsValue::from_str(&format!("Failed to delete element: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Sweeps resources left behind by removed elements; returns the GcReport as JSON
+#[wasm_bindgen]
+pub fn collect_garbage() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let report = engine.collect_garbage();
+        serde_json::to_string(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize GC report: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_gc_stats() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(engine.gc_totals())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize GC stats: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_animation(target_element: &str, animation_type: &str, duration: f64, keyframes_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let animation_type = match animation_type {
+            "transform" => AnimationType::Transform,
+            "style" => AnimationType::Style,
+            "path" => AnimationType::Path,
+            "morph" => AnimationType::Morph,
+            _ => return Err(JsValue::from_str("Invalid animation type")),
+        };
+        
+        let keyframes: Vec<Keyframe> = serde_json::from_str(keyframes_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse keyframes: {}", e)))?;
+        
+        engine.create_animation(target_element, animation_type, duration, keyframes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create animation: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn stop_animation(animation_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.stop_animation(animation_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to stop animation: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_timeline() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.create_timeline()
+            .map_err(|e| JsValue::from_str(&format!("Failed to create timeline: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// An Animation, or an array of them to run in parallel; placement JSON such as
+// {"Sequence":{"delay_ms":200}}, empty to follow everything already on the timeline.
+// Returns the start time in milliseconds.
+#[wasm_bindgen]
+pub fn timeline_add(timeline_id: &str, animation_json: &str, placement_json: &str) -> Result<f64, JsValue> {
+    let value: serde_json::Value = serde_json::from_str(animation_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse animation: {}", e)))?;
+    let animations: Vec<Animation> = match value {
+        serde_json::Value::Array(_) => serde_json::from_value(value),
+        _ => serde_json::from_value(value).map(|animation| vec![animation]),
+    }
+    .map_err(|e| JsValue::from_str(&format!("Failed to parse animation: {}", e)))?;
+    let placement: TimelinePlacement = if placement_json.is_empty() {
+        TimelinePlacement::default()
+    } else {
+        serde_json::from_str(placement_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse placement: {}", e)))?
+    };
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.timeline_add(timeline_id, animations, &placement)
+            .map_err(|e| JsValue::from_str(&format!("Failed to add to timeline: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn timeline_play(timeline_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.timeline_play(timeline_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to play timeline: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn timeline_pause(timeline_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.timeline_pause(timeline_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to pause timeline: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn timeline_seek(timeline_id: &str, time_ms: f64) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.timeline_seek(timeline_id, time_ms)
+            .map_err(|e| JsValue::from_str(&format!("Failed to seek timeline: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn timeline_reverse(timeline_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.timeline_reverse(timeline_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to reverse timeline: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_timeline(timeline_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.remove_timeline(timeline_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to remove timeline: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// kind is "number", "angle", "color", "transform", "path" or "discrete"; property is a full
+// name like "style.fill" or its last segment
+#[wasm_bindgen]
+pub fn register_property_interpolator(property: &str, kind: &str) -> Result<(), JsValue> {
+    let kind = match kind {
+        "number" => InterpolatorKind::Number,
+        "angle" => InterpolatorKind::Angle,
+        "color" => InterpolatorKind::Color,
+        "transform" => InterpolatorKind::Transform,
+        "path" => InterpolatorKind::Path,
+        "discrete" => InterpolatorKind::Discrete,
+        other => return Err(JsValue::from_str(&format!("Unknown interpolator kind: {}", other))),
+    };
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.register_property_interpolator(property, kind);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn unregister_property_interpolator(property: &str) -> Result<bool, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        Ok(engine.unregister_property_interpolator(property))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_timeline_state(timeline_id: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let state = engine.timeline_state(timeline_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to get timeline state: {}", e.message)))?;
+        serde_json::to_string(&state)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize timeline state: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn add_event_handler(element_id: &str, event_type: &str, handler_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.add_event_handler(element_id, event_type, handler_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to add event handler: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn register_component(component_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let component: ComponentDefinition = serde_json::from_str(component_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse component: {}", e)))?;
+        
+        engine.register_component(component)
+            .map_err(|e| JsValue::from_str(&format!("Failed to register component: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn list_components() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(engine.list_components())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize components: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns the new instance id
+// slots_json maps slot names to arrays of element ids and may be empty
+#[wasm_bindgen]
+pub fn instantiate_component(component: &str, args_json: &str, slots_json: &str, instance_id: Option<String>, parent_id: Option<String>) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let args: HashMap<String, serde_json::Value> = if args_json.trim().is_empty() {
+            HashMap::new()
+        } else {
+            serde_json::from_str(args_json)
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse component arguments: {}", e)))?
+        };
+        let slots: HashMap<String, Vec<String>> = if slots_json.trim().is_empty() {
+            HashMap::new()
+        } else {
+            serde_json::from_str(slots_json)
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse slot content: {}", e)))?
+        };
+        
+        engine.instantiate_component(component, instance_id, args, slots, parent_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to instantiate component: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_component_slot(instance_id: &str, slot: &str, element_ids_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let element_ids: Vec<String> = serde_json::from_str(element_ids_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse slot content: {}", e)))?;
+        
+        engine.set_component_slot(instance_id, slot, element_ids)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set slot content: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_component_override(instance_id: &str, local_id: &str, properties_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let properties: HashMap<String, serde_json::Value> = serde_json::from_str(properties_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse properties: {}", e)))?;
+        
+        engine.set_component_override(instance_id, local_id, properties)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set override: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Scoped CSS for all components; re-fetch after registering components or changing tokens
+#[wasm_bindgen]
+pub fn get_component_styles() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        Ok(engine.component_stylesheet())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_style_token(name: &str, value: Option<String>) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_style_token(name, value)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set style token: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_component_token(instance_id: &str, name: &str, value: Option<String>) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_component_token(instance_id, name, value)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set component token: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_component_instance(instance_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.remove_component_instance(instance_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to remove component instance: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Host scroll position in document coordinates; visibility triggers are re-evaluated on the next frame
+#[wasm_bindgen]
+pub fn set_viewport_offset(offset_x: f64, offset_y: f64) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_viewport_offset(offset_x, offset_y);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn add_visibility_trigger(trigger_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let trigger: VisibilityTrigger = serde_json::from_str(trigger_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse visibility trigger: {}", e)))?;
+        
+        engine.add_visibility_trigger(trigger)
+            .map_err(|e| JsValue::from_str(&format!("Failed to add visibility trigger: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_visibility_trigger(trigger_id: &str) -> Result<bool, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        Ok(engine.remove_visibility_trigger(trigger_id))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_text_story(story_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let story: TextStory = serde_json::from_str(story_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse text story: {}", e)))?;
+        
+        engine.set_text_story(story)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set text story: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_text_story(story_id: &str) -> Result<bool, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.remove_text_story(story_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to remove text story: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn link_text_frame(story_id: &str, frame_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.link_text_frame(story_id, frame_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to link text frame: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Whitespace-separated Liang patterns for a language such as "de"; English is built in
+#[wasm_bindgen]
+pub fn register_hyphenation_patterns(language: &str, patterns: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.register_hyphenation_patterns(language, patterns)
+            .map_err(|e| JsValue::from_str(&format!("Failed to register hyphenation patterns: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Metrics of one face, e.g. from the font's hhea and hmtx tables; Text elements with its
+// font_family are measured with them instead of the shaping callback
+#[wasm_bindgen]
+pub fn register_font_table(table_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let table: FontTable = serde_json::from_str(table_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse font table: {}", e)))?;
+        
+        engine.register_font_table(table)
+            .map_err(|e| JsValue::from_str(&format!("Failed to register font table: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// The callback receives a ShapingRequest as JSON and returns a JSON array of ShapedCluster, e.g.
+// from HarfBuzz; it must not call back into the engine. Pass null for the built-in estimate.
+#[wasm_bindgen]
+pub fn set_shaping_callback(callback: Option<js_sys::Function>) {
+    SHAPING_CALLBACK.with(|slot| *slot.borrow_mut() = callback);
+    if let Some(engine) = ENGINE.lock().unwrap().as_mut() {
+        engine.invalidate_text_layouts();
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_story_layout(story_id: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let layout = engine.get_story_layout(story_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to lay out text story: {}", e.message)))?;
+        
+        serde_json::to_string(&layout)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize story layout: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_overflow_text(frame_id: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        engine.get_overflow_text(frame_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to get overflow text: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_question(question_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let question: Question = serde_json::from_str(question_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse question: {}", e)))?;
+        
+        engine.set_question(question)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set question: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Answer JSON is a QuestionAnswer, e.g. {"Choice": 2} or {"Order": [1, 0, 2]}
+#[wasm_bindgen]
+pub fn submit_answer(element_id: &str, answer_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let answer: QuestionAnswer = serde_json::from_str(answer_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse answer: {}", e)))?;
+        
+        let render_update = engine.submit_answer(element_id, answer)
+            .map_err(|e| JsValue::from_str(&format!("Failed to submit answer: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_quiz_score() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_quiz_score())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize quiz score: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of QuestionAttempt to hand back to restore_quiz_attempts later
+#[wasm_bindgen]
+pub fn get_quiz_attempts() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_quiz_attempts())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize quiz attempts: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns how many attempts were restored
+#[wasm_bindgen]
+pub fn restore_quiz_attempts(attempts_json: &str) -> Result<usize, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let attempts: Vec<QuestionAttempt> = serde_json::from_str(attempts_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse quiz attempts: {}", e)))?;
+        
+        engine.restore_quiz_attempts(attempts)
+            .map_err(|e| JsValue::from_str(&format!("Failed to restore quiz attempts: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn reset_quiz() -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.reset_quiz()
+            .map_err(|e| JsValue::from_str(&format!("Failed to reset quiz: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of QuizEvent
+#[wasm_bindgen]
+pub fn drain_quiz_events() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        serde_json::to_string(&engine.drain_quiz_events())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize quiz events: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Tour JSON: {"id": "intro", "steps": [{"target": "toolbar", "title": "...", "body": "..."}]}
+#[wasm_bindgen]
+pub fn define_tour(tour_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let tour: Tour = serde_json::from_str(tour_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse tour: {}", e)))?;
+        
+        engine.define_tour(tour)
+            .map_err(|e| JsValue::from_str(&format!("Failed to define tour: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn start_tour(tour_id: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.start_tour(tour_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to start tour: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn tour_next() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.tour_next()
+            .map_err(|e| JsValue::from_str(&format!("Failed to advance tour: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn tour_back() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.tour_back()
+            .map_err(|e| JsValue::from_str(&format!("Failed to go back in tour: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn skip_tour() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.skip_tour()
+            .map_err(|e| JsValue::from_str(&format!("Failed to skip tour: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a TourProgress JSON object, or null when no tour is running
+#[wasm_bindgen]
+pub fn get_tour_progress() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_tour_progress())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize tour progress: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Pages JSON is an array of Page; returns the PageReachability analysis as JSON
+#[wasm_bindgen]
+pub fn set_pages(pages_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let pages: Vec<Page> = serde_json::from_str(pages_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse pages: {}", e)))?;
+        
+        let reachability = engine.set_pages(pages)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set pages: {}", e.message)))?;
+        
+        serde_json::to_string(&reachability)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize page reachability: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_reachable_pages() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_reachable_pages())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize page reachability: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_current_page() -> Result<Option<String>, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        Ok(engine.get_current_page())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of page ids, oldest first
+#[wasm_bindgen]
+pub fn get_page_history() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_page_history())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize page history: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn go_to_page(page_id: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.go_to_page(page_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to go to page: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn next_page() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.next_page()
+            .map_err(|e| JsValue::from_str(&format!("Failed to go to next page: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn page_back() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.page_back()
+            .map_err(|e| JsValue::from_str(&format!("Failed to go back a page: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns the ViewHistory as JSON
+#[wasm_bindgen]
+pub fn get_view_history() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_view_history())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize view history: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn push_view() -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.push_view();
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn navigate_back() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.navigate_back()
+            .map_err(|e| JsValue::from_str(&format!("Failed to navigate back: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn navigate_forward() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.navigate_forward()
+            .map_err(|e| JsValue::from_str(&format!("Failed to navigate forward: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn encode_view_link() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        Ok(engine.encode_view_link())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn open_view_link(link: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let render_update = engine.open_view_link(link)
+            .map_err(|e| JsValue::from_str(&format!("Failed to open view link: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn schedule_timer(timer_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let timer: TimerDefinition = serde_json::from_str(timer_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse timer: {}", e)))?;
+        
+        engine.schedule_timer(timer)
+            .map_err(|e| JsValue::from_str(&format!("Failed to schedule timer: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn cancel_timer(timer_id: &str) -> Result<bool, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        Ok(engine.cancel_timer(timer_id))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// StreamConfig JSON, empty for the defaults; returns StreamStatus JSON. Needs allow_networking.
+#[wasm_bindgen]
+pub fn open_stream(source_id: &str, config_json: &str) -> Result<String, JsValue> {
+    let config: StreamConfig = if config_json.trim().is_empty() {
+        StreamConfig::default()
+    } else {
+        serde_json::from_str(config_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid stream config: {}", e)))?
+    };
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let status = engine.open_stream(source_id, config)
+            .map_err(|e| JsValue::from_str(&format!("Failed to open stream: {}", e.message)))?;
+        serialize_stream_status(&status)
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// One JSON or binary frame from the host's connection. Stop reading while the returned status
+// is paused; a frame that overflows the buffer is refused with STREAM_BUFFER_FULL.
+#[wasm_bindgen]
+pub fn push_stream_frame(source_id: &str, bytes: &[u8]) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let status = engine.push_stream_frame(source_id, bytes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to push stream frame: {}", e.message)))?;
+        serialize_stream_status(&status)
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn stream_connected(source_id: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let status = engine.stream_connected(source_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to update stream: {}", e.message)))?;
+        serialize_stream_status(&status)
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns the status with reconnect_in_ms, or state Closed once the attempts run out
+#[wasm_bindgen]
+pub fn stream_disconnected(source_id: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let status = engine.stream_disconnected(source_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to update stream: {}", e.message)))?;
+        serialize_stream_status(&status)
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn close_stream(source_id: &str) -> Result<bool, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        Ok(engine.close_stream(source_id))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_stream_status(source_id: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let status = engine.stream_status(source_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to get stream status: {}", e.message)))?;
+        serialize_stream_status(&status)
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+fn serialize_stream_status(status: &StreamStatus) -> Result<String, JsValue> {
+    serde_json::to_string(status)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize stream status: {}", e)))
+}
+
+#[wasm_bindgen]
+pub fn get_timers() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_timers())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize timers: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_timers_paused(paused: bool) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_timers_paused(paused);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Subscribes a handler binding to a custom event name, "*" or a "prefix.*" wildcard; returns the subscription id
+#[wasm_bindgen]
+pub fn subscribe_event(pattern: &str, element_id: Option<String>, handler_id: &str, parameters_json: &str, once: bool) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let parameters: HashMap<String, serde_json::Value> = if parameters_json.trim().is_empty() {
+            HashMap::new()
+        } else {
+            serde_json::from_str(parameters_json)
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse handler parameters: {}", e)))?
+        };
+        
+        engine.subscribe_event(pattern, element_id, handler_id, parameters, once)
+            .map_err(|e| JsValue::from_str(&format!("Failed to subscribe: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn unsubscribe_event(subscription_id: &str) -> Result<bool, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        Ok(engine.unsubscribe_event(subscription_id))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn emit_event(event_name: &str, payload_json: &str, source: Option<String>) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let payload: serde_json::Value = if payload_json.trim().is_empty() {
+            serde_json::Value::Null
+        } else {
+            serde_json::from_str(payload_json)
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse event payload: {}", e)))?
+        };
+        
+        let render_update = engine.emit_event(event_name, payload, source)
+            .map_err(|e| JsValue::from_str(&format!("Failed to emit event: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Recent bus traffic (oldest first) for inspection tooling
+#[wasm_bindgen]
+pub fn get_event_bus_log() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_event_bus_log())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize event bus log: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn update_viewport(width: f64, height: f64, scale: f64) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.update_viewport(width, height, scale)
+            .map_err(|e| JsValue::from_str(&format!("Failed to update viewport: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Viewport JSON: {"width", "height", "scale", "offset_x", "offset_y"}
+#[wasm_bindgen]
+pub fn register_viewport(viewport_id: &str, viewport_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let viewport: Viewport = serde_json::from_str(viewport_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse viewport: {}", e)))?;
+        
+        engine.register_viewport(viewport_id, viewport)
+            .map_err(|e| JsValue::from_str(&format!("Failed to register viewport: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_viewport_camera(viewport_id: &str, viewport_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let viewport: Viewport = serde_json::from_str(viewport_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse viewport: {}", e)))?;
+        
+        engine.set_viewport_camera(viewport_id, viewport)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set viewport camera: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_viewport(viewport_id: &str) -> Result<bool, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        Ok(engine.remove_viewport(viewport_id))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_viewports() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_viewports())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize viewports: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of RenderUpdates for one view, produced by render_frame
+#[wasm_bindgen]
+pub fn take_viewport_updates(viewport_id: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let updates = engine.take_viewport_updates(viewport_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to take viewport updates: {}", e.message)))?;
+        
+        serde_json::to_string(&updates)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize updates: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn resync_viewport(viewport_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.resync_viewport(viewport_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to resync viewport: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Permissions JSON: what the embedded document asks for; it gets at most this engine's own
+#[wasm_bindgen]
+pub fn embed_document(element_id: &str, document_json: String, permissions_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let permissions: WASMPermissions = serde_json::from_str(permissions_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse permissions: {}", e)))?;
+        
+        engine.embed_document(element_id, document_json, permissions)
+            .map_err(|e| JsValue::from_str(&format!("Failed to embed document: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_embedded_document(element_id: &str) -> Result<bool, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        Ok(engine.remove_embedded_document(element_id))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Budget JSON: {"memory_limit", "frame_time_ms", "max_interactions_per_second", "max_strikes"}
+#[wasm_bindgen]
+pub fn set_widget_budget(element_id: &str, budget_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let budget: WidgetBudget = serde_json::from_str(budget_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse widget budget: {}", e)))?;
+        
+        engine.set_widget_budget(element_id, budget)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set widget budget: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_widget_status(element_id: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let status = engine.get_widget_status(element_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to get widget status: {}", e.message)))?;
+        
+        serde_json::to_string(&status)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize widget status: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn kill_widget(element_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.kill_widget(element_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to kill widget: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn restart_widget(element_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.restart_widget(element_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to restart widget: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn update_widget_data(element_id: &str, data_source_id: &str, data_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let data: serde_json::Value = serde_json::from_str(data_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse data: {}", e)))?;
+        
+        engine.update_widget_data(element_id, data_source_id, data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to update widget data: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+thread_local! {
+    // Receives each telemetry batch as JSON, right after the render_frame that produced it
+    static TELEMETRY_CALLBACK: std::cell::RefCell<Option<js_sys::Function>> = std::cell::RefCell::new(None);
+}
+
+fn deliver_telemetry(engine: &mut InteractiveEngine) {
+    TELEMETRY_CALLBACK.with(|callback| {
+        if let Some(callback) = callback.borrow().as_ref() {
+            for batch in engine.drain_telemetry() {
+                if let Ok(json) = serde_json::to_string(&batch) {
+                    let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(&json));
+                }
+            }
+        }
+    });
+}
+
+// Config JSON: {"namespace", "sample_rate", "flush_interval_ms"}
+#[wasm_bindgen]
+pub fn enable_telemetry(config_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let config: TelemetryConfig = serde_json::from_str(config_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse telemetry config: {}", e)))?;
+        
+        engine.enable_telemetry(config)
+            .map_err(|e| JsValue::from_str(&format!("Failed to enable telemetry: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn disable_telemetry() -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.disable_telemetry();
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of ErrorCodeInfo covering every code errors can carry
+#[wasm_bindgen]
+pub fn get_error_registry() -> Result<String, JsValue> {
+    serde_json::to_string(&WASMError::registry())
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize error registry: {}", e)))
+}
+
+// Pass null to stop callbacks; batches then wait for drain_telemetry
+#[wasm_bindgen]
+pub fn set_telemetry_callback(callback: Option<js_sys::Function>) {
+    TELEMETRY_CALLBACK.with(|slot| *slot.borrow_mut() = callback);
+}
+
+// Returns a JSON array of TelemetryBatch
+#[wasm_bindgen]
+pub fn drain_telemetry() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        serde_json::to_string(&engine.drain_telemetry())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize telemetry: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Id of the topmost visible element at a point in document coordinates, or undefined
+#[wasm_bindgen]
+pub fn hit_test_point(x: f64, y: f64) -> Result<Option<String>, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        Ok(engine.hit_test(x, y))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Restarts the document's random streams and noise from a new seed
+#[wasm_bindgen]
+pub fn set_random_seed(seed: u64) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_random_seed(seed);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_random_seed() -> Result<u64, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        Ok(engine.random_seed())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Distribution JSON such as {"Normal":{"mean":0,"std_dev":1}}; returns a JSON array of draws
+#[wasm_bindgen]
+pub fn random_sample(stream: &str, distribution_json: &str, count: usize) -> Result<String, JsValue> {
+    let distribution: Distribution = serde_json::from_str(distribution_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid distribution: {}", e)))?;
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let samples = engine.random_sample(stream, &distribution, count)
+            .map_err(|e| JsValue::from_str(&format!("Failed to sample: {}", e.message)))?;
+        serde_json::to_string(&samples)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize samples: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// "perlin" (also when empty) or "simplex"; the result is within -1..=1
+#[wasm_bindgen]
+pub fn noise(kind: &str, x: f64, y: f64, z: f64, octaves: u32) -> Result<f64, JsValue> {
+    let kind = match kind {
+        "" | "perlin" => NoiseKind::Perlin,
+        "simplex" => NoiseKind::Simplex,
+        other => return Err(JsValue::from_str(&format!("Unknown noise kind: {}", other))),
+    };
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        Ok(engine.noise(kind, x, y, z, octaves))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_element_bounds(element_id: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let bounds = engine.get_element_bounds(element_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to get element bounds: {}", e.message)))?;
+        
+        serde_json::to_string(&bounds)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize bounds: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// CSS matrix(a, b, c, d, e, f) from client to document coordinates, applied to every incoming
+// event position; identity by default
+#[wasm_bindgen]
+pub fn set_input_transform(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Result<(), JsValue> {
+    let transform = InputTransform::new(a, b, c, d, e, f)
+        .map_err(|e| JsValue::from_str(&format!("Failed to set input transform: {}", e.message)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_input_transform(transform);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// `from` and `to` are "client", "document", "viewport" or "element"; element needs element_id
+#[wasm_bindgen]
+pub fn convert_point(x: f64, y: f64, from: &str, to: &str, element_id: Option<String>) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let position = CoordinateSpace::parse(from, element_id.as_deref())
+            .and_then(|from| Ok((from, CoordinateSpace::parse(to, element_id.as_deref())?)))
+            .and_then(|(from, to)| engine.convert_point(&Position { x, y }, &from, &to))
+            .map_err(|e| JsValue::from_str(&format!("Failed to convert point: {}", e.message)))?;
+        
+        serde_json::to_string(&position)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize position: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn query_elements_by_type(element_type: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let element_type = match element_type {
+            "chart" => ElementType::Chart,
+            "animation" => ElementType::Animation,
+            "interactive" => ElementType::Interactive,
+            "vector" => ElementType::Vector,
+            "text" => ElementType::Text,
+            "image" => ElementType::Image,
+            "container" => ElementType::Container,
+            "embed" => ElementType::Embed,
+            "question" => ElementType::Question,
+            "kpi" => ElementType::Kpi,
+            _ => return Err(JsValue::from_str("Invalid element type")),
+        };
+        
+        let element_ids = engine.query_elements_by_type(element_type);
+        serde_json::to_string(&element_ids)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize element IDs: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn destroy_engine() {
+    let mut global_engine = ENGINE.lock().unwrap();
+    *global_engine = None;
+    log("LIV Interactive Engine destroyed");
+}
+
+// WASM-bindgen interface functions for charts and visualization
+#[wasm_bindgen]
+pub fn create_chart(chart_type: &str, data_source_id: &str, config_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let chart_type = match chart_type {
+            "line" => ChartType::Line,
+            "bar" => ChartType::Bar,
+            "pie" => ChartType::Pie,
+            "scatter" => ChartType::Scatter,
+            "area" => ChartType::Area,
+            "histogram" => ChartType::Histogram,
+            "heatmap" => ChartType::Heatmap,
+            "treemap" => ChartType::Treemap,
+            "sankey" => ChartType::Sankey,
+            "radar" => ChartType::Radar,
+            "gauge" => ChartType::Gauge,
+            "candlestick" => ChartType::Candlestick,
+            "gantt" => ChartType::Gantt,
+            "waterfall" => ChartType::Waterfall,
+            "funnel" => ChartType::Funnel,
+            "pyramid" => ChartType::Pyramid,
+            "map" => ChartType::Map,
+            custom if engine.chart_renderer.has_custom_type(custom) => ChartType::Custom(custom.to_string()),
+            _ => return Err(JsValue::from_str("Invalid chart type")),
+        };
+        
+        let config: ChartConfig = serde_json::from_str(config_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse config: {}", e)))?;
+        
+        engine.chart_renderer.create_chart(chart_type, data_source_id.to_string(), config)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create chart: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns CreatedPreset as JSON; options_json is a PresetOptions object ("{}" for defaults)
+#[wasm_bindgen]
+pub fn create_preset(preset: &str, options_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let options: PresetOptions = serde_json::from_str(options_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse options: {}", e)))?;
+        
+        let created = engine.create_preset(preset, options)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create preset: {}", e.message)))?;
+        serde_json::to_string(&created)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize preset: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_chart_element(chart_type: &str, data_source_id: &str, options_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let chart_type = match chart_type {
+            "line" => ChartType::Line,
+            "bar" => ChartType::Bar,
+            "pie" => ChartType::Pie,
+            "scatter" => ChartType::Scatter,
+            "area" => ChartType::Area,
+            "histogram" => ChartType::Histogram,
+            "heatmap" => ChartType::Heatmap,
+            "treemap" => ChartType::Treemap,
+            "sankey" => ChartType::Sankey,
+            "radar" => ChartType::Radar,
+            "gauge" => ChartType::Gauge,
+            "candlestick" => ChartType::Candlestick,
+            "gantt" => ChartType::Gantt,
+            "waterfall" => ChartType::Waterfall,
+            "funnel" => ChartType::Funnel,
+            "pyramid" => ChartType::Pyramid,
+            "map" => ChartType::Map,
+            custom if engine.chart_renderer.has_custom_type(custom) => ChartType::Custom(custom.to_string()),
+            _ => return Err(JsValue::from_str("Invalid chart type")),
+        };
+        let options: PresetOptions = serde_json::from_str(options_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse options: {}", e)))?;
+        
+        let created = engine.create_chart_element(chart_type, data_source_id, options)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create chart element: {}", e.message)))?;
+        serde_json::to_string(&created)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize preset: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// spec_json is a DeclarativeChartType; create_chart and create_chart_element then accept the name
+#[wasm_bindgen]
+pub fn register_custom_chart_type(name: &str, spec_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let spec: DeclarativeChartType = serde_json::from_str(spec_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse chart type: {}", e)))?;
+        
+        engine.register_declarative_chart_type(name, spec)
+            .map_err(|e| JsValue::from_str(&format!("Failed to register chart type: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[cfg(not(feature = "editor-interop"))]
+#[wasm_bindgen]
+pub fn upsert_element(_element_id: &str, _spec_json: &str) -> Result<String, JsValue> {
+    Err(feature_disabled("editor-interop"))
+}
+
+#[cfg(feature = "editor-interop")]
+#[wasm_bindgen]
+pub fn upsert_element(element_id: &str, spec_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let spec: ElementSpec = serde_json::from_str(spec_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse element spec: {}", e)))?;
+        
+        let result = engine.upsert_element(element_id, spec)
+            .map_err(|e| JsValue::from_str(&format!("Failed to upsert element: {}", e.message)))?;
+        serde_json::to_string(&result)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize upsert result: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[cfg(not(feature = "editor-interop"))]
+#[wasm_bindgen]
+pub fn upsert_data_source(_source_id: &str, _spec_json: &str) -> Result<String, JsValue> {
+    Err(feature_disabled("editor-interop"))
+}
+
+#[cfg(feature = "editor-interop")]
+#[wasm_bindgen]
+pub fn upsert_data_source(source_id: &str, spec_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let spec: DataSourceSpec = serde_json::from_str(spec_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse data source spec: {}", e)))?;
+        
+        let result = engine.upsert_data_source(source_id, spec)
+            .map_err(|e| JsValue::from_str(&format!("Failed to upsert data source: {}", e.message)))?;
+        serde_json::to_string(&result)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize upsert result: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn render_chart(chart_id: &str, data_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let data: serde_json::Value = serde_json::from_str(data_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse data: {}", e)))?;
+        
+        let rendered_chart = engine.chart_renderer.render_chart(chart_id, &data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to render chart: {}", e.message)))?;
+        
+        serde_json::to_string(&rendered_chart)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize chart: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// render_chart as an SvgNode tree
+#[wasm_bindgen]
+pub fn render_chart_nodes(chart_id: &str, data_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let data: serde_json::Value = serde_json::from_str(data_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse data: {}", e)))?;
+        
+        let nodes = engine.chart_renderer.render_chart_nodes(chart_id, &data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to render chart: {}", e.message)))?;
+        
+        serde_json::to_string(&nodes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize chart: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// View JSON: {"west": -10, "south": 35, "east": 30, "north": 60}; returns the redrawn map
+#[wasm_bindgen]
+pub fn set_map_view(chart_id: &str, view_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let view: GeoBounds = serde_json::from_str(view_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse map view: {}", e)))?;
+        
+        let rendered_chart = engine.set_map_view(chart_id, view)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set map view: {}", e.message)))?;
+        
+        serde_json::to_string(&rendered_chart)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize chart: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn fit_map_view(chart_id: &str, padding: f64) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let rendered_chart = engine.fit_map_view(chart_id, padding)
+            .map_err(|e| JsValue::from_str(&format!("Failed to fit map view: {}", e.message)))?;
+        
+        serde_json::to_string(&rendered_chart)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize chart: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Great-circle distance in meters between two latitude/longitude points
+#[wasm_bindgen]
+pub fn geo_distance(latitude_a: f64, longitude_a: f64, latitude_b: f64, longitude_b: f64) -> f64 {
+    geo::great_circle_distance((latitude_a, longitude_a), (latitude_b, longitude_b))
+}
+
+#[wasm_bindgen]
+pub fn render_chart_from_source(chart_id: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let rendered_chart = engine.render_chart_from_source(chart_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to render chart: {}", e.message)))?;
+        
+        serde_json::to_string(&rendered_chart)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize chart: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Legend clicks: find the series with RenderedChart.legend_regions, then toggle it here;
+// returns the redrawn chart
+#[wasm_bindgen]
+pub fn toggle_chart_series(chart_id: &str, series_id: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let rendered_chart = engine.toggle_chart_series(chart_id, series_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to toggle chart series: {}", e.message)))?;
+        
+        serde_json::to_string(&rendered_chart)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize chart: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// `format` is "svg" or "png"; returns a ChartExport
+#[wasm_bindgen]
+pub fn export_chart(chart_id: &str, format: &str, scale: f64) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let export = ExportFormat::from_name(format)
+            .and_then(|format| engine.export_chart(chart_id, format, scale))
+            .map_err(|e| JsValue::from_str(&format!("Failed to export chart: {}", e.message)))?;
+        
+        serde_json::to_string(&export)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize export: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn register_export_font(family: &str, url: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.chart_renderer.register_export_font(family, url);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn add_chart_series(chart_id: &str, series_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let series: ChartSeries = serde_json::from_str(series_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse series: {}", e)))?;
+        
+        engine.chart_renderer.add_series(chart_id, series)
+            .map_err(|e| JsValue::from_str(&format!("Failed to add series: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_vector_shape(shape_type: &str, x: f64, y: f64, width: f64, height: f64) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let shape_type = match shape_type {
+            "rectangle" => ShapeType::Rectangle,
+            "circle" => ShapeType::Circle,
+            "ellipse" => ShapeType::Ellipse,
+            "line" => ShapeType::Line,
+            "text" => ShapeType::Text,
+            _ => return Err(JsValue::from_str("Invalid shape type")),
+        };
+        
+        let position = Position { x, y };
+        let size = Size { width, height };
+        
+        engine.vector_engine.create_shape(shape_type, position, size)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create shape: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// VectorText JSON such as {"content":"LIV","font_family":"Inter","font_size":48}, for a "text" shape
+#[wasm_bindgen]
+pub fn set_vector_text(shape_id: &str, text_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let text: VectorText = serde_json::from_str(text_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid vector text: {}", e)))?;
+        engine.vector_engine.set_shape_text(shape_id, text)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set text: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns the id of the path that replaces the text shape
+#[wasm_bindgen]
+pub fn convert_text_to_path(shape_id: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.vector_engine.convert_text_to_path(shape_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to convert text: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Hatch JSON such as {"kind":"Stripes","density":8,"angle":45,"color":"#333"}; returns the
+// pattern id
+#[wasm_bindgen]
+pub fn create_hatch_pattern(hatch_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let hatch: Hatch = serde_json::from_str(hatch_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid hatch pattern: {}", e)))?;
+        engine.vector_engine.create_hatch_pattern(&hatch)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create pattern: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_vector_fill_pattern(shape_id: &str, pattern_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.vector_engine.set_shape_pattern(shape_id, pattern_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set fill pattern: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Gradient type JSON such as {"Linear":{"x1":0,"y1":0,"x2":1,"y2":0}} and a stops array of
+// {"offset","color","opacity"}; returns the gradient id
+#[wasm_bindgen]
+pub fn create_gradient(gradient_type_json: &str, stops_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let gradient_type: GradientType = serde_json::from_str(gradient_type_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid gradient type: {}", e)))?;
+        let stops: Vec<GradientStop> = serde_json::from_str(stops_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid gradient stops: {}", e)))?;
+        engine.vector_engine.create_gradient(gradient_type, stops)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create gradient: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_vector_fill_gradient(shape_id: &str, gradient_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.vector_engine.set_shape_gradient(shape_id, gradient_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set fill gradient: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Replaces the stops and repaints the elements drawing shapes filled with the gradient
+#[wasm_bindgen]
+pub fn set_gradient_stops(gradient_id: &str, stops_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let stops: Vec<GradientStop> = serde_json::from_str(stops_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid gradient stops: {}", e)))?;
+        engine.set_gradient_stops(gradient_id, stops)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set gradient stops: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_gradient_geometry(gradient_id: &str, gradient_type_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let gradient_type: GradientType = serde_json::from_str(gradient_type_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid gradient type: {}", e)))?;
+        engine.set_gradient_geometry(gradient_id, gradient_type)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set gradient geometry: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Animation JSON such as {"stops":[...],"gradient_type":{...},"duration":800}: the gradient
+// moves from its current stops and geometry to these; returns the animation id
+#[wasm_bindgen]
+pub fn animate_gradient(gradient_id: &str, animation_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let animation: GradientAnimation = serde_json::from_str(animation_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid gradient animation: {}", e)))?;
+        engine.animate_gradient(gradient_id, &animation)
+            .map_err(|e| JsValue::from_str(&format!("Failed to animate gradient: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns {"gradient_id","animation_id"}
+#[wasm_bindgen]
+pub fn create_animated_gradient(gradient_type_json: &str, stops_json: &str, animation_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let gradient_type: GradientType = serde_json::from_str(gradient_type_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid gradient type: {}", e)))?;
+        let stops: Vec<GradientStop> = serde_json::from_str(stops_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid gradient stops: {}", e)))?;
+        let animation: GradientAnimation = serde_json::from_str(animation_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid gradient animation: {}", e)))?;
+        let animated = engine.create_animated_gradient(gradient_type, stops, &animation)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create animated gradient: {}", e.message)))?;
+        serde_json::to_string(&animated)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize animated gradient: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn render_vector_graphics(width: f64, height: f64) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        Ok(engine.vector_engine.render_to_svg(width, height))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// render_vector_graphics as an SvgNode tree
+#[wasm_bindgen]
+pub fn render_vector_nodes(width: f64, height: f64) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let nodes = engine.vector_engine.render_to_nodes(width, height)
+            .map_err(|e| JsValue::from_str(&format!("Failed to render vector graphics: {}", e.message)))?;
+        serde_json::to_string(&nodes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize vector graphics: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// SvgPatch list that turns the `previous` node tree into `next`; needs no engine state
+#[wasm_bindgen]
+pub fn diff_svg_nodes(previous_json: &str, next_json: &str) -> Result<String, JsValue> {
+    let previous: SvgNode = serde_json::from_str(previous_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse node tree: {}", e)))?;
+    let next: SvgNode = serde_json::from_str(next_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse node tree: {}", e)))?;
+    serde_json::to_string(&previous.diff(&next))
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize patches: {}", e)))
+}
+
+#[wasm_bindgen]
+pub fn get_chart_performance_stats() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.chart_renderer.performance_stats)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize stats: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_data_source(source_id: &str, source_type: &str, data_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let source_type = match source_type {
+            "static" => DataSourceType::Static,
+            "dynamic" => DataSourceType::Dynamic,
+            "stream" => DataSourceType::Stream,
+            "computed" => DataSourceType::Computed,
+            _ => return Err(JsValue::from_str("Invalid data source type")),
+        };
+        
+        let data: serde_json::Value = serde_json::from_str(data_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse data: {}", e)))?;
+        
+        let data_source = DataSource::new(source_id.to_string(), source_type, data);
+        engine.document_state.data_sources.insert(source_id.to_string(), data_source);
+        
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn update_data_source(source_id: &str, data_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let new_data: serde_json::Value = serde_json::from_str(data_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse data: {}", e)))?;
+        
+        engine.update_data_source(source_id, new_data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to update data source: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_data_schema(source_id: &str, schema_json: &str) -> Result<(), JsValue> {
+    let schema: Option<DataSchema> = serde_json::from_str(schema_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse schema: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_data_schema(source_id, schema)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set schema: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn export_data_snapshot() -> Result<Vec<u8>, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let snapshot = engine.export_data_snapshot()
+            .map_err(|e| JsValue::from_str(&format!("Failed to export data: {}", e.message)))?;
+        serde_json::to_vec(&snapshot)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize snapshot: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Snapshot bytes for thaw(), e.g. before a tab is discarded or to hand the document to a worker
+#[wasm_bindgen]
+pub fn freeze() -> Result<Vec<u8>, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        engine.freeze()
+            .map_err(|e| JsValue::from_str(&format!("Failed to freeze engine: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn thaw(snapshot: &[u8]) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.thaw(snapshot)
+            .map_err(|e| JsValue::from_str(&format!("Failed to thaw engine: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON array of SupportedVersions, one per persisted structure
+#[wasm_bindgen]
+pub fn get_supported_versions() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_supported_versions())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize versions: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_data_versions() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_data_versions())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize versions: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn export_data_delta(since_versions_json: &str) -> Result<Vec<u8>, JsValue> {
+    let since: HashMap<String, u64> = serde_json::from_str(since_versions_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse versions: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let delta = engine.export_data_delta(&since)
+            .map_err(|e| JsValue::from_str(&format!("Failed to export data: {}", e.message)))?;
+        serde_json::to_vec(&delta)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize delta: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_replica_id() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        Ok(engine.get_replica_id().to_string())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_replica_id(replica_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_replica_id(replica_id);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns a JSON DeltaImport listing the applied and conflicting sources
+#[wasm_bindgen]
+pub fn apply_data_delta(delta: &[u8]) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        if delta.len() > engine.security_context.max_data_size() {
+            return Err(JsValue::from_str("Data size exceeds security limits"));
+        }
+        
+        let delta = engine.read_data_delta(delta)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse delta: {}", e.message)))?;
+        let import = engine.apply_data_delta(delta)
+            .map_err(|e| JsValue::from_str(&format!("Failed to apply delta: {}", e.message)))?;
+        
+        serde_json::to_string(&import)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize delta import: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Pass null as the config to stop journaling the source
+#[wasm_bindgen]
+pub fn set_mutation_journal(data_source_id: &str, config_json: Option<String>) -> Result<(), JsValue> {
+    let config: Option<MutationJournalConfig> = config_json
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse journal config: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_mutation_journal(data_source_id, config);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn export_mutations() -> Result<Vec<u8>, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let export = engine.export_mutations()
+            .map_err(|e| JsValue::from_str(&format!("Failed to export mutations: {}", e.message)))?;
+        serde_json::to_vec(&export)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize mutations: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// `policy` is a MutationConflictPolicy name, e.g. "PreferDocument"; returns a MutationImport
+#[wasm_bindgen]
+pub fn apply_mutations(mutations: &[u8], policy: &str) -> Result<String, JsValue> {
+    let policy: MutationConflictPolicy = serde_json::from_value(serde_json::json!(policy))
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse conflict policy: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        if mutations.len() > engine.security_context.max_data_size() {
+            return Err(JsValue::from_str("Data size exceeds security limits"));
+        }
+        
+        let export = engine.read_mutations(mutations)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse mutations: {}", e.message)))?;
+        let import = engine.apply_mutations(export, policy)
+            .map_err(|e| JsValue::from_str(&format!("Failed to apply mutations: {}", e.message)))?;
+        
+        serde_json::to_string(&import)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize import: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn load_document_chunked(document_json: String) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        Ok(engine.load_document_chunked(document_json))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns ProgressiveLoad JSON: the token to keep streaming with and the assets to preload.
+// hints_json is optional LoadingHints that override the document's own.
+#[wasm_bindgen]
+pub fn load_document_progressive(document_json: String, hints_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let hints: LoadingHints = if hints_json.trim().is_empty() {
+            LoadingHints::default()
+        } else {
+            serde_json::from_str(hints_json)
+                .map_err(|e| JsValue::from_str(&format!("Invalid loading hints: {}", e)))?
+        };
+        let started = engine.load_document_progressive(document_json, hints)
+            .map_err(|e| JsValue::from_str(&format!("Failed to load document: {}", e.message)))?;
+        
+        serde_json::to_string(&started)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize load: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns OperationProgress JSON; call again with the same token until `done` is true
+#[wasm_bindgen]
+pub fn continue_operation(token: &str, budget_ms: f64) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let progress = engine.continue_operation(token, budget_ms)
+            .map_err(|e| JsValue::from_str(&format!("Operation failed: {}", e.message)))?;
+        
+        serde_json::to_string(&progress)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize progress: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn cancel_operation(token: &str) -> Result<bool, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        Ok(engine.cancel_operation(token))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// patch_json is an RFC 6902 operation array; returns PatchResult JSON
+#[wasm_bindgen]
+pub fn apply_document_patch(patch_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let operations: Vec<patch::PatchOperation> = serde_json::from_str(patch_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse document patch: {}", e)))?;
+        
+        let result = engine.apply_document_patch(&operations)
+            .map_err(|e| JsValue::from_str(&format!("Failed to apply document patch: {}", e.message)))?;
+        serde_json::to_string(&result)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize patch result: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn drain_operation_events() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let events = engine.drain_operation_events();
+        serde_json::to_string(&events)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize operation events: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn drain_data_events() -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let events = engine.drain_data_events();
+        serde_json::to_string(&events)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize data events: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_computed_data_source(source_id: &str, dependencies_json: &str, formula: &str) -> Result<(), JsValue> {
+    let dependencies: Vec<String> = serde_json::from_str(dependencies_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse dependencies: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.create_computed_source(source_id, dependencies, formula)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create computed source: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn explain_dependencies(source_id: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let explanation = engine.explain_dependencies(source_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to explain dependencies: {}", e.message)))?;
+        serde_json::to_string(&explanation)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize dependencies: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_data_binding(source_id: &str, target_element: &str, property_path: &str, transform_function: Option<String>) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let binding = DataBinding {
+            source_id: source_id.to_string(),
+            target_element: target_element.to_string(),
+            property_path: property_path.to_string(),
+            target_property: None,
+            transform_function,
+            update_trigger: UpdateTrigger::Immediate,
+        };
+        
+        let binding_id = engine.data_binding_manager.add_binding(binding);
+        Ok(binding_id)
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_data_binding(binding_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.data_binding_manager.remove_binding(binding_id);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_data_statistics(source_id: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        if let Some(data_source) = engine.document_state.data_sources.get(source_id) {
+            let stats = data_source.get_data_statistics();
+            serde_json::to_string(&stats)
+                .map_err(|e| JsValue::from_str(&format!("Failed to serialize statistics: {}", e)))
+        } else {
+            Err(JsValue::from_str("Data source not found"))
+        }
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_data_percentiles(source_id: &str, percentiles_json: &str) -> Result<String, JsValue> {
+    let percentiles: Vec<f64> = serde_json::from_str(percentiles_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse percentiles: {}", e)))?;
+    
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let data_source = engine.document_state.data_sources.get(source_id)
+            .ok_or_else(|| JsValue::from_str("Data source not found"))?;
+        serde_json::to_string(&data_source.get_percentiles(&percentiles))
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize percentiles: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_data_histogram(source_id: &str, bin_count: usize, range_min: Option<f64>, range_max: Option<f64>) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let data_source = engine.document_state.data_sources.get(source_id)
+            .ok_or_else(|| JsValue::from_str("Data source not found"))?;
+        let range = range_min.zip(range_max);
+        serde_json::to_string(&data_source.get_histogram(bin_count, range))
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize histogram: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn detect_data_outliers(source_id: &str, iqr_multiplier: f64) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let data_source = engine.document_state.data_sources.get(source_id)
+            .ok_or_else(|| JsValue::from_str("Data source not found"))?;
+        serde_json::to_string(&data_source.detect_outliers(iqr_multiplier))
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize outliers: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_data_correlation(source_a: &str, source_b: &str) -> Result<Option<f64>, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let sources = &engine.document_state.data_sources;
+        match (sources.get(source_a), sources.get(source_b)) {
+            (Some(a), Some(b)) => Ok(a.correlation(b)),
+            _ => Err(JsValue::from_str("Data source not found")),
+        }
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Reads "x" and "value" unless `x_field`/`y_field` name other columns or row keys; the result goes
+// to `output_id`, or "<source>_downsampled", and running it again refreshes it
+#[wasm_bindgen]
+pub fn downsample(source_id: &str, method: &str, target_points: usize, x_field: Option<String>, y_field: Option<String>, output_id: Option<String>) -> Result<String, JsValue> {
+    let method = match method {
+        "lttb" => DownsampleMethod::Lttb,
+        "min_max" => DownsampleMethod::MinMax,
+        "bucket_average" => DownsampleMethod::BucketAverage,
+        _ => return Err(JsValue::from_str("Invalid downsample method")),
+    };
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let defaults = PointFields::default();
+        let fields = PointFields { x: x_field.unwrap_or(defaults.x), y: y_field.unwrap_or(defaults.y) };
+        engine.downsample_data(source_id, method, target_points, &fields, output_id.as_deref())
+            .map_err(|e| JsValue::from_str(&format!("Downsample failed: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// The result goes to `output_id`, or "<source>_by_<group_field>", and running it again refreshes it
+#[wasm_bindgen]
+pub fn aggregate(source_id: &str, group_field: &str, value_field: &str, agg: &str, output_id: Option<String>) -> Result<String, JsValue> {
+    let function = match agg {
+        "sum" => AggregateFunction::Sum,
+        "mean" => AggregateFunction::Mean,
+        "min" => AggregateFunction::Min,
+        "max" => AggregateFunction::Max,
+        "count" => AggregateFunction::Count,
+        _ => return Err(JsValue::from_str("Invalid aggregate function")),
+    };
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.aggregate_data(source_id, group_field, value_field, function, output_id.as_deref())
+            .map_err(|e| JsValue::from_str(&format!("Aggregate failed: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn animate_vector_shape(shape_id: &str, target_transform_json: &str, duration: f64) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let target_transform: Transform = serde_json::from_str(target_transform_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse transform: {}", e)))?;
+        
+        engine.vector_engine.animate_shape(shape_id, target_transform, duration)
+            .map_err(|e| JsValue::from_str(&format!("Failed to animate shape: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_complex_path(path_type: &str, parameters_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let path_type = match path_type {
+            "bezier" => ComplexPathType::Bezier,
+            "spiral" => ComplexPathType::Spiral,
+            "star" => ComplexPathType::Star,
+            "wave" => ComplexPathType::Wave,
+            _ => return Err(JsValue::from_str("Invalid path type")),
+        };
+        
+        let parameters: HashMap<String, f64> = serde_json::from_str(parameters_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse parameters: {}", e)))?;
+        
+        engine.vector_engine.create_complex_path(path_type, parameters)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create complex path: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn enable_chart_interactions_wasm(chart_id: &str, interactions_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let interactions: ChartInteractions = serde_json::from_str(interactions_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse interactions: {}", e)))?;
+        
+        engine.chart_renderer.enable_chart_interactions(chart_id, interactions)
+            .map_err(|e| JsValue::from_str(&format!("Failed to enable interactions: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn update_chart_data_wasm(chart_id: &str, data_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let data: serde_json::Value = serde_json::from_str(data_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse data: {}", e)))?;
+        
+        engine.chart_renderer.update_chart_data(chart_id, &data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to update chart data: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn add_interaction_delegate(target_element: &str, event_types_json: &str, handler_id: &str, capture: bool, priority: i32) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let event_types: Vec<String> = serde_json::from_str(event_types_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse event types: {}", e)))?;
+        
+        let interaction_types: Vec<InteractionType> = event_types.iter()
+            .filter_map(|event_type| match event_type.as_str() {
+                "click" => Some(InteractionType::Click),
+                "doubleclick" => Some(InteractionType::DoubleClick),
+                "mousedown" => Some(InteractionType::MouseDown),
+                "mouseup" => Some(InteractionType::MouseUp),
+                "mousemove" => Some(InteractionType::MouseMove),
+                "touchstart" => Some(InteractionType::TouchStart),
+                "touchmove" => Some(InteractionType::TouchMove),
+                "touchend" => Some(InteractionType::TouchEnd),
+                "keydown" => Some(InteractionType::KeyDown),
+                "keyup" => Some(InteractionType::KeyUp),
+                "scroll" => Some(InteractionType::Scroll),
+                "focus" => Some(InteractionType::Focus),
+                "blur" => Some(InteractionType::Blur),
+                _ => None,
+            })
+            .collect();
+        
+        let delegate = EventDelegate {
+            element_id: target_element.to_string(),
+            event_types: interaction_types,
+            handler_id: handler_id.to_string(),
+            capture,
+            priority,
+        };
+        
+        engine.add_interaction_delegate(target_element, delegate)
+            .map_err(|e| JsValue::from_str(&format!("Failed to add interaction delegate: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn remove_interaction_delegate(target_element: &str, handler_id: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.remove_interaction_delegate(target_element, handler_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to remove interaction delegate: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_interaction_state(element_id: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        if let Some(state) = engine.get_interaction_state(element_id) {
+            serde_json::to_string(state)
+                .map_err(|e| JsValue::from_str(&format!("Failed to serialize interaction state: {}", e)))
+        } else {
+            Err(JsValue::from_str("Interaction state not found"))
+        }
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_interaction_metrics() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let metrics = engine.get_interaction_metrics();
+        serde_json::to_string(&metrics)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize interaction metrics: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn update_device_capabilities(device_info_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let device_info: DeviceInfo = serde_json::from_str(device_info_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse device info: {}", e)))?;
+        
+        engine.update_device_capabilities(device_info)
+            .map_err(|e| JsValue::from_str(&format!("Failed to update device capabilities: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_ink_settings(settings_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let settings: InkSettings = serde_json::from_str(settings_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse ink settings: {}", e)))?;
+        
+        engine.ink_layer.settings = settings;
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_ink_strokes() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(engine.ink_layer.strokes())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize ink strokes: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Removal operations are delivered with the next render_frame update
+#[wasm_bindgen]
+pub fn clear_ink() -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.ink_layer.clear();
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn describe_element(element_id: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let description = engine.describe_element(element_id)
+            .map_err(|e| JsValue::from_str(&format!("Failed to describe element: {}", e.message)))?;
+        
+        serde_json::to_string(&description)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize description: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_accessibility_tree() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(&engine.get_accessibility_tree())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize accessibility tree: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns Sonification JSON; settings_json may be empty for the default pitch range
+#[wasm_bindgen]
+pub fn sonify_series(chart_id: &str, series_id: &str, settings_json: &str) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let settings: SonificationSettings = if settings_json.trim().is_empty() {
+            SonificationSettings::default()
+        } else {
+            serde_json::from_str(settings_json)
+                .map_err(|e| JsValue::from_str(&format!("Invalid sonification settings: {}", e)))?
+        };
+        let sonification = engine.sonify_series(chart_id, series_id, &settings)
+            .map_err(|e| JsValue::from_str(&format!("Sonification failed: {}", e.message)))?;
+        
+        serde_json::to_string(&sonification)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize sonification: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Returns ContrastReport JSON; pass auto_fix to rewrite failing colors in place
+#[wasm_bindgen]
+pub fn check_color_contrast(auto_fix: bool) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let report = engine.check_color_contrast(auto_fix)
+            .map_err(|e| JsValue::from_str(&format!("Contrast check failed: {}", e.message)))?;
+        
+        serde_json::to_string(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize contrast report: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Palette JSON such as "OkabeIto", "Viridis" or {"Custom":["#..."]}; empty clears it
+#[wasm_bindgen]
+pub fn set_chart_palette(chart_id: &str, palette_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let palette: Option<ChartPalette> = if palette_json.trim().is_empty() {
+            None
+        } else {
+            Some(serde_json::from_str(palette_json)
+                .map_err(|e| JsValue::from_str(&format!("Invalid chart palette: {}", e)))?)
+        };
+        engine.chart_renderer.set_chart_palette(chart_id, palette)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set chart palette: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// ChartAxes JSON such as {"x_axis":{},"y_axis":{"min_value":0,"scale_type":"Logarithmic"}}; fields
+// left out of an axis take their defaults
+#[wasm_bindgen]
+pub fn set_chart_axes(chart_id: &str, axes_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let axes: ChartAxes = serde_json::from_str(axes_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid chart axes: {}", e)))?;
+        engine.chart_renderer.set_chart_axes(chart_id, axes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set chart axes: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// FacetOptions JSON such as {"field":"region","columns":3}; empty draws the chart whole
+#[wasm_bindgen]
+pub fn set_chart_facet(chart_id: &str, facet_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let facet: Option<FacetOptions> = if facet_json.trim().is_empty() {
+            None
+        } else {
+            Some(serde_json::from_str(facet_json)
+                .map_err(|e| JsValue::from_str(&format!("Invalid facet options: {}", e)))?)
+        };
+        engine.chart_renderer.set_chart_facet(chart_id, facet)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set chart facets: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Accepts "light", "dark" or ChartTheme JSON; empty clears the theme
+#[wasm_bindgen]
+pub fn set_chart_theme(chart_id: &str, theme: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let theme = match theme.trim() {
+            "" => None,
+            "light" => Some(ChartTheme::light()),
+            "dark" => Some(ChartTheme::dark()),
+            json => Some(serde_json::from_str(json)
+                .map_err(|e| JsValue::from_str(&format!("Invalid chart theme: {}", e)))?),
+        };
+        engine.chart_renderer.set_chart_theme(chart_id, theme)
+            .map_err(|e| JsValue::from_str(&format!("Failed to set chart theme: {}", e.message)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn register_command(command_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let command: CommandDefinition = serde_json::from_str(command_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse command: {}", e)))?;
+        
+        engine.register_command(command);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn list_commands() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(engine.list_commands())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize commands: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Hosts feed recognized intents here, e.g. dispatch_command("filter chart by region", '{"region":"north"}')
+#[wasm_bindgen]
+pub fn dispatch_command(name: &str, args_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let args: HashMap<String, serde_json::Value> = serde_json::from_str(args_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse command arguments: {}", e)))?;
+        
+        let render_update = engine.dispatch_command(name, args)
+            .map_err(|e| JsValue::from_str(&format!("Command failed: {}", e.message)))?;
+        
+        serde_json::to_string(&render_update)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize render update: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Accepts {"Gamepad":{"button":12}} or {"Key":{"key":"ArrowUp"}}; returns NavigationResult JSON
+#[wasm_bindgen]
+pub fn handle_navigation_input(input_json: &str) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let input: NavigationInput = serde_json::from_str(input_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse navigation input: {}", e)))?;
+        
+        let result = engine.handle_navigation_input(&input)
+            .map_err(|e| JsValue::from_str(&format!("Navigation failed: {}", e.message)))?;
+        
+        serde_json::to_string(&result)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize navigation result: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_input_mapping(mapping_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let mapping: InputMapping = serde_json::from_str(mapping_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse input mapping: {}", e)))?;
+        
+        engine.set_input_mapping(mapping);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_touch_filter_settings() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        serde_json::to_string(engine.responsive_adapter.touch_filter_settings())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize touch filter settings: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_touch_filter_settings(settings_json: &str) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let settings: TouchFilterSettings = serde_json::from_str(settings_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse touch filter settings: {}", e)))?;
+        
+        engine.responsive_adapter.set_touch_filter_settings(settings);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn process_touch_gesture(touch_data_json: &str, timestamp: f64) -> Result<String, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        let mut touch_data: TouchData = serde_json::from_str(touch_data_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse touch data: {}", e)))?;
+        
+        engine.responsive_adapter.filter_touches(&mut touch_data, timestamp);
+        let gesture_events = engine.gesture_recognizer.process_touch_input(&touch_data, timestamp);
+        serde_json::to_string(&gesture_events)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize gesture events: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_gesture_thresholds(scale_threshold: f64, rotation_threshold: f64) -> Result<(), JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.gesture_recognizer.set_multi_touch_thresholds(scale_threshold, rotation_threshold);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_gesture_history() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let history = engine.gesture_recognizer.get_gesture_history();
+        serde_json::to_string(history)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize gesture history: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_optimal_touch_target_size(element_width: f64, element_height: f64) -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let element_size = Size {
+            width: element_width,
+            height: element_height,
+        };
+        let optimal_size = engine.responsive_adapter.get_optimal_touch_target_size(&element_size);
+        serde_json::to_string(&optimal_size)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize optimal size: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_interaction_settings() -> Result<String, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let settings = engine.responsive_adapter.get_interaction_settings();
+        serde_json::to_string(settings)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize interaction settings: {}", e)))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn set_event_rate_limit(config_json: &str) -> Result<(), JsValue> {
+    let config: EventRateLimitConfig = serde_json::from_str(config_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse rate limit: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_event_rate_limit(config);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Engine-wide drag threshold, tap slop and click timing over the device profile (InteractionOverrides)
+#[wasm_bindgen]
+pub fn set_interaction_overrides(overrides_json: &str) -> Result<(), JsValue> {
+    let overrides: InteractionOverrides = serde_json::from_str(overrides_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse interaction overrides: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_interaction_overrides(overrides)
+            .map_err(|e| JsValue::from_str(&e.message))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Overrides for one element; no overrides_json removes them
+#[wasm_bindgen]
+pub fn set_element_interaction_overrides(element_id: &str, overrides_json: Option<String>) -> Result<(), JsValue> {
+    let overrides: Option<InteractionOverrides> = overrides_json
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse interaction overrides: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_element_interaction_overrides(element_id, overrides)
+            .map_err(|e| JsValue::from_str(&e.message))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Limits the responses delivered with render updates, e.g. {"types": ["Click", "StateChanged"]}
+#[wasm_bindgen]
+pub fn set_response_filter(filter_json: &str) -> Result<(), JsValue> {
+    let filter: ResponseFilter = serde_json::from_str(filter_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse response filter: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_response_filter(filter);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Hover intent for one element; no config_json makes its hover effects instant again
+#[wasm_bindgen]
+pub fn set_hover_intent(element_id: &str, config_json: Option<String>) -> Result<(), JsValue> {
+    let config: Option<HoverIntentConfig> = config_json
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse hover intent config: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.set_hover_intent(element_id, config)
+            .map_err(|e| JsValue::from_str(&e.message))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+#[wasm_bindgen]
+pub fn clear_event_rate_limit(target_element: Option<String>, event_type_json: &str) -> Result<(), JsValue> {
+    let event_type: InteractionType = serde_json::from_str(event_type_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse event type: {}", e)))?;
+    
+    let mut global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_mut() {
+        engine.clear_event_rate_limit(target_element.as_deref(), &event_type);
+        Ok(())
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
+
+// Host-side heuristic kept for compatibility; limits set with set_event_rate_limit
+// are applied inside process_interaction instead
+#[wasm_bindgen]
+pub fn should_throttle_event(event_type: &str, last_event_time: f64) -> Result<bool, JsValue> {
+    let global_engine = ENGINE.lock().unwrap();
+    if let Some(engine) = global_engine.as_ref() {
+        let interaction_type = match event_type {
+            "mousemove" => InteractionType::MouseMove,
+            "touchmove" => InteractionType::TouchMove,
+            "scroll" => InteractionType::Scroll,
+            "wheel" => InteractionType::Wheel,
+            _ => return Ok(false),
+        };
+        
+        Ok(engine.responsive_adapter.should_throttle_event(&interaction_type, last_event_time))
+    } else {
+        Err(JsValue::from_str("Engine not initialized"))
+    }
+}
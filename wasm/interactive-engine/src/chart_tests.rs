@@ -1,5 +1,8 @@
 use super::*;
 use wasm_bindgen_test::*;
+// Native `cargo test` runs the same tests as plain #[test]s
+#[cfg(not(target_family = "wasm"))]
+use std::prelude::v1::test as wasm_bindgen_test;
 
 wasm_bindgen_test_configure!(run_in_browser);
 
@@ -45,8 +48,12 @@ fn test_line_chart_rendering() {
         fill_opacity: None,
         marker_size: Some(4.0),
         marker_shape: Some(MarkerShape::Circle),
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
     
     chart_renderer.add_series(&chart_id, series).unwrap();
@@ -89,8 +96,12 @@ fn test_bar_chart_rendering() {
         fill_opacity: Some(0.8),
         marker_size: None,
         marker_shape: None,
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
     
     chart_renderer.add_series(&chart_id, series).unwrap();
@@ -127,8 +138,12 @@ fn test_pie_chart_rendering() {
         fill_opacity: None,
         marker_size: None,
         marker_shape: None,
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
     
     chart_renderer.add_series(&chart_id, series).unwrap();
@@ -166,8 +181,12 @@ fn test_chart_caching() {
         fill_opacity: None,
         marker_size: None,
         marker_shape: None,
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
     
     chart_renderer.add_series(&chart_id, series).unwrap();
@@ -209,14 +228,14 @@ fn test_vector_shape_rendering() {
     let mut vector_engine = VectorEngine::new();
     
     // Create a rectangle
-    let rect_id = vector_engine.create_shape(
+    let _rect_id = vector_engine.create_shape(
         ShapeType::Rectangle,
         Position { x: 10.0, y: 20.0 },
         Size { width: 100.0, height: 50.0 }
     ).unwrap();
     
     // Create a circle
-    let circle_id = vector_engine.create_shape(
+    let _circle_id = vector_engine.create_shape(
         ShapeType::Circle,
         Position { x: 150.0, y: 20.0 },
         Size { width: 60.0, height: 60.0 }
@@ -310,8 +329,12 @@ fn test_chart_performance_stats() {
             fill_opacity: None,
             marker_size: None,
             marker_shape: None,
+            markers: None,
+            line_style: None,
             visible: true,
             y_axis: AxisReference::Primary,
+            error: None,
+            pattern: None,
         };
         
         chart_renderer.add_series(&chart_id, series).unwrap();
@@ -362,13 +385,11 @@ fn test_chart_styling_defaults() {
 fn test_multiple_chart_types() {
     let mut chart_renderer = ChartRenderer::new();
     
-    let chart_types = vec![
-        ChartType::Line,
+    let chart_types = [ChartType::Line,
         ChartType::Bar,
         ChartType::Pie,
         ChartType::Scatter,
-        ChartType::Area,
-    ];
+        ChartType::Area];
     
     let test_data = serde_json::json!([
         {"value": 10, "label": "A"},
@@ -393,8 +414,12 @@ fn test_multiple_chart_types() {
             fill_opacity: None,
             marker_size: None,
             marker_shape: None,
+            markers: None,
+            line_style: None,
             visible: true,
             y_axis: AxisReference::Primary,
+            error: None,
+            pattern: None,
         };
         
         chart_renderer.add_series(&chart_id, series).unwrap();
@@ -428,8 +453,12 @@ fn test_scatter_chart_rendering() {
         fill_opacity: None,
         marker_size: Some(6.0),
         marker_shape: Some(MarkerShape::Circle),
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
     
     chart_renderer.add_series(&chart_id, series).unwrap();
@@ -473,8 +502,12 @@ fn test_area_chart_rendering() {
         fill_opacity: Some(0.4),
         marker_size: None,
         marker_shape: None,
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
     
     chart_renderer.add_series(&chart_id, series).unwrap();
@@ -514,8 +547,12 @@ fn test_histogram_chart_rendering() {
         fill_opacity: None,
         marker_size: None,
         marker_shape: None,
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
     
     chart_renderer.add_series(&chart_id, series).unwrap();
@@ -582,8 +619,12 @@ fn test_radar_chart_rendering() {
         fill_opacity: Some(0.3),
         marker_size: None,
         marker_shape: None,
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
     
     chart_renderer.add_series(&chart_id, series).unwrap();
@@ -624,8 +665,12 @@ fn test_gauge_chart_rendering() {
         fill_opacity: None,
         marker_size: None,
         marker_shape: None,
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
     
     chart_renderer.add_series(&chart_id, series).unwrap();
@@ -726,6 +771,278 @@ fn test_stream_data_source() {
     ]);
 }
 
+#[wasm_bindgen_test]
+fn test_typed_column_ingestion() {
+    let mut data_source = DataSource::new(
+        "typed_source".to_string(),
+        DataSourceType::Dynamic,
+        serde_json::Value::Null
+    );
+
+    data_source.update_column(DataSource::DEFAULT_COLUMN, &[2.0, 4.0, 6.0]).unwrap();
+
+    // Statistics read the native column instead of the JSON payload
+    let stats = data_source.get_data_statistics();
+    assert_eq!(stats.count, 3);
+    assert_eq!(stats.mean, 4.0);
+
+    // Columnar batches must be rectangular
+    let x = [0.0, 1.0];
+    let y = [10.0, 20.0];
+    data_source.update_columns(&[("x".to_string(), &x[..]), ("y".to_string(), &y[..])]).unwrap();
+    assert_eq!(data_source.column("y"), Some(&y[..]));
+    assert!(data_source.update_columns(&[("x".to_string(), &x[..]), ("y".to_string(), &y[..1])]).is_err());
+
+    // Charts consume columns as row objects
+    let rows = data_source.to_rows();
+    assert_eq!(rows[1]["y"], serde_json::json!(20.0));
+    assert!(data_source.column(DataSource::DEFAULT_COLUMN).is_none());
+
+    // Later row data replaces the columns instead of being shadowed by them
+    data_source.update_data(serde_json::json!([7, 9])).unwrap();
+    assert_eq!(data_source.to_rows(), serde_json::json!([7, 9]));
+    assert_eq!(data_source.numeric_values(), vec![7.0, 9.0]);
+
+    let mut stream = DataSource::new("typed_stream".to_string(), DataSourceType::Stream, serde_json::Value::Null);
+    stream.update_column("v", &[1.0, 2.0]).unwrap();
+    stream.update_column("v", &[3.0]).unwrap();
+    assert_eq!(stream.column("v"), Some(&[1.0, 2.0, 3.0][..]));
+
+    // Stream appends keep every column the same length
+    assert_eq!(stream.update_column("w", &[1.0]).unwrap_err().code, "COLUMN_LENGTH_MISMATCH");
+    stream.update_data(serde_json::json!([{"v": 4.0}])).unwrap();
+    assert_eq!(stream.to_rows().as_array().unwrap().len(), 4);
+    assert!(stream.columns.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_extended_data_statistics() {
+    let data_source = DataSource::new(
+        "stats_source".to_string(),
+        DataSourceType::Static,
+        serde_json::json!([1, 2, 2, 3, 4, 5, 100])
+    );
+
+    let stats = data_source.get_data_statistics();
+    assert_eq!(stats.median, 3.0);
+    assert_eq!(stats.mode, Some(2.0));
+    assert!(stats.skewness > 0.0);
+    assert_eq!(stats.outlier_count, 1);
+
+    assert_eq!(data_source.detect_outliers(1.5), vec![6]);
+    assert_eq!(data_source.get_percentiles(&[0.0, 50.0, 100.0]), vec![1.0, 3.0, 100.0]);
+
+    let histogram = data_source.get_histogram(4, Some((0.0, 8.0)));
+    assert_eq!(histogram.bins.len(), 4);
+    assert_eq!(histogram.bins.iter().map(|bin| bin.count).sum::<usize>(), 6);
+    assert_eq!(histogram.skipped, 0);
+
+    // Non-finite values stay out of the bins and out of the derived range
+    let mut noisy = DataSource::new("noisy".to_string(), DataSourceType::Dynamic, serde_json::Value::Null);
+    noisy.update_column(DataSource::DEFAULT_COLUMN, &[f64::NAN, 1.0, 2.0, f64::INFINITY, 3.0, 4.0, f64::NEG_INFINITY]).unwrap();
+    let histogram = noisy.get_histogram(3, None);
+    assert_eq!(histogram.skipped, 3);
+    assert_eq!(histogram.bins[0].start, 1.0);
+    assert_eq!(histogram.bins[2].end, 4.0);
+    assert_eq!(histogram.bins.iter().map(|bin| bin.count).collect::<Vec<_>>(), vec![1, 1, 2]);
+
+    let a = DataSource::new("a".to_string(), DataSourceType::Static, serde_json::json!([1, 2, 3, 4]));
+    let b = DataSource::new("b".to_string(), DataSourceType::Static, serde_json::json!([8, 6, 4, 2]));
+    assert!((a.correlation(&b).unwrap() + 1.0).abs() < 1e-9);
+
+    // Every statistic leaves the non-finite values out, not just the histogram
+    let stats = noisy.get_data_statistics();
+    assert_eq!(stats.skipped, 3);
+    assert_eq!(stats.count, 4);
+    assert_eq!((stats.min, stats.max, stats.mean), (1.0, 4.0, 2.5));
+    assert!(stats.std_dev.is_finite());
+
+    // The remaining statistics are available to computed sources as formulas
+    let sources: HashMap<String, DataSource> = [("stats_source".to_string(), data_source)].into_iter().collect();
+    let mut computed = DataSource::new("computed".to_string(), DataSourceType::Computed, serde_json::Value::Null);
+    computed.compute_from_sources(&sources, "percentile:50").unwrap();
+    assert_eq!(computed.data, serde_json::json!(3.0));
+    computed.compute_from_sources(&sources, "outliers").unwrap();
+    assert_eq!(computed.data, serde_json::json!([100.0]));
+    computed.compute_from_sources(&sources, "max").unwrap();
+    assert_eq!(computed.data, serde_json::json!(100.0));
+    computed.compute_from_sources(&sources, "histogram:3").unwrap();
+    assert_eq!(computed.data["bins"].as_array().unwrap().len(), 3);
+    computed.compute_from_sources(&sources, "statistics").unwrap();
+    assert_eq!(computed.data["median"], serde_json::json!(3.0));
+    for formula in ["percentile:101", "histogram:0", "geometric_mean"] {
+        let error = computed.compute_from_sources(&sources, formula).unwrap_err();
+        assert_eq!(error.code, "UNKNOWN_FORMULA", "{}", formula);
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_statistic_annotations() {
+    let mut chart_renderer = ChartRenderer::new();
+    let config = ChartConfig {
+        annotations: vec![
+            StatisticAnnotation { series_id: "series1".to_string(), statistic: "mean".to_string(), label: None, color: None },
+            StatisticAnnotation { series_id: "series1".to_string(), statistic: "percentile:90".to_string(), label: Some("P90 <target>".to_string()), color: Some("#d62728".to_string()) },
+            // Neither a single number nor a known series, so nothing is drawn for these
+            StatisticAnnotation { series_id: "series1".to_string(), statistic: "histogram:4".to_string(), label: None, color: None },
+            StatisticAnnotation { series_id: "missing".to_string(), statistic: "mean".to_string(), label: None, color: None },
+        ],
+        ..ChartConfig::default()
+    };
+    let chart_id = chart_renderer.create_chart(ChartType::Line, "test_data".to_string(), config).unwrap();
+    chart_renderer.add_series(&chart_id, ChartSeries {
+        id: "series1".to_string(),
+        name: "Test Series".to_string(),
+        data_field: "value".to_string(),
+        color: "#1f77b4".to_string(),
+        line_width: Some(2.0),
+        fill_opacity: None,
+        marker_size: None,
+        marker_shape: None,
+        markers: None,
+        line_style: None,
+        visible: true,
+        y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
+    }).unwrap();
+
+    let data = serde_json::json!([{"value": 10}, {"value": 20}, {"value": 15}, {"value": 35}]);
+    let svg = chart_renderer.render_chart(&chart_id, &data).unwrap().svg_content;
+    assert_eq!(svg.matches(r#"class="chart-annotation""#).count(), 2);
+    assert!(svg.contains(r#"data-statistic="mean" data-value="20""#));
+    assert!(svg.contains(">mean 20<"));
+    assert!(svg.contains("P90 &lt;target&gt;"));
+    assert!(svg.contains(r##"stroke="#d62728""##));
+}
+
+#[wasm_bindgen_test]
+fn test_data_downsampling_and_aggregation() {
+    let values: Vec<f64> = (0..1000).map(|i| (i as f64 / 10.0).sin()).collect();
+    let mut data_source = DataSource::new("large".to_string(), DataSourceType::Dynamic, serde_json::Value::Null);
+    data_source.update_column(DataSource::DEFAULT_COLUMN, &values).unwrap();
+    let fields = PointFields::default();
+
+    let lttb = data_source.downsample(&DownsampleMethod::Lttb, 100, &fields);
+    assert_eq!(lttb.len(), 100);
+    assert_eq!(lttb[0], (0.0, values[0]));
+    assert_eq!(lttb[99], (999.0, values[999]));
+
+    // Min-max decimation keeps the global extremes
+    let min_max = data_source.downsample(&DownsampleMethod::MinMax, 50, &fields);
+    let max_y = min_max.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    assert_eq!(max_y, values.iter().copied().fold(f64::NEG_INFINITY, f64::max));
+
+    let buckets = data_source.downsample(&DownsampleMethod::BucketAverage, 10, &fields);
+    assert_eq!(buckets.len(), 10);
+
+    // Points that aren't finite are left out rather than bucketed
+    let mut gappy = DataSource::new("gappy".to_string(), DataSourceType::Dynamic, serde_json::Value::Null);
+    gappy.update_column(DataSource::DEFAULT_COLUMN, &[1.0, f64::NAN, 3.0, f64::INFINITY, 2.0, 5.0, f64::NAN, 4.0]).unwrap();
+    for method in [DownsampleMethod::Lttb, DownsampleMethod::MinMax, DownsampleMethod::BucketAverage] {
+        let sampled = gappy.downsample(&method, 4, &fields);
+        assert!(!sampled.is_empty() && sampled.iter().all(|(x, y)| x.is_finite() && y.is_finite()), "{:?}", method);
+    }
+    assert_eq!(gappy.downsample(&DownsampleMethod::MinMax, 4, &fields), vec![(0.0, 1.0), (2.0, 3.0), (5.0, 5.0), (7.0, 4.0)]);
+
+    // Row objects are read through the x and y fields; rows without a numeric y are skipped
+    let rows: Vec<serde_json::Value> = (0..1000).map(|i| serde_json::json!({"t": i * 10, "y": values[i]})).collect();
+    let mut row_data = DataSource::new("rows".to_string(), DataSourceType::Static, serde_json::Value::Array(rows));
+    let row_fields = PointFields { x: "t".to_string(), y: "y".to_string() };
+    let sampled = row_data.downsample(&DownsampleMethod::Lttb, 100, &row_fields);
+    assert_eq!(sampled.len(), 100);
+    assert_eq!(sampled[99], (9990.0, values[999]));
+    assert!(row_data.downsample(&DownsampleMethod::Lttb, 100, &fields).is_empty());
+    row_data.replace_rows(serde_json::json!([{"y": 1}, {"t": 5, "y": "n/a"}, {"t": 7, "y": 2}]));
+    assert_eq!(row_data.numeric_points(&row_fields), vec![(0.0, 1.0), (7.0, 2.0)]);
+
+    let sales = DataSource::new("sales".to_string(), DataSourceType::Static, serde_json::json!([
+        {"region": "north", "amount": 10},
+        {"region": "south", "amount": 5},
+        {"region": "north", "amount": 30}
+    ]));
+    let totals = sales.aggregate("region", "amount", &AggregateFunction::Sum);
+    assert_eq!(totals, serde_json::json!([
+        {"region": "north", "amount": 40.0},
+        {"region": "south", "amount": 5.0}
+    ]));
+}
+
+#[wasm_bindgen_test]
+fn test_data_dependency_graph() {
+    let mut sources = BTreeMap::new();
+    sources.insert("raw".to_string(), DataSource::new("raw".to_string(), DataSourceType::Dynamic, serde_json::json!([1, 2, 3])));
+    sources.insert("total".to_string(), DataSource::computed("total".to_string(), vec!["raw".to_string()], "sum"));
+    sources.insert("mean".to_string(), DataSource::computed("mean".to_string(), vec!["raw".to_string()], "average"));
+    sources.insert("summary".to_string(), DataSource::computed("summary".to_string(), vec!["total".to_string(), "mean".to_string()], "sum"));
+
+    let graph = DataDependencyGraph::from_sources(&sources);
+    assert!(graph.find_cycle().is_none());
+    assert_eq!(graph.upstream("summary"), vec!["mean", "raw", "total"]);
+
+    // Dependents are recomputed after all of their inputs
+    let order = graph.recompute_order("raw").unwrap();
+    assert_eq!(order.len(), 3);
+    assert_eq!(order.last().unwrap(), "summary");
+
+    sources.get_mut("raw").unwrap().dependencies = vec!["summary".to_string()];
+    let graph = DataDependencyGraph::from_sources(&sources);
+    assert!(graph.find_cycle().is_some());
+    assert_eq!(graph.recompute_order("raw").unwrap_err().code, "DEPENDENCY_CYCLE");
+}
+
+#[wasm_bindgen_test]
+fn test_data_schema_validation() {
+    let mut fields = HashMap::new();
+    fields.insert("price".to_string(), FieldSchema { field_type: FieldType::Number, min: Some(0.0), max: None, nullable: false });
+    fields.insert("symbol".to_string(), FieldSchema { field_type: FieldType::String, min: None, max: None, nullable: false });
+    let schema = DataSchema { fields, required: vec!["symbol".to_string()], mode: ValidationMode::Reject };
+
+    let payload = serde_json::json!([
+        {"symbol": "ABC", "price": 10.5},
+        {"symbol": "XYZ", "price": -1},
+        {"price": "n/a"}
+    ]);
+
+    let mut rejecting = DataSource::new("quotes".to_string(), DataSourceType::Dynamic, serde_json::json!([]))
+        .with_schema(schema.clone());
+    let errors = rejecting.validate_incoming(payload.clone()).unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0].row, Some(1));
+    assert_eq!(errors[0].field, "price");
+
+    let mut quarantining = DataSource::new("quotes".to_string(), DataSourceType::Dynamic, serde_json::json!([]))
+        .with_schema(DataSchema { mode: ValidationMode::Quarantine, ..schema });
+    let (accepted, errors) = quarantining.validate_incoming(payload).unwrap();
+    assert_eq!(accepted, serde_json::json!([{"symbol": "ABC", "price": 10.5}]));
+    assert_eq!(errors.len(), 3);
+    assert_eq!(quarantining.quarantine.len(), 2);
+}
+
+#[wasm_bindgen_test]
+fn test_column_schema_validation() {
+    let mut fields = HashMap::new();
+    fields.insert("price".to_string(), FieldSchema { field_type: FieldType::Number, min: Some(0.0), max: None, nullable: false });
+    fields.insert("symbol".to_string(), FieldSchema { field_type: FieldType::String, min: None, max: None, nullable: false });
+    let schema = DataSchema { fields, required: Vec::new(), mode: ValidationMode::Reject };
+
+    let mut rejecting = DataSource::new("quotes".to_string(), DataSourceType::Dynamic, serde_json::json!([]))
+        .with_schema(schema.clone());
+    let error = rejecting.update_column("price", &[1.0, f64::NAN]).unwrap_err();
+    assert_eq!(error.code, "DATA_VALIDATION_FAILED");
+    assert_eq!(rejecting.update_column("volume", &[1.0]).unwrap_err().code, "DATA_VALIDATION_FAILED");
+    assert_eq!(rejecting.update_column("symbol", &[1.0]).unwrap_err().code, "DATA_VALIDATION_FAILED");
+    assert!(rejecting.columns.is_empty());
+
+    // Quarantine drops bad rows but still refuses columns the schema cannot hold
+    let mut quarantining = DataSource::new("quotes".to_string(), DataSourceType::Dynamic, serde_json::json!([]))
+        .with_schema(DataSchema { mode: ValidationMode::Quarantine, ..schema });
+    quarantining.update_column("price", &[2.0, f64::INFINITY, -1.0, 3.0]).unwrap();
+    assert_eq!(quarantining.column("price"), Some(&[2.0, 3.0][..]));
+    assert_eq!(quarantining.quarantine.len(), 2);
+    assert!(quarantining.update_column("volume", &[1.0]).is_err());
+}
+
 #[wasm_bindgen_test]
 fn test_data_binding_manager() {
     let mut binding_manager = DataBindingManager::new();
@@ -734,6 +1051,7 @@ fn test_data_binding_manager() {
         source_id: "test_source".to_string(),
         target_element: "test_element".to_string(),
         property_path: "value".to_string(),
+        target_property: None,
         transform_function: Some("percentage".to_string()),
         update_trigger: UpdateTrigger::Immediate,
     };
@@ -779,6 +1097,64 @@ fn test_complex_vector_paths() {
     assert_eq!(vector_engine.paths.len(), 2);
 }
 
+#[cfg(feature = "vector")]
+#[wasm_bindgen_test]
+fn test_vector_text() {
+    let mut vector_engine = VectorEngine::new();
+    let font: FontTable = serde_json::from_value(serde_json::json!({
+        "family": "Logo", "units_per_em": 100, "ascent": 80, "descent": 20, "default_advance": 60,
+        "glyphs": {"L": "M0,0h40v10h-30v60h-10z", "I": "M 0 0 H 10 V 70 H 0 Z"},
+    })).unwrap();
+    font.validate().unwrap();
+    vector_engine.fonts.push(font);
+    let gradient_id = vector_engine.create_gradient(GradientType::Linear { x1: 0.0, y1: 0.0, x2: 1.0, y2: 0.0 }, vec![
+        GradientStop { offset: 0.0, color: "#ff0000".to_string(), opacity: 1.0 },
+        GradientStop { offset: 1.0, color: "#0000ff".to_string(), opacity: 1.0 },
+    ]).unwrap();
+    let shape_id = vector_engine.create_shape(ShapeType::Text, Position { x: 10.0, y: 20.0 }, Size { width: 100.0, height: 50.0 }).unwrap();
+    vector_engine.shapes.get_mut(&shape_id).unwrap().fill = Fill { color: None, gradient_id: Some(gradient_id.clone()), pattern_id: None, opacity: 1.0 };
+    let text = |content: &str| VectorText {
+        content: content.to_string(),
+        font_family: "Logo".to_string(),
+        font_size: 50.0,
+        bold: false,
+        italic: false,
+        letter_spacing: 0.0,
+    };
+
+    // Glyphs scale from font units, flip to y down and sit on the baseline, 40px below the top
+    vector_engine.set_shape_text(&shape_id, text("LI")).unwrap();
+    let svg = vector_engine.render_to_svg(200.0, 100.0);
+    assert!(svg.contains(r#"<path d="M 10 60 L 30 60 L 30 55 L 15 55 L 15 25 L 10 25 Z M 40 60 L 45 60 L 45 25 L 40 25 Z""#));
+    assert!(svg.contains(&format!(r#"fill="url(#{})""#, gradient_id)));
+
+    // Without an outline for every character the text stays live
+    vector_engine.set_shape_text(&shape_id, text("LIV")).unwrap();
+    assert!(vector_engine.render_to_svg(200.0, 100.0).contains(r#"<tspan x="10" dy="40">LIV</tspan>"#));
+    let error = vector_engine.convert_text_to_path(&shape_id).unwrap_err();
+    assert_eq!(error.code, "MISSING_GLYPHS");
+    assert!(error.message.ends_with(": V"));
+
+    vector_engine.set_shape_text(&shape_id, text("LI")).unwrap();
+    let path_id = vector_engine.convert_text_to_path(&shape_id).unwrap();
+    assert!(vector_engine.shapes.is_empty());
+    let path = &vector_engine.paths[&path_id];
+    assert_eq!(path.commands.len(), 12);
+    assert_eq!(path.fill.gradient_id.as_deref(), Some(gradient_id.as_str()));
+    assert_eq!(vector_engine.convert_text_to_path(&shape_id).unwrap_err().code, "SHAPE_NOT_FOUND");
+
+    let rect_id = vector_engine.create_shape(ShapeType::Rectangle, Position { x: 0.0, y: 0.0 }, Size { width: 1.0, height: 1.0 }).unwrap();
+    assert_eq!(vector_engine.set_shape_text(&rect_id, text("LI")).unwrap_err().code, "NOT_TEXT");
+
+    assert_eq!(text::parse_path_data("M.5.5l1e1-2").unwrap().len(), 2);
+    assert!(matches!(text::parse_path_data("M.5.5l1e1-2").unwrap()[1], PathCommand::LineTo { x, y } if x == 10.5 && y == -1.5));
+    assert!(text::parse_path_data("M 0 0 A 1 1 0 0 1 5 5").is_err());
+    let broken: FontTable = serde_json::from_value(serde_json::json!({
+        "family": "Logo", "units_per_em": 100, "ascent": 80, "descent": 20, "default_advance": 60, "glyphs": {"X": "M 0"},
+    })).unwrap();
+    assert_eq!(broken.validate().unwrap_err().code(), "INVALID_FONT_TABLE");
+}
+
 #[wasm_bindgen_test]
 fn test_chart_interactions() {
     let mut chart_renderer = ChartRenderer::new();
@@ -847,6 +1223,7 @@ fn test_interaction_manager_mouse_events() {
         }),
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -879,6 +1256,7 @@ fn test_interaction_manager_touch_events() {
                 radius: Some(10.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             changed_touches: vec![TouchPoint {
                 identifier: 1,
@@ -886,6 +1264,7 @@ fn test_interaction_manager_touch_events() {
                 radius: Some(10.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             target_touches: vec![],
             force: Some(0.5),
@@ -895,6 +1274,7 @@ fn test_interaction_manager_touch_events() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -922,6 +1302,7 @@ fn test_gesture_recognizer_tap_detection() {
             radius: Some(8.0),
             rotation_angle: None,
             force: Some(0.3),
+            confidence: None,
         }],
         changed_touches: vec![TouchPoint {
             identifier: 1,
@@ -929,6 +1310,7 @@ fn test_gesture_recognizer_tap_detection() {
             radius: Some(8.0),
             rotation_angle: None,
             force: Some(0.3),
+            confidence: None,
         }],
         target_touches: vec![],
         force: Some(0.3),
@@ -951,6 +1333,7 @@ fn test_gesture_recognizer_tap_detection() {
             radius: Some(8.0),
             rotation_angle: None,
             force: Some(0.3),
+            confidence: None,
         }],
         target_touches: vec![],
         force: None,
@@ -981,6 +1364,7 @@ fn test_gesture_recognizer_swipe_detection() {
             radius: Some(8.0),
             rotation_angle: None,
             force: Some(0.4),
+            confidence: None,
         }],
         changed_touches: vec![TouchPoint {
             identifier: 1,
@@ -988,6 +1372,7 @@ fn test_gesture_recognizer_swipe_detection() {
             radius: Some(8.0),
             rotation_angle: None,
             force: Some(0.4),
+            confidence: None,
         }],
         target_touches: vec![],
         force: Some(0.4),
@@ -1005,6 +1390,7 @@ fn test_gesture_recognizer_swipe_detection() {
             radius: Some(8.0),
             rotation_angle: None,
             force: Some(0.4),
+            confidence: None,
         }],
         changed_touches: vec![TouchPoint {
             identifier: 1,
@@ -1012,6 +1398,7 @@ fn test_gesture_recognizer_swipe_detection() {
             radius: Some(8.0),
             rotation_angle: None,
             force: Some(0.4),
+            confidence: None,
         }],
         target_touches: vec![],
         force: Some(0.4),
@@ -1089,6 +1476,7 @@ fn test_responsive_adapter_event_adaptation() {
                 radius: Some(5.0), // Small radius
                 rotation_angle: None,
                 force: Some(0.3),
+                confidence: None,
             }],
             changed_touches: vec![],
             target_touches: vec![],
@@ -1099,6 +1487,7 @@ fn test_responsive_adapter_event_adaptation() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -1151,6 +1540,7 @@ fn test_interaction_event_delegation() {
         }),
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -1188,6 +1578,7 @@ fn test_interaction_performance_metrics() {
             }),
             keyboard_data: None,
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -1226,6 +1617,7 @@ fn test_keyboard_state_management() {
             repeat: false,
         }),
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -1257,6 +1649,7 @@ fn test_keyboard_state_management() {
             repeat: false,
         }),
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -1270,4 +1663,825 @@ fn test_keyboard_state_management() {
     // Control key should no longer be tracked as pressed
     assert!(!interaction_manager.keyboard_state.pressed_keys.contains_key("Control"));
     assert!(!interaction_manager.keyboard_state.modifiers.ctrl);
-}
\ No newline at end of file
+}
+
+#[wasm_bindgen_test]
+fn test_series_sonification() {
+    let series = ChartSeries {
+        id: "revenue".to_string(),
+        name: "Revenue".to_string(),
+        data_field: "value".to_string(),
+        color: "#1f77b4".to_string(),
+        line_width: Some(2.0),
+        fill_opacity: None,
+        marker_size: None,
+        marker_shape: None,
+        markers: None,
+        line_style: None,
+        visible: true,
+        y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
+    };
+    let points: Vec<(f64, Option<String>)> = vec![
+        (10.0, Some("Jan".to_string())),
+        (95.0, Some("Mar".to_string())),
+        (40.0, Some("Apr".to_string())),
+        (80.0, Some("Jun".to_string())),
+    ];
+    let settings = SonificationSettings::default();
+    let sonification = Sonification::from_values("chart_1", &series, &points, &settings);
+
+    assert_eq!(sonification.tones.len(), 4);
+    assert_eq!(sonification.tones[0].frequency, settings.min_frequency);
+    assert!((sonification.tones[1].frequency - settings.max_frequency).abs() < 1e-9);
+    assert_eq!(sonification.tones[0].pan, -1.0);
+    assert_eq!(sonification.tones[3].pan, 1.0);
+    assert_eq!(sonification.tones[2].start_ms, 500.0);
+    assert_eq!(sonification.total_duration_ms, 950.0);
+    assert_eq!(sonification.summary, "Revenue rises from 10 to 80 between Jan and Jun, peaking at 95 in Mar.");
+
+    let flat = Sonification::from_values("chart_1", &series, &[(5.0, None), (5.0, None)], &settings);
+    assert!(flat.tones.iter().all(|tone| tone.frequency > settings.min_frequency && tone.frequency < settings.max_frequency));
+    assert_eq!(flat.summary, "Revenue ends where it started, at 5, between point 1 and point 2.");
+    assert_eq!(Sonification::from_values("chart_1", &series, &[], &settings).summary, "Revenue has no data.");
+}
+
+#[wasm_bindgen_test]
+fn test_chart_description() {
+    let mut chart_renderer = ChartRenderer::new();
+    let config = ChartConfig {
+        title: Some(ChartTitle {
+            text: "Sales".to_string(),
+            font_size: 16.0,
+            font_family: "Arial".to_string(),
+            color: "#333333".to_string(),
+            alignment: TextAlignment::Center,
+        }),
+        ..ChartConfig::default()
+    };
+    let chart_id = chart_renderer.create_chart(ChartType::Line, "sales".to_string(), config).unwrap();
+    chart_renderer.add_series(&chart_id, ChartSeries {
+        id: "revenue".to_string(),
+        name: "Revenue".to_string(),
+        data_field: "value".to_string(),
+        color: "#1f77b4".to_string(),
+        line_width: None,
+        fill_opacity: None,
+        marker_size: None,
+        marker_shape: None,
+        markers: None,
+        line_style: None,
+        visible: true,
+        y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
+    }).unwrap();
+
+    let data = serde_json::json!([
+        {"value": 30, "label": "Jan"},
+        {"value": 10, "label": "Feb"},
+        {"value": 20, "label": "Mar"},
+    ]);
+    let description = ChartDescription::from_chart(&chart_renderer.charts[&chart_id], &data);
+
+    assert_eq!(description.chart_type, "line");
+    assert_eq!(description.title.as_deref(), Some("Sales"));
+    let series = &description.series[0];
+    assert_eq!(series.trend, "falling");
+    assert_eq!((series.min, series.min_label.as_deref()), (Some(10.0), Some("Feb")));
+    assert_eq!((series.max, series.max_label.as_deref()), (Some(30.0), Some("Jan")));
+    assert_eq!(
+        description.to_text(),
+        "Line chart \"Sales\". Revenue falls from 30 to 20 between Jan and Mar, with a low of 10 in Feb."
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_chart_palette_and_theme_tokens() {
+    let okabe_ito = ChartPalette::OkabeIto.colors(8);
+    assert_eq!(okabe_ito, ChartPalette::OkabeIto.base_colors());
+    let extended = ChartPalette::OkabeIto.colors(10);
+    assert_eq!(extended.len(), 10);
+    assert_eq!(extended.iter().collect::<HashSet<_>>().len(), 10);
+    assert_eq!(ChartPalette::Viridis.colors(3).first().map(String::as_str), Some("#440154"));
+    assert_eq!(ChartPalette::Viridis.colors(3).last().map(String::as_str), Some("#fde725"));
+
+    let mut chart_renderer = ChartRenderer::new();
+    let config = ChartConfig {
+        background_color: Some("theme:background".to_string()),
+        ..ChartConfig::default()
+    };
+    let chart_id = chart_renderer.create_chart(ChartType::Line, "sales".to_string(), config).unwrap();
+    for (id, color) in [("a", "auto"), ("b", ""), ("c", "palette:3"), ("d", "theme:text")] {
+        chart_renderer.add_series(&chart_id, ChartSeries {
+            id: id.to_string(),
+            name: id.to_string(),
+            data_field: "value".to_string(),
+            color: color.to_string(),
+            line_width: None,
+            fill_opacity: None,
+            marker_size: None,
+            marker_shape: None,
+            markers: None,
+            line_style: None,
+            visible: true,
+            y_axis: AxisReference::Primary,
+            error: None,
+            pattern: None,
+        }).unwrap();
+    }
+    chart_renderer.set_chart_palette(&chart_id, Some(ChartPalette::OkabeIto)).unwrap();
+    chart_renderer.set_chart_theme(&chart_id, Some(ChartTheme::dark())).unwrap();
+
+    let resolved = chart_renderer.charts[&chart_id].resolve_style_tokens();
+    let colors: Vec<&str> = resolved.series.iter().map(|series| series.color.as_str()).collect();
+    assert_eq!(colors, ["#e69f00", "#56b4e9", "#f0e442", "#e0e0e0"]);
+    assert_eq!(resolved.config.background_color.as_deref(), Some("#121212"));
+    assert_eq!(resolved.styling.color_palette, okabe_ito);
+}
+
+#[wasm_bindgen_test]
+fn test_gantt_chart_rendering() {
+    assert_eq!(parse_time_value(&serde_json::json!("2024-03-01")), Some(1_709_251_200_000.0));
+    assert_eq!(parse_time_value(&serde_json::json!("2024-03-01T12:00:00Z")), Some(1_709_294_400_000.0));
+    assert_eq!(format_date(1_709_294_400_000.0), "2024-03-01");
+
+    // "Today" is pinned so the marker does not depend on the clock
+    let day = 86_400_000.0;
+    let now = 1_709_294_400_000.0;
+    let mut chart_renderer = ChartRenderer::new();
+    let chart_id = "plan_chart";
+    let config = ChartConfig { today: Some(now + day), ..ChartConfig::default() };
+    chart_renderer.create_chart_with_id(chart_id, ChartType::Gantt, "plan".to_string(), config).unwrap();
+    
+    let test_data = serde_json::json!([
+        {"id": "design", "task": "Design", "start": now - 2.0 * day, "end": now, "progress": 1.0},
+        {"id": "build", "task": "Build", "start": now, "end": now + 3.0 * day, "progress": 0.25, "dependencies": ["design"]},
+        {"task": "No dates"}
+    ]);
+    
+    let rendered_chart = chart_renderer.render_chart(chart_id, &test_data).unwrap();
+    
+    assert_eq!(rendered_chart.data_points.len(), 2); // Rows without dates are skipped
+    assert_eq!(rendered_chart.svg_content.matches(r#"class="progress""#).count(), 2);
+    assert_eq!(rendered_chart.svg_content.matches(r#"class="dependency""#).count(), 1);
+    assert!(rendered_chart.svg_content.contains(r#"<line class="today" x1="244.00""#));
+    assert!(rendered_chart.svg_content.contains(">Build</text>"));
+
+    chart_renderer.create_chart_with_id("empty_plan", ChartType::Gantt, "plan".to_string(), ChartConfig::default()).unwrap();
+    assert_eq!(chart_renderer.render_chart("empty_plan", &serde_json::json!([])).unwrap_err().code, "NO_DATA");
+}
+
+#[wasm_bindgen_test]
+fn test_waterfall_chart_rendering() {
+    let mut chart_renderer = ChartRenderer::new();
+    let chart_id = chart_renderer.create_chart(
+        ChartType::Waterfall,
+        "bridge".to_string(),
+        ChartConfig::default()
+    ).unwrap();
+    
+    let test_data = serde_json::json!([
+        {"label": "Start", "value": 100},
+        {"label": "Sales", "value": 40},
+        {"label": "Costs", "value": -60},
+        {"label": "End", "total": true}
+    ]);
+    
+    let rendered_chart = chart_renderer.render_chart(&chart_id, &test_data).unwrap();
+    
+    let totals: Vec<f64> = rendered_chart.data_points.iter().map(|point| point.y).collect();
+    assert_eq!(totals, [100.0, 140.0, 80.0, 80.0]);
+    let colors: Vec<&str> = rendered_chart.data_points.iter().map(|point| point.color.as_str()).collect();
+    assert_eq!(colors, ["#4CAF50", "#4CAF50", "#F44336", "#607D8B"]);
+    assert_eq!(rendered_chart.svg_content.matches(r#"class="connector""#).count(), 3);
+    assert!(rendered_chart.svg_content.contains(">Costs</text>"));
+}
+
+#[wasm_bindgen_test]
+fn test_funnel_and_pyramid_rendering() {
+    let mut chart_renderer = ChartRenderer::new();
+    let test_data = serde_json::json!([
+        {"label": "Visits", "value": 1000},
+        {"label": "Signups", "value": 250},
+        {"label": "Orders", "value": 50}
+    ]);
+    
+    let funnel_id = chart_renderer.create_chart(ChartType::Funnel, "conversions".to_string(), ChartConfig::default()).unwrap();
+    let rendered_chart = chart_renderer.render_chart(&funnel_id, &test_data).unwrap();
+    assert_eq!(rendered_chart.svg_content.matches(r#"class="stage""#).count(), 3);
+    assert!(rendered_chart.svg_content.contains("Orders: 5.0%"));
+    assert_eq!(rendered_chart.data_points[2].value["percent_of_previous"], serde_json::json!(20.0));
+    
+    let config = ChartConfig {
+        stages: StageOptions { neck_width: 0.1, labels: StageLabels::PercentOfPrevious },
+        ..ChartConfig::default()
+    };
+    let previous_id = chart_renderer.create_chart(ChartType::Funnel, "conversions".to_string(), config).unwrap();
+    let rendered_chart = chart_renderer.render_chart(&previous_id, &test_data).unwrap();
+    assert!(rendered_chart.svg_content.contains("Signups: 25.0%"));
+    
+    // Pyramid stages are as tall as their share of the total
+    let pyramid_id = chart_renderer.create_chart(ChartType::Pyramid, "conversions".to_string(), ChartConfig::default()).unwrap();
+    chart_renderer.render_chart(&pyramid_id, &test_data).unwrap();
+    let chart = &chart_renderer.charts[&pyramid_id];
+    let bands = stage_bands(chart, &[1000.0, 250.0, 50.0]);
+    let heights: Vec<f64> = bands.iter().map(|(top, bottom)| bottom - top).collect();
+    assert!((heights[0] / heights[1] - 4.0).abs() < 1e-9);
+    assert!((bands[2].1 - (chart.config.height - chart.config.margin.bottom)).abs() < 1e-9);
+}
+
+#[wasm_bindgen_test]
+fn test_declarative_custom_chart_type() {
+    let mut chart_renderer = ChartRenderer::new();
+    let schedule = DeclarativeChartType {
+        header: String::new(),
+        row: r#"<rect x="{start:x}" y="{band}" width="{duration:w}" height="{band_size}" fill="{color}"><title>{task}</title></rect>"#.to_string(),
+        footer: String::new(),
+    };
+    let error = chart_renderer.register_custom_type("bar", Arc::new(schedule.clone())).unwrap_err();
+    assert_eq!(error.code, "RESERVED_CHART_TYPE");
+    chart_renderer.register_custom_type("schedule", Arc::new(schedule)).unwrap();
+    assert!(chart_renderer.has_custom_type("schedule"));
+
+    let config = ChartConfig::default();
+    let margin_left = config.margin.left;
+    chart_renderer.create_chart_with_id("roadmap", ChartType::Custom("schedule".to_string()), "tasks".to_string(), config).unwrap();
+    let data = serde_json::json!([
+        {"task": "Design", "start": 0, "duration": 3},
+        {"task": "R&D", "start": 3, "duration": 5},
+    ]);
+    let rendered = chart_renderer.render_chart("roadmap", &data).unwrap();
+    assert_eq!(rendered.svg_content.matches("<title>").count(), 2);
+    assert!(rendered.svg_content.contains("<title>R&amp;D</title>"));
+    assert!(rendered.svg_content.contains(&format!(r#"x="{:.2}""#, margin_left)));
+
+    // Charts of a type that is not registered (e.g. after thaw) fail to render
+    chart_renderer.create_chart_with_id("orphan", ChartType::Custom("bullet".to_string()), "tasks".to_string(), ChartConfig::default()).unwrap();
+    let error = chart_renderer.render_chart("orphan", &data).unwrap_err();
+    assert_eq!(error.code, "UNKNOWN_CHART_TYPE");
+}
+
+#[wasm_bindgen_test]
+fn test_error_bars_and_confidence_bands() {
+    let mut chart_renderer = ChartRenderer::new();
+    let test_data = serde_json::json!([
+        {"label": "A", "x": 10, "value": 40, "sd": 5, "lo": 30, "hi": 55},
+        {"label": "B", "x": 20, "value": 60, "sd": 8, "lo": 50, "hi": 70},
+        {"label": "C", "x": 30, "value": 50}
+    ]);
+    let series = |error: ErrorFields| ChartSeries {
+        id: "mean".to_string(),
+        name: "Mean".to_string(),
+        data_field: "value".to_string(),
+        color: "#1f77b4".to_string(),
+        line_width: None,
+        fill_opacity: None,
+        marker_size: None,
+        marker_shape: None,
+        markers: None,
+        line_style: None,
+        visible: true,
+        y_axis: AxisReference::Primary,
+        error: Some(error),
+        pattern: None,
+    };
+
+    let bar_id = chart_renderer.create_chart(ChartType::Bar, "trial".to_string(), ChartConfig::default()).unwrap();
+    chart_renderer.add_series(&bar_id, series(ErrorFields::Symmetric { field: "sd".to_string(), label: None })).unwrap();
+    let rendered_chart = chart_renderer.render_chart(&bar_id, &test_data).unwrap();
+    assert_eq!(rendered_chart.svg_content.matches(r#"class="error-bar""#).count(), 2);
+    assert_eq!(rendered_chart.data_points[0].error, Some(ErrorRange { lower: 35.0, upper: 45.0 }));
+    assert_eq!(rendered_chart.data_points[2].error, None); // No error field in the row
+
+    let scatter_id = chart_renderer.create_chart(ChartType::Scatter, "trial".to_string(), ChartConfig::default()).unwrap();
+    chart_renderer.add_series(&scatter_id, series(ErrorFields::Bounds { lower: "lo".to_string(), upper: "hi".to_string(), label: None })).unwrap();
+    let rendered_chart = chart_renderer.render_chart(&scatter_id, &test_data).unwrap();
+    assert_eq!(rendered_chart.svg_content.matches(r#"class="error-bar""#).count(), 2);
+    assert_eq!(rendered_chart.data_points[1].error, Some(ErrorRange { lower: 50.0, upper: 70.0 }));
+
+    let line_id = chart_renderer.create_chart(ChartType::Line, "trial".to_string(), ChartConfig::default()).unwrap();
+    let labelled = ErrorFields::Bounds { lower: "lo".to_string(), upper: "hi".to_string(), label: Some("95% CI".to_string()) };
+    chart_renderer.add_series(&line_id, series(labelled)).unwrap();
+    let rendered_chart = chart_renderer.render_chart(&line_id, &test_data).unwrap();
+    assert_eq!(rendered_chart.svg_content.matches(r#"class="confidence-band""#).count(), 1);
+    let description = ChartDescription::from_chart(&chart_renderer.charts[&line_id], &test_data);
+    assert!(description.series[0].summary.ends_with("A shaded band shows the 95% CI."));
+}
+
+#[wasm_bindgen_test]
+fn test_chart_node_tree_and_diff() {
+    let mut chart_renderer = ChartRenderer::new();
+    let chart_id = chart_renderer.create_chart(ChartType::Waterfall, "bridge".to_string(), ChartConfig::default()).unwrap();
+    let test_data = serde_json::json!([
+        {"label": "Start", "value": 100},
+        {"label": "Costs", "value": -60},
+        {"label": "End", "total": true}
+    ]);
+    
+    let nodes = chart_renderer.render_chart_nodes(&chart_id, &test_data).unwrap();
+    assert_eq!(nodes.id, chart_id);
+    assert_eq!(nodes.tag, "svg");
+    let connector = nodes.find(&format!("{}/line.connector[1]", chart_id)).unwrap();
+    assert_eq!(connector.attribute("class"), Some("connector"));
+    assert_eq!(SvgNode::parse(&nodes.to_svg(), &chart_id).unwrap(), nodes);
+    
+    let before = SvgNode::parse(r#"<svg><rect class="bar" height="10"/><text>A &amp; B</text></svg>"#, "c").unwrap();
+    assert_eq!(before.find("c/text[0]").unwrap().text.as_deref(), Some("A & B"));
+    let after = SvgNode::parse(r#"<svg><rect class="bar" height="20"/><rect class="bar" height="5"/><text>A</text></svg>"#, "c").unwrap();
+    let patches = before.diff(&after);
+    assert_eq!(patches.len(), 3);
+    assert_eq!(patches[0], SvgPatch::SetAttributes {
+        id: "c/rect.bar[0]".to_string(),
+        set: vec![("height".to_string(), "20".to_string())],
+        removed: vec![],
+    });
+    assert!(matches!(&patches[1], SvgPatch::Insert { parent_id, index: 1, node } if parent_id == "c" && node.id == "c/rect.bar[1]"));
+    assert_eq!(patches[2], SvgPatch::SetText { id: "c/text[0]".to_string(), text: Some("A".to_string()) });
+    assert!(after.diff(&after).is_empty());
+    
+    assert_eq!(SvgNode::parse("<svg><rect></svg>", "c").unwrap_err().code, "MALFORMED_SVG");
+}
+
+#[wasm_bindgen_test]
+fn test_marker_shapes_sizes_and_legend() {
+    let mut chart_renderer = ChartRenderer::new();
+    let config = ChartConfig {
+        legend: Some(ChartLegend {
+            position: LegendPosition::Top,
+            show: true,
+            font_size: 12.0,
+            color: "#333333".to_string(),
+        }),
+        ..ChartConfig::default()
+    };
+    let chart_id = chart_renderer.create_chart(ChartType::Scatter, "bubbles".to_string(), config).unwrap();
+    chart_renderer.add_series(&chart_id, ChartSeries {
+        id: "deals".to_string(),
+        name: "Deals".to_string(),
+        data_field: "y".to_string(),
+        color: "#1f77b4".to_string(),
+        line_width: None,
+        fill_opacity: None,
+        marker_size: None,
+        marker_shape: Some(MarkerShape::Diamond),
+        markers: Some(MarkerOptions {
+            shape_field: Some("kind".to_string()),
+            size_field: Some("amount".to_string()),
+            size_range: (2.0, 10.0),
+            size_scale: MarkerSizeScale::Linear,
+        }),
+        line_style: None,
+        visible: true,
+        y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
+    }).unwrap();
+    
+    let test_data = serde_json::json!([
+        {"x": 10, "y": 20, "amount": 0, "kind": "square"},
+        {"x": 30, "y": 40, "amount": 50},
+        {"x": 50, "y": 60, "amount": 100, "kind": "Plus"}
+    ]);
+    let rendered_chart = chart_renderer.render_chart(&chart_id, &test_data).unwrap();
+    
+    let markers: Vec<PointMarker> = rendered_chart.data_points.iter().map(|point| point.marker.clone().unwrap()).collect();
+    assert_eq!(markers, [
+        PointMarker { shape: MarkerShape::Square, size: 2.0 },
+        PointMarker { shape: MarkerShape::Diamond, size: 6.0 },
+        PointMarker { shape: MarkerShape::Plus, size: 10.0 },
+    ]);
+    let svg = &rendered_chart.svg_content;
+    for shape in ["square", "diamond", "plus"] {
+        assert_eq!(svg.matches(&format!(r#"<symbol id="{}-marker-{}""#, chart_id, shape)).count(), 1);
+    }
+    assert!(!svg.contains("-marker-circle"));
+    // Three points and the legend swatch, which uses the series' own shape
+    assert_eq!(svg.matches(r#"class="marker""#).count(), 4);
+    assert!(svg.contains(&format!(r##"<g class="legend-entry" data-series-id="deals"><use class="marker" href="#{}-marker-diamond""##, chart_id)));
+    assert!(svg.contains(">Deals</text>"));
+    
+    assert_eq!(MarkerShape::from_name(" TRIANGLE "), Some(MarkerShape::Triangle));
+    assert!(MarkerShape::Cross.swatch("#000", 5.0).contains("rotate(45)"));
+}
+
+#[wasm_bindgen_test]
+fn test_line_styles_curves_and_gaps() {
+    let mut chart_renderer = ChartRenderer::new();
+    let test_data = serde_json::json!([
+        {"value": 10},
+        {"value": 20},
+        {"value": null},
+        {"value": 80},
+        {"value": 90}
+    ]);
+    let series = |style: Option<LineStyle>| ChartSeries {
+        id: "trend".to_string(),
+        name: "Trend".to_string(),
+        data_field: "value".to_string(),
+        color: "#2ca02c".to_string(),
+        line_width: None,
+        fill_opacity: None,
+        marker_size: None,
+        marker_shape: None,
+        markers: None,
+        line_style: style,
+        visible: true,
+        y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
+    };
+    let render = |chart_renderer: &mut ChartRenderer, chart_type: ChartType, style: Option<LineStyle>| {
+        let chart_id = chart_renderer.create_chart(chart_type, "trend".to_string(), ChartConfig::default()).unwrap();
+        chart_renderer.add_series(&chart_id, series(style)).unwrap();
+        chart_renderer.render_chart(&chart_id, &test_data).unwrap()
+    };
+    let series_path = |svg: &str| {
+        let start = svg.find(r##"stroke="#2ca02c""##).unwrap();
+        let d = svg[..start].rfind(r#"d=""#).unwrap() + 3;
+        svg[d..d + svg[d..].find('"').unwrap()].to_string()
+    };
+    
+    // Null rows break the line by default, and are not plotted at zero
+    let rendered_chart = render(&mut chart_renderer, ChartType::Line, None);
+    assert_eq!(rendered_chart.data_points.len(), 4);
+    assert_eq!(series_path(&rendered_chart.svg_content).matches('M').count(), 2);
+    
+    let connected = LineStyle { gaps: GapMode::Connect, dash: Some(vec![4.0, 2.0]), ..LineStyle::default() };
+    let rendered_chart = render(&mut chart_renderer, ChartType::Line, Some(connected));
+    assert_eq!(series_path(&rendered_chart.svg_content).matches('M').count(), 1);
+    assert!(rendered_chart.svg_content.contains(r#"stroke-dasharray="4,2""#));
+    
+    let stepped = LineStyle { curve: CurveType::StepAfter, ..LineStyle::default() };
+    let path = series_path(&render(&mut chart_renderer, ChartType::Line, Some(stepped)).svg_content);
+    assert_eq!(path.matches(" H ").count(), 2);
+    
+    // Monotone control points stay within the y range of their segment
+    let monotone = LineStyle { curve: CurveType::Monotone, gaps: GapMode::Connect, ..LineStyle::default() };
+    let path = series_path(&render(&mut chart_renderer, ChartType::Line, Some(monotone)).svg_content);
+    let numbers: Vec<f64> = path.split_whitespace().filter_map(|token| token.parse().ok()).collect();
+    let ys: Vec<f64> = numbers.iter().skip(1).step_by(2).copied().collect();
+    for window in ys.windows(2) {
+        assert!(window[1] <= window[0] + 1e-9);
+    }
+    
+    let catmull = LineStyle { curve: CurveType::CatmullRom, ..LineStyle::default() };
+    let rendered_chart = render(&mut chart_renderer, ChartType::Area, Some(catmull));
+    let path = series_path(&rendered_chart.svg_content);
+    assert_eq!(path.matches(" C ").count(), 2);
+    assert_eq!(path.matches('Z').count(), 2);
+}
+
+#[wasm_bindgen_test]
+fn test_chart_export() {
+    let mut chart_renderer = ChartRenderer::new();
+    let config = ChartConfig {
+        title: Some(ChartTitle {
+            text: "Q3 & Q4".to_string(),
+            font_size: 16.0,
+            font_family: "Inter".to_string(),
+            color: "#111111".to_string(),
+            alignment: TextAlignment::Center,
+        }),
+        ..ChartConfig::default()
+    };
+    let chart_id = chart_renderer.create_chart(ChartType::Bar, "revenue".to_string(), config).unwrap();
+    chart_renderer.add_series(&chart_id, ChartSeries {
+        id: "revenue".to_string(),
+        name: "Revenue".to_string(),
+        data_field: "value".to_string(),
+        color: "#1f77b4".to_string(),
+        line_width: None,
+        fill_opacity: None,
+        marker_size: None,
+        marker_shape: None,
+        markers: None,
+        line_style: None,
+        visible: true,
+        y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
+    }).unwrap();
+    chart_renderer.register_export_font("Inter", "data:font/woff2;base64,AAAA");
+    chart_renderer.register_export_font("Unused", "data:font/woff2;base64,BBBB");
+    let test_data = serde_json::json!([{"label": "Q3", "value": 40}, {"label": "Q4", "value": 60}]);
+    
+    let export = chart_renderer.export_chart(&chart_id, &test_data, ExportFormat::Svg, 2.0).unwrap();
+    assert_eq!(export.mime_type, "image/svg+xml");
+    assert_eq!((export.width, export.height), (800.0, 600.0));
+    let svg = &export.content;
+    assert!(svg.starts_with(r#"<svg width="800" height="600" viewBox="0 0 400 300""#));
+    assert!(svg.contains("<title>Q3 &amp; Q4</title>"));
+    assert!(svg.contains("<desc>"));
+    // Bar charts draw no title of their own, and the export adds a legend
+    assert!(svg.contains(r#"class="chart-title""#));
+    assert!(svg.contains(">Revenue</text>"));
+    assert!(svg.contains("data:font/woff2;base64,AAAA"));
+    assert!(!svg.contains("BBBB"));
+    let root = SvgNode::parse(svg, &chart_id).unwrap();
+    assert!(root.children.iter().any(|node| node.tag == "style"));
+    
+    assert_eq!(chart_renderer.export_chart(&chart_id, &test_data, ExportFormat::Svg, 0.0).unwrap_err().code, "INVALID_EXPORT_SCALE");
+    assert_eq!(ExportFormat::from_name("gif").unwrap_err().code, "UNSUPPORTED_EXPORT_FORMAT");
+    assert_eq!(ExportFormat::from_name("PNG").unwrap(), ExportFormat::Png);
+    #[cfg(not(feature = "raster"))]
+    assert_eq!(chart_renderer.export_chart(&chart_id, &test_data, ExportFormat::Png, 1.0).unwrap_err().code, "FEATURE_DISABLED");
+    
+    let rect = SvgNode::parse(r#"<rect x="10" y="5" width="50%" height="20"/>"#, "r").unwrap();
+    assert_eq!(rect.path_data((400.0, 300.0)).unwrap(), "M 10 5 H 210 V 25 H 10 Z");
+    let circle = SvgNode::parse(r#"<circle cx="0" cy="0" r="1"/>"#, "c").unwrap();
+    assert!(circle.path_data((400.0, 300.0)).unwrap().starts_with("M -1 0 A 1 1"));
+}
+
+#[cfg(feature = "vector")]
+#[wasm_bindgen_test]
+fn test_deterministic_output_order() {
+    let build = |ids: &[&str]| {
+        let mut vector_engine = VectorEngine::new();
+        let mut sources = BTreeMap::new();
+        for (index, id) in ids.iter().enumerate() {
+            let offset = index as f64;
+            vector_engine.create_shape_with_id(id, ShapeType::Rectangle, Position { x: offset, y: 0.0 }, Size { width: 10.0, height: 10.0 }).unwrap();
+            vector_engine.gradients.insert(id.to_string(), Gradient {
+                id: id.to_string(),
+                gradient_type: GradientType::Linear { x1: 0.0, y1: 0.0, x2: 1.0, y2: 0.0 },
+                stops: vec![GradientStop { offset: 0.0, color: "#000000".to_string(), opacity: 1.0 }],
+                transform: None,
+            });
+            sources.insert(id.to_string(), DataSource::new(id.to_string(), DataSourceType::Static, serde_json::json!([offset])));
+        }
+        (vector_engine, sources)
+    };
+    let (forward, forward_sources) = build(&["a", "b", "c", "d"]);
+    let (backward, backward_sources) = build(&["d", "c", "b", "a"]);
+
+    // Definitions and shapes come out in id order whatever order they were added in
+    let svg = forward.render_to_svg(100.0, 100.0);
+    let positions: Vec<usize> = ["id=\"a\"", "id=\"b\"", "id=\"c\"", "id=\"d\""].iter().map(|id| svg.find(id).unwrap()).collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(serde_json::to_string(&forward.gradients).unwrap(), serde_json::to_string(&backward.gradients).unwrap());
+    assert_eq!(forward_sources.keys().collect::<Vec<_>>(), backward_sources.keys().collect::<Vec<_>>());
+
+    let attributes = |pairs: &[(&str, &str)]| DOMOperation::Update {
+        element_id: "card".to_string(),
+        attributes: pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+    };
+    assert_eq!(
+        serde_json::to_string(&attributes(&[("width", "10"), ("fill", "red"), ("height", "5")])).unwrap(),
+        serde_json::to_string(&attributes(&[("height", "5"), ("width", "10"), ("fill", "red")])).unwrap(),
+    );
+
+    // A multi-property update and a multi-value animation frame serialize the same whatever
+    // order their properties were set in
+    let render = |pairs: &[(&str, serde_json::Value)]| {
+        let permissions = WASMPermissions {
+            memory_limit: 1024 * 1024,
+            allowed_imports: vec!["console".to_string()],
+            cpu_time_limit: 5000,
+            allow_networking: false,
+            allow_file_system: false,
+            allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+            max_data_size: 1024 * 1024,
+            max_elements: 100,
+        };
+        let engine = InteractiveEngine::new(permissions).unwrap();
+        let properties: BTreeMap<String, serde_json::Value> = pairs.iter().map(|(name, value)| (name.to_string(), value.clone())).collect();
+        let update = engine.generate_render_update(vec![
+            ElementChange::Update { element_id: "card".to_string(), properties: properties.clone() },
+            ElementChange::AnimationUpdate { animation_id: "pulse".to_string(), progress: 0.5, values: properties },
+        ]).unwrap();
+        serde_json::to_string(&(&update.dom_operations, &update.style_changes, &update.animation_updates)).unwrap()
+    };
+    let pairs = [
+        ("width", serde_json::json!(10)),
+        ("style.opacity", serde_json::json!(0.5)),
+        ("fill", serde_json::json!("red")),
+        ("style.color", serde_json::json!("#333333")),
+        ("height", serde_json::json!(5)),
+    ];
+    let reversed: Vec<_> = pairs.iter().rev().cloned().collect();
+    let forward = render(&pairs);
+    assert_eq!(forward, render(&reversed));
+    let order: Vec<usize> = ["\"fill\"", "\"height\"", "\"width\"", "\"color\"", "\"opacity\""].iter().map(|name| forward.find(name).unwrap()).collect();
+    assert!(order.windows(2).all(|pair| pair[0] < pair[1]), "{}", forward);
+}
+
+#[wasm_bindgen_test]
+fn test_chart_scales_stay_finite() {
+    let mut chart_renderer = ChartRenderer::new();
+    let chart_id = chart_renderer.create_chart(ChartType::Pie, "empty".to_string(), ChartConfig::default()).unwrap();
+    chart_renderer.add_series(&chart_id, ChartSeries {
+        id: "share".to_string(),
+        name: "Share".to_string(),
+        data_field: "value".to_string(),
+        color: "#1f77b4".to_string(),
+        line_width: None,
+        fill_opacity: None,
+        marker_size: None,
+        marker_shape: None,
+        markers: None,
+        line_style: None,
+        visible: true,
+        y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
+    }).unwrap();
+
+    // An all-zero pie used to divide by its zero total
+    let rendered = chart_renderer.render_chart(&chart_id, &serde_json::json!([{"value": 0}, {"value": 0}])).unwrap();
+    assert!(!rendered.svg_content.contains("NaN"));
+    assert!(crate::numeric::take_numeric_issues().is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_axis_scaling() {
+    use crate::axis::AxisScale;
+    let labels = |scale: &AxisScale| scale.ticks.iter().map(|tick| tick.label.clone()).collect::<Vec<_>>();
+    
+    // Data ranges widen out to round ticks; set bounds stay where they are
+    let scale = AxisScale::fit(&ChartAxis::default(), &[3.0, 87.0], false);
+    assert_eq!((scale.min, scale.max), (0.0, 100.0));
+    assert_eq!(labels(&scale), ["0", "25", "50", "75", "100"]);
+    let scale = AxisScale::fit(&ChartAxis { min_value: Some(-5.0), ..ChartAxis::default() }, &[0.12, 0.37], false);
+    assert_eq!((scale.min, scale.max), (-5.0, 2.0));
+    assert_eq!(labels(&scale), ["-4", "-2", "0", "2"]);
+    let scale = AxisScale::fit(&ChartAxis { format: Some("percent".to_string()), ..ChartAxis::default() }, &[0.12, 0.37], true);
+    assert_eq!(labels(&scale), ["0%", "10%", "20%", "30%", "40%"]);
+    let scale = AxisScale::fit(&ChartAxis { format: Some("compact".to_string()), ..ChartAxis::default() }, &[1200.0, 9800.0], false);
+    assert_eq!(labels(&scale), ["0", "2.5K", "5K", "7.5K", "10K"]);
+    // A single value is padded by a tenth on each side
+    assert_eq!(labels(&AxisScale::fit(&ChartAxis::default(), &[42.0], false)), ["37.5", "40.0", "42.5", "45.0", "47.5"]);
+    
+    let log = ChartAxis { scale_type: ScaleType::Logarithmic, ..ChartAxis::default() };
+    let scale = AxisScale::fit(&log, &[-1.0, 3.0, 4500.0], true);
+    assert_eq!((scale.min, scale.max), (1.0, 10000.0));
+    assert_eq!(labels(&scale), ["1", "10", "100", "1,000", "10,000"]);
+    assert_eq!(scale.fraction(100.0), 0.5);
+    
+    let time = ChartAxis { scale_type: ScaleType::Time, ..ChartAxis::default() };
+    let day = 86_400_000.0;
+    assert_eq!(labels(&AxisScale::fit(&time, &[0.0, 6.0 * day], false)), ["1970-01-01", "1970-01-03", "1970-01-05", "1970-01-07"]);
+    assert_eq!(labels(&AxisScale::fit(&time, &[0.0, 3_600_000.0], false)), ["00:00", "00:15", "00:30", "00:45", "01:00"]);
+    
+    // Bars are scaled to the data instead of assuming 0-100, with gridlines and tick labels
+    let mut chart_renderer = ChartRenderer::new();
+    let chart_id = chart_renderer.create_chart(ChartType::Bar, "revenue".to_string(), ChartConfig::default()).unwrap();
+    chart_renderer.add_series(&chart_id, ChartSeries {
+        id: "revenue".to_string(),
+        name: "Revenue".to_string(),
+        data_field: "value".to_string(),
+        color: "#1f77b4".to_string(),
+        line_width: None,
+        fill_opacity: None,
+        marker_size: None,
+        marker_shape: None,
+        markers: None,
+        line_style: None,
+        visible: true,
+        y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
+    }).unwrap();
+    let rendered_chart = chart_renderer.render_chart(&chart_id, &serde_json::json!([
+        {"label": "Q1", "value": 1500},
+        {"label": "Q2", "value": 3000}
+    ])).unwrap();
+    let svg = &rendered_chart.svg_content;
+    // The tallest bar reaches the top of the plot, 3000 on a 0-3000 axis
+    assert!(svg.contains(r##"y="20" width="136" height="240" fill="#1f77b4""##));
+    assert_eq!(svg.matches(r#"class="grid-line""#).count(), 4);
+    for label in [">Q1</text>", ">Q2</text>", ">0</text>", ">3,000</text>"] {
+        assert!(svg.contains(label));
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_legend_series_toggling() {
+    let mut chart_renderer = ChartRenderer::new();
+    let config = ChartConfig {
+        legend: Some(ChartLegend {
+            position: LegendPosition::Top,
+            show: true,
+            font_size: 12.0,
+            color: "#333333".to_string(),
+        }),
+        ..ChartConfig::default()
+    };
+    let left = config.margin.left;
+    let chart_id = chart_renderer.create_chart(ChartType::Bar, "sales".to_string(), config).unwrap();
+    for (id, name, color) in [("north", "North", "#1f77b4"), ("south", "South", "#ff7f0e")] {
+        chart_renderer.add_series(&chart_id, ChartSeries {
+            id: id.to_string(),
+            name: name.to_string(),
+            data_field: id.to_string(),
+            color: color.to_string(),
+            line_width: None,
+            fill_opacity: None,
+            marker_size: None,
+            marker_shape: None,
+            markers: None,
+            line_style: None,
+            visible: true,
+            y_axis: AxisReference::Primary,
+            error: None,
+            pattern: None,
+        }).unwrap();
+    }
+    let test_data = serde_json::json!([
+        {"label": "Q1", "north": 10, "south": 40},
+        {"label": "Q2", "north": 20, "south": 30}
+    ]);
+    
+    // Entries run in a row above the plot, each as wide as its swatch and name
+    let rendered_chart = chart_renderer.render_chart(&chart_id, &test_data).unwrap();
+    assert_eq!(rendered_chart.legend_regions, [
+        LegendRegion { series_id: "north".to_string(), x: left, y: 0.0, width: 60.0, height: 18.0, visible: true },
+        LegendRegion { series_id: "south".to_string(), x: left + 72.0, y: 0.0, width: 60.0, height: 18.0, visible: true },
+    ]);
+    assert_eq!(rendered_chart.legend_series_at(left + 80.0, 9.0), Some("south"));
+    assert_eq!(rendered_chart.legend_series_at(left + 66.0, 9.0), None);
+    let svg = &rendered_chart.svg_content;
+    assert_eq!(svg.matches(r##"fill="#ff7f0e""##).count(), 3);
+    
+    // A hidden series leaves the plot and its scale but keeps a dimmed legend entry
+    assert!(!chart_renderer.toggle_series(&chart_id, "south").unwrap());
+    let rendered_chart = chart_renderer.render_chart(&chart_id, &test_data).unwrap();
+    assert!(rendered_chart.data_points.iter().all(|point| point.series_id == "north"));
+    assert!(!rendered_chart.legend_regions[1].visible);
+    let svg = &rendered_chart.svg_content;
+    assert!(svg.contains(r#"<g class="legend-entry hidden" data-series-id="south" opacity="0.4">"#));
+    assert_eq!(svg.matches(r##"fill="#ff7f0e""##).count(), 1);
+    assert!(svg.contains(">20</text>"));
+    assert!(!svg.contains(">40</text>"));
+    
+    assert!(chart_renderer.toggle_series(&chart_id, "south").unwrap());
+    let rendered_chart = chart_renderer.render_chart(&chart_id, &test_data).unwrap();
+    assert_eq!(rendered_chart.data_points.len(), 4);
+    assert_eq!(chart_renderer.toggle_series(&chart_id, "west").unwrap_err().code, "SERIES_NOT_FOUND");
+    assert_eq!(chart_renderer.toggle_series("missing", "south").unwrap_err().code, "CHART_NOT_FOUND");
+}
+
+#[cfg(feature = "vector")]
+#[wasm_bindgen_test]
+fn test_hatch_patterns() {
+    use crate::hatch::{self, Hatch, HatchKind};
+    
+    // Tiles are drawn from numbers and parsed colors only
+    let stripes = Hatch { angle: 45.0, color: Some("RED".to_string()), background: Some("white".to_string()), ..Hatch::new(HatchKind::Stripes) };
+    let pattern = stripes.to_pattern("stripes", "#000000").unwrap();
+    assert_eq!((pattern.width, pattern.height), (10.0, 10.0));
+    assert_eq!(pattern.content, r##"<rect width="10" height="10" fill="#ffffff"/><rect width="10" height="3" fill="#ff0000"/>"##);
+    assert!(hatch::definition(&pattern).contains(r#"patternTransform="translate(0 0) scale(1 1) rotate(45)""#));
+    let checker = Hatch { density: 20.0, ..Hatch::new(HatchKind::Checker) }.to_pattern("checker", "#1f77b4").unwrap();
+    assert_eq!(checker.width, 10.0);
+    assert_eq!(checker.content.matches(r##"fill="#1f77b4""##).count(), 2);
+    assert!(Hatch::new(HatchKind::Dots).to_pattern("dots", "#000").unwrap().content.starts_with(r#"<circle cx="5" cy="5" r="1.5""#));
+    assert!(hatch::definition(&Hatch::new(HatchKind::Crosshatch).to_pattern("cross", "#000").unwrap()).starts_with(r#"<pattern id="cross" width="10" height="10" patternUnits="userSpaceOnUse"><rect"#));
+    
+    let injected = Hatch { color: Some(r#"red"/><script>alert(1)</script>"#.to_string()), ..Hatch::new(HatchKind::Dots) };
+    assert!(injected.validate().unwrap_err().contains("is not a color"));
+    assert!(Hatch { density: 0.0, ..Hatch::new(HatchKind::Dots) }.validate().is_err());
+    assert!(Hatch { weight: 1.5, ..Hatch::new(HatchKind::Dots) }.validate().is_err());
+    assert!(Hatch { angle: f64::NAN, ..Hatch::new(HatchKind::Dots) }.validate().is_err());
+    assert!(Hatch { background: Some("transparent".to_string()), ..Hatch::new(HatchKind::Dots) }.validate().is_ok());
+    
+    // Bars and their legend swatch fill with the series' pattern, marked in the series color
+    let mut chart_renderer = ChartRenderer::new();
+    let config = ChartConfig {
+        legend: Some(ChartLegend {
+            position: LegendPosition::Bottom,
+            show: true,
+            font_size: 12.0,
+            color: "#333333".to_string(),
+        }),
+        ..ChartConfig::default()
+    };
+    let chart_id = chart_renderer.create_chart(ChartType::Bar, "sales".to_string(), config).unwrap();
+    let mut series: ChartSeries = serde_json::from_value(serde_json::json!({
+        "id": "forecast", "name": "Forecast", "data_field": "value", "color": "#d62728",
+        "line_width": null, "fill_opacity": null, "marker_size": null, "marker_shape": null,
+        "visible": true, "y_axis": "Primary",
+        "pattern": {"kind": "Stripes", "angle": 45},
+    })).unwrap();
+    chart_renderer.add_series(&chart_id, series.clone()).unwrap();
+    let svg = chart_renderer.render_chart(&chart_id, &serde_json::json!([{"label": "Q1", "value": 10}, {"label": "Q2", "value": 20}])).unwrap().svg_content;
+    let pattern_id = format!("{}-pattern-forecast", chart_id);
+    assert_eq!(svg.matches(&format!(r#"<pattern id="{}""#, pattern_id)).count(), 1);
+    assert!(svg.contains(r##"<rect width="10" height="3" fill="#d62728"/>"##));
+    assert_eq!(svg.matches(&format!(r#"fill="url(#{})""#, pattern_id)).count(), 3);
+    
+    series.id = "broken".to_string();
+    series.pattern = Some(Hatch { density: 500.0, ..Hatch::new(HatchKind::Dots) });
+    assert_eq!(chart_renderer.add_series(&chart_id, series).unwrap_err().code, "INVALID_SERIES_PATTERN");
+    
+    let mut vector_engine = VectorEngine::new();
+    let shape_id = vector_engine.create_shape(ShapeType::Rectangle, Position { x: 0.0, y: 0.0 }, Size { width: 50.0, height: 50.0 }).unwrap();
+    let pattern_id = vector_engine.create_hatch_pattern(&Hatch::new(HatchKind::Crosshatch)).unwrap();
+    vector_engine.set_shape_pattern(&shape_id, &pattern_id).unwrap();
+    let svg = vector_engine.render_to_svg(100.0, 100.0);
+    assert!(svg.contains(&format!(r#"<pattern id="{}""#, pattern_id)));
+    assert!(svg.contains(&format!(r#"fill="url(#{})""#, pattern_id)));
+    assert_eq!(vector_engine.set_shape_pattern(&shape_id, "missing").unwrap_err().code, "PATTERN_NOT_FOUND");
+    assert_eq!(vector_engine.create_hatch_pattern(&Hatch { weight: 0.0, ..Hatch::new(HatchKind::Stripes) }).unwrap_err().code, "INVALID_PATTERN");
+}
@@ -0,0 +1,175 @@
+// What an element's property shows when several animations, standalone or on timelines, write it
+// in the same frame. Each animation has a composition: Replace (the default) or Additive, and a
+// priority. Among the replacing writers the highest priority owns the property; at equal
+// priority the one that started writing it last does, and ties left after that go to the id
+// that sorts last, so the outcome never depends on the order animations were updated in.
+// Additive writers' numbers are summed onto the owner's value, or onto 0 when nothing replaces;
+// an additive value that isn't a number, or a non-numeric owner value, can't be summed and the
+// additive writer is overridden instead.
+//
+// The composed value goes out in the owner's animation update (the first additive writer's when
+// there is no owner) and the property is removed from every other writer's update. A writer
+// that skips a frame loses its place in the ownership table and rejoins as the newest.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::ElementChange;
+
+// Newly detected conflicts kept until taken
+pub const MAX_DETECTED_CONFLICTS: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub enum CompositionMode {
+    #[default]
+    Replace,
+    Additive,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub struct Composition {
+    #[serde(default)]
+    pub mode: CompositionMode,
+    // Higher wins
+    #[serde(default)]
+    pub priority: i32,
+}
+
+// One property written by more than one animation in a frame, and how it was settled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub struct AnimationConflict {
+    pub element_id: String,
+    pub property: String,
+    // None when every writer is additive
+    pub owner: Option<String>,
+    // Sorted animation ids
+    pub added: Vec<String>,
+    pub overridden: Vec<String>,
+}
+
+// Who owns each animated (element, property) pair, with the frame each writer joined on
+#[derive(Clone, Debug, Default)]
+pub struct PropertyOwnership {
+    writers: BTreeMap<(String, String), BTreeMap<String, u64>>,
+    frame: u64,
+    conflicts: Vec<AnimationConflict>,
+    detected: Vec<AnimationConflict>,
+}
+
+struct Writer {
+    change: usize,
+    animation_id: String,
+    composition: Composition,
+    value: Value,
+}
+
+impl PropertyOwnership {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Settles every property more than one animation update in `changes` writes. `animations`
+    // gives each animation's target element and composition; unknown ones replace at priority 0.
+    pub fn resolve(&mut self, changes: &mut [ElementChange], animations: &HashMap<String, (String, Composition)>) {
+        self.frame += 1;
+        let mut written: BTreeMap<(String, String), Vec<Writer>> = BTreeMap::new();
+        for (index, change) in changes.iter().enumerate() {
+            let ElementChange::AnimationUpdate { animation_id, values, .. } = change else { continue };
+            let Some((element_id, composition)) = animations.get(animation_id) else { continue };
+            for (property, value) in values {
+                written.entry((element_id.clone(), property.clone())).or_default().push(Writer {
+                    change: index,
+                    animation_id: animation_id.clone(),
+                    composition: *composition,
+                    value: value.clone(),
+                });
+            }
+        }
+
+        // Writers that stopped writing leave the table
+        self.writers.retain(|key, _| written.contains_key(key));
+        let mut conflicts = Vec::new();
+        for (key, writers) in written {
+            let frame = self.frame;
+            let joined = self.writers.entry(key.clone()).or_default();
+            let previous: HashSet<String> = joined.keys().cloned().collect();
+            joined.retain(|animation_id, _| writers.iter().any(|writer| &writer.animation_id == animation_id));
+            for writer in &writers {
+                joined.entry(writer.animation_id.clone()).or_insert(frame);
+            }
+            if writers.len() < 2 {
+                continue;
+            }
+            let rank = |writer: &Writer| (writer.composition.priority, joined[&writer.animation_id], writer.animation_id.clone());
+
+            let summable = |writer: &Writer| writer.composition.mode == CompositionMode::Additive && writer.value.is_number();
+            let owner = writers.iter().filter(|writer| !summable(writer)).max_by_key(|writer| rank(writer));
+            let base = owner.map_or(Some(0.0), |owner| owner.value.as_f64());
+            let (mut added, mut overridden) = (Vec::new(), Vec::new());
+            let mut total = base.unwrap_or_default();
+            for writer in &writers {
+                if owner.is_some_and(|owner| owner.animation_id == writer.animation_id) {
+                    continue;
+                }
+                match (summable(writer), base) {
+                    (true, Some(_)) => {
+                        total += writer.value.as_f64().unwrap_or_default();
+                        added.push(writer.animation_id.clone());
+                    }
+                    _ => overridden.push(writer.animation_id.clone()),
+                }
+            }
+            added.sort();
+            overridden.sort();
+
+            let carrier = owner.or_else(|| writers.iter().find(|writer| Some(&writer.animation_id) == added.first())).map(|writer| writer.change);
+            let composed = match (owner, added.is_empty()) {
+                (Some(owner), true) => owner.value.clone(),
+                _ => serde_json::json!(total),
+            };
+            for writer in &writers {
+                let ElementChange::AnimationUpdate { values, .. } = &mut changes[writer.change] else { continue };
+                match Some(writer.change) == carrier {
+                    true => { values.insert(key.1.clone(), composed.clone()); }
+                    false => { values.remove(&key.1); }
+                }
+            }
+
+            let conflict = AnimationConflict {
+                element_id: key.0.clone(),
+                property: key.1.clone(),
+                owner: owner.map(|owner| owner.animation_id.clone()),
+                added,
+                overridden,
+            };
+            let current: HashSet<String> = writers.iter().map(|writer| writer.animation_id.clone()).collect();
+            if current != previous {
+                self.detected.push(conflict.clone());
+            }
+            conflicts.push(conflict);
+        }
+        if self.detected.len() > MAX_DETECTED_CONFLICTS {
+            let excess = self.detected.len() - MAX_DETECTED_CONFLICTS;
+            self.detected.drain(..excess);
+        }
+        self.conflicts = conflicts;
+    }
+
+    // Conflicts settled in the last frame, sorted by element and property
+    pub fn conflicts(&self) -> &[AnimationConflict] {
+        &self.conflicts
+    }
+
+    // Conflicts that began, or changed writers, since the last call
+    pub fn take_detected(&mut self) -> Vec<AnimationConflict> {
+        std::mem::take(&mut self.detected)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
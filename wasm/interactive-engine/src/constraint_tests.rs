@@ -1,6 +1,8 @@
 use super::*;
 use wasm_bindgen_test::*;
-use std::time::{SystemTime, UNIX_EPOCH};
+// Native `cargo test` runs the same tests as plain #[test]s
+#[cfg(not(target_family = "wasm"))]
+use std::prelude::v1::test as wasm_bindgen_test;
 
 wasm_bindgen_test_configure!(run_in_browser);
 
@@ -28,7 +30,7 @@ fn test_memory_limit_enforcement() {
     let mut created_elements = Vec::new();
     let mut creation_failed = false;
 
-    for i in 0..10 {
+    for _i in 0..10 {
         let properties = [
             ("width".to_string(), serde_json::Value::Number(serde_json::Number::from(100))),
             ("height".to_string(), serde_json::Value::Number(serde_json::Number::from(100))),
@@ -70,7 +72,7 @@ fn test_cpu_time_limit_enforcement() {
     let mut engine = InteractiveEngine::new(time_limited_permissions).unwrap();
 
     // Create many elements and animations to consume CPU time
-    for i in 0..50 {
+    for _i in 0..50 {
         let element_id = engine.create_element(ElementType::Container, HashMap::new()).unwrap();
         
         let keyframes = vec![
@@ -132,7 +134,7 @@ fn test_element_count_limit_enforcement() {
     let mut created_count = 0;
     let mut creation_blocked = false;
 
-    for i in 0..10 {
+    for _i in 0..10 {
         match engine.create_element(ElementType::Container, HashMap::new()) {
             Ok(_) => {
                 created_count += 1;
@@ -221,6 +223,7 @@ fn test_interaction_permission_enforcement() {
         }),
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -246,6 +249,7 @@ fn test_interaction_permission_enforcement() {
                 radius: Some(5.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             changed_touches: vec![],
             target_touches: vec![],
@@ -256,6 +260,7 @@ fn test_interaction_permission_enforcement() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -373,6 +378,7 @@ fn test_interaction_rate_limiting() {
             }),
             keyboard_data: None,
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -418,7 +424,7 @@ fn test_resource_cleanup_on_constraint_violation() {
 
     // Create elements up to the limit
     let mut elements = Vec::new();
-    for i in 0..5 {
+    for _i in 0..5 {
         let element_id = engine.create_element(ElementType::Container, HashMap::new()).unwrap();
         elements.push(element_id);
     }
@@ -441,4 +447,67 @@ fn test_resource_cleanup_on_constraint_violation() {
     for element_id in &elements {
         assert!(engine.document_state.get_element(element_id).is_some());
     }
-}
\ No newline at end of file
+}
+
+#[wasm_bindgen_test]
+fn test_security_audit_log() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["modify_element".to_string()],
+        max_data_size: 16,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    assert!(engine.get_security_audit().is_empty());
+
+    assert!(engine.create_element(ElementType::Container, HashMap::new()).is_err());
+    let error = engine.update_data("source", &[b' '; 32]).unwrap_err();
+    assert_eq!(error.code, "DATA_SIZE_EXCEEDED");
+
+    let audit = engine.get_security_audit();
+    assert_eq!(audit.len(), 2);
+    assert_eq!((audit[0].sequence, audit[0].code.as_str(), audit[0].capability.as_str()), (0, "ELEMENT_CREATION_NOT_ALLOWED", "create_element"));
+    assert_eq!((audit[1].sequence, audit[1].capability.as_str()), (1, "max_data_size"));
+    assert_eq!(audit[1].message, error.message);
+    assert!(audit.iter().all(|denial| denial.rate_limit.is_none() && denial.timestamp > 0.0));
+
+    // The log keeps the newest denials once full
+    for _ in 0..SECURITY_AUDIT_CAPACITY {
+        let _ = engine.create_element(ElementType::Container, HashMap::new());
+    }
+    let audit = engine.get_security_audit();
+    assert_eq!(audit.len(), SECURITY_AUDIT_CAPACITY);
+    assert_eq!(audit[0].sequence, 2);
+    assert_eq!(audit.last().unwrap().sequence, SECURITY_AUDIT_CAPACITY as u64 + 1);
+
+    engine.clear_security_audit();
+    assert!(engine.get_security_audit().is_empty());
+
+    // Rate limit denials say how busy the engine has been
+    engine.security_context.permissions.allowed_interactions.push("Click".to_string());
+    engine.security_context.resource_limits.max_interactions_per_second = 1;
+    let click = InteractionEvent {
+        event_type: InteractionType::Click,
+        target_element: Some("button".to_string()),
+        position: Some(Position { x: 10.0, y: 10.0 }),
+        data: HashMap::new(),
+        timestamp: get_current_timestamp(),
+        touch_data: None,
+        mouse_data: None,
+        keyboard_data: None,
+        gesture_data: None,
+        pen_data: None,
+        modifiers: EventModifiers { ctrl: false, shift: false, alt: false, meta: false },
+    };
+    let _ = engine.process_interaction(click.clone());
+    assert!(engine.process_interaction(click).is_err());
+    let audit = engine.get_security_audit();
+    assert_eq!(audit[0].element_id.as_deref(), Some("button"));
+    let rate_limit = audit[0].rate_limit.clone().unwrap();
+    assert_eq!(rate_limit.limit_per_second, 1);
+    assert!(rate_limit.interactions_per_second > 1.0);
+}
@@ -0,0 +1,134 @@
+// Which parts of the document a render update repaints, so the host can clip its repaint to them.
+// An update covers the box of every element it touches, widened by that element's scale and
+// rotation, plus the boxes its elements left: where a moved element was, or a removed one. The
+// render tree collects those vacated boxes between updates. An animated element covers its box
+// as the frame's animated values place it, and the box the previous frame painted.
+//
+// Layers the engine draws over the document add their area when they change: what is on screen
+// for ink, tour and watermark, and its corner for the frame overlay.
+//
+// Regions are in document coordinates. Overlapping or touching rectangles are merged until none
+// overlap; past MAX_DIRTY_REGIONS the update sends their union instead, which clips less but is
+// cheaper for the host than many small clips. A full resync sends no regions and repaints
+// everything, so an empty list on any other update means there is nothing to repaint.
+
+use super::{BoundingBox, Transform};
+use std::collections::BTreeMap;
+
+pub const MAX_DIRTY_REGIONS: usize = 32;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DirtyRegions {
+    rects: Vec<BoundingBox>,
+}
+
+impl DirtyRegions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    // Boxes without area cover nothing and are dropped
+    pub fn add(&mut self, rect: BoundingBox) {
+        let finite = [rect.x, rect.y, rect.width, rect.height].iter().all(|value| value.is_finite());
+        if finite && rect.width > 0.0 && rect.height > 0.0 {
+            self.rects.push(rect);
+        }
+    }
+
+    // An element's box as painted: scaled and rotated about its centre like the CSS the engine emits
+    pub fn add_painted(&mut self, bounds: &BoundingBox, transform: &Transform) {
+        self.add(painted_area(bounds, transform));
+    }
+
+    pub fn extend(&mut self, other: &DirtyRegions) {
+        self.rects.extend(other.rects.iter().cloned());
+    }
+
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+
+    pub fn merged(&self) -> Vec<BoundingBox> {
+        merge(self.rects.clone())
+    }
+}
+
+// Smallest axis-aligned box around the transformed one
+pub fn painted_area(bounds: &BoundingBox, transform: &Transform) -> BoundingBox {
+    let identity = transform.rotation == 0.0 && transform.scale_x == 1.0 && transform.scale_y == 1.0;
+    if identity {
+        return bounds.clone();
+    }
+    let (sin, cos) = transform.rotation.to_radians().sin_cos();
+    let half_width = bounds.width * transform.scale_x.abs() / 2.0;
+    let half_height = bounds.height * transform.scale_y.abs() / 2.0;
+    let extent_x = half_width * cos.abs() + half_height * sin.abs();
+    let extent_y = half_width * sin.abs() + half_height * cos.abs();
+    let (centre_x, centre_y) = (bounds.x + bounds.width / 2.0, bounds.y + bounds.height / 2.0);
+    BoundingBox { x: centre_x - extent_x, y: centre_y - extent_y, width: 2.0 * extent_x, height: 2.0 * extent_y }
+}
+
+// An element's box as an animation frame paints it. Numeric values named like Transform's fields
+// (x, y, scale_x, scale_y, rotation, and scale for both axes), width and height stand in for the
+// element's own; a dotted name counts by its last segment, so "transform.rotation" is rotation.
+// Other properties, CSS transform strings among them, leave the box where it is.
+pub fn animated_area(bounds: &BoundingBox, transform: &Transform, values: &BTreeMap<String, serde_json::Value>) -> BoundingBox {
+    let (mut bounds, mut transform) = (bounds.clone(), transform.clone());
+    for (property, value) in values {
+        let Some(value) = value.as_f64().filter(|value| value.is_finite()) else { continue };
+        match property.rsplit('.').next().unwrap_or(property) {
+            "x" => bounds.x += value - transform.x,
+            "y" => bounds.y += value - transform.y,
+            "width" => bounds.width = value,
+            "height" => bounds.height = value,
+            "scale" => (transform.scale_x, transform.scale_y) = (value, value),
+            "scale_x" => transform.scale_x = value,
+            "scale_y" => transform.scale_y = value,
+            "rotation" => transform.rotation = value,
+            _ => {}
+        }
+    }
+    painted_area(&bounds, &transform)
+}
+
+// Unions overlapping and touching rectangles until none overlap, largest first
+pub fn merge(mut rects: Vec<BoundingBox>) -> Vec<BoundingBox> {
+    let mut merged: Vec<BoundingBox> = Vec::with_capacity(rects.len());
+    while let Some(mut rect) = rects.pop() {
+        // A union can reach rectangles already kept, so keep absorbing until it stops growing
+        loop {
+            let before = merged.len();
+            merged.retain(|kept| {
+                if !touches(kept, &rect) {
+                    return true;
+                }
+                rect = union(kept, &rect);
+                false
+            });
+            if merged.len() == before {
+                break;
+            }
+        }
+        merged.push(rect);
+    }
+    if merged.len() > MAX_DIRTY_REGIONS {
+        let all = merged.iter().skip(1).fold(merged[0].clone(), |all, rect| union(&all, rect));
+        return vec![all];
+    }
+    merged.sort_by(|a, b| (b.width * b.height).total_cmp(&(a.width * a.height)));
+    merged
+}
+
+fn touches(a: &BoundingBox, b: &BoundingBox) -> bool {
+    a.x <= b.x + b.width && b.x <= a.x + a.width && a.y <= b.y + b.height && b.y <= a.y + a.height
+}
+
+fn union(a: &BoundingBox, b: &BoundingBox) -> BoundingBox {
+    let (left, top) = (a.x.min(b.x), a.y.min(b.y));
+    let (right, bottom) = ((a.x + a.width).max(b.x + b.width), (a.y + a.height).max(b.y + b.height));
+    BoundingBox { x: left, y: top, width: right - left, height: bottom - top }
+}
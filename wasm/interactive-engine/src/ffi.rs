@@ -0,0 +1,285 @@
+// C ABI for native hosts, e.g. iOS and Android viewers embedding the engine without a WebView.
+//
+// Each engine is an opaque handle with the same permission model as the wasm build. Strings are
+// NUL-terminated UTF-8 JSON; render updates come back as a LivBuffer holding a flatbuffer of
+// include/liv_update.fbs (see encode_render_update). Failed calls return null, an empty buffer or
+// -1, and liv_last_error describes the failure. Engine panics are caught at this boundary and
+// fail the call the same way, as unwinding into C, Swift or JNI frames is undefined behaviour;
+// they are only caught when the library is built with panic = "unwind" (profile release-mobile).
+// include/liv_engine.h declares this API for C, Swift and JNI callers and must change with it.
+
+// Every pointer argument must be null or valid as described in the header
+#![allow(clippy::missing_safety_doc)]
+
+use super::*;
+use flatbuffers::{FlatBufferBuilder, TableFinishedWIPOffset, VOffsetT, WIPOffset};
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+// Bytes owned by the engine library until passed to liv_buffer_free
+#[repr(C)]
+pub struct LivBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl LivBuffer {
+    fn empty() -> Self {
+        Self { data: std::ptr::null_mut(), len: 0 }
+    }
+
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let bytes = Box::leak(bytes.into_boxed_slice());
+        Self { data: bytes.as_mut_ptr(), len: bytes.len() }
+    }
+}
+
+// file_identifier of include/liv_update.fbs, at bytes 4..8 of every update
+pub const UPDATE_FILE_IDENTIFIER: &str = "LIVU";
+
+// DomOperationKind in include/liv_update.fbs
+const DOM_CREATE: u8 = 0;
+const DOM_UPDATE: u8 = 1;
+const DOM_REMOVE: u8 = 2;
+const DOM_MOVE: u8 = 3;
+
+type TableOffset = WIPOffset<TableFinishedWIPOffset>;
+
+// The vtable slot of a table's `field`th field, counting from 0 in schema order
+pub const fn field_slot(field: u16) -> VOffsetT {
+    4 + 2 * field
+}
+
+// A RenderUpdate as a liv.RenderUpdate flatbuffer; hosts read it with code flatc generates from
+// include/liv_update.fbs. The fields mirror RenderUpdate's, except that animation values are JSON
+// text and responses a JSON array, as both hold arbitrary JSON.
+pub fn encode_render_update(update: &RenderUpdate) -> Result<Vec<u8>, String> {
+    let responses = serde_json::to_string(&update.responses).map_err(|e| format!("Failed to serialize render update: {}", e))?;
+    let mut fbb = FlatBufferBuilder::new();
+
+    let dom_operations: Vec<TableOffset> = update.dom_operations.iter().map(|operation| encode_dom_operation(&mut fbb, operation)).collect();
+    let dom_operations = fbb.create_vector(&dom_operations);
+    let style_changes: Vec<TableOffset> = update.style_changes.iter()
+        .map(|change| string_table(&mut fbb, &[&change.element_id, &change.property, &change.value]))
+        .collect();
+    let style_changes = fbb.create_vector(&style_changes);
+    let animation_updates: Vec<TableOffset> = update.animation_updates.iter().map(|animation| encode_animation_update(&mut fbb, animation)).collect();
+    let animation_updates = fbb.create_vector(&animation_updates);
+    let responses = fbb.create_string(&responses);
+    let dirty_regions: Vec<TableOffset> = update.dirty_regions.iter()
+        .map(|rect| {
+            let start = fbb.start_table();
+            for (field, value) in [rect.x, rect.y, rect.width, rect.height].into_iter().enumerate() {
+                fbb.push_slot(field_slot(field as u16), value, 0.0);
+            }
+            fbb.end_table(start)
+        })
+        .collect();
+    let dirty_regions = fbb.create_vector(&dirty_regions);
+
+    let start = fbb.start_table();
+    fbb.push_slot_always(field_slot(0), dom_operations);
+    fbb.push_slot_always(field_slot(1), style_changes);
+    fbb.push_slot_always(field_slot(2), animation_updates);
+    fbb.push_slot(field_slot(3), update.timestamp, 0.0);
+    fbb.push_slot(field_slot(4), update.sequence, 0);
+    fbb.push_slot(field_slot(5), update.full_resync, false);
+    fbb.push_slot_always(field_slot(6), responses);
+    fbb.push_slot_always(field_slot(7), dirty_regions);
+    let root = fbb.end_table(start);
+    fbb.finish(root, Some(UPDATE_FILE_IDENTIFIER));
+    Ok(fbb.finished_data().to_vec())
+}
+
+// A table of string fields in schema order
+fn string_table(fbb: &mut FlatBufferBuilder, fields: &[&str]) -> TableOffset {
+    let strings: Vec<_> = fields.iter().map(|text| fbb.create_string(text)).collect();
+    let start = fbb.start_table();
+    for (field, text) in strings.into_iter().enumerate() {
+        fbb.push_slot_always(field_slot(field as u16), text);
+    }
+    fbb.end_table(start)
+}
+
+fn encode_dom_operation(fbb: &mut FlatBufferBuilder, operation: &DOMOperation) -> TableOffset {
+    let element_id = fbb.create_string(operation.element_id());
+    let (kind, tag, parent_id, index, attributes) = match operation {
+        DOMOperation::Create { tag, parent_id, .. } => {
+            (DOM_CREATE, Some(fbb.create_string(tag)), parent_id.as_deref().map(|parent_id| fbb.create_string(parent_id)), 0, None)
+        }
+        DOMOperation::Update { attributes, .. } => {
+            let attributes: Vec<TableOffset> = attributes.iter().map(|(name, value)| string_table(fbb, &[name, value])).collect();
+            (DOM_UPDATE, None, None, 0, Some(fbb.create_vector(&attributes)))
+        }
+        DOMOperation::Remove { .. } => (DOM_REMOVE, None, None, 0, None),
+        DOMOperation::Move { new_parent_id, index, .. } => (DOM_MOVE, None, Some(fbb.create_string(new_parent_id)), *index as u64, None),
+    };
+    let start = fbb.start_table();
+    fbb.push_slot(field_slot(0), kind, DOM_CREATE);
+    fbb.push_slot_always(field_slot(1), element_id);
+    if let Some(tag) = tag {
+        fbb.push_slot_always(field_slot(2), tag);
+    }
+    if let Some(parent_id) = parent_id {
+        fbb.push_slot_always(field_slot(3), parent_id);
+    }
+    fbb.push_slot(field_slot(4), index, 0);
+    if let Some(attributes) = attributes {
+        fbb.push_slot_always(field_slot(5), attributes);
+    }
+    fbb.end_table(start)
+}
+
+fn encode_animation_update(fbb: &mut FlatBufferBuilder, animation: &AnimationUpdate) -> TableOffset {
+    let animation_id = fbb.create_string(&animation.animation_id);
+    let values: Vec<TableOffset> = animation.current_values.iter()
+        .map(|(name, value)| string_table(fbb, &[name, &value.to_string()]))
+        .collect();
+    let values = fbb.create_vector(&values);
+    let start = fbb.start_table();
+    fbb.push_slot_always(field_slot(0), animation_id);
+    fbb.push_slot(field_slot(1), animation.progress, 0.0);
+    fbb.push_slot_always(field_slot(2), values);
+    fbb.end_table(start)
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = CString::new(message.replace('\0', " ")).ok());
+}
+
+unsafe fn read_str<'a>(text: *const c_char, name: &str) -> Result<&'a str, String> {
+    if text.is_null() {
+        return Err(format!("{} is null", name));
+    }
+    CStr::from_ptr(text).to_str().map_err(|_| format!("{} is not UTF-8", name))
+}
+
+unsafe fn read_json<T: serde::de::DeserializeOwned>(text: *const c_char, name: &str) -> Result<T, String> {
+    serde_json::from_str(read_str(text, name)?).map_err(|e| format!("Failed to parse {}: {}", name, e))
+}
+
+// Runs `call`, returning `failed` and keeping the error for liv_last_error when it fails or panics
+fn guarded<T>(failed: T, call: impl FnOnce() -> Result<T, String>) -> T {
+    let result = catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|panic| Err(format!("Engine panicked: {}", panic_message(&*panic))));
+    result.unwrap_or_else(|message| {
+        set_last_error(message);
+        failed
+    })
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic.downcast_ref::<&str>().copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+// Runs `call` on the engine behind the handle, as guarded does
+pub(crate) unsafe fn with_engine<T>(engine: *mut InteractiveEngine, failed: T, call: impl FnOnce(&mut InteractiveEngine) -> Result<T, String>) -> T {
+    guarded(failed, || match engine.as_mut() {
+        Some(engine) => call(engine),
+        None => Err("engine is null".to_string()),
+    })
+}
+
+unsafe fn update_buffer(engine: *mut InteractiveEngine, call: impl FnOnce(&mut InteractiveEngine) -> Result<RenderUpdate, String>) -> LivBuffer {
+    with_engine(engine, LivBuffer::empty(), |engine| {
+        let update = call(engine)?;
+        Ok(LivBuffer::from_vec(encode_render_update(&update)?))
+    })
+}
+
+// Message of the last failed call on this thread, or null; valid until the next failure
+#[no_mangle]
+pub extern "C" fn liv_last_error() -> *const c_char {
+    LAST_ERROR.with(|slot| slot.borrow().as_ref().map_or(std::ptr::null(), |message| message.as_ptr()))
+}
+
+// A new engine from WASMPermissions JSON; free it with liv_engine_free
+#[no_mangle]
+pub unsafe extern "C" fn liv_engine_new(permissions_json: *const c_char) -> *mut InteractiveEngine {
+    guarded(std::ptr::null_mut(), || {
+        let permissions = read_json::<WASMPermissions>(permissions_json, "permissions")?;
+        let engine = InteractiveEngine::new(permissions).map_err(|e| format!("Failed to create engine: {}", e.message))?;
+        Ok(Box::into_raw(Box::new(engine)))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn liv_engine_free(engine: *mut InteractiveEngine) {
+    if !engine.is_null() {
+        guarded((), || {
+            drop(Box::from_raw(engine));
+            Ok(())
+        });
+    }
+}
+
+// 0 once the whole document is loaded, -1 on failure
+#[no_mangle]
+pub unsafe extern "C" fn liv_engine_load_document(engine: *mut InteractiveEngine, document_json: *const c_char) -> i32 {
+    with_engine(engine, -1, |engine| {
+        let json = read_str(document_json, "document")?;
+        engine.load_document(json).map_err(|e| format!("Failed to load document: {}", e.message))?;
+        Ok(0)
+    })
+}
+
+// Takes an InteractionEvent as JSON
+#[no_mangle]
+pub unsafe extern "C" fn liv_engine_process_interaction(engine: *mut InteractiveEngine, event_json: *const c_char) -> LivBuffer {
+    update_buffer(engine, |engine| {
+        let event: InteractionEvent = read_json(event_json, "interaction event")?;
+        engine.process_interaction(event).map_err(|e| format!("Failed to process interaction: {}", e.message))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn liv_engine_render_frame(engine: *mut InteractiveEngine, timestamp: f64) -> LivBuffer {
+    update_buffer(engine, |engine| {
+        engine.render_frame(timestamp).map_err(|e| format!("Failed to render frame: {}", e.message))
+    })
+}
+
+// Publishes on the event bus, as from the page's scripts; `source` may be null
+#[no_mangle]
+pub unsafe extern "C" fn liv_engine_emit_event(engine: *mut InteractiveEngine, event_name: *const c_char, payload_json: *const c_char, source: *const c_char) -> LivBuffer {
+    update_buffer(engine, |engine| {
+        let event_name = read_str(event_name, "event name")?;
+        let payload = read_json(payload_json, "event payload")?;
+        let source = if source.is_null() { None } else { Some(read_str(source, "source")?.to_string()) };
+        engine.emit_event(event_name, payload, source).map_err(|e| format!("Failed to emit event: {}", e.message))
+    })
+}
+
+// 0 once the source has the new data, -1 on failure
+#[no_mangle]
+pub unsafe extern "C" fn liv_engine_update_data_source(engine: *mut InteractiveEngine, data_source_id: *const c_char, data_json: *const c_char) -> i32 {
+    with_engine(engine, -1, |engine| {
+        let data_source_id = read_str(data_source_id, "data source id")?;
+        let data = read_json(data_json, "data")?;
+        engine.update_data_source(data_source_id, data).map_err(|e| format!("Failed to update data source: {}", e.message))?;
+        Ok(0)
+    })
+}
+
+// The event bus log as a JSON array of BusEventRecord
+#[no_mangle]
+pub unsafe extern "C" fn liv_engine_event_log(engine: *mut InteractiveEngine) -> LivBuffer {
+    with_engine(engine, LivBuffer::empty(), |engine| {
+        let json = serde_json::to_vec(&engine.get_event_bus_log()).map_err(|e| format!("Failed to serialize event log: {}", e))?;
+        Ok(LivBuffer::from_vec(json))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn liv_buffer_free(buffer: LivBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)));
+    }
+}
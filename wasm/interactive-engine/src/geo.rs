@@ -0,0 +1,171 @@
+// Geographic coordinates: projections between latitude/longitude and the plane, great-circle
+// distance and fitting a bounding box to a viewport. The map chart draws with these, and custom
+// chart types (ChartTypeRenderer) drawing geographic data can use them the same way.
+//
+// Projected coordinates run from 0 to 1 across the world, with y growing southwards like screen
+// coordinates. Latitudes and longitudes are in degrees.
+
+use serde::{Deserialize, Serialize};
+
+// Mean earth radius (IUGG)
+pub const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+// Web Mercator is unbounded towards the poles; this latitude makes the world square
+pub const MAX_MERCATOR_LATITUDE: f64 = 85.051_128_78;
+// Narrowest span GeoBounds::fit zooms to, as a share of the world; about 10 km at the equator
+const MIN_FIT_SPAN: f64 = 1.0 / 4096.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub enum Projection {
+    // Web Mercator, as used by web map tiles
+    #[default]
+    Mercator,
+    // Plate carrée: longitude and latitude map linearly to x and y
+    Equirectangular,
+}
+
+impl Projection {
+    // Latitudes beyond this are clamped by project
+    pub fn latitude_limit(&self) -> f64 {
+        match self {
+            Projection::Mercator => MAX_MERCATOR_LATITUDE,
+            Projection::Equirectangular => 90.0,
+        }
+    }
+
+    pub fn project(&self, latitude: f64, longitude: f64) -> (f64, f64) {
+        let limit = self.latitude_limit();
+        let latitude = latitude.clamp(-limit, limit);
+        let x = (longitude + 180.0) / 360.0;
+        let y = match self {
+            Projection::Mercator => {
+                let latitude = latitude.to_radians();
+                (1.0 - (latitude.tan() + 1.0 / latitude.cos()).ln() / std::f64::consts::PI) / 2.0
+            }
+            Projection::Equirectangular => (90.0 - latitude) / 180.0,
+        };
+        (x, y)
+    }
+
+    // Inverse of project, as (latitude, longitude)
+    pub fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        let longitude = x * 360.0 - 180.0;
+        let latitude = match self {
+            Projection::Mercator => (std::f64::consts::PI * (1.0 - 2.0 * y)).sinh().atan().to_degrees(),
+            Projection::Equirectangular => 90.0 - y * 180.0,
+        };
+        (latitude, longitude)
+    }
+}
+
+// Haversine distance along the earth's surface, in meters
+pub fn great_circle_distance(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (latitude_a, latitude_b) = (from.0.to_radians(), to.0.to_radians());
+    let half_latitude = (latitude_b - latitude_a) / 2.0;
+    let half_longitude = (to.1 - from.1).to_radians() / 2.0;
+    let a = half_latitude.sin().powi(2) + latitude_a.cos() * latitude_b.cos() * half_longitude.sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin()
+}
+
+// An area of the map, in degrees. West is less than east; boxes across the antimeridian are not
+// supported.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub struct GeoBounds {
+    pub west: f64,
+    pub south: f64,
+    pub east: f64,
+    pub north: f64,
+}
+
+impl GeoBounds {
+    // Everything the projection can show
+    pub fn world(projection: Projection) -> Self {
+        let limit = projection.latitude_limit();
+        Self { west: -180.0, south: -limit, east: 180.0, north: limit }
+    }
+
+    // Smallest box around (latitude, longitude) points; None without points
+    pub fn around(points: &[(f64, f64)]) -> Option<Self> {
+        let (first, rest) = points.split_first()?;
+        Some(rest.iter().fold(
+            Self { west: first.1, south: first.0, east: first.1, north: first.0 },
+            |bounds, (latitude, longitude)| Self {
+                west: bounds.west.min(*longitude),
+                south: bounds.south.min(*latitude),
+                east: bounds.east.max(*longitude),
+                north: bounds.north.max(*latitude),
+            },
+        ))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if ![self.west, self.south, self.east, self.north].iter().all(|value| value.is_finite()) {
+            return Err("edges must be numbers".to_string());
+        }
+        if self.west >= self.east || self.south >= self.north {
+            return Err("west must be less than east and south less than north".to_string());
+        }
+        if self.south < -90.0 || self.north > 90.0 {
+            return Err("latitudes must be between -90 and 90".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        (self.south..=self.north).contains(&latitude) && (self.west..=self.east).contains(&longitude)
+    }
+
+    // Smallest box around this one with the aspect ratio of a width x height viewport once
+    // projected, leaving `padding` pixels free on every side; the result is centred on this box.
+    // A single point fits to about 10 km across.
+    pub fn fit(&self, projection: Projection, width: f64, height: f64, padding: f64) -> GeoBounds {
+        let (left, top) = projection.project(self.north, self.west);
+        let (right, bottom) = projection.project(self.south, self.east);
+        let (center_x, center_y) = ((left + right) / 2.0, (top + bottom) / 2.0);
+        let inner_width = (width - padding * 2.0).max(1.0);
+        let inner_height = (height - padding * 2.0).max(1.0);
+        // Pixels per projected unit that show the whole box in the padded area
+        let scale = (inner_width / (right - left).max(MIN_FIT_SPAN)).min(inner_height / (bottom - top).max(MIN_FIT_SPAN));
+        let (half_width, half_height) = (width / scale / 2.0, height / scale / 2.0);
+
+        let (north, west) = projection.unproject(center_x - half_width, (center_y - half_height).max(0.0));
+        let (south, east) = projection.unproject(center_x + half_width, (center_y + half_height).min(1.0));
+        GeoBounds { west, south, east, north }
+    }
+}
+
+// A geographic box shown in a rectangle of the plane, e.g. a chart's plot area
+#[derive(Clone, Debug)]
+pub struct GeoViewport {
+    pub projection: Projection,
+    pub bounds: GeoBounds,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl GeoViewport {
+    // Projected corners of the bounds: (left, top, right, bottom)
+    fn extent(&self) -> (f64, f64, f64, f64) {
+        let (left, top) = self.projection.project(self.bounds.north, self.bounds.west);
+        let (right, bottom) = self.projection.project(self.bounds.south, self.bounds.east);
+        (left, top, right, bottom)
+    }
+
+    pub fn to_pixels(&self, latitude: f64, longitude: f64) -> (f64, f64) {
+        let (left, top, right, bottom) = self.extent();
+        let (x, y) = self.projection.project(latitude, longitude);
+        (self.x + (x - left) / (right - left) * self.width, self.y + (y - top) / (bottom - top) * self.height)
+    }
+
+    // Inverse of to_pixels, as (latitude, longitude)
+    pub fn to_geo(&self, x: f64, y: f64) -> (f64, f64) {
+        let (left, top, right, bottom) = self.extent();
+        self.projection.unproject(
+            left + (x - self.x) / self.width * (right - left),
+            top + (y - self.y) / self.height * (bottom - top),
+        )
+    }
+}
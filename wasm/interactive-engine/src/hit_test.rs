@@ -0,0 +1,92 @@
+// Which element is under a point. The render tree is walked the way the host stacks the DOM:
+// later siblings above earlier ones, children above their parent, and a "style.z-index"
+// property reordering siblings. Points are in document coordinates.
+//
+// Each element's box is its computed size, placed by its transform relative to the parent's box
+// and rotated and scaled about its centre like the CSS the engine emits. Elements that are not
+// visible (a hidden render node, "style.display" none, "style.visibility" hidden or zero
+// opacity) are skipped along with their children; "style.pointer-events" none lets the point
+// through to whatever is below while children can still be hit.
+
+use super::{DocumentState, InteractiveElement, Position, RenderNode};
+use std::collections::{HashMap, HashSet};
+
+// Topmost element under `point`
+pub fn hit_test(document: &DocumentState, point: &Position) -> Option<String> {
+    let tree = &document.render_tree;
+    let elements: HashMap<&str, &InteractiveElement> = document.elements.iter()
+        .map(|element| (element.id.as_str(), element))
+        .collect();
+    // Top level in document order
+    let roots: Vec<&str> = document.elements.iter()
+        .filter(|element| tree.nodes.get(&element.id)
+            .is_some_and(|node| node.parent.as_ref().map_or(true, |parent| !tree.nodes.contains_key(parent))))
+        .map(|element| element.id.as_str())
+        .collect();
+    let walk = Walk { nodes: &tree.nodes, elements: &elements };
+    walk.topmost(&roots, (point.x, point.y), &mut HashSet::new())
+}
+
+struct Walk<'a> {
+    nodes: &'a HashMap<String, RenderNode>,
+    elements: &'a HashMap<&'a str, &'a InteractiveElement>,
+}
+
+impl<'a> Walk<'a> {
+    fn topmost(&self, ids: &[&'a str], point: (f64, f64), visited: &mut HashSet<&'a str>) -> Option<String> {
+        let mut stacked: Vec<(usize, &str)> = ids.iter().copied().enumerate().collect();
+        stacked.sort_by(|a, b| z_index(self.elements.get(a.1)).total_cmp(&z_index(self.elements.get(b.1))).then(a.0.cmp(&b.0)));
+
+        for (_, id) in stacked.into_iter().rev() {
+            let (Some((id, node)), Some(element)) = (self.nodes.get_key_value(id), self.elements.get(id)) else { continue };
+            if !visited.insert(id.as_str()) || !node.visible || is_hidden(element) {
+                continue;
+            }
+            let size = &node.computed_style.size;
+            let Some(local) = to_local(element, size.width, size.height, point) else { continue };
+            let children: Vec<&str> = node.children.iter().map(|child| child.as_str()).collect();
+            if let Some(hit) = self.topmost(&children, local, visited) {
+                return Some(hit);
+            }
+            let inside = local.0 >= 0.0 && local.0 <= size.width && local.1 >= 0.0 && local.1 <= size.height;
+            if inside && style_property(element, "style.pointer-events") != Some("none") {
+                return Some(id.clone());
+            }
+        }
+        None
+    }
+}
+
+// `point` from the parent's box into the element's, undoing translate(x, y) rotate() scale()
+// about the element's centre. None when a zero scale collapses the element.
+fn to_local(element: &InteractiveElement, width: f64, height: f64, point: (f64, f64)) -> Option<(f64, f64)> {
+    let transform = &element.transform;
+    if transform.scale_x == 0.0 || transform.scale_y == 0.0 {
+        return None;
+    }
+    let (center_x, center_y) = (width / 2.0, height / 2.0);
+    let (x, y) = (point.0 - transform.x - center_x, point.1 - transform.y - center_y);
+    let (sin, cos) = (-transform.rotation.to_radians()).sin_cos();
+    let (x, y) = (x * cos - y * sin, x * sin + y * cos);
+    let local = (x / transform.scale_x + center_x, y / transform.scale_y + center_y);
+    (local.0.is_finite() && local.1.is_finite()).then_some(local)
+}
+
+fn is_hidden(element: &InteractiveElement) -> bool {
+    element.transform.opacity <= 0.0
+        || style_property(element, "style.display") == Some("none")
+        || style_property(element, "style.visibility") == Some("hidden")
+}
+
+fn style_property<'e>(element: &'e InteractiveElement, property: &str) -> Option<&'e str> {
+    element.properties.get(property).and_then(|value| value.as_str())
+}
+
+// A number or numeric string; 0 when unset
+fn z_index(element: Option<&&InteractiveElement>) -> f64 {
+    let Some(value) = element.and_then(|element| element.properties.get("style.z-index")) else { return 0.0 };
+    value.as_f64()
+        .or_else(|| value.as_str().and_then(|text| text.trim().parse().ok()))
+        .filter(|z| z.is_finite())
+        .unwrap_or(0.0)
+}
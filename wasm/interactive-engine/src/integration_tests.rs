@@ -1,5 +1,8 @@
 use super::*;
 use wasm_bindgen_test::*;
+// Native `cargo test` runs the same tests as plain #[test]s
+#[cfg(not(target_family = "wasm"))]
+use std::prelude::v1::test as wasm_bindgen_test;
 
 wasm_bindgen_test_configure!(run_in_browser);
 
@@ -95,8 +98,12 @@ fn test_full_interactive_document_workflow() {
         fill_opacity: Some(0.2),
         marker_size: Some(5.0),
         marker_shape: Some(MarkerShape::Circle),
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
 
     engine.chart_renderer.add_series(&chart_id, chart_series).unwrap();
@@ -151,6 +158,7 @@ fn test_full_interactive_document_workflow() {
         source_id: "chart_data".to_string(),
         target_element: chart_element_id.clone(),
         property_path: "data".to_string(),
+        target_property: None,
         transform_function: None,
         update_trigger: UpdateTrigger::Immediate,
     };
@@ -197,6 +205,7 @@ fn test_full_interactive_document_workflow() {
                 radius: Some(8.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             changed_touches: vec![TouchPoint {
                 identifier: 1,
@@ -204,6 +213,7 @@ fn test_full_interactive_document_workflow() {
                 radius: Some(8.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             target_touches: vec![],
             force: Some(0.5),
@@ -213,6 +223,7 @@ fn test_full_interactive_document_workflow() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -239,6 +250,7 @@ fn test_full_interactive_document_workflow() {
                     radius: Some(8.0),
                     rotation_angle: None,
                     force: Some(0.5),
+                    confidence: None,
                 }],
                 changed_touches: vec![TouchPoint {
                     identifier: 1,
@@ -246,6 +258,7 @@ fn test_full_interactive_document_workflow() {
                     radius: Some(8.0),
                     rotation_angle: None,
                     force: Some(0.5),
+                    confidence: None,
                 }],
                 target_touches: vec![],
                 force: Some(0.5),
@@ -255,6 +268,7 @@ fn test_full_interactive_document_workflow() {
             mouse_data: None,
             keyboard_data: None,
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -282,6 +296,7 @@ fn test_full_interactive_document_workflow() {
                 radius: Some(8.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             target_touches: vec![],
             force: None,
@@ -291,6 +306,7 @@ fn test_full_interactive_document_workflow() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -325,6 +341,7 @@ fn test_full_interactive_document_workflow() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -344,16 +361,11 @@ fn test_full_interactive_document_workflow() {
     // Phase 11: Process animation frames
     for frame in 0..120 { // 2 seconds at 60fps
         let timestamp = start_time + 1000.0 + (frame as f64 * 16.67);
-        let render_update = engine.render_frame(timestamp).unwrap();
-        
-        // Should have animation updates for the first part of the timeline
-        if frame < 100 {
-            assert!(render_update.animation_updates.len() >= 0);
-        }
+        engine.render_frame(timestamp).unwrap();
     }
 
     // Phase 12: Create vector graphics
-    let rect_id = engine.vector_engine.create_shape(
+    let _rect_id = engine.vector_engine.create_shape(
         ShapeType::Rectangle,
         Position { x: 10.0, y: 10.0 },
         Size { width: 100.0, height: 50.0 }
@@ -367,7 +379,7 @@ fn test_full_interactive_document_workflow() {
         ("turns".to_string(), 2.0),
     ].into_iter().collect();
 
-    let spiral_id = engine.vector_engine.create_complex_path(
+    let _spiral_id = engine.vector_engine.create_complex_path(
         ComplexPathType::Spiral,
         spiral_params
     ).unwrap();
@@ -378,7 +390,7 @@ fn test_full_interactive_document_workflow() {
     assert!(svg_content.contains("<path"));
 
     // Phase 13: Test gesture recognition
-    let gesture_events = engine.gesture_recognizer.process_touch_input(
+    let _gesture_events = engine.gesture_recognizer.process_touch_input(
         &TouchData {
             touches: vec![TouchPoint {
                 identifier: 1,
@@ -386,6 +398,7 @@ fn test_full_interactive_document_workflow() {
                 radius: Some(8.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             changed_touches: vec![TouchPoint {
                 identifier: 1,
@@ -393,6 +406,7 @@ fn test_full_interactive_document_workflow() {
                 radius: Some(8.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             target_touches: vec![],
             force: Some(0.5),
@@ -404,7 +418,6 @@ fn test_full_interactive_document_workflow() {
 
     // Phase 14: Verify final state
     assert_eq!(engine.document_state.elements.len(), 3); // container, chart, interactive
-    assert!(engine.document_state.animations.len() >= 0); // Animations may have completed
     assert_eq!(engine.document_state.data_sources.len(), 2);
     assert_eq!(engine.chart_renderer.charts.len(), 1);
     assert_eq!(engine.vector_engine.shapes.len(), 1);
@@ -495,8 +508,12 @@ fn test_performance_under_realistic_load() {
             fill_opacity: Some(0.3),
             marker_size: Some(4.0),
             marker_shape: Some(MarkerShape::Circle),
+            markers: None,
+            line_style: None,
             visible: true,
             y_axis: AxisReference::Primary,
+            error: None,
+            pattern: None,
         };
 
         engine.chart_renderer.add_series(&chart_id, series).unwrap();
@@ -586,6 +603,7 @@ fn test_performance_under_realistic_load() {
             }),
             keyboard_data: None,
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -594,7 +612,7 @@ fn test_performance_under_realistic_load() {
             },
         };
 
-        let render_update = engine.process_interaction(click_event).unwrap();
+        let _render_update = engine.process_interaction(click_event).unwrap();
         interaction_count += 1;
 
         // Update data periodically
@@ -624,6 +642,7 @@ fn test_performance_under_realistic_load() {
                 mouse_data: None,
                 keyboard_data: None,
                 gesture_data: None,
+                pen_data: None,
                 modifiers: EventModifiers {
                     ctrl: false,
                     shift: false,
@@ -651,7 +670,7 @@ fn test_performance_under_realistic_load() {
 
     // Performance assertions
     let average_cycle_time = render_times.iter().sum::<f64>() / render_times.len() as f64;
-    let max_cycle_time = render_times.iter().fold(0.0, |a, &b| a.max(b));
+    let max_cycle_time = render_times.iter().fold(0.0_f64, |a, &b| a.max(b));
 
     assert!(average_cycle_time < 100.0, "Average cycle time too slow: {}ms", average_cycle_time);
     assert!(max_cycle_time < 500.0, "Max cycle time too slow: {}ms", max_cycle_time);
@@ -1,5 +1,8 @@
 use super::*;
 use wasm_bindgen_test::*;
+// Native `cargo test` runs the same tests as plain #[test]s
+#[cfg(not(target_family = "wasm"))]
+use std::prelude::v1::test as wasm_bindgen_test;
 
 wasm_bindgen_test_configure!(run_in_browser);
 
@@ -47,6 +50,7 @@ fn test_comprehensive_mouse_interaction_handling() {
         }),
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -81,6 +85,7 @@ fn test_comprehensive_mouse_interaction_handling() {
             }),
             keyboard_data: None,
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -114,6 +119,7 @@ fn test_comprehensive_mouse_interaction_handling() {
         }),
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -170,6 +176,7 @@ fn test_comprehensive_touch_interaction_handling() {
                 radius: Some(8.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             changed_touches: vec![TouchPoint {
                 identifier: 1,
@@ -177,6 +184,7 @@ fn test_comprehensive_touch_interaction_handling() {
                 radius: Some(8.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             target_touches: vec![],
             force: Some(0.5),
@@ -186,6 +194,7 @@ fn test_comprehensive_touch_interaction_handling() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -212,6 +221,7 @@ fn test_comprehensive_touch_interaction_handling() {
                     radius: Some(8.0),
                     rotation_angle: None,
                     force: Some(0.5 + (i as f64 * 0.01)),
+                    confidence: None,
                 }],
                 changed_touches: vec![TouchPoint {
                     identifier: 1,
@@ -219,6 +229,7 @@ fn test_comprehensive_touch_interaction_handling() {
                     radius: Some(8.0),
                     rotation_angle: None,
                     force: Some(0.5 + (i as f64 * 0.01)),
+                    confidence: None,
                 }],
                 target_touches: vec![],
                 force: Some(0.5 + (i as f64 * 0.01)),
@@ -228,6 +239,7 @@ fn test_comprehensive_touch_interaction_handling() {
             mouse_data: None,
             keyboard_data: None,
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -255,6 +267,7 @@ fn test_comprehensive_touch_interaction_handling() {
                 radius: Some(8.0),
                 rotation_angle: None,
                 force: Some(0.7),
+                confidence: None,
             }],
             target_touches: vec![],
             force: None,
@@ -264,6 +277,7 @@ fn test_comprehensive_touch_interaction_handling() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -316,6 +330,7 @@ fn test_multi_touch_gesture_recognition() {
                     radius: Some(8.0),
                     rotation_angle: None,
                     force: Some(0.5),
+                    confidence: None,
                 },
                 TouchPoint {
                     identifier: 2,
@@ -323,6 +338,7 @@ fn test_multi_touch_gesture_recognition() {
                     radius: Some(8.0),
                     rotation_angle: None,
                     force: Some(0.5),
+                    confidence: None,
                 },
             ],
             changed_touches: vec![
@@ -332,6 +348,7 @@ fn test_multi_touch_gesture_recognition() {
                     radius: Some(8.0),
                     rotation_angle: None,
                     force: Some(0.5),
+                    confidence: None,
                 },
                 TouchPoint {
                     identifier: 2,
@@ -339,6 +356,7 @@ fn test_multi_touch_gesture_recognition() {
                     radius: Some(8.0),
                     rotation_angle: None,
                     force: Some(0.5),
+                    confidence: None,
                 },
             ],
             target_touches: vec![],
@@ -349,6 +367,7 @@ fn test_multi_touch_gesture_recognition() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -379,6 +398,7 @@ fn test_multi_touch_gesture_recognition() {
                         radius: Some(8.0),
                         rotation_angle: None,
                         force: Some(0.5),
+                        confidence: None,
                     },
                     TouchPoint {
                         identifier: 2,
@@ -386,6 +406,7 @@ fn test_multi_touch_gesture_recognition() {
                         radius: Some(8.0),
                         rotation_angle: None,
                         force: Some(0.5),
+                        confidence: None,
                     },
                 ],
                 changed_touches: vec![
@@ -395,6 +416,7 @@ fn test_multi_touch_gesture_recognition() {
                         radius: Some(8.0),
                         rotation_angle: None,
                         force: Some(0.5),
+                        confidence: None,
                     },
                     TouchPoint {
                         identifier: 2,
@@ -402,6 +424,7 @@ fn test_multi_touch_gesture_recognition() {
                         radius: Some(8.0),
                         rotation_angle: None,
                         force: Some(0.5),
+                        confidence: None,
                     },
                 ],
                 target_touches: vec![],
@@ -412,6 +435,7 @@ fn test_multi_touch_gesture_recognition() {
             mouse_data: None,
             keyboard_data: None,
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -478,6 +502,7 @@ fn test_keyboard_interaction_handling() {
                 repeat: false,
             }),
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: key == "Control",
                 shift: key == "Shift",
@@ -506,6 +531,7 @@ fn test_keyboard_interaction_handling() {
                 repeat: false,
             }),
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -571,6 +597,7 @@ fn test_event_delegation_system() {
         }),
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -596,6 +623,7 @@ fn test_event_delegation_system() {
                 radius: Some(8.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             changed_touches: vec![TouchPoint {
                 identifier: 1,
@@ -603,6 +631,7 @@ fn test_event_delegation_system() {
                 radius: Some(8.0),
                 rotation_angle: None,
                 force: Some(0.5),
+                confidence: None,
             }],
             target_touches: vec![],
             force: Some(0.5),
@@ -612,6 +641,7 @@ fn test_event_delegation_system() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -642,6 +672,7 @@ fn test_event_delegation_system() {
         }),
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -704,6 +735,7 @@ fn test_responsive_interaction_adaptation() {
                 radius: Some(4.0), // Small radius that should be adapted
                 rotation_angle: None,
                 force: Some(0.3),
+                confidence: None,
             }],
             changed_touches: vec![],
             target_touches: vec![],
@@ -714,6 +746,7 @@ fn test_responsive_interaction_adaptation() {
         mouse_data: None,
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -757,6 +790,7 @@ fn test_responsive_interaction_adaptation() {
         }),
         keyboard_data: None,
         gesture_data: None,
+        pen_data: None,
         modifiers: EventModifiers {
             ctrl: false,
             shift: false,
@@ -793,11 +827,9 @@ fn test_interaction_performance_metrics() {
     let element_id = engine.create_element(ElementType::Interactive, HashMap::new()).unwrap();
 
     // Generate various interactions to test metrics
-    let interaction_types = vec![
-        InteractionType::Click,
+    let interaction_types = [InteractionType::Click,
         InteractionType::TouchStart,
-        InteractionType::MouseMove,
-    ];
+        InteractionType::MouseMove];
 
     for (i, interaction_type) in interaction_types.iter().cycle().take(100).enumerate() {
         let event = match interaction_type {
@@ -817,6 +849,7 @@ fn test_interaction_performance_metrics() {
                 }),
                 keyboard_data: None,
                 gesture_data: None,
+                pen_data: None,
                 modifiers: EventModifiers {
                     ctrl: false,
                     shift: false,
@@ -837,6 +870,7 @@ fn test_interaction_performance_metrics() {
                         radius: Some(8.0),
                         rotation_angle: None,
                         force: Some(0.5),
+                        confidence: None,
                     }],
                     changed_touches: vec![],
                     target_touches: vec![],
@@ -847,6 +881,7 @@ fn test_interaction_performance_metrics() {
                 mouse_data: None,
                 keyboard_data: None,
                 gesture_data: None,
+                pen_data: None,
                 modifiers: EventModifiers {
                     ctrl: false,
                     shift: false,
@@ -870,6 +905,7 @@ fn test_interaction_performance_metrics() {
                 }),
                 keyboard_data: None,
                 gesture_data: None,
+                pen_data: None,
                 modifiers: EventModifiers {
                     ctrl: false,
                     shift: false,
@@ -890,4 +926,567 @@ fn test_interaction_performance_metrics() {
     assert!(metrics.events_per_second > 0.0);
     assert!(metrics.mouse_events_processed > 0);
     assert!(metrics.touch_points_processed > 0);
-}
\ No newline at end of file
+}
+
+#[wasm_bindgen_test]
+fn test_event_rate_limiting() {
+    let event_at = |event_type: InteractionType, target: &str, timestamp: f64| InteractionEvent {
+        event_type,
+        target_element: Some(target.to_string()),
+        position: Some(Position { x: 0.0, y: 0.0 }),
+        data: HashMap::new(),
+        timestamp,
+        touch_data: None,
+        mouse_data: None,
+        keyboard_data: None,
+        gesture_data: None,
+        pen_data: None,
+        modifiers: EventModifiers {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            meta: false,
+        },
+    };
+
+    let mut limiter = EventRateLimiter::new();
+    limiter.set_limit(EventRateLimitConfig {
+        target_element: Some("canvas".to_string()),
+        event_type: InteractionType::MouseMove,
+        limit: RateLimit::Throttle { interval_ms: 1000.0 / 30.0 },
+    });
+    limiter.set_limit(EventRateLimitConfig {
+        target_element: None,
+        event_type: InteractionType::Resize,
+        limit: RateLimit::Debounce { delay_ms: 150.0 },
+    });
+
+    // Throttled to 30Hz on the configured element only
+    assert!(matches!(limiter.check(&event_at(InteractionType::MouseMove, "canvas", 0.0)), RateDecision::Accept));
+    assert!(matches!(limiter.check(&event_at(InteractionType::MouseMove, "canvas", 10.0)), RateDecision::Throttled));
+    assert!(matches!(limiter.check(&event_at(InteractionType::MouseMove, "canvas", 40.0)), RateDecision::Accept));
+    assert!(matches!(limiter.check(&event_at(InteractionType::MouseMove, "other", 41.0)), RateDecision::Accept));
+
+    // Debounced: only the last resize is delivered after the quiet period
+    assert!(matches!(limiter.check(&event_at(InteractionType::Resize, "window", 0.0)), RateDecision::Debounced { superseded: false }));
+    assert!(matches!(limiter.check(&event_at(InteractionType::Resize, "window", 100.0)), RateDecision::Debounced { superseded: true }));
+    assert!(limiter.take_due(200.0).is_empty());
+    let due = limiter.take_due(250.0);
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].timestamp, 100.0);
+
+    // Events held back per element never count toward the engine-wide interaction rate
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec![],
+        cpu_time_limit: 10000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string(), "MouseMove".to_string(), "Click".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 10,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let element_id = engine.create_element(ElementType::Interactive, HashMap::new()).unwrap();
+    engine.set_event_rate_limit(EventRateLimitConfig {
+        target_element: Some(element_id.clone()),
+        event_type: InteractionType::MouseMove,
+        limit: RateLimit::Throttle { interval_ms: 1000.0 },
+    });
+    engine.set_event_rate_limit(EventRateLimitConfig {
+        target_element: Some(element_id.clone()),
+        event_type: InteractionType::Click,
+        limit: RateLimit::Debounce { delay_ms: 100.0 },
+    });
+    for i in 0..5 {
+        engine.process_interaction(event_at(InteractionType::MouseMove, &element_id, i as f64)).unwrap();
+    }
+    engine.process_interaction(event_at(InteractionType::Click, &element_id, 10.0)).unwrap();
+    assert_eq!(engine.security_context.interaction_count, 1);
+
+    // A debounced event dies with its element
+    engine.delete_element(&element_id).unwrap();
+    assert!(engine.event_rate_limiter.pending.is_empty());
+    engine.render_frame(1_000.0).unwrap();
+    assert_eq!(engine.security_context.interaction_count, 1);
+}
+
+#[wasm_bindgen_test]
+fn test_event_queue_ordering() {
+    let event_at = |event_type: InteractionType, (x, y): (f64, f64), timestamp: f64| {
+        let position = Position { x, y };
+        let touch = TouchPoint { identifier: 1, position: position.clone(), radius: None, rotation_angle: None, force: None, confidence: None };
+        let touching = matches!(event_type, InteractionType::TouchStart | InteractionType::TouchMove | InteractionType::TouchEnd);
+        let mouse = matches!(event_type, InteractionType::MouseDown | InteractionType::MouseMove | InteractionType::MouseUp | InteractionType::Click);
+        InteractionEvent {
+            event_type,
+            target_element: Some("canvas".to_string()),
+            position: Some(position.clone()),
+            data: HashMap::new(),
+            timestamp,
+            touch_data: touching.then(|| TouchData {
+                touches: vec![touch.clone()],
+                changed_touches: vec![touch],
+                target_touches: vec![],
+                force: None,
+                rotation_angle: None,
+                scale: None,
+            }),
+            mouse_data: mouse.then_some(MouseData { button: MouseButton::Left, buttons: 1, position, movement: None, wheel_delta: None }),
+            keyboard_data: None,
+            gesture_data: None,
+            pen_data: None,
+            modifiers: EventModifiers {
+                ctrl: false,
+                shift: false,
+                alt: false,
+                meta: false,
+            },
+        }
+    };
+
+    // The emulated mouse events arrive first but are delivered, and dropped, after their touch
+    let mut queue = EventQueue::new();
+    assert!(!queue.push(event_at(InteractionType::MouseDown, (102.0, 100.0), 120.0)));
+    assert!(!queue.push(event_at(InteractionType::Click, (102.0, 100.0), 125.0)));
+    assert!(queue.push(event_at(InteractionType::TouchStart, (100.0, 100.0), 10.0)));
+    assert!(queue.push(event_at(InteractionType::TouchEnd, (100.0, 100.0), 100.0)));
+    let (events, synthesized) = queue.take_due(16.0);
+    assert_eq!(events.iter().map(|event| event.timestamp).collect::<Vec<_>>(), vec![10.0, 100.0]);
+    assert_eq!(synthesized, 2);
+    assert!(queue.is_empty());
+
+    // A real mouse somewhere else, or long after the touch, is kept
+    queue.push(event_at(InteractionType::MouseDown, (400.0, 100.0), 200.0));
+    queue.push(event_at(InteractionType::MouseDown, (100.0, 100.0), 2_000.0));
+    let (events, synthesized) = queue.take_due(32.0);
+    assert_eq!(events.len(), 2);
+    assert_eq!(synthesized, 0);
+
+    // Held for the reorder window, then drag moves are predicted one frame ahead
+    queue.set_config(EventQueueConfig { reorder_window_ms: 16.0, drag_prediction_ms: 16.0, ..EventQueueConfig::default() });
+    queue.push(event_at(InteractionType::MouseMove, (100.0, 300.0), 3_000.0));
+    queue.push(event_at(InteractionType::MouseMove, (110.0, 300.0), 3_010.0));
+    queue.push(event_at(InteractionType::MouseMove, (1_110.0, 300.0), 3_020.0));
+    queue.push(event_at(InteractionType::MouseUp, (1_110.0, 300.0), 3_030.0));
+    assert!(queue.take_due(40.0).0.is_empty());
+    let (events, _) = queue.take_due(48.0);
+    let x = |event: &InteractionEvent| event.position.as_ref().unwrap().x;
+    assert_eq!(x(&events[0]), 100.0);
+    assert_eq!(x(&events[1]), 126.0);
+    assert_eq!(events[1].mouse_data.as_ref().unwrap().position.x, 126.0);
+    assert_eq!(events[1].data["reported_position"]["x"], serde_json::json!(110.0));
+    // The lead is capped, and releasing the button is reported where it happened
+    assert_eq!(x(&events[2]), 1_110.0 + MAX_DRAG_PREDICTION);
+    assert_eq!(x(&events[3]), 1_110.0);
+}
+
+#[wasm_bindgen_test]
+fn test_interaction_overrides() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let slider = engine.create_element(ElementType::Interactive, HashMap::new()).unwrap();
+
+    // Engine overrides sit over the device profile, element overrides over both
+    engine.set_interaction_overrides(InteractionOverrides { double_click_timeout: Some(250.0), ..InteractionOverrides::default() }).unwrap();
+    engine.set_element_interaction_overrides(&slider, Some(InteractionOverrides { drag_threshold: Some(0.0), ..InteractionOverrides::default() })).unwrap();
+    let settings = engine.interaction_settings_for(None);
+    assert_eq!((settings.drag_threshold, settings.double_click_timeout), (5.0, 250.0));
+    let settings = engine.interaction_settings_for(Some(&slider));
+    assert_eq!((settings.drag_threshold, settings.double_click_timeout), (0.0, 250.0));
+
+    let negative = InteractionOverrides { tap_slop: Some(-1.0), ..InteractionOverrides::default() };
+    assert_eq!(engine.set_interaction_overrides(negative).unwrap_err().code, "INVALID_EVENT_VALUE");
+    assert_eq!(engine.set_element_interaction_overrides("missing", None).unwrap_err().code, "ELEMENT_NOT_FOUND");
+
+    // A zero threshold starts the slider's drag on the first pixel; elsewhere it takes 5
+    let mouse = |event_type: InteractionType, target: &str, x: f64, buttons: u16| InteractionEvent {
+        event_type,
+        target_element: Some(target.to_string()),
+        position: Some(Position { x, y: 0.0 }),
+        data: HashMap::new(),
+        timestamp: 0.0,
+        touch_data: None,
+        mouse_data: Some(MouseData { button: MouseButton::Left, buttons, position: Position { x, y: 0.0 }, movement: None, wheel_delta: None }),
+        keyboard_data: None,
+        gesture_data: None,
+        pen_data: None,
+        modifiers: EventModifiers {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            meta: false,
+        },
+    };
+    let mut manager = InteractionManager::new();
+    manager.set_settings(engine.interaction_settings_for(None));
+    manager.set_element_overrides("slider", Some(InteractionOverrides { drag_threshold: Some(0.0), ..InteractionOverrides::default() }));
+    let drags = |manager: &mut InteractionManager, target: &str| {
+        manager.process_event(&mouse(InteractionType::MouseDown, target, 100.0, 1)).unwrap();
+        let responses = manager.process_event(&mouse(InteractionType::MouseMove, target, 101.0, 1)).unwrap();
+        manager.process_event(&mouse(InteractionType::MouseUp, target, 101.0, 0)).unwrap();
+        responses.iter().any(|response| matches!(response.response_type, ResponseType::DragStart))
+    };
+    assert!(drags(&mut manager, "slider"));
+    assert!(!drags(&mut manager, "button"));
+}
+
+#[wasm_bindgen_test]
+fn test_hover_intent() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "Hover".to_string(), "MouseMove".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let menu = engine.create_element(ElementType::Interactive, HashMap::new()).unwrap();
+    let tooltip = engine.create_element(ElementType::Interactive, HashMap::new()).unwrap();
+    engine.document_state.get_element_mut(&tooltip).unwrap().transform.x = 200.0;
+
+    let invalid = HoverIntentConfig { hide_delay_ms: -1.0, ..HoverIntentConfig::default() };
+    assert_eq!(engine.set_hover_intent(&menu, Some(invalid)).unwrap_err().code, "INVALID_EVENT_VALUE");
+    assert_eq!(engine.set_hover_intent("missing", None).unwrap_err().code, "ELEMENT_NOT_FOUND");
+    let config = HoverIntentConfig { show_delay_ms: Some(100.0), hide_delay_ms: 200.0, tooltip_id: Some(tooltip.clone()) };
+    engine.set_hover_intent(&menu, Some(config)).unwrap();
+
+    let pointer = |event_type: InteractionType, target: Option<&str>, state: &str, (x, y): (f64, f64)| InteractionEvent {
+        event_type,
+        target_element: target.map(str::to_string),
+        position: Some(Position { x, y }),
+        data: [("hover_state".to_string(), serde_json::json!(state))].into_iter().collect(),
+        timestamp: 0.0,
+        touch_data: None,
+        mouse_data: None,
+        keyboard_data: None,
+        gesture_data: None,
+        pen_data: None,
+        modifiers: EventModifiers {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            meta: false,
+        },
+    };
+    let style = |update: &RenderUpdate, element_id: &str, property: &str| update.style_changes.iter()
+        .find(|change| change.element_id == element_id && change.property == property)
+        .map(|change| change.value.trim_matches('"').to_string());
+    let hover_timers = |engine: &InteractiveEngine| engine.get_timers().iter().filter(|timer| timer.id.starts_with(HOVER_INTENT_TIMER_PREFIX)).count();
+    engine.render_frame(0.0).unwrap();
+
+    // The cursor changes at once; passing through without stopping never shows the hover
+    let update = engine.process_interaction(pointer(InteractionType::Hover, Some(&menu), "enter", (50.0, 50.0))).unwrap();
+    assert_eq!(style(&update, &menu, "cursor").as_deref(), Some("pointer"));
+    assert_eq!(style(&update, &menu, "opacity"), None);
+    assert_eq!(hover_timers(&engine), 1);
+    engine.process_interaction(pointer(InteractionType::Hover, Some(&menu), "leave", (120.0, 50.0))).unwrap();
+    assert_eq!(hover_timers(&engine), 0);
+
+    // Staying past the show delay shows the hover and the tooltip
+    engine.process_interaction(pointer(InteractionType::Hover, Some(&menu), "enter", (50.0, 50.0))).unwrap();
+    assert!(style(&engine.render_frame(50.0).unwrap(), &menu, "opacity").is_none());
+    let update = engine.render_frame(160.0).unwrap();
+    assert!(engine.is_hover_intent_active(&menu));
+    assert_eq!(style(&update, &menu, "opacity").as_deref(), Some("0.8"));
+    assert_eq!(style(&update, &tooltip, "visibility").as_deref(), Some("visible"));
+
+    // Heading for the tooltip pushes the hide back until the pointer strays or arrives
+    engine.process_interaction(pointer(InteractionType::Hover, Some(&menu), "leave", (100.0, 50.0))).unwrap();
+    engine.render_frame(300.0).unwrap();
+    engine.process_interaction(pointer(InteractionType::MouseMove, None, "", (150.0, 50.0))).unwrap();
+    engine.render_frame(400.0).unwrap();
+    assert!(engine.is_hover_intent_active(&menu));
+    engine.process_interaction(pointer(InteractionType::Hover, Some(&tooltip), "enter", (210.0, 50.0))).unwrap();
+    assert_eq!(hover_timers(&engine), 0);
+    engine.render_frame(1_000.0).unwrap();
+    assert!(engine.is_hover_intent_active(&menu));
+
+    // Leaving the tooltip hides both after the grace period
+    engine.process_interaction(pointer(InteractionType::Hover, Some(&tooltip), "leave", (320.0, 50.0))).unwrap();
+    engine.render_frame(1_100.0).unwrap();
+    assert!(engine.is_hover_intent_active(&menu));
+    let update = engine.render_frame(1_300.0).unwrap();
+    assert!(!engine.is_hover_intent_active(&menu));
+    assert_eq!(style(&update, &menu, "opacity").as_deref(), Some("1.0"));
+    assert_eq!(style(&update, &tooltip, "visibility").as_deref(), Some("hidden"));
+
+    // Leaving away from the tooltip hides on schedule
+    engine.process_interaction(pointer(InteractionType::Hover, Some(&menu), "enter", (50.0, 50.0))).unwrap();
+    engine.render_frame(1_500.0).unwrap();
+    engine.process_interaction(pointer(InteractionType::Hover, Some(&menu), "leave", (50.0, 100.0))).unwrap();
+    engine.process_interaction(pointer(InteractionType::MouseMove, None, "", (50.0, 150.0))).unwrap();
+    engine.render_frame(1_600.0).unwrap();
+    engine.render_frame(1_750.0).unwrap();
+    assert!(!engine.is_hover_intent_active(&menu));
+}
+
+#[wasm_bindgen_test]
+fn test_response_filter() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "Click".to_string(), "Hover".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let button = engine.create_element(ElementType::Interactive, HashMap::new()).unwrap();
+    let event = |event_type: InteractionType| InteractionEvent {
+        event_type,
+        target_element: Some(button.clone()),
+        position: Some(Position { x: 10.0, y: 10.0 }),
+        data: HashMap::new(),
+        timestamp: 0.0,
+        touch_data: None,
+        mouse_data: Some(MouseData { button: MouseButton::Left, buttons: 0, position: Position { x: 10.0, y: 10.0 }, movement: None, wheel_delta: None }),
+        keyboard_data: None,
+        gesture_data: None,
+        pen_data: None,
+        modifiers: EventModifiers {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            meta: false,
+        },
+    };
+    let types = |update: &RenderUpdate| update.responses.iter().map(|response| response.response_type).collect::<Vec<_>>();
+
+    // Unfiltered, every response comes through, tagged with its channel and severity
+    let update = engine.process_interaction(event(InteractionType::Click)).unwrap();
+    let click = &update.responses[0];
+    assert_eq!((click.response_type, click.channel, click.severity), (ResponseType::Click, ResponseChannel::Pointer, ResponseSeverity::Important));
+    assert_eq!(types(&engine.process_interaction(event(InteractionType::Hover)).unwrap()), vec![ResponseType::EventProcessed]);
+    assert!(engine.render_frame(0.0).unwrap().responses.is_empty());
+
+    engine.set_response_filter(ResponseFilter { min_severity: ResponseSeverity::Important, ..ResponseFilter::default() });
+    assert_eq!(types(&engine.process_interaction(event(InteractionType::Click)).unwrap()), vec![ResponseType::Click]);
+    assert!(engine.process_interaction(event(InteractionType::Hover)).unwrap().responses.is_empty());
+
+    let filter: ResponseFilter = serde_json::from_str(r#"{"types": ["DoubleClick"], "channels": ["Pointer"]}"#).unwrap();
+    engine.set_response_filter(filter);
+    let update = engine.process_interaction(event(InteractionType::Click)).unwrap();
+    assert!(update.responses.is_empty());
+    assert!(!serde_json::to_string(&update).unwrap().contains("responses"));
+}
+
+#[wasm_bindgen_test]
+fn test_pinch_rotate_tracking() {
+    let mut recognizer = GestureRecognizer::new();
+    let two_fingers = |a: (f64, f64), b: (f64, f64)| {
+        let point = |identifier: u32, (x, y): (f64, f64)| TouchPoint {
+            identifier,
+            position: Position { x, y },
+            radius: None,
+            rotation_angle: None,
+            force: None,
+            confidence: None,
+        };
+        TouchData {
+            touches: vec![point(1, a), point(2, b)],
+            changed_touches: vec![],
+            target_touches: vec![],
+            force: None,
+            rotation_angle: None,
+            scale: None,
+        }
+    };
+
+    // Fingers 100px apart spreading to 120px in steps of 4px
+    assert!(recognizer.process_touch_input(&two_fingers((100.0, 100.0), (200.0, 100.0)), 0.0).is_empty());
+    let mut updates = Vec::new();
+    for step in 1..=5 {
+        let offset = step as f64 * 2.0;
+        updates.extend(recognizer.process_touch_input(&two_fingers((100.0 - offset, 100.0), (200.0 + offset, 100.0)), step as f64 * 16.0));
+    }
+
+    // Below the 10% threshold no update fires; afterwards every move is reported
+    assert!(updates.iter().all(|g| g.gesture_type == GestureType::PinchUpdate));
+    assert_eq!(updates.len(), 3);
+    let last = updates.last().unwrap();
+    assert!((last.properties["cumulative_scale"] - 1.2).abs() < 1e-9);
+    assert!((last.properties["scale"] - 120.0 / 116.0).abs() < 1e-9);
+
+    // Rotating a quarter turn across the ±180° boundary accumulates smoothly
+    let mut recognizer = GestureRecognizer::new();
+    recognizer.set_multi_touch_thresholds(0.1, 5.0);
+    let mut rotation = Vec::new();
+    for step in 0..=9 {
+        let angle = (170.0 + step as f64 * 10.0).to_radians();
+        rotation.extend(recognizer.process_touch_input(&two_fingers((0.0, 0.0), (100.0 * angle.cos(), 100.0 * angle.sin())), step as f64 * 16.0));
+    }
+    let last = rotation.iter().rev().find(|g| g.gesture_type == GestureType::RotateUpdate).unwrap();
+    assert!((last.properties["cumulative_rotation"] - 90.0).abs() < 1e-6);
+    assert!(rotation.iter().all(|g| g.gesture_type != GestureType::PinchUpdate));
+
+    // Lifting a finger completes the rotation with its total
+    let single = TouchData {
+        touches: vec![TouchPoint { identifier: 1, position: Position { x: 0.0, y: 0.0 }, radius: None, rotation_angle: None, force: None, confidence: None }],
+        changed_touches: vec![],
+        target_touches: vec![],
+        force: None,
+        rotation_angle: None,
+        scale: None,
+    };
+    let ended = recognizer.process_touch_input(&single, 200.0);
+    assert_eq!(ended.len(), 1);
+    assert_eq!(ended[0].gesture_type, GestureType::Rotate);
+    assert!((ended[0].properties["rotation"] - 90.0).abs() < 1e-6);
+}
+
+#[wasm_bindgen_test]
+fn test_palm_rejection_and_smoothing() {
+    let mut filter = TouchFilter::new(TouchFilterSettings::for_device(&DeviceType::Tablet));
+    let touch = |identifier: u32, x: f64, radius: f64, confidence: Option<f64>| TouchPoint {
+        identifier,
+        position: Position { x, y: 100.0 },
+        radius: Some(radius),
+        rotation_angle: None,
+        force: None,
+        confidence,
+    };
+    let frame = |touches: Vec<TouchPoint>| TouchData {
+        changed_touches: touches.clone(),
+        touches,
+        target_touches: vec![],
+        force: None,
+        rotation_angle: None,
+        scale: None,
+    };
+
+    // A large contact and a low-confidence contact are dropped; the finger survives
+    let mut data = frame(vec![touch(1, 100.0, 8.0, None), touch(2, 400.0, 60.0, None), touch(3, 700.0, 8.0, Some(0.1))]);
+    assert_eq!(filter.filter(&mut data, 0.0), 2);
+    assert_eq!(data.touches.len(), 1);
+    assert_eq!(data.changed_touches.len(), 1);
+
+    // A rejected palm stays rejected even after its radius shrinks
+    let mut data = frame(vec![touch(1, 100.0, 8.0, None), touch(2, 400.0, 8.0, None)]);
+    assert_eq!(filter.filter(&mut data, 16.0), 1);
+
+    // Touches right next to an active pen are ignored
+    filter.note_pen_contact(Position { x: 1000.0, y: 100.0 }, 20.0);
+    let mut data = frame(vec![touch(4, 1050.0, 8.0, None)]);
+    assert_eq!(filter.filter(&mut data, 30.0), 1);
+
+    // Jitter around a resting finger is damped by the one-euro filter
+    let mut filter = TouchFilter::new(TouchFilterSettings::default());
+    let mut max_deviation: f64 = 0.0;
+    for i in 0..30 {
+        let jitter = if i % 2 == 0 { 3.0 } else { -3.0 };
+        let mut data = frame(vec![touch(1, 200.0 + jitter, 8.0, None)]);
+        filter.filter(&mut data, i as f64 * 16.0);
+        if i > 0 {
+            max_deviation = max_deviation.max((data.touches[0].position.x - 200.0).abs());
+        }
+    }
+    assert!(max_deviation < 3.0);
+
+    // Disabled filtering leaves input untouched
+    let mut filter = TouchFilter::new(TouchFilterSettings::for_device(&DeviceType::Desktop));
+    let mut data = frame(vec![touch(2, 400.0, 60.0, None)]);
+    assert_eq!(filter.filter(&mut data, 0.0), 0);
+    assert_eq!(data.touches[0].position.x, 400.0);
+}
+
+#[wasm_bindgen_test]
+fn test_pen_ink_strokes() {
+    let mut ink = InkLayer::new();
+    let pen_settings = InteractionSettings::pen();
+    let pen = |pressure: f64| PenData { pressure, tilt_x: 0.0, tilt_y: 0.0, twist: 0.0, barrel_button: false, eraser: false };
+    let at = |x: f64| Position { x, y: 50.0 };
+
+    // Pressure maps monotonically onto stroke width
+    let light = ink.settings.stroke_width(&pen(0.1));
+    let firm = ink.settings.stroke_width(&pen(1.0));
+    assert!(light < firm);
+    assert!((firm - ink.settings.base_width).abs() < 1e-9);
+
+    // Hovering shows a preview circle inside the ink layer
+    ink.handle_pen_event(&InteractionType::PenHover, &pen(0.0), &at(10.0), 0.0, &pen_settings);
+    let operations = ink.take_dom_operations();
+    assert!(operations.iter().any(|op| matches!(op, DOMOperation::Create { element_id, .. } if element_id == InkLayer::LAYER_ID)));
+    assert!(operations.iter().any(|op| matches!(op, DOMOperation::Create { element_id, .. } if element_id == InkLayer::HOVER_ID)));
+
+    // Drawing produces a path whose outline follows the pressure
+    ink.handle_pen_event(&InteractionType::PenDown, &pen(0.2), &at(10.0), 10.0, &pen_settings);
+    for step in 1..=5 {
+        ink.handle_pen_event(&InteractionType::PenMove, &pen(0.2 + step as f64 * 0.15), &at(10.0 + step as f64 * 10.0), 10.0 + step as f64 * 16.0, &pen_settings);
+    }
+    assert!(ink.handle_pen_event(&InteractionType::PenUp, &pen(0.95), &at(60.0), 100.0, &pen_settings).is_none());
+    let operations = ink.take_dom_operations();
+    assert!(operations.iter().any(|op| matches!(op, DOMOperation::Remove { element_id } if element_id == InkLayer::HOVER_ID)));
+    assert_eq!(ink.strokes().len(), 1);
+    let stroke = &ink.strokes()[0];
+    assert_eq!(stroke.points.len(), 6);
+    assert!(stroke.points.first().unwrap().width < stroke.points.last().unwrap().width);
+    assert!(stroke.to_svg_path().starts_with("M "));
+
+    // A quick, still contact is a tap rather than ink
+    ink.handle_pen_event(&InteractionType::PenDown, &pen(0.5), &at(200.0), 200.0, &pen_settings);
+    let tap = ink.handle_pen_event(&InteractionType::PenUp, &pen(0.5), &at(201.0), 250.0, &pen_settings);
+    assert_eq!(tap.map(|gesture| gesture.gesture_type), Some(GestureType::Tap));
+    assert_eq!(ink.strokes().len(), 1);
+
+    // The eraser end removes strokes it touches
+    let eraser = PenData { eraser: true, ..pen(0.5) };
+    ink.take_dom_operations();
+    ink.handle_pen_event(&InteractionType::PenDown, &eraser, &at(30.0), 300.0, &pen_settings);
+    assert!(ink.strokes().is_empty());
+    assert!(ink.take_dom_operations().iter().any(|op| matches!(op, DOMOperation::Remove { element_id } if element_id == "ink_stroke_1")));
+
+    // Pen input uses tighter slop than fingers
+    let adapter = ResponsiveAdapter::new();
+    assert!(adapter.interaction_settings_for(&PointerKind::Pen).drag_threshold < adapter.interaction_settings_for(&PointerKind::Finger).drag_threshold);
+}
+
+#[wasm_bindgen_test]
+fn test_spatial_focus_navigation() {
+    let tile = |x: f64, y: f64| BoundingBox { x, y, width: 100.0, height: 60.0 };
+    // Two rows of tiles; the second row is offset to the right
+    let candidates = vec![
+        ("a".to_string(), tile(0.0, 0.0)),
+        ("b".to_string(), tile(120.0, 0.0)),
+        ("c".to_string(), tile(240.0, 0.0)),
+        ("d".to_string(), tile(150.0, 100.0)),
+        ("e".to_string(), tile(400.0, 100.0)),
+    ];
+    let others = |id: &str| -> Vec<(String, BoundingBox)> {
+        candidates.iter().filter(|(other, _)| other != id).cloned().collect()
+    };
+
+    assert_eq!(SpatialNavigator::first(&candidates), Some("a".to_string()));
+    assert_eq!(SpatialNavigator::find_next(&tile(0.0, 0.0), &FocusDirection::Right, &others("a")), Some("b".to_string()));
+    // Down from "b" prefers the aligned tile over a farther one
+    assert_eq!(SpatialNavigator::find_next(&tile(120.0, 0.0), &FocusDirection::Down, &others("b")), Some("d".to_string()));
+    assert_eq!(SpatialNavigator::find_next(&tile(150.0, 100.0), &FocusDirection::Up, &others("d")), Some("b".to_string()));
+    assert_eq!(SpatialNavigator::find_next(&tile(0.0, 0.0), &FocusDirection::Left, &others("a")), None);
+
+    // Standard gamepad and remote bindings resolve to the same actions
+    let mapping = InputMapping::default();
+    assert_eq!(mapping.resolve(&NavigationInput::Gamepad { button: 13 }), Some(NavigationAction::MoveFocus(FocusDirection::Down)));
+    assert_eq!(mapping.resolve(&NavigationInput::Key { key: "ArrowDown".to_string() }), Some(NavigationAction::MoveFocus(FocusDirection::Down)));
+    assert_eq!(mapping.resolve(&NavigationInput::Gamepad { button: 0 }), Some(NavigationAction::Activate));
+    assert_eq!(mapping.resolve(&NavigationInput::Key { key: "GoBack".to_string() }), Some(NavigationAction::Back));
+    assert_eq!(mapping.resolve(&NavigationInput::Gamepad { button: 99 }), None);
+
+    // Mappings round-trip through JSON so hosts can rebind buttons
+    let json = serde_json::to_string(&mapping).unwrap();
+    let restored: InputMapping = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.resolve(&NavigationInput::Gamepad { button: 12 }), Some(NavigationAction::MoveFocus(FocusDirection::Up)));
+}
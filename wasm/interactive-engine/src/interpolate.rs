@@ -0,0 +1,277 @@
+// How keyframe values blend. A property uses the interpolator registered under its name, or
+// under the last segment of a dotted name ("fill" covers "style.fill"). Otherwise a few names
+// have built-in kinds (colors, "transform", path data "d"), and anything else is blended by
+// what its values look like: numbers linearly, colors channel by channel, and strings with the
+// same shape ("10px", "translate(4px, 8px)") number by number. Arrays and objects blend member
+// by member. Values that cannot be blended hold the earlier value until halfway, then switch.
+
+use super::{finite_or, normalize_angle_delta, Rgb};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Blends one property. Plugins implement it for properties the built-in kinds do not cover.
+pub trait PropertyInterpolator: Send + Sync {
+    // The value `progress` (0..=1) of the way from `from` to `to`; None when the two cannot be
+    // blended, in which case the property switches at the midpoint
+    fn interpolate(&self, from: &Value, to: &Value, progress: f64) -> Option<Value>;
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub enum InterpolatorKind {
+    // Numbers, and strings with the same shape, number by number
+    Number,
+    // Degrees, turning the short way round: 350 to 10 passes through 0. Results are in 0..360.
+    Angle,
+    // #hex, rgb() and rgba() colors, blended per channel and written as #rrggbb
+    Color,
+    // CSS transform lists; a function missing on one side starts from its identity
+    Transform,
+    // SVG path data with the same commands; separators may differ
+    Path,
+    // No blending: the earlier value until halfway
+    Discrete,
+}
+
+impl PropertyInterpolator for InterpolatorKind {
+    fn interpolate(&self, from: &Value, to: &Value, progress: f64) -> Option<Value> {
+        match self {
+            InterpolatorKind::Number => blend_numbers(from, to, progress),
+            InterpolatorKind::Angle => {
+                let (from, to) = (from.as_f64()?, to.as_f64()?);
+                Some(number((from + normalize_angle_delta(to - from) * progress).rem_euclid(360.0), from))
+            }
+            InterpolatorKind::Color => {
+                let (from, to) = (Rgb::parse(from.as_str()?)?, Rgb::parse(to.as_str()?)?);
+                Some(Value::String(from.lerp(&to, progress).to_hex()))
+            }
+            InterpolatorKind::Transform => blend_transforms(from.as_str()?, to.as_str()?, progress).map(Value::String),
+            InterpolatorKind::Path => {
+                let (from, to) = (Template::parse(from.as_str()?), Template::parse(to.as_str()?));
+                let same_commands = from.texts.iter().map(|text| path_commands(text)).eq(to.texts.iter().map(|text| path_commands(text)));
+                (same_commands && from.values.len() == to.values.len()).then(|| Value::String(from.blend(&to, progress)))
+            }
+            InterpolatorKind::Discrete => None,
+        }
+    }
+}
+
+// Kinds picked by property name where the values alone would be blended differently
+fn built_in_kind(name: &str) -> Option<InterpolatorKind> {
+    match name {
+        "color" | "fill" | "stroke" | "background" | "background-color" | "border-color" | "outline-color" | "stop-color" => Some(InterpolatorKind::Color),
+        "transform" => Some(InterpolatorKind::Transform),
+        "d" | "path" => Some(InterpolatorKind::Path),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Interpolators(HashMap<String, Arc<dyn PropertyInterpolator>>);
+
+impl std::fmt::Debug for Interpolators {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names: Vec<&String> = self.0.keys().collect();
+        names.sort();
+        f.debug_tuple("Interpolators").field(&names).finish()
+    }
+}
+
+impl Interpolators {
+    // Replaces any interpolator registered under the same name
+    pub fn register(&mut self, property: &str, interpolator: Arc<dyn PropertyInterpolator>) {
+        self.0.insert(property.to_string(), interpolator);
+    }
+
+    pub fn unregister(&mut self, property: &str) -> bool {
+        self.0.remove(property).is_some()
+    }
+
+    pub fn interpolate(&self, property: &str, from: &Value, to: &Value, progress: f64) -> Value {
+        let name = property.rsplit('.').next().unwrap_or(property);
+        let registered = self.0.get(property).or_else(|| self.0.get(name));
+        let blended = match (registered, built_in_kind(name)) {
+            (Some(interpolator), _) => interpolator.interpolate(from, to, progress),
+            (None, Some(kind)) => kind.interpolate(from, to, progress),
+            (None, None) => blend_by_value(from, to, progress),
+        };
+        blended.unwrap_or_else(|| if progress > 0.5 { to.clone() } else { from.clone() })
+    }
+}
+
+fn blend_by_value(from: &Value, to: &Value, progress: f64) -> Option<Value> {
+    match (from, to) {
+        (Value::Number(_), Value::Number(_)) => blend_numbers(from, to, progress),
+        (Value::String(from_text), Value::String(to_text)) => match (Rgb::parse(from_text), Rgb::parse(to_text)) {
+            (Some(from), Some(to)) => Some(Value::String(from.lerp(&to, progress).to_hex())),
+            _ => blend_numbers(from, to, progress),
+        },
+        (Value::Array(from), Value::Array(to)) if from.len() == to.len() => Some(Value::Array(
+            from.iter().zip(to)
+                .map(|(from, to)| blend_by_value(from, to, progress).unwrap_or_else(|| if progress > 0.5 { to.clone() } else { from.clone() }))
+                .collect(),
+        )),
+        (Value::Object(from), Value::Object(to)) => Some(Value::Object(
+            from.iter()
+                .map(|(key, from)| {
+                    let blended = to.get(key).map(|to| blend_by_value(from, to, progress).unwrap_or_else(|| if progress > 0.5 { to.clone() } else { from.clone() }));
+                    (key.clone(), blended.unwrap_or_else(|| from.clone()))
+                })
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+fn blend_numbers(from: &Value, to: &Value, progress: f64) -> Option<Value> {
+    if let (Some(from), Some(to)) = (from.as_f64(), to.as_f64()) {
+        return Some(number(from + (to - from) * progress, from));
+    }
+    let (from, to) = (Template::parse(from.as_str()?), Template::parse(to.as_str()?));
+    (!from.values.is_empty() && from.texts == to.texts).then(|| Value::String(from.blend(&to, progress)))
+}
+
+fn number(value: f64, fallback: f64) -> Value {
+    let value = finite_or(value, fallback, "animation.interpolation");
+    serde_json::Number::from_f64(value).map_or(Value::from(0), Value::Number)
+}
+
+// Blends function lists like "translate(0px, 0px) scale(1)", filling in identities when one
+// side is "none" or lacks a function the other has at the end
+fn blend_transforms(from: &str, to: &str, progress: f64) -> Option<String> {
+    let (from, to) = (transform_functions(from)?, transform_functions(to)?);
+    let length = from.len().max(to.len());
+    let mut blended = Vec::with_capacity(length);
+    for index in 0..length {
+        let (from, to) = match (from.get(index), to.get(index)) {
+            (Some(from), Some(to)) => (from.clone(), to.clone()),
+            (Some(from), None) => (from.clone(), identity_transform(from)),
+            (None, Some(to)) => (identity_transform(to), to.clone()),
+            (None, None) => break,
+        };
+        let (from, to) = (Template::parse(&from), Template::parse(&to));
+        if from.texts != to.texts {
+            return None;
+        }
+        blended.push(from.blend(&to, progress));
+    }
+    Some(blended.join(" "))
+}
+
+// "name(args)" pieces; an empty list for "none"
+fn transform_functions(transform: &str) -> Option<Vec<String>> {
+    let transform = transform.trim();
+    if transform.is_empty() || transform == "none" {
+        return Some(Vec::new());
+    }
+    let mut functions = Vec::new();
+    let mut rest = transform;
+    while !rest.is_empty() {
+        let end = rest.find(')')? + 1;
+        functions.push(rest[..end].trim().to_string());
+        rest = rest[end..].trim_start();
+    }
+    Some(functions)
+}
+
+// The same function with arguments that leave an element unchanged
+fn identity_transform(function: &str) -> String {
+    let template = Template::parse(function);
+    let values = if function.starts_with("matrix(") && template.values.len() == 6 {
+        vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0]
+    } else {
+        let neutral = if function.starts_with("scale") { 1.0 } else { 0.0 };
+        vec![neutral; template.values.len()]
+    };
+    Template { values, ..template }.to_string()
+}
+
+// Command letters of a piece of path data, without separators
+fn path_commands(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace() && *c != ',').collect()
+}
+
+// A string split around its numbers: texts[i] comes before values[i], and the last text after
+// the last value
+#[derive(Clone, Debug, PartialEq)]
+struct Template {
+    texts: Vec<String>,
+    values: Vec<f64>,
+}
+
+impl Template {
+    fn parse(text: &str) -> Template {
+        let bytes = text.as_bytes();
+        let digit_at = |i: usize| bytes.get(i).is_some_and(|b| b.is_ascii_digit());
+        let mut texts = Vec::new();
+        let mut values = Vec::new();
+        let (mut literal_start, mut i) = (0, 0);
+        while i < bytes.len() {
+            // Hex colors and the 3d of "translate3d(" are text
+            if bytes[i] == b'#' {
+                i += 1;
+                while bytes.get(i).is_some_and(|b| b.is_ascii_alphanumeric()) {
+                    i += 1;
+                }
+                continue;
+            }
+            let sign = matches!(bytes[i], b'-' | b'+') as usize;
+            let starts_number = digit_at(i + sign) || (bytes.get(i + sign) == Some(&b'.') && digit_at(i + sign + 1));
+            if !starts_number || text[i..].starts_with("3d(") {
+                i += 1;
+                continue;
+            }
+            let mut end = i + sign;
+            while digit_at(end) {
+                end += 1;
+            }
+            if bytes.get(end) == Some(&b'.') && digit_at(end + 1) {
+                end += 1;
+                while digit_at(end) {
+                    end += 1;
+                }
+            }
+            if matches!(bytes.get(end), Some(b'e' | b'E')) {
+                let exponent_sign = matches!(bytes.get(end + 1), Some(b'-' | b'+')) as usize;
+                if digit_at(end + 1 + exponent_sign) {
+                    end += 1 + exponent_sign;
+                    while digit_at(end) {
+                        end += 1;
+                    }
+                }
+            }
+            if let Ok(value) = text[i..end].parse::<f64>() {
+                texts.push(text[literal_start..i].to_string());
+                values.push(value);
+                literal_start = end;
+            }
+            i = end;
+        }
+        texts.push(text[literal_start..].to_string());
+        Template { texts, values }
+    }
+
+    // `self` with each value moved towards the matching one in `to`
+    fn blend(&self, to: &Template, progress: f64) -> String {
+        let values = self.values.iter().zip(&to.values)
+            .map(|(from, to)| finite_or(from + (to - from) * progress, *from, "animation.interpolation"))
+            .collect();
+        Template { texts: self.texts.clone(), values }.to_string()
+    }
+}
+
+impl std::fmt::Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, text) in self.texts.iter().enumerate() {
+            f.write_str(text)?;
+            if let Some(value) = self.values.get(index) {
+                // Whole numbers stay whole; the rest keep three decimals, without trailing zeros
+                let rounded = (value * 1000.0).round() / 1000.0;
+                write!(f, "{}", if rounded == 0.0 { 0.0 } else { rounded })?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,454 @@
+// Where each element's box goes. Containers arrange their children in one of four modes, picked
+// by the "layout" property:
+//
+//   "absolute" (the default)  each child at its transform's x and y inside the parent
+//   "flex-row"                children side by side, left to right
+//   "flex-column"             children stacked top to bottom
+//   "grid"                    children fill "grid_columns" equal columns, row by row
+//
+// "padding" insets the children from the container's edges and "gap" separates them. In the flex
+// modes, "justify_content" (start, center, end, space-between) places children along the line
+// when there is room left, children with a "flex_grow" share that room instead, and
+// "align_items" (start, center, end, stretch) places them across it. A stretched child without
+// its own size across the line fills the line. A grid child without a width fills its column,
+// and without a height, its row.
+//
+// An element's size is its "width" and "height", in pixels or as a percentage of the parent's
+// room ("50%"). A flex or grid container without one fits its children, and a Text element its
+// text, wrapped at whatever width it has (see text.rs); anything else falls back to 100 like
+// ComputedStyle. Frames of linked text stories are the exception: their text flows into the box
+// they are given, so they never size to it. Top-level elements are placed in the viewport, so a resize moves
+// those sized in percent. Children with "style.display" none take no room. Boxes are in document
+// coordinates and ignore transform scale and rotation, like the CSS the host applies on top.
+
+use super::text::{self, FontTable};
+use super::{BoundingBox, DocumentState, HostShaper, InteractiveElement, RenderNode, Size, Transform};
+use std::collections::{HashMap, HashSet};
+
+const DEFAULT_SIZE: f64 = 100.0;
+const DEFAULT_GRID_COLUMNS: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    Absolute,
+    FlexRow,
+    FlexColumn,
+    Grid,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Align {
+    Start,
+    Center,
+    End,
+    // Justify only: the room left goes between children
+    SpaceBetween,
+    // Align only: children without a cross size fill the line
+    Stretch,
+}
+
+impl Align {
+    fn parse(value: Option<&str>, default: Align) -> Align {
+        match value {
+            Some("start") => Align::Start,
+            Some("center") => Align::Center,
+            Some("end") => Align::End,
+            Some("space-between") => Align::SpaceBetween,
+            Some("stretch") => Align::Stretch,
+            _ => default,
+        }
+    }
+
+    // Offset of something `size` long in `room`
+    fn offset(self, room: f64, size: f64) -> f64 {
+        match self {
+            Align::Center => (room - size) / 2.0,
+            Align::End => room - size,
+            _ => 0.0,
+        }
+    }
+}
+
+fn mode(element: &InteractiveElement) -> Mode {
+    match element.properties.get("layout").and_then(|v| v.as_str()) {
+        Some("flex-row") => Mode::FlexRow,
+        Some("flex-column") => Mode::FlexColumn,
+        Some("grid") => Mode::Grid,
+        _ => Mode::Absolute,
+    }
+}
+
+fn number(element: &InteractiveElement, name: &str) -> Option<f64> {
+    element.properties.get(name).and_then(|v| v.as_f64()).filter(|value| value.is_finite() && *value >= 0.0)
+}
+
+// "50%" of `room`
+fn percent(element: &InteractiveElement, name: &str, room: f64) -> Option<f64> {
+    let value = element.properties.get(name)?.as_str()?.trim().strip_suffix('%')?.trim().parse::<f64>().ok()?;
+    (value.is_finite() && value >= 0.0).then(|| room * value / 100.0)
+}
+
+fn sized(element: &InteractiveElement, name: &str) -> bool {
+    number(element, name).is_some() || percent(element, name, 0.0).is_some()
+}
+
+fn in_flow(element: &InteractiveElement) -> bool {
+    element.properties.get("style.display").and_then(|v| v.as_str()) != Some("none")
+}
+
+// Whether the element's position comes from its parent's flex or grid layout rather than its own
+// transform
+pub fn placed_by_parent(document: &DocumentState, element_id: &str) -> bool {
+    document.render_tree.nodes.get(element_id)
+        .and_then(|node| node.parent.as_deref())
+        .and_then(|parent| document.get_element(parent))
+        .is_some_and(|parent| mode(parent) != Mode::Absolute)
+}
+
+// Lays out the subtrees under `roots`, writing RenderNode.bounds. Returns the ids whose bounds
+// changed, parents first.
+pub fn layout(document: &mut DocumentState, roots: &[String]) -> Vec<String> {
+    let placed = {
+        let mut pass = Pass {
+            nodes: &document.render_tree.nodes,
+            elements: document.elements.iter().map(|element| (element.id.as_str(), element)).collect(),
+            fonts: &document.font_tables,
+            story_frames: document.text_stories.iter().flat_map(|story| story.frames.iter().map(String::as_str)).collect(),
+            measured: HashMap::new(),
+            placed: Vec::new(),
+            visited: HashSet::new(),
+        };
+        let viewport = BoundingBox { x: 0.0, y: 0.0, width: document.viewport.width, height: document.viewport.height };
+        for root in pass.starts(roots) {
+            let Some(element) = pass.elements.get(root.as_str()).copied() else { continue };
+            let parent = pass.nodes.get(&root)
+                .and_then(|node| node.parent.as_ref())
+                .and_then(|parent| Some((pass.elements.get(parent.as_str()).copied()?, &pass.nodes.get(parent)?.bounds)));
+            let bounds = match parent {
+                // A fixed-size child of a flex or grid parent keeps the place it was given
+                Some((parent, _)) if mode(parent) != Mode::Absolute => match pass.nodes.get(&root) {
+                    Some(node) => node.bounds.clone(),
+                    None => continue,
+                },
+                // Otherwise it depends only on its parent's box, or the viewport at the top level
+                parent => {
+                    let container = parent.map_or_else(|| viewport.clone(), |(parent, bounds)| content_box(parent, bounds));
+                    let (width, height) = pass.size_in(&root, element, (container.width, container.height));
+                    BoundingBox { x: container.x + element.transform.x, y: container.y + element.transform.y, width, height }
+                }
+            };
+            pass.place(&root, bounds);
+        }
+        pass.placed
+    };
+    let mut moved = Vec::new();
+    let render_tree = &mut document.render_tree;
+    let transforms: HashMap<&str, &Transform> = document.elements.iter().map(|element| (element.id.as_str(), &element.transform)).collect();
+    for (element_id, bounds) in placed {
+        let Some(node) = render_tree.nodes.get_mut(&element_id) else { continue };
+        // Hit testing reads the computed size
+        node.computed_style.size = Size { width: bounds.width, height: bounds.height };
+        if node.bounds != bounds {
+            // Both where the element was and where it is now need repainting
+            if let Some(transform) = transforms.get(element_id.as_str()) {
+                render_tree.damage.add_painted(&node.bounds, transform);
+                render_tree.damage.add_painted(&bounds, transform);
+            }
+            node.bounds = bounds;
+            moved.push(element_id);
+        }
+    }
+    moved
+}
+
+// The box children are placed in: the element's bounds inside its padding
+fn content_box(element: &InteractiveElement, bounds: &BoundingBox) -> BoundingBox {
+    let padding = number(element, "padding").unwrap_or(0.0);
+    BoundingBox {
+        x: bounds.x + padding,
+        y: bounds.y + padding,
+        width: (bounds.width - 2.0 * padding).max(0.0),
+        height: (bounds.height - 2.0 * padding).max(0.0),
+    }
+}
+
+struct Pass<'a> {
+    nodes: &'a HashMap<String, RenderNode>,
+    elements: HashMap<&'a str, &'a InteractiveElement>,
+    fonts: &'a [FontTable],
+    story_frames: HashSet<&'a str>,
+    measured: HashMap<String, (f64, f64)>,
+    placed: Vec<(String, BoundingBox)>,
+    visited: HashSet<String>,
+}
+
+impl<'a> Pass<'a> {
+    // Where to start for each dirty root. Siblings in a flex or grid parent move each other, and a
+    // parent that fits its children grows with them, so the pass climbs to the first ancestor
+    // whose children can be placed without looking further up.
+    fn starts(&self, roots: &[String]) -> Vec<String> {
+        let mut starts: Vec<String> = Vec::new();
+        for root in roots {
+            let mut current = root.clone();
+            let mut seen = HashSet::new();
+            while let Some(parent) = self.nodes.get(&current).and_then(|node| node.parent.clone()) {
+                let Some(element) = self.elements.get(parent.as_str()).copied() else { break };
+                if mode(element) == Mode::Absolute || !seen.insert(parent.clone()) {
+                    break;
+                }
+                current = parent;
+                if sized(element, "width") && sized(element, "height") {
+                    break;
+                }
+            }
+            if !starts.contains(&current) {
+                starts.push(current);
+            }
+        }
+        // A start inside another start's subtree is laid out with it
+        let covered: HashSet<String> = starts.iter()
+            .flat_map(|start| self.descendants(start))
+            .collect();
+        starts.retain(|start| !covered.contains(start));
+        starts
+    }
+
+    fn descendants(&self, root: &str) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut seen = HashSet::new();
+        let mut stack: Vec<&String> = self.nodes.get(root).map(|node| node.children.iter().collect()).unwrap_or_default();
+        while let Some(element_id) = stack.pop() {
+            if !seen.insert(element_id.as_str()) {
+                continue;
+            }
+            if let Some(node) = self.nodes.get(element_id) {
+                stack.extend(node.children.iter());
+            }
+            found.push(element_id.clone());
+        }
+        found
+    }
+
+    fn children(&self, element_id: &str) -> Vec<(&'a str, &'a InteractiveElement)> {
+        self.nodes.get(element_id).into_iter()
+            .flat_map(|node| node.children.iter())
+            .filter_map(|child| self.elements.get_key_value(child.as_str()).map(|(id, element)| (*id, *element)))
+            .collect()
+    }
+
+    // The element's size in a parent with `room` to give
+    fn size_in(&mut self, element_id: &str, element: &InteractiveElement, room: (f64, f64)) -> (f64, f64) {
+        let (width, height) = self.measure(element_id);
+        match percent(element, "width", room.0) {
+            Some(width) => (width, percent(element, "height", room.1).or_else(|| self.height_at(element, width)).unwrap_or(height)),
+            None => (width, percent(element, "height", room.1).unwrap_or(height)),
+        }
+    }
+
+    // The size of a Text element's text wrapped at `width`, or unwrapped when None; None for
+    // other elements and story frames
+    fn text_size(&self, element: &InteractiveElement, width: Option<f64>) -> Option<(f64, f64)> {
+        if self.story_frames.contains(element.id.as_str()) {
+            return None;
+        }
+        let story = text::element_story(element, self.fonts)?;
+        let (_, width, height) = text::measure(&story, width, self.fonts, &HostShaper);
+        Some((width, height))
+    }
+
+    // The height of a Text element without one once it is given `width`
+    fn height_at(&self, element: &InteractiveElement, width: f64) -> Option<f64> {
+        match sized(element, "height") {
+            true => None,
+            false => self.text_size(element, Some(width)).map(|size| size.1),
+        }
+    }
+
+    // The element's own size: its width and height, or for a flex or grid container, what its
+    // children need, and for a Text element, its text. Percentages count as unset here, since the
+    // parent may be fitting its children.
+    fn measure(&mut self, element_id: &str) -> (f64, f64) {
+        if let Some(size) = self.measured.get(element_id) {
+            return *size;
+        }
+        let Some(element) = self.elements.get(element_id).copied() else { return (0.0, 0.0) };
+        // Guards against a cycle in the tree while measuring it
+        self.measured.insert(element_id.to_string(), (DEFAULT_SIZE, DEFAULT_SIZE));
+        let (width, height) = (number(element, "width"), number(element, "height"));
+        let mode = mode(element);
+        let fitted = if mode != Mode::Absolute && (width.is_none() || height.is_none()) {
+            let padding = number(element, "padding").unwrap_or(0.0);
+            let (content_width, content_height) = self.fit(element_id, element, mode);
+            Some((content_width + 2.0 * padding, content_height + 2.0 * padding))
+        } else {
+            None
+        };
+        let text = if width.is_none() || height.is_none() { self.text_size(element, width) } else { None };
+        let size = (
+            width.or(fitted.map(|size| size.0)).or(text.map(|size| size.0)).unwrap_or(DEFAULT_SIZE),
+            height.or(fitted.map(|size| size.1)).or(text.map(|size| size.1)).unwrap_or(DEFAULT_SIZE),
+        );
+        self.measured.insert(element_id.to_string(), size);
+        size
+    }
+
+    // The room a flex or grid container's children take up, without padding
+    fn fit(&mut self, element_id: &str, element: &InteractiveElement, mode: Mode) -> (f64, f64) {
+        let gap = number(element, "gap").unwrap_or(0.0);
+        let padding = number(element, "padding").unwrap_or(0.0);
+        let room = number(element, "width").map(|width| (width - 2.0 * padding).max(0.0));
+        let sizes: Vec<(f64, f64)> = self.children(element_id).into_iter()
+            .filter(|(_, child)| in_flow(child))
+            .map(|(id, child)| {
+                let size = self.measure(id);
+                // Text wraps at the width place() will give it, when the container's is known
+                let given = room.and_then(|room| given_width(element, mode, child, room));
+                match given.and_then(|width| self.height_at(child, width)) {
+                    Some(height) => (size.0, height),
+                    None => size,
+                }
+            })
+            .collect();
+        if sizes.is_empty() {
+            return (0.0, 0.0);
+        }
+        let gaps = gap * (sizes.len() - 1) as f64;
+        match mode {
+            Mode::FlexRow => (sizes.iter().map(|size| size.0).sum::<f64>() + gaps, sizes.iter().map(|size| size.1).fold(0.0, f64::max)),
+            Mode::FlexColumn => (sizes.iter().map(|size| size.0).fold(0.0, f64::max), sizes.iter().map(|size| size.1).sum::<f64>() + gaps),
+            Mode::Grid => {
+                let columns = grid_columns(element).min(sizes.len());
+                let cell = sizes.iter().map(|size| size.0).fold(0.0, f64::max);
+                let rows: Vec<f64> = sizes.chunks(columns).map(|row| row.iter().map(|size| size.1).fold(0.0, f64::max)).collect();
+                (cell * columns as f64 + gap * (columns - 1) as f64, rows.iter().sum::<f64>() + gap * (rows.len() - 1) as f64)
+            }
+            Mode::Absolute => (0.0, 0.0),
+        }
+    }
+
+    fn place(&mut self, element_id: &str, bounds: BoundingBox) {
+        if !self.visited.insert(element_id.to_string()) {
+            return;
+        }
+        self.placed.push((element_id.to_string(), bounds.clone()));
+        let Some(element) = self.elements.get(element_id).copied() else { return };
+        let children = self.children(element_id);
+        if children.is_empty() {
+            return;
+        }
+        let content = content_box(element, &bounds);
+        let (flowing, hidden): (Vec<_>, Vec<_>) = children.into_iter().partition(|(_, child)| in_flow(child));
+        for (child_id, _) in hidden {
+            self.place(child_id, BoundingBox { x: content.x, y: content.y, width: 0.0, height: 0.0 });
+        }
+        match mode(element) {
+            Mode::Absolute => {
+                for (child_id, child) in flowing {
+                    let (width, height) = self.size_in(child_id, child, (content.width, content.height));
+                    let child_bounds = BoundingBox { x: content.x + child.transform.x, y: content.y + child.transform.y, width, height };
+                    self.place(child_id, child_bounds);
+                }
+            }
+            Mode::FlexRow => self.flex(element, &content, &flowing, true),
+            Mode::FlexColumn => self.flex(element, &content, &flowing, false),
+            Mode::Grid => self.grid(element, &content, &flowing),
+        }
+    }
+
+    fn flex(&mut self, element: &InteractiveElement, content: &BoundingBox, children: &[(&'a str, &'a InteractiveElement)], row: bool) {
+        let gap = number(element, "gap").unwrap_or(0.0);
+        let justify = Align::parse(element.properties.get("justify_content").and_then(|v| v.as_str()), Align::Start);
+        let align = Align::parse(element.properties.get("align_items").and_then(|v| v.as_str()), Align::Stretch);
+        let (main_room, cross_room) = if row { (content.width, content.height) } else { (content.height, content.width) };
+
+        let mut mains = Vec::with_capacity(children.len());
+        let mut crosses = Vec::with_capacity(children.len());
+        for (child_id, child) in children {
+            let (width, height) = self.size_in(child_id, child, (content.width, content.height));
+            let (main, cross, cross_set) = if row {
+                (width, height, sized(child, "height"))
+            } else {
+                (height, width, sized(child, "width"))
+            };
+            let stretched = align == Align::Stretch && !cross_set;
+            // Text stretched across a column wraps at the column's width
+            let main = match (row, stretched) {
+                (false, true) => self.height_at(child, cross_room).unwrap_or(main),
+                _ => main,
+            };
+            mains.push(main);
+            crosses.push(if stretched { cross_room } else { cross });
+        }
+
+        let grows: Vec<f64> = children.iter().map(|(_, child)| number(child, "flex_grow").unwrap_or(0.0)).collect();
+        let total_grow: f64 = grows.iter().sum();
+        let mut free = main_room - mains.iter().sum::<f64>() - gap * children.len().saturating_sub(1) as f64;
+        if free > 0.0 && total_grow > 0.0 {
+            for (main, grow) in mains.iter_mut().zip(&grows) {
+                *main += free * grow / total_grow;
+            }
+            free = 0.0;
+        }
+        let free = free.max(0.0);
+        let (mut cursor, spacing) = match justify {
+            Align::SpaceBetween if children.len() > 1 => (0.0, gap + free / (children.len() - 1) as f64),
+            _ => (justify.offset(free, 0.0), gap),
+        };
+
+        for (index, (child_id, _)) in children.iter().enumerate() {
+            let cross_offset = align.offset(cross_room, crosses[index]);
+            let child_bounds = if row {
+                BoundingBox { x: content.x + cursor, y: content.y + cross_offset, width: mains[index], height: crosses[index] }
+            } else {
+                BoundingBox { x: content.x + cross_offset, y: content.y + cursor, width: crosses[index], height: mains[index] }
+            };
+            self.place(child_id, child_bounds);
+            cursor += mains[index] + spacing;
+        }
+    }
+
+    fn grid(&mut self, element: &InteractiveElement, content: &BoundingBox, children: &[(&'a str, &'a InteractiveElement)]) {
+        let gap = number(element, "gap").unwrap_or(0.0);
+        let columns = grid_columns(element);
+        let cell_width = ((content.width - gap * (columns - 1) as f64) / columns as f64).max(0.0);
+        let mut y = content.y;
+        for row in children.chunks(columns) {
+            let sizes: Vec<(f64, f64)> = row.iter().map(|(child_id, child)| {
+                let width = number(child, "width").or_else(|| percent(child, "width", cell_width)).unwrap_or(cell_width);
+                let height = self.height_at(child, width).unwrap_or_else(|| self.measure(child_id).1);
+                (width, height)
+            }).collect();
+            let row_height = sizes.iter().map(|size| size.1).fold(0.0, f64::max);
+            for (column, ((child_id, child), (width, height))) in row.iter().zip(&sizes).enumerate() {
+                let (width, height) = (*width, if number(child, "height").is_some() { *height } else { row_height });
+                let x = content.x + column as f64 * (cell_width + gap);
+                self.place(child_id, BoundingBox { x, y, width, height });
+            }
+            y += row_height + gap;
+        }
+    }
+}
+
+// The width a child without a fixed one gets in a container with `room` inside its padding
+fn given_width(element: &InteractiveElement, mode: Mode, child: &InteractiveElement, room: f64) -> Option<f64> {
+    match mode {
+        Mode::FlexColumn => {
+            let stretched = Align::parse(element.properties.get("align_items").and_then(|v| v.as_str()), Align::Stretch) == Align::Stretch;
+            percent(child, "width", room).or((stretched && !sized(child, "width")).then_some(room))
+        }
+        Mode::Grid => {
+            let columns = grid_columns(element);
+            let gap = number(element, "gap").unwrap_or(0.0);
+            let cell_width = ((room - gap * (columns - 1) as f64) / columns as f64).max(0.0);
+            match number(child, "width") {
+                Some(_) => None,
+                None => Some(percent(child, "width", cell_width).unwrap_or(cell_width)),
+            }
+        }
+        Mode::FlexRow | Mode::Absolute => percent(child, "width", room),
+    }
+}
+
+fn grid_columns(element: &InteractiveElement) -> usize {
+    element.properties.get("grid_columns").and_then(|v| v.as_u64()).filter(|columns| *columns > 0).map_or(DEFAULT_GRID_COLUMNS, |columns| columns as usize)
+}
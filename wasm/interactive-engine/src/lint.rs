@@ -0,0 +1,337 @@
+// Document lint rules shared by the runtime and the editor. editor-engine compiles this file in
+// as its own `lint` module, so it uses nothing beyond serde and std: each engine describes its
+// document as a LintDocument and gets the same findings back. The editor reports them in its
+// ValidationReport; the runtime lints every document it loads and only warns.
+//
+// A profile picks the categories to run and can change any rule's severity; RULES lists every
+// rule with its default, for hosts building settings screens. A finding may carry a fix: one
+// property to set, or to remove when the value is None.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintCategory {
+    Performance,
+    Accessibility,
+    Security,
+    Style,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintSeverity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LintRule {
+    pub id: String,
+    pub category: LintCategory,
+    pub default_severity: LintSeverity,
+    pub description: String,
+    // Whether findings come with a fix
+    pub fixable: bool,
+}
+
+// Id, category, default severity, description, fixable
+const RULES: [(&str, LintCategory, LintSeverity, &str, bool); 12] = [
+    ("a11y/image-alt", LintCategory::Accessibility, LintSeverity::Warning, "Images and vector figures need alt text unless they are marked decorative", true),
+    ("a11y/alt-length", LintCategory::Accessibility, LintSeverity::Info, "Alt text over 250 characters belongs in a caption", false),
+    ("a11y/interactive-name", LintCategory::Accessibility, LintSeverity::Warning, "Interactive elements need an accessible name", false),
+    ("a11y/chart-description", LintCategory::Accessibility, LintSeverity::Info, "Charts without alt text fall back to a generated summary", false),
+    ("perf/element-count", LintCategory::Performance, LintSeverity::Warning, "Documents over 1000 elements are slow to lay out and render", false),
+    ("perf/nesting-depth", LintCategory::Performance, LintSeverity::Warning, "Elements nested more than 24 levels deep are slow to lay out", false),
+    ("perf/inline-data-size", LintCategory::Performance, LintSeverity::Warning, "Data sources over 1 MiB inline in the document slow down loading", false),
+    ("security/script-url", LintCategory::Security, LintSeverity::Error, "javascript: URLs are never followed", true),
+    ("security/inline-handler", LintCategory::Security, LintSeverity::Error, "Inline DOM event handlers such as onclick are never run", true),
+    ("security/insecure-url", LintCategory::Security, LintSeverity::Warning, "Resources loaded over plain http", true),
+    ("style/hardcoded-color", LintCategory::Style, LintSeverity::Info, "Literal colors in a document that defines style tokens", true),
+    ("style/duplicate-alias", LintCategory::Style, LintSeverity::Warning, "The same property set under both its snake_case and camelCase names", true),
+];
+
+const MAX_ELEMENTS: usize = 1000;
+const MAX_DEPTH: usize = 24;
+const MAX_INLINE_DATA: usize = 1024 * 1024;
+const MAX_ALT_LENGTH: usize = 250;
+const COLOR_PROPERTIES: [&str; 6] = ["color", "style.color", "style.background-color", "style.border-color", "fill", "stroke"];
+const URL_PROPERTIES: [&str; 4] = ["src", "href", "url", "poster"];
+const DOM_EVENTS: [&str; 16] = [
+    "click", "dblclick", "load", "error", "focus", "blur", "change", "input", "submit",
+    "keydown", "keyup", "mousedown", "mouseup", "mouseover", "pointerdown", "touchstart",
+];
+
+pub fn rules() -> Vec<LintRule> {
+    RULES.iter()
+        .map(|&(id, category, default_severity, description, fixable)| LintRule {
+            id: id.to_string(),
+            category,
+            default_severity,
+            description: description.to_string(),
+            fixable,
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LintProfile {
+    pub name: String,
+    // Categories that run; empty runs all of them
+    #[serde(default)]
+    pub categories: Vec<LintCategory>,
+    // Severity by rule id, in place of the rule's default; Off disables the rule
+    #[serde(default)]
+    pub severities: BTreeMap<String, LintSeverity>,
+    // Reports warnings as errors
+    #[serde(default)]
+    pub warnings_as_errors: bool,
+}
+
+impl Default for LintProfile {
+    fn default() -> Self {
+        LintProfile::recommended()
+    }
+}
+
+impl LintProfile {
+    pub fn recommended() -> Self {
+        Self { name: "recommended".to_string(), categories: Vec::new(), severities: BTreeMap::new(), warnings_as_errors: false }
+    }
+
+    // Built-in profiles: recommended, strict, and one per category
+    pub fn built_in() -> Vec<LintProfile> {
+        let category = |name: &str, category: LintCategory| LintProfile { name: name.to_string(), categories: vec![category], ..LintProfile::recommended() };
+        vec![
+            LintProfile::recommended(),
+            LintProfile {
+                name: "strict".to_string(),
+                severities: [("style/hardcoded-color".to_string(), LintSeverity::Warning)].into_iter().collect(),
+                warnings_as_errors: true,
+                ..LintProfile::recommended()
+            },
+            category("accessibility", LintCategory::Accessibility),
+            category("performance", LintCategory::Performance),
+            category("security", LintCategory::Security),
+            category("style", LintCategory::Style),
+        ]
+    }
+
+    pub fn named(name: &str) -> Option<LintProfile> {
+        LintProfile::built_in().into_iter().find(|profile| profile.name == name)
+    }
+
+    // Severity the rule reports at under this profile; Off when it does not run
+    pub fn severity(&self, rule: &LintRule) -> LintSeverity {
+        if !self.categories.is_empty() && !self.categories.contains(&rule.category) {
+            return LintSeverity::Off;
+        }
+        match self.severities.get(&rule.id).copied().unwrap_or(rule.default_severity) {
+            LintSeverity::Warning if self.warnings_as_errors => LintSeverity::Error,
+            severity => severity,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let known: HashSet<&str> = RULES.iter().map(|rule| rule.0).collect();
+        match self.severities.keys().find(|id| !known.contains(id.as_str())) {
+            Some(id) => Err(format!("unknown lint rule '{}'", id)),
+            None => Ok(()),
+        }
+    }
+}
+
+// The parts of a document the rules read
+pub struct LintDocument<'a> {
+    pub elements: Vec<LintElement<'a>>,
+    // Serialized size of each data source kept in the document
+    pub data_sizes: Vec<(&'a str, usize)>,
+    // Style tokens by name, "--brand" and the like
+    pub tokens: Vec<(&'a str, &'a str)>,
+}
+
+pub struct LintElement<'a> {
+    pub id: &'a str,
+    // ElementType variant name, e.g. "Image"
+    pub element_type: String,
+    pub properties: &'a HashMap<String, serde_json::Value>,
+    pub children: &'a [String],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LintFix {
+    pub description: String,
+    pub element_id: String,
+    pub property: String,
+    // None removes the property
+    pub value: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LintFinding {
+    pub rule_id: String,
+    pub category: LintCategory,
+    pub severity: LintSeverity,
+    pub element_id: Option<String>,
+    pub message: String,
+    pub fix: Option<LintFix>,
+}
+
+// Findings in rule order, then document order
+pub fn lint(document: &LintDocument, profile: &LintProfile) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    for rule in rules() {
+        let severity = profile.severity(&rule);
+        if severity == LintSeverity::Off {
+            continue;
+        }
+        for (element_id, message, fix) in check(&rule.id, document) {
+            findings.push(LintFinding {
+                rule_id: rule.id.clone(),
+                category: rule.category,
+                severity,
+                element_id,
+                message,
+                fix,
+            });
+        }
+    }
+    findings
+}
+
+type Found = (Option<String>, String, Option<LintFix>);
+
+fn check(rule_id: &str, document: &LintDocument) -> Vec<Found> {
+    let mut found = Vec::new();
+    match rule_id {
+        "perf/element-count" if document.elements.len() > MAX_ELEMENTS => {
+            found.push((None, format!("{} elements, over the {} that render smoothly", document.elements.len(), MAX_ELEMENTS), None));
+        }
+        "perf/nesting-depth" => {
+            if let Some(element_id) = too_deep(document) {
+                found.push((Some(element_id), format!("Nested more than {} levels deep", MAX_DEPTH), None));
+            }
+        }
+        "perf/inline-data-size" => {
+            for (source_id, size) in &document.data_sizes {
+                if *size > MAX_INLINE_DATA {
+                    found.push((None, format!("Data source '{}' keeps {} KiB inline; load it from a URL or stream it", source_id, size / 1024), None));
+                }
+            }
+        }
+        _ => {
+            for element in &document.elements {
+                found.extend(check_element(rule_id, element, document));
+            }
+        }
+    }
+    found
+}
+
+fn check_element(rule_id: &str, element: &LintElement, document: &LintDocument) -> Vec<Found> {
+    let text = |key: &str| element.properties.get(key).and_then(|value| value.as_str()).map(str::trim).filter(|text| !text.is_empty());
+    let fix = |description: &str, property: &str, value: Option<serde_json::Value>| Some(LintFix {
+        description: description.to_string(),
+        element_id: element.id.to_string(),
+        property: property.to_string(),
+        value,
+    });
+    let mut found = Vec::new();
+    let mut report = |message: String, fix: Option<LintFix>| found.push((Some(element.id.to_string()), message, fix));
+    let alt = text("alt_text").or_else(|| text("alt"));
+    let decorative = element.properties.get("decorative").and_then(|value| value.as_bool()).unwrap_or(false);
+    let mut keys: Vec<&String> = element.properties.keys().collect();
+    keys.sort();
+
+    match rule_id {
+        "a11y/image-alt" if matches!(element.element_type.as_str(), "Image" | "Vector") && !decorative && alt.is_none() => {
+            report("Figure has no alt text".to_string(), fix("Mark it decorative, hiding it from assistive technology", "decorative", Some(serde_json::json!(true))));
+        }
+        "a11y/alt-length" if alt.is_some_and(|alt| alt.chars().count() > MAX_ALT_LENGTH) => {
+            report(format!("Alt text is longer than {} characters", MAX_ALT_LENGTH), None);
+        }
+        "a11y/interactive-name" if element.element_type == "Interactive" && ["aria_label", "text", "content"].iter().all(|key| text(key).is_none()) => {
+            report("Interactive element has no aria_label or text".to_string(), None);
+        }
+        "a11y/chart-description" if element.element_type == "Chart" && alt.is_none() && text("aria_label").is_none() => {
+            report("Chart has no alt text; readers get a generated summary".to_string(), None);
+        }
+        "security/script-url" => {
+            for key in &keys {
+                if text(key).is_some_and(|value| value.to_ascii_lowercase().starts_with("javascript:")) {
+                    report(format!("'{}' is a javascript: URL", key), fix("Remove the property", key, None));
+                }
+            }
+        }
+        "security/inline-handler" => {
+            for key in &keys {
+                if key.strip_prefix("on").is_some_and(|event| DOM_EVENTS.contains(&event)) {
+                    report(format!("Inline handler '{}'; use event handlers instead", key), fix("Remove the property", key, None));
+                }
+            }
+        }
+        "security/insecure-url" => {
+            for key in keys.iter().filter(|key| URL_PROPERTIES.contains(&key.as_str())) {
+                if let Some(rest) = text(key).and_then(|url| url.strip_prefix("http://")) {
+                    report(format!("'{}' loads over plain http", key), fix("Load it over https", key, Some(serde_json::json!(format!("https://{}", rest)))));
+                }
+            }
+        }
+        "style/hardcoded-color" if !document.tokens.is_empty() => {
+            for key in keys.iter().filter(|key| COLOR_PROPERTIES.contains(&key.as_str())) {
+                let Some(color) = text(key).filter(|color| !color.starts_with("var(")) else { continue };
+                // Suggest the token when one already holds this color
+                let token = document.tokens.iter().find(|(_, value)| value.trim().eq_ignore_ascii_case(color));
+                let token_fix = token.and_then(|(name, _)| fix(&format!("Use {}", name), key, Some(serde_json::json!(format!("var({})", name)))));
+                report(format!("'{}' is the literal color {}", key, color), token_fix);
+            }
+        }
+        "style/duplicate-alias" => {
+            for key in keys.iter().filter(|key| key.contains('_')) {
+                let camel = camel_case(key);
+                if element.properties.contains_key(&camel) {
+                    report(format!("Both '{}' and '{}' are set", key, camel), fix(&format!("Remove '{}'", camel), &camel, None));
+                }
+            }
+        }
+        _ => {}
+    }
+    found
+}
+
+// First element deeper than MAX_DEPTH below a top-level element
+fn too_deep(document: &LintDocument) -> Option<String> {
+    let by_id: HashMap<&str, &LintElement> = document.elements.iter().map(|element| (element.id, element)).collect();
+    let child_ids: HashSet<&str> = document.elements.iter().flat_map(|element| element.children.iter().map(String::as_str)).collect();
+    let mut visited = HashSet::new();
+    let mut stack: Vec<(&str, usize)> = document.elements.iter()
+        .filter(|element| !child_ids.contains(element.id))
+        .map(|element| (element.id, 0))
+        .collect();
+    while let Some((id, depth)) = stack.pop() {
+        if depth > MAX_DEPTH {
+            return Some(id.to_string());
+        }
+        if !visited.insert(id) {
+            continue;
+        }
+        if let Some(element) = by_id.get(id) {
+            stack.extend(element.children.iter().map(|child| (child.as_str(), depth + 1)));
+        }
+    }
+    None
+}
+
+fn camel_case(name: &str) -> String {
+    let mut parts = name.split('_');
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
@@ -1,5 +1,8 @@
 use super::*;
 use wasm_bindgen_test::*;
+// Native `cargo test` runs the same tests as plain #[test]s
+#[cfg(not(target_family = "wasm"))]
+use std::prelude::v1::test as wasm_bindgen_test;
 
 wasm_bindgen_test_configure!(run_in_browser);
 
@@ -60,7 +63,7 @@ fn test_memory_safety_with_concurrent_operations() {
     }
 
     // Create animations for elements
-    for (i, element_id) in element_ids.iter().enumerate().take(20) {
+    for (_i, element_id) in element_ids.iter().enumerate().take(20) {
         let keyframes = vec![
             Keyframe {
                 time: 0.0,
@@ -98,6 +101,7 @@ fn test_memory_safety_with_concurrent_operations() {
             }),
             keyboard_data: None,
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -143,7 +147,7 @@ fn test_resource_cleanup_on_element_deletion() {
     let mut element_ids = Vec::new();
     let mut animation_ids = Vec::new();
 
-    for i in 0..20 {
+    for _i in 0..20 {
         // Create element with data
         let properties = [
             ("width".to_string(), serde_json::Value::Number(serde_json::Number::from(100))),
@@ -175,8 +179,7 @@ fn test_resource_cleanup_on_element_deletion() {
     let initial_animation_count = engine.document_state.animations.len();
 
     // Delete half of the elements
-    for i in 0..10 {
-        let element_id = &element_ids[i];
+    for element_id in &element_ids[..10] {
         let result = engine.delete_element(element_id);
         assert!(result.is_ok(), "Failed to delete element {}", element_id);
     }
@@ -192,8 +195,7 @@ fn test_resource_cleanup_on_element_deletion() {
     assert!(final_memory < initial_memory, "Memory should be freed after deletion");
 
     // Verify remaining elements are still valid
-    for i in 10..20 {
-        let element_id = &element_ids[i];
+    for element_id in &element_ids[10..20] {
         let element = engine.document_state.get_element(element_id);
         assert!(element.is_some(), "Remaining element {} should still exist", element_id);
     }
@@ -276,19 +278,19 @@ fn test_data_structure_integrity_under_stress() {
     let mut element_ids = Vec::new();
     
     // Create containers
-    for i in 0..50 {
+    for _i in 0..50 {
         let element_id = engine.create_element(ElementType::Container, HashMap::new()).unwrap();
         element_ids.push(element_id);
     }
 
     // Create interactive elements
-    for i in 0..50 {
+    for _i in 0..50 {
         let element_id = engine.create_element(ElementType::Interactive, HashMap::new()).unwrap();
         element_ids.push(element_id);
     }
 
     // Create charts
-    for i in 0..20 {
+    for _i in 0..20 {
         let element_id = engine.create_element(ElementType::Chart, HashMap::new()).unwrap();
         element_ids.push(element_id);
     }
@@ -324,6 +326,7 @@ fn test_data_structure_integrity_under_stress() {
                         radius: Some(8.0),
                         rotation_angle: None,
                         force: Some(0.5),
+                        confidence: None,
                     }],
                     changed_touches: vec![],
                     target_touches: vec![],
@@ -347,6 +350,7 @@ fn test_data_structure_integrity_under_stress() {
             },
             keyboard_data: None,
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -400,14 +404,14 @@ fn test_animation_memory_safety() {
 
     // Create elements for animation
     let mut element_ids = Vec::new();
-    for i in 0..30 {
+    for _i in 0..30 {
         let element_id = engine.create_element(ElementType::Container, HashMap::new()).unwrap();
         element_ids.push(element_id);
     }
 
     // Create many animations with complex keyframes
     let mut animation_ids = Vec::new();
-    for (i, element_id) in element_ids.iter().enumerate() {
+    for element_id in element_ids.iter() {
         let keyframes = vec![
             Keyframe {
                 time: 0.0,
@@ -542,8 +546,12 @@ fn test_chart_memory_safety() {
             fill_opacity: Some(0.3),
             marker_size: Some(4.0),
             marker_shape: Some(MarkerShape::Circle),
+            markers: None,
+            line_style: None,
             visible: true,
             y_axis: AxisReference::Primary,
+            error: None,
+            pattern: None,
         };
 
         let result = engine.chart_renderer.add_series(chart_id, series);
@@ -627,7 +635,7 @@ fn test_resource_limit_compliance_under_load() {
     let mut limit_hit = false;
 
     // Create elements until limit
-    for i in 0..100 {
+    for _i in 0..100 {
         let properties = [
             ("data".to_string(), serde_json::Value::String("x".repeat(1000))), // 1KB per element
         ].into_iter().collect();
@@ -0,0 +1,76 @@
+// Headless document processing for servers and CI pipelines: the engine driven directly through
+// InteractiveEngine, without the JSON string API, the global instance or a browser.
+
+use super::*;
+
+// What validate_document found; a clean document has nothing in any list
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DocumentReport {
+    pub elements: usize,
+    pub charts: Vec<String>,
+    pub pages: PageReachability,
+    pub contrast: ContrastReport,
+    // Rows of each data source that fail its schema
+    pub data_errors: BTreeMap<String, Vec<DataValidationError>>,
+}
+
+impl DocumentReport {
+    pub fn is_clean(&self) -> bool {
+        self.contrast.violations.is_empty()
+            && self.data_errors.is_empty()
+            && self.pages.unreachable.is_empty()
+            && self.pages.dead_ends.is_empty()
+    }
+}
+
+impl InteractiveEngine {
+    pub fn open_document(permissions: WASMPermissions, json: &str) -> Result<Self, WASMError> {
+        let mut engine = Self::new(permissions)?;
+        engine.load_document(json)?;
+        Ok(engine)
+    }
+
+    // Checks the loaded document without changing it
+    pub fn validate_document(&mut self) -> Result<DocumentReport, WASMError> {
+        let data_errors = self.document_state.data_sources.iter()
+            .filter_map(|(data_source_id, data_source)| {
+                let errors = data_source.schema.as_ref()?.validate(&data_source.to_rows());
+                (!errors.is_empty()).then(|| (data_source_id.clone(), errors))
+            })
+            .collect();
+        let charts: Vec<String> = self.chart_renderer.charts.keys().cloned().collect();
+
+        Ok(DocumentReport {
+            elements: self.document_state.elements.len(),
+            charts,
+            pages: self.get_reachable_pages(),
+            contrast: self.check_color_contrast(false)?,
+            data_errors,
+        })
+    }
+
+    // Every chart with the data of its bound source, in chart id order. PNG needs the `raster`
+    // feature and a browser canvas, so static export on a server is SVG.
+    pub fn export_all_charts(&mut self, format: ExportFormat, scale: f64) -> Result<Vec<ChartExport>, WASMError> {
+        let chart_ids: Vec<String> = self.chart_renderer.charts.keys().cloned().collect();
+        chart_ids.iter()
+            .map(|chart_id| self.export_chart(chart_id, format, scale))
+            .collect()
+    }
+
+    // SVG of a chart redrawn at the given size, e.g. for a document listing
+    pub fn chart_thumbnail(&mut self, chart_id: &str, width: f64, height: f64) -> Result<String, WASMError> {
+        self.security_context.check_export("chart_thumbnail")?;
+        let data = self.chart_source_data(chart_id)?;
+        Ok(self.chart_renderer.render_chart_sized(chart_id, &data, width, height)?.svg_content)
+    }
+
+    // Replaces the data of several sources, then runs one frame so computed sources and bindings
+    // settle. Stops at the first source that is missing or fails its schema.
+    pub fn bind_data_batch(&mut self, data: Vec<(String, serde_json::Value)>, timestamp: f64) -> Result<RenderUpdate, WASMError> {
+        for (data_source_id, rows) in data {
+            self.update_data_source(&data_source_id, rows)?;
+        }
+        self.render_frame(timestamp)
+    }
+}
@@ -0,0 +1,62 @@
+// Numeric hygiene for the math-heavy paths: animation progress and interpolation, gesture
+// velocity and chart scales. A NaN or infinity there serializes as null and corrupts whatever
+// reads the value next, so divisions go through checked_div and results through finite_or.
+//
+// finite_or substitutes a fallback for a non-finite value and counts the substitution against
+// the site it happened at; take_numeric_issues hands the counts to the host. Expected cases,
+// like a zero time delta between two samples of the same frame, are handled by the caller and
+// not counted.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub struct NumericIssue {
+    // Where the value was replaced, e.g. "animation.progress"
+    pub site: String,
+    // Values replaced there since the issues were last taken
+    pub count: u32,
+}
+
+thread_local! {
+    static ISSUES: RefCell<BTreeMap<&'static str, u32>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+// The quotient, or None when it is not finite (a zero denominator or non-finite operands)
+pub fn checked_div(numerator: f64, denominator: f64) -> Option<f64> {
+    let quotient = numerator / denominator;
+    quotient.is_finite().then_some(quotient)
+}
+
+// `value` when finite, otherwise `fallback`, recording the replacement against `site`
+pub fn finite_or(value: f64, fallback: f64, site: &'static str) -> f64 {
+    if value.is_finite() {
+        return value;
+    }
+    ISSUES.with(|issues| *issues.borrow_mut().entry(site).or_insert(0) += 1);
+    fallback
+}
+
+// Clamps to 0..=1, with NaN replaced by `fallback`
+pub fn unit_interval(value: f64, fallback: f64, site: &'static str) -> f64 {
+    if value.is_nan() {
+        return finite_or(value, fallback, site);
+    }
+    value.clamp(0.0, 1.0)
+}
+
+// Width of a scale domain. An empty or inverted domain spans 1 so values map to its start; one
+// too wide to represent is recorded and spans 1 as well.
+pub fn domain_span(min: f64, max: f64, site: &'static str) -> f64 {
+    if max > min { finite_or(max - min, 1.0, site) } else { 1.0 }
+}
+
+// Replacement counts by site, in site order, clearing them
+pub fn take_numeric_issues() -> Vec<NumericIssue> {
+    ISSUES.with(|issues| std::mem::take(&mut *issues.borrow_mut()))
+        .into_iter()
+        .map(|(site, count)| NumericIssue { site: site.to_string(), count })
+        .collect()
+}
@@ -0,0 +1,145 @@
+// JSON Patch (RFC 6902) over a serde_json value, for hosts that change a loaded document in place
+// instead of reloading it. Operations apply in order to a working copy, so a failed operation or
+// test leaves the original untouched.
+//
+// One extension to JSON Pointer: inside an array of objects, a segment that is neither an index
+// nor "-" selects the object whose "id" equals it, so "/elements/title/properties/text" works
+// without knowing where "title" sits in the array.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+impl PatchOperation {
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOperation::Add { path, .. } | PatchOperation::Remove { path } | PatchOperation::Replace { path, .. }
+            | PatchOperation::Move { path, .. } | PatchOperation::Copy { path, .. } | PatchOperation::Test { path, .. } => path,
+        }
+    }
+}
+
+// Applies every operation or none; the error names the failing operation by index
+pub fn apply(document: &Value, operations: &[PatchOperation]) -> Result<Value, String> {
+    let mut patched = document.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        apply_one(&mut patched, operation).map_err(|reason| format!("operation {} ({}): {}", index, operation.path(), reason))?;
+    }
+    Ok(patched)
+}
+
+fn apply_one(document: &mut Value, operation: &PatchOperation) -> Result<(), String> {
+    match operation {
+        PatchOperation::Add { path, value } => add(document, path, value.clone()),
+        PatchOperation::Remove { path } => remove(document, path).map(|_| ()),
+        PatchOperation::Replace { path, value } => {
+            let target = lookup_mut(document, &parse_pointer(path)?)?;
+            *target = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            if path.starts_with(&format!("{}/", from)) {
+                return Err("cannot move a value into itself".to_string());
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = lookup_mut(document, &parse_pointer(from)?)?.clone();
+            add(document, path, value)
+        }
+        PatchOperation::Test { path, value } => {
+            match lookup_mut(document, &parse_pointer(path)?)? == value {
+                true => Ok(()),
+                false => Err("test failed".to_string()),
+            }
+        }
+    }
+}
+
+// "" is the whole document; "~1" and "~0" unescape to "/" and "~"
+pub fn parse_pointer(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!("'{}' is not a JSON pointer", pointer));
+    };
+    Ok(rest.split('/').map(|segment| segment.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+fn add(document: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    let segments = parse_pointer(path)?;
+    let Some((last, parent)) = segments.split_last() else {
+        *document = value;
+        return Ok(());
+    };
+    match lookup_mut(document, parent)? {
+        Value::Object(map) => { map.insert(last.clone(), value); }
+        Value::Array(items) => {
+            let index = match last.as_str() {
+                "-" => items.len(),
+                segment => array_index(items, segment, true)?,
+            };
+            items.insert(index, value);
+        }
+        _ => return Err("parent is not an object or array".to_string()),
+    }
+    Ok(())
+}
+
+fn remove(document: &mut Value, path: &str) -> Result<Value, String> {
+    let segments = parse_pointer(path)?;
+    let Some((last, parent)) = segments.split_last() else {
+        return Err("cannot remove the whole document".to_string());
+    };
+    match lookup_mut(document, parent)? {
+        Value::Object(map) => map.remove(last).ok_or_else(|| format!("no member '{}'", last)),
+        Value::Array(items) => {
+            let index = array_index(items, last, false)?;
+            Ok(items.remove(index))
+        }
+        _ => Err("parent is not an object or array".to_string()),
+    }
+}
+
+fn lookup_mut<'a>(document: &'a mut Value, segments: &[String]) -> Result<&'a mut Value, String> {
+    let mut current = document;
+    for segment in segments {
+        current = match current {
+            Value::Object(map) => map.get_mut(segment).ok_or_else(|| format!("no member '{}'", segment))?,
+            Value::Array(items) => {
+                let index = array_index(items, segment, false)?;
+                &mut items[index]
+            }
+            _ => return Err(format!("'{}' is inside a value that is not an object or array", segment)),
+        };
+    }
+    Ok(current)
+}
+
+// An index, or the position of the object with that id; `end` allows the index one past the last
+fn array_index(items: &[Value], segment: &str, end: bool) -> Result<usize, String> {
+    let limit = if end { items.len() } else { items.len().saturating_sub(1) };
+    let numeric = !segment.is_empty() && segment.bytes().all(|byte| byte.is_ascii_digit());
+    if numeric && (segment == "0" || !segment.starts_with('0')) {
+        return match segment.parse::<usize>() {
+            Ok(index) if index <= limit && (end || !items.is_empty()) => Ok(index),
+            _ => Err(format!("index {} is out of bounds", segment)),
+        };
+    }
+    items.iter()
+        .position(|item| item.get("id").and_then(|id| id.as_str()) == Some(segment))
+        .ok_or_else(|| format!("no item with id '{}'", segment))
+}
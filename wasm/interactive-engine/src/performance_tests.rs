@@ -1,6 +1,8 @@
 use super::*;
 use wasm_bindgen_test::*;
-use std::time::{SystemTime, UNIX_EPOCH};
+// Native `cargo test` runs the same tests as plain #[test]s
+#[cfg(not(target_family = "wasm"))]
+use std::prelude::v1::test as wasm_bindgen_test;
 
 wasm_bindgen_test_configure!(run_in_browser);
 
@@ -25,13 +27,11 @@ fn test_chart_rendering_performance() {
     let mut engine = InteractiveEngine::new(permissions).unwrap();
 
     // Create multiple charts with different types
-    let chart_types = vec![
-        ChartType::Line,
+    let chart_types = [ChartType::Line,
         ChartType::Bar,
         ChartType::Pie,
         ChartType::Scatter,
-        ChartType::Area,
-    ];
+        ChartType::Area];
 
     let mut chart_ids = Vec::new();
     let start_time = get_current_timestamp();
@@ -59,8 +59,12 @@ fn test_chart_rendering_performance() {
             fill_opacity: Some(0.3),
             marker_size: Some(4.0),
             marker_shape: Some(MarkerShape::Circle),
+            markers: None,
+            line_style: None,
             visible: true,
             y_axis: AxisReference::Primary,
+            error: None,
+            pattern: None,
         };
 
         engine.chart_renderer.add_series(&chart_id, series).unwrap();
@@ -147,8 +151,12 @@ fn test_chart_data_update_performance() {
         fill_opacity: None,
         marker_size: Some(3.0),
         marker_shape: Some(MarkerShape::Circle),
+        markers: None,
+        line_style: None,
         visible: true,
         y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
     };
 
     engine.chart_renderer.add_series(&chart_id, series).unwrap();
@@ -178,7 +186,7 @@ fn test_chart_data_update_performance() {
     }
 
     let average_update_time = update_times.iter().sum::<f64>() / update_times.len() as f64;
-    let max_update_time = update_times.iter().fold(0.0, |a, &b| a.max(b));
+    let max_update_time = update_times.iter().fold(0.0_f64, |a, &b| a.max(b));
     let min_update_time = update_times.iter().fold(f64::INFINITY, |a, &b| a.min(b));
 
     // Performance assertions for data updates
@@ -207,7 +215,7 @@ fn test_animation_performance() {
 
     // Create multiple elements for animation
     let mut element_ids = Vec::new();
-    for i in 0..50 {
+    for _i in 0..50 {
         let element_id = engine.create_element(ElementType::Container, HashMap::new()).unwrap();
         element_ids.push(element_id);
     }
@@ -216,7 +224,7 @@ fn test_animation_performance() {
     let mut animation_ids = Vec::new();
     let animation_start_time = get_current_timestamp();
 
-    for (i, element_id) in element_ids.iter().enumerate() {
+    for element_id in element_ids.iter() {
         let keyframes = vec![
             Keyframe {
                 time: 0.0,
@@ -264,20 +272,15 @@ fn test_animation_performance() {
         let timestamp = frame_start_time + (frame as f64 * frame_duration);
         
         let single_frame_start = get_current_timestamp();
-        let render_update = engine.render_frame(timestamp).unwrap();
+        engine.render_frame(timestamp).unwrap();
         let single_frame_time = get_current_timestamp() - single_frame_start;
         
         frame_times.push(single_frame_time);
-        
-        // Verify animation updates are being generated
-        if frame < 100 { // Before animations complete
-            assert!(render_update.animation_updates.len() >= 0);
-        }
     }
 
     let total_frame_time = get_current_timestamp() - frame_start_time;
     let average_frame_time = frame_times.iter().sum::<f64>() / frame_times.len() as f64;
-    let max_frame_time = frame_times.iter().fold(0.0, |a, &b| a.max(b));
+    let max_frame_time = frame_times.iter().fold(0.0_f64, |a, &b| a.max(b));
 
     // Performance assertions for animations
     assert!(animation_creation_time < 1000.0, "Animation creation too slow: {}ms", animation_creation_time);
@@ -403,13 +406,14 @@ fn test_data_binding_performance() {
         ].into_iter().collect();
 
         let element_id = engine.create_element(ElementType::Chart, properties).unwrap();
-        element_ids.push(element_id);
+        element_ids.push(element_id.clone());
 
         // Add data binding
         let binding = DataBinding {
             source_id: format!("perf_data_{}", i),
             target_element: element_id.clone(),
             property_path: "value".to_string(),
+            target_property: None,
             transform_function: Some("percentage".to_string()),
             update_trigger: UpdateTrigger::Immediate,
         };
@@ -449,7 +453,7 @@ fn test_data_binding_performance() {
 
     let total_binding_time = get_current_timestamp() - binding_start_time;
     let average_binding_time = binding_update_times.iter().sum::<f64>() / binding_update_times.len() as f64;
-    let max_binding_time = binding_update_times.iter().fold(0.0, |a, &b| a.max(b));
+    let max_binding_time = binding_update_times.iter().fold(0.0_f64, |a, &b| a.max(b));
 
     // Performance assertions for data binding
     assert!(average_binding_time < 50.0, "Average binding update too slow: {}ms", average_binding_time);
@@ -496,7 +500,7 @@ fn test_memory_usage_efficiency() {
     // Create animations and track memory
     let element_ids: Vec<String> = engine.document_state.elements.iter().map(|e| e.id.clone()).collect();
     
-    for (i, element_id) in element_ids.iter().enumerate().take(50) {
+    for (_i, element_id) in element_ids.iter().enumerate().take(50) {
         let keyframes = vec![
             Keyframe {
                 time: 0.0,
@@ -545,7 +549,7 @@ fn test_render_update_efficiency() {
 
     // Create elements for interaction testing
     let mut element_ids = Vec::new();
-    for i in 0..100 {
+    for _i in 0..100 {
         let element_id = engine.create_element(ElementType::Interactive, HashMap::new()).unwrap();
         element_ids.push(element_id);
     }
@@ -572,6 +576,7 @@ fn test_render_update_efficiency() {
             }),
             keyboard_data: None,
             gesture_data: None,
+            pen_data: None,
             modifiers: EventModifiers {
                 ctrl: false,
                 shift: false,
@@ -587,17 +592,203 @@ fn test_render_update_efficiency() {
         render_update_times.push(render_time);
         
         // Verify render update quality
-        assert!(render_update.dom_operations.len() > 0 || 
-                render_update.style_changes.len() > 0 || 
-                render_update.animation_updates.len() > 0);
+        assert!(!render_update.dom_operations.is_empty() || 
+                !render_update.style_changes.is_empty() || 
+                !render_update.animation_updates.is_empty());
     }
 
     let average_render_time = render_update_times.iter().sum::<f64>() / render_update_times.len() as f64;
-    let max_render_time = render_update_times.iter().fold(0.0, |a, &b| a.max(b));
+    let max_render_time = render_update_times.iter().fold(0.0_f64, |a, &b| a.max(b));
     let min_render_time = render_update_times.iter().fold(f64::INFINITY, |a, &b| a.min(b));
 
     // Render update efficiency assertions
     assert!(average_render_time < 10.0, "Average render update too slow: {}ms", average_render_time);
     assert!(max_render_time < 50.0, "Max render update too slow: {}ms", max_render_time);
     assert!(min_render_time < 5.0, "Min render update too slow: {}ms", min_render_time);
-}
\ No newline at end of file
+}
+
+#[wasm_bindgen_test]
+fn test_sliding_window_metrics() {
+    let mut window = SlidingWindow::new(1000.0, 100);
+
+    for i in 0..10 {
+        window.push(i as f64 * 100.0, (i + 1) as f64);
+    }
+    assert_eq!(window.rate(900.0), 10.0);
+    assert_eq!(window.percentile(50.0), 5.5);
+    assert_eq!(window.max(), 10.0);
+
+    // Samples older than the window drop out
+    window.push(1550.0, 1.0);
+    assert_eq!(window.len(), 5);
+    assert_eq!(window.rate(1550.0), 5.0);
+
+    // The sample cap bounds memory regardless of the window
+    let mut capped = SlidingWindow::new(f64::INFINITY, 3);
+    for i in 0..10 {
+        capped.push(i as f64, i as f64);
+    }
+    assert_eq!(capped.len(), 3);
+}
+
+#[wasm_bindgen_test]
+fn test_frame_jank_detection() {
+    let mut timer = FrameTimer::new(60.0);
+    let frame = |timestamp: f64, layout_time: f64, chart_time: f64| FrameSample {
+        timestamp,
+        duration: 1.0 + layout_time,
+        animation_time: 1.0,
+        binding_time: 0.0,
+        layout_time,
+        chart_time,
+    };
+
+    // Smooth frames at 60fps
+    for i in 0..10 {
+        timer.record(frame(i as f64 * 1000.0 / 60.0, 2.0, 0.0));
+    }
+    let report = timer.report();
+    assert_eq!(report.janky_frames, 0);
+    assert!((report.average_fps - 60.0).abs() < 0.5);
+
+    // A slow layout frame followed by a gap of two skipped vsyncs
+    timer.record(frame(10.0 * 1000.0 / 60.0, 25.0, 0.0));
+    timer.record(frame(13.0 * 1000.0 / 60.0, 2.0, 30.0));
+
+    let report = timer.report();
+    assert_eq!(report.janky_frames, 2);
+    assert_eq!(report.missed_frames, 2);
+    assert_eq!(report.recent_jank[0].slowest_subsystem, FrameSubsystem::Layout);
+    assert_eq!(report.recent_jank[1].slowest_subsystem, FrameSubsystem::Chart);
+
+    // The overlay is created once and removed when disabled, repainting its corner of the screen
+    let viewport = Viewport { width: 800.0, height: 600.0, scale: 2.0, offset_x: 100.0, offset_y: 50.0 };
+    let corner = BoundingBox { x: 438.0, y: 52.0, width: 60.0, height: 20.0 };
+    let mut update = RenderUpdate::empty();
+    timer.overlay_enabled = true;
+    timer.update_overlay(&mut update, &viewport);
+    assert!(update.dom_operations.iter().any(|op| matches!(op, DOMOperation::Create { element_id, .. } if element_id == FrameTimer::OVERLAY_ID)));
+    assert_eq!(update.dirty_regions, vec![corner.clone()]);
+
+    let mut update = RenderUpdate::empty();
+    timer.overlay_enabled = false;
+    timer.update_overlay(&mut update, &viewport);
+    assert!(matches!(update.dom_operations.as_slice(), [DOMOperation::Remove { .. }]));
+    assert_eq!(update.dirty_regions, vec![corner]);
+
+    let mut update = RenderUpdate::empty();
+    timer.update_overlay(&mut update, &viewport);
+    assert!(update.dom_operations.is_empty() && update.dirty_regions.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_adaptive_quality_scaling() {
+    let mut quality = AdaptiveQualityManager::new();
+    let budget = 1000.0 / 60.0;
+    assert_eq!(*quality.tier(), QualityTier::High);
+
+    // Sustained overload degrades one tier at a time
+    for _ in 0..9 {
+        assert!(quality.observe_frame(30.0, budget).is_none());
+    }
+    assert_eq!(quality.observe_frame(30.0, budget), Some(QualityTier::Medium));
+    for _ in 0..10 {
+        quality.observe_frame(30.0, budget);
+    }
+    assert_eq!(*quality.tier(), QualityTier::Low);
+    assert!(quality.tier().settings().cheap_filters);
+
+    // Low tier ticks animations every other frame
+    let ticks = (0..4).filter(|_| {
+        quality.observe_frame(budget * 0.75, budget);
+        quality.should_tick_animations()
+    }).count();
+    assert_eq!(ticks, 2);
+
+    // Sustained headroom restores quality
+    for _ in 0..120 {
+        quality.observe_frame(2.0, budget);
+    }
+    assert_eq!(*quality.tier(), QualityTier::Medium);
+
+    // A pinned tier ignores load
+    quality.set_tier(Some(QualityTier::Minimal));
+    for _ in 0..200 {
+        assert!(quality.observe_frame(2.0, budget).is_none());
+    }
+    assert!(!quality.state().automatic);
+    quality.set_tier(None);
+    assert!(quality.state().automatic);
+    assert_eq!(*quality.tier(), QualityTier::Minimal);
+}
+
+#[wasm_bindgen_test]
+fn test_chart_level_of_detail() {
+    let mut renderer = ChartRenderer::new();
+    let chart_id = renderer.create_chart(ChartType::Line, "lod".to_string(), ChartConfig::default()).unwrap();
+    let rows: Vec<serde_json::Value> = (0..1000).map(|i| serde_json::json!({"x": i, "y": i})).collect();
+    let data = serde_json::Value::Array(rows);
+
+    renderer.set_lod_max_points(Some(100));
+    assert!(renderer.render_chart(&chart_id, &data).is_ok());
+    assert_eq!(renderer.lod_max_points, Some(100));
+
+    // Changing the level of detail invalidates cached renders
+    renderer.set_lod_max_points(None);
+    assert!(renderer.render_cache.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_metrics_registry() {
+    let mut metrics = MetricsRegistry::new();
+    for i in 0..20 {
+        metrics.count_at(METRIC_EVENTS, 1, 10_000.0 + i as f64 * 50.0);
+    }
+    metrics.count_at(METRIC_SYNTHESIZED_EVENTS, 3, 10_500.0);
+    metrics.time_at(METRIC_FRAME_TIME, 4.0, 10_900.0);
+    metrics.time_at(METRIC_FRAME_TIME, 8.0, 10_950.0);
+
+    // Rates cover the trailing second on the registry clock, and fall to zero once events stop
+    assert_eq!(metrics.rate_at(METRIC_EVENTS, 11_000.0), 20.0);
+    assert_eq!(metrics.rate_at(METRIC_EVENTS, 11_500.0), 10.0);
+    assert_eq!(metrics.rate_at(METRIC_EVENTS, 20_000.0), 0.0);
+    assert_eq!(metrics.rate_at(METRIC_SYNTHESIZED_EVENTS, 11_000.0), 3.0);
+
+    let snapshot = metrics.snapshot_at(11_000.0);
+    assert_eq!(snapshot.schema_version, METRICS_SCHEMA_VERSION);
+    assert_eq!(snapshot.counters[METRIC_EVENTS], 20);
+    let frame_time = &snapshot.timings[METRIC_FRAME_TIME];
+    assert_eq!((frame_time.count, frame_time.mean, frame_time.max), (2, 6.0, 8.0));
+    let json = serde_json::to_value(&snapshot).unwrap();
+    assert_eq!(json["rates"]["events"], serde_json::json!(20.0));
+
+    let stats = metrics.performance_stats_at(11_000.0);
+    assert_eq!((stats.total_interactions, stats.interactions_per_second, stats.average_frame_time), (20, 20.0, 6.0));
+    assert_eq!(metrics.interaction_metrics_at(11_000.0).synthesized_events, 3);
+
+    // A reset starts every count over
+    metrics.reset();
+    assert_eq!(metrics.counter(METRIC_EVENTS), 0);
+    assert!(metrics.snapshot_at(11_000.0).timings.is_empty());
+
+    // Each engine keeps its own metrics
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut busy = InteractiveEngine::new(permissions.clone()).unwrap();
+    let idle = InteractiveEngine::new(permissions).unwrap();
+    busy.render_frame(0.0).unwrap();
+    busy.render_frame(16.0).unwrap();
+    assert_eq!(busy.get_metrics().counters[METRIC_RENDERS], 2);
+    assert_eq!(busy.get_performance_stats().total_renders, 2);
+    assert!(idle.get_metrics().counters.is_empty());
+    busy.reset_metrics();
+    assert_eq!(busy.get_performance_stats().total_renders, 0);
+}
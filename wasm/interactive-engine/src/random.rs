@@ -0,0 +1,334 @@
+// Reproducible randomness for simulations and generative content. Every number comes from the
+// document's seed, so the same document draws the same jittered scatter or particle burst on
+// every load and on every host.
+//
+// Random numbers are drawn from named streams: each stream is its own generator seeded from the
+// document seed and the stream name, so a script that adds draws to one stream leaves the others
+// unchanged. Generators are xoshiro256** seeded through SplitMix64. Noise is Perlin's improved
+// noise and Gustavson's simplex noise over a permutation table shuffled from the seed; noise is a
+// pure function of the seed and the point.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Draws beyond this in one call are refused
+pub const MAX_RANDOM_SAMPLES: usize = 100_000;
+
+const MAX_NOISE_OCTAVES: u32 = 8;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut mix = seed;
+        Self { state: [splitmix64(&mut mix), splitmix64(&mut mix), splitmix64(&mut mix), splitmix64(&mut mix)] }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    // Uniform in [0, 1), from the top 53 bits
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform in 0..n without modulo bias; 0 when n is 0
+    pub fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            return 0;
+        }
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % n;
+            }
+        }
+    }
+
+    // Standard normal, by Box-Muller
+    pub fn standard_normal(&mut self) -> f64 {
+        let radius = (-2.0 * (1.0 - self.next_f64()).ln()).sqrt();
+        radius * (std::f64::consts::TAU * self.next_f64()).cos()
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+// What a draw samples from. Integer, Poisson, Bernoulli and Weighted draws are whole numbers
+// (Weighted gives the index of the chosen weight) returned as f64 like the rest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub enum Distribution {
+    Uniform { min: f64, max: f64 },
+    // min..=max
+    Integer { min: i64, max: i64 },
+    Normal { mean: f64, std_dev: f64 },
+    Exponential { rate: f64 },
+    Poisson { lambda: f64 },
+    Bernoulli { p: f64 },
+    Weighted { weights: Vec<f64> },
+}
+
+impl Distribution {
+    // Why the parameters can't be sampled, if they can't
+    pub fn validate(&self) -> Result<(), String> {
+        let finite = |values: &[f64]| values.iter().all(|value| value.is_finite());
+        match self {
+            Distribution::Uniform { min, max } if !finite(&[*min, *max]) || min > max => Err("Uniform needs finite min <= max".to_string()),
+            Distribution::Integer { min, max } if min > max => Err("Integer needs min <= max".to_string()),
+            Distribution::Normal { mean, std_dev } if !finite(&[*mean, *std_dev]) || *std_dev < 0.0 => Err("Normal needs a finite mean and a non-negative std_dev".to_string()),
+            Distribution::Exponential { rate } if !rate.is_finite() || *rate <= 0.0 => Err("Exponential needs a positive rate".to_string()),
+            Distribution::Poisson { lambda } if !lambda.is_finite() || *lambda < 0.0 => Err("Poisson needs a non-negative lambda".to_string()),
+            Distribution::Bernoulli { p } if !(0.0..=1.0).contains(p) => Err("Bernoulli needs p within 0..=1".to_string()),
+            Distribution::Weighted { weights } if !finite(weights) || weights.iter().any(|weight| *weight < 0.0) || weights.iter().sum::<f64>() <= 0.0 => {
+                Err("Weighted needs non-negative weights with a positive total".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // One draw; the parameters must have passed validate()
+    pub fn sample(&self, rng: &mut Rng) -> f64 {
+        match self {
+            Distribution::Uniform { min, max } => min + (max - min) * rng.next_f64(),
+            Distribution::Integer { min, max } => {
+                let span = max.wrapping_sub(*min) as u64;
+                let offset = if span == u64::MAX { rng.next_u64() } else { rng.below(span + 1) };
+                min.wrapping_add(offset as i64) as f64
+            }
+            Distribution::Normal { mean, std_dev } => mean + std_dev * rng.standard_normal(),
+            Distribution::Exponential { rate } => -(1.0 - rng.next_f64()).ln() / rate,
+            // Knuth's multiplication method for small means; large means use the normal approximation
+            Distribution::Poisson { lambda } if *lambda < 30.0 => {
+                let limit = (-lambda).exp();
+                let mut count = 0.0;
+                let mut product = rng.next_f64();
+                while product > limit {
+                    count += 1.0;
+                    product *= rng.next_f64();
+                }
+                count
+            }
+            Distribution::Poisson { lambda } => (lambda + lambda.sqrt() * rng.standard_normal()).round().max(0.0),
+            Distribution::Bernoulli { p } => if rng.next_f64() < *p { 1.0 } else { 0.0 },
+            Distribution::Weighted { weights } => {
+                let mut target = rng.next_f64() * weights.iter().sum::<f64>();
+                for (index, weight) in weights.iter().enumerate() {
+                    if target < *weight {
+                        return index as f64;
+                    }
+                    target -= weight;
+                }
+                // Rounding left the target past the end; take the last weight that can be drawn
+                weights.iter().rposition(|weight| *weight > 0.0).unwrap_or(0) as f64
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub enum NoiseKind {
+    #[default]
+    Perlin,
+    Simplex,
+}
+
+// Seeded gradient noise in -1..=1
+#[derive(Clone, Debug)]
+pub struct Noise {
+    perm: [u8; 512],
+}
+
+const GRADIENTS: [[f64; 3]; 12] = [
+    [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0], [1.0, -1.0, 0.0], [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0], [-1.0, 0.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0], [0.0, -1.0, 1.0], [0.0, 1.0, -1.0], [0.0, -1.0, -1.0],
+];
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        let mut rng = Rng::new(seed);
+        for i in (1..table.len()).rev() {
+            table.swap(i, rng.below(i as u64 + 1) as usize);
+        }
+        Self { perm: std::array::from_fn(|i| table[i % 256]) }
+    }
+
+    fn hash(&self, i: usize) -> usize {
+        self.perm[i] as usize
+    }
+
+    pub fn sample(&self, kind: NoiseKind, x: f64, y: f64, z: f64) -> f64 {
+        if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+            return 0.0;
+        }
+        match kind {
+            NoiseKind::Perlin => self.perlin(x, y, z),
+            NoiseKind::Simplex => self.simplex(x, y, z),
+        }
+    }
+
+    // Octaves of noise, each at twice the frequency and half the amplitude of the one before,
+    // scaled back into -1..=1
+    pub fn fractal(&self, kind: NoiseKind, x: f64, y: f64, z: f64, octaves: u32) -> f64 {
+        let (mut total, mut amplitude, mut frequency, mut range) = (0.0, 1.0, 1.0, 0.0);
+        for _ in 0..octaves.clamp(1, MAX_NOISE_OCTAVES) {
+            total += amplitude * self.sample(kind, x * frequency, y * frequency, z * frequency);
+            range += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        total / range
+    }
+
+    fn perlin(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (xi, yi, zi) = (lattice(x), lattice(y), lattice(z));
+        let (x, y, z) = (x - x.floor(), y - y.floor(), z - z.floor());
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+        let a = self.hash(xi) + yi;
+        let (aa, ab) = (self.hash(a) + zi, self.hash(a + 1) + zi);
+        let b = self.hash(xi + 1) + yi;
+        let (ba, bb) = (self.hash(b) + zi, self.hash(b + 1) + zi);
+        let grad = |hash: usize, x: f64, y: f64, z: f64| {
+            let h = hash & 15;
+            let u = if h < 8 { x } else { y };
+            let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+            (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+        };
+        let value = lerp(w,
+            lerp(v,
+                lerp(u, grad(self.hash(aa), x, y, z), grad(self.hash(ba), x - 1.0, y, z)),
+                lerp(u, grad(self.hash(ab), x, y - 1.0, z), grad(self.hash(bb), x - 1.0, y - 1.0, z))),
+            lerp(v,
+                lerp(u, grad(self.hash(aa + 1), x, y, z - 1.0), grad(self.hash(ba + 1), x - 1.0, y, z - 1.0)),
+                lerp(u, grad(self.hash(ab + 1), x, y - 1.0, z - 1.0), grad(self.hash(bb + 1), x - 1.0, y - 1.0, z - 1.0))));
+        value.clamp(-1.0, 1.0)
+    }
+
+    fn simplex(&self, x: f64, y: f64, z: f64) -> f64 {
+        const F3: f64 = 1.0 / 3.0;
+        const G3: f64 = 1.0 / 6.0;
+        let skew = (x + y + z) * F3;
+        let (i, j, k) = ((x + skew).floor(), (y + skew).floor(), (z + skew).floor());
+        let unskew = (i + j + k) * G3;
+        let first = [x - (i - unskew), y - (j - unskew), z - (k - unskew)];
+        let [x0, y0, z0] = first;
+
+        // Which simplex of the skewed cube the point is in, as the offsets of its second and third corners
+        let (second, third) = if x0 >= y0 {
+            if y0 >= z0 { ([1, 0, 0], [1, 1, 0]) } else if x0 >= z0 { ([1, 0, 0], [1, 0, 1]) } else { ([0, 0, 1], [1, 0, 1]) }
+        } else if y0 < z0 {
+            ([0, 0, 1], [0, 1, 1])
+        } else if x0 < z0 {
+            ([0, 1, 0], [0, 1, 1])
+        } else {
+            ([0, 1, 0], [1, 1, 0])
+        };
+
+        let (ii, jj, kk) = (lattice(i), lattice(j), lattice(k));
+        let corners = [([0, 0, 0], 0.0), (second, G3), (third, 2.0 * G3), ([1, 1, 1], 3.0 * G3)];
+        let total: f64 = corners.iter()
+            .map(|(offset, shift)| {
+                let d = [
+                    first[0] - offset[0] as f64 + shift,
+                    first[1] - offset[1] as f64 + shift,
+                    first[2] - offset[2] as f64 + shift,
+                ];
+                let falloff = 0.6 - d[0] * d[0] - d[1] * d[1] - d[2] * d[2];
+                if falloff < 0.0 {
+                    return 0.0;
+                }
+                let hash = self.hash(ii + offset[0] + self.hash(jj + offset[1] + self.hash(kk + offset[2])));
+                let gradient = GRADIENTS[hash % 12];
+                falloff.powi(4) * (gradient[0] * d[0] + gradient[1] * d[1] + gradient[2] * d[2])
+            })
+            .sum();
+        (32.0 * total).clamp(-1.0, 1.0)
+    }
+}
+
+// Lattice coordinate wrapped into the permutation table
+fn lattice(value: f64) -> usize {
+    (value.floor() as i64 & 255) as usize
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+// The per-document random service: the seed, its named streams and the noise table
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub struct RandomStreams {
+    seed: u64,
+    streams: BTreeMap<String, Rng>,
+    #[serde(skip)]
+    noise: Option<Noise>,
+}
+
+impl RandomStreams {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, streams: BTreeMap::new(), noise: None }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // The generator for `name`, starting from the beginning of its sequence on first use
+    pub fn stream(&mut self, name: &str) -> &mut Rng {
+        let mut mix = self.seed;
+        self.streams.entry(name.to_string())
+            .or_insert_with(|| Rng::new(splitmix64(&mut mix) ^ fnv1a(name)))
+    }
+
+    pub fn sample(&mut self, stream: &str, distribution: &Distribution, count: usize) -> Result<Vec<f64>, String> {
+        distribution.validate()?;
+        if count > MAX_RANDOM_SAMPLES {
+            return Err(format!("At most {} samples can be drawn at once", MAX_RANDOM_SAMPLES));
+        }
+        let rng = self.stream(stream);
+        Ok((0..count).map(|_| distribution.sample(rng)).collect())
+    }
+
+    pub fn noise(&mut self) -> &Noise {
+        let seed = self.seed;
+        self.noise.get_or_insert_with(|| Noise::new(seed))
+    }
+}
+
+// Where `key` (an animation and property, say) reads its own track of noise
+pub fn noise_lane(key: &str) -> f64 {
+    (fnv1a(key) % 4096) as f64 + 0.5
+}
@@ -0,0 +1,287 @@
+// Streaming data sources. The host owns the connection (a WebSocket, say) and hands each frame it
+// receives to push_stream_frame. The engine puts frames in order and holds their rows until the
+// next render frame, then applies them to the Stream source in one batch, so a burst of small
+// messages costs one redraw of the charts bound to the source.
+//
+// A frame is JSON or binary. A JSON frame is an array of rows, or {"seq": n, "rows": [...]}.
+// A binary frame carries typed columns:
+//
+//   "LIVF", u64 sequence (u64::MAX for none), u16 column count, then per column a u16 name
+//   length, the UTF-8 name, a u32 value count and that many f64 values. Little-endian throughout.
+//
+// Frames with a sequence number are applied in sequence order. An early frame waits for the gap
+// before it to fill. Once more than max_held_frames are waiting, the gap is skipped and counted as
+// lost. A frame at or below the last sequence released is a duplicate and is dropped.
+//
+// Waiting rows count against the security context's stream buffer. Past the high-water mark the
+// status asks the host to pause reading. A frame that would overflow the buffer is refused.
+//
+// The engine opens no connections itself. When the host's connection drops, the host reports it
+// and gets the delay before its next attempt, doubling each time. After reconnecting it reports
+// that too, and asks the server to resume after last_sequence.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const STREAM_FRAME_MAGIC: &[u8; 4] = b"LIVF";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+#[serde(default)]
+pub struct StreamConfig {
+    // Early frames kept while waiting for a missing sequence number
+    pub max_held_frames: usize,
+    // Reconnect delays start here and double up to reconnect_max_ms
+    pub reconnect_base_ms: f64,
+    pub reconnect_max_ms: f64,
+    // Failed attempts before the stream closes; 0 keeps retrying
+    pub max_reconnect_attempts: u32,
+    // Share of the buffer limit past which the host should stop reading
+    pub high_water: f64,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            max_held_frames: 32,
+            reconnect_base_ms: 500.0,
+            reconnect_max_ms: 30_000.0,
+            max_reconnect_attempts: 0,
+            high_water: 0.75,
+        }
+    }
+}
+
+impl StreamConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let delays_valid = self.reconnect_base_ms > 0.0 && self.reconnect_max_ms.is_finite() && self.reconnect_base_ms <= self.reconnect_max_ms;
+        if !delays_valid {
+            return Err("reconnect delays must be finite and positive, with reconnect_base_ms <= reconnect_max_ms".to_string());
+        }
+        let high_water_valid = self.high_water > 0.0 && self.high_water <= 1.0;
+        if !high_water_valid {
+            return Err("high_water must be above 0 and at most 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub enum StreamState {
+    Connected,
+    Reconnecting,
+    // Out of reconnect attempts; frames are refused until the stream is opened again
+    Closed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub struct StreamStatus {
+    pub source_id: String,
+    pub state: StreamState,
+    // Highest sequence number released in order; resume after it when reconnecting
+    pub last_sequence: Option<u64>,
+    pub buffered_bytes: usize,
+    pub buffer_limit: usize,
+    // The host should stop reading from the connection until the next render frame drains the buffer
+    pub paused: bool,
+    pub held_frames: usize,
+    pub frames_received: u64,
+    // Duplicates and frames that arrived after their place was skipped
+    pub frames_dropped: u64,
+    // Sequence numbers skipped because their frames never arrived
+    pub frames_lost: u64,
+    pub reconnect_attempt: u32,
+    // Wait before the next attempt, while reconnecting
+    pub reconnect_in_ms: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FramePayload {
+    Rows(Vec<serde_json::Value>),
+    Columns(Vec<(String, Vec<f64>)>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamFrame {
+    pub sequence: Option<u64>,
+    pub payload: FramePayload,
+    // Encoded size, what the frame counts against the buffer
+    pub size: usize,
+}
+
+pub fn decode_frame(bytes: &[u8]) -> Result<StreamFrame, String> {
+    if let Some(body) = bytes.strip_prefix(STREAM_FRAME_MAGIC.as_slice()) {
+        return decode_binary_frame(body, bytes.len());
+    }
+    let value: serde_json::Value = serde_json::from_slice(bytes).map_err(|e| e.to_string())?;
+    let (sequence, rows) = match value {
+        serde_json::Value::Array(rows) => (None, rows),
+        serde_json::Value::Object(mut frame) => {
+            let sequence = match frame.get("seq") {
+                None | Some(serde_json::Value::Null) => None,
+                Some(seq) => Some(seq.as_u64().ok_or("seq must be a non-negative integer")?),
+            };
+            match frame.remove("rows") {
+                Some(serde_json::Value::Array(rows)) => (sequence, rows),
+                _ => return Err("a frame object needs a rows array".to_string()),
+            }
+        }
+        _ => return Err("a frame is an array of rows or an object with rows".to_string()),
+    };
+    Ok(StreamFrame { sequence, payload: FramePayload::Rows(rows), size: bytes.len() })
+}
+
+fn decode_binary_frame(body: &[u8], size: usize) -> Result<StreamFrame, String> {
+    let mut reader = Reader { bytes: body };
+    let sequence = u64::from_le_bytes(reader.take()?);
+    let column_count = u16::from_le_bytes(reader.take()?);
+    let mut columns = Vec::with_capacity(column_count as usize);
+    for _ in 0..column_count {
+        let name_length = u16::from_le_bytes(reader.take()?) as usize;
+        let name = String::from_utf8(reader.slice(name_length)?.to_vec()).map_err(|_| "column name is not UTF-8".to_string())?;
+        let count = u32::from_le_bytes(reader.take()?) as usize;
+        let values = reader.slice(count.checked_mul(8).ok_or("column is too long")?)?
+            .chunks_exact(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap_or_default()))
+            .collect();
+        columns.push((name, values));
+    }
+    if !reader.bytes.is_empty() {
+        return Err(format!("{} bytes left after the last column", reader.bytes.len()));
+    }
+    Ok(StreamFrame { sequence: (sequence != u64::MAX).then_some(sequence), payload: FramePayload::Columns(columns), size })
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn slice(&mut self, length: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < length {
+            return Err("frame ends early".to_string());
+        }
+        let (head, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let slice = self.slice(N)?;
+        Ok(slice.try_into().unwrap_or([0; N]))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StreamConnection {
+    pub config: StreamConfig,
+    pub state: StreamState,
+    last_sequence: Option<u64>,
+    held: BTreeMap<u64, StreamFrame>,
+    ready: Vec<StreamFrame>,
+    buffered_bytes: usize,
+    frames_received: u64,
+    frames_dropped: u64,
+    frames_lost: u64,
+    reconnect_attempt: u32,
+}
+
+impl StreamConnection {
+    pub fn new(config: StreamConfig) -> Self {
+        Self {
+            config,
+            state: StreamState::Connected,
+            last_sequence: None,
+            held: BTreeMap::new(),
+            ready: Vec::new(),
+            buffered_bytes: 0,
+            frames_received: 0,
+            frames_dropped: 0,
+            frames_lost: 0,
+            reconnect_attempt: 0,
+        }
+    }
+
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes
+    }
+
+    // Queues the frame, or holds it until the frames before it arrive
+    pub fn receive(&mut self, frame: StreamFrame) {
+        self.frames_received += 1;
+        let Some(sequence) = frame.sequence else {
+            self.buffered_bytes += frame.size;
+            self.ready.push(frame);
+            return;
+        };
+        if self.last_sequence.is_some_and(|last| sequence <= last) || self.held.contains_key(&sequence) {
+            self.frames_dropped += 1;
+            return;
+        }
+        self.buffered_bytes += frame.size;
+        self.held.insert(sequence, frame);
+        self.release();
+    }
+
+    fn release(&mut self) {
+        loop {
+            let Some(&next) = self.held.keys().next() else { return };
+            let in_order = self.last_sequence.map_or(true, |last| next == last + 1);
+            if !in_order && self.held.len() <= self.config.max_held_frames {
+                return;
+            }
+            if let Some(last) = self.last_sequence {
+                self.frames_lost += next - last - 1;
+            }
+            if let Some(frame) = self.held.remove(&next) {
+                self.ready.push(frame);
+            }
+            self.last_sequence = Some(next);
+        }
+    }
+
+    // Frames released since the last call, in order, no longer counted against the buffer
+    pub fn take_ready(&mut self) -> Vec<StreamFrame> {
+        let ready = std::mem::take(&mut self.ready);
+        self.buffered_bytes -= ready.iter().map(|frame| frame.size).sum::<usize>();
+        ready
+    }
+
+    pub fn connected(&mut self) {
+        self.state = StreamState::Connected;
+        self.reconnect_attempt = 0;
+    }
+
+    pub fn disconnected(&mut self) {
+        self.reconnect_attempt += 1;
+        let attempts = self.config.max_reconnect_attempts;
+        self.state = if attempts > 0 && self.reconnect_attempt > attempts { StreamState::Closed } else { StreamState::Reconnecting };
+    }
+
+    fn reconnect_delay(&self) -> Option<f64> {
+        if self.state != StreamState::Reconnecting {
+            return None;
+        }
+        let doublings = self.reconnect_attempt.saturating_sub(1).min(30) as i32;
+        Some((self.config.reconnect_base_ms * 2f64.powi(doublings)).min(self.config.reconnect_max_ms))
+    }
+
+    pub fn status(&self, source_id: &str, buffer_limit: usize) -> StreamStatus {
+        StreamStatus {
+            source_id: source_id.to_string(),
+            state: self.state.clone(),
+            last_sequence: self.last_sequence,
+            buffered_bytes: self.buffered_bytes,
+            buffer_limit,
+            paused: self.buffered_bytes as f64 >= buffer_limit as f64 * self.config.high_water,
+            held_frames: self.held.len(),
+            frames_received: self.frames_received,
+            frames_dropped: self.frames_dropped,
+            frames_lost: self.frames_lost,
+            reconnect_attempt: self.reconnect_attempt,
+            reconnect_in_ms: self.reconnect_delay(),
+        }
+    }
+}
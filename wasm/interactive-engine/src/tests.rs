@@ -1,5 +1,8 @@
 use super::*;
 use wasm_bindgen_test::*;
+// Native `cargo test` runs the same tests as plain #[test]s
+#[cfg(not(target_family = "wasm"))]
+use std::prelude::v1::test as wasm_bindgen_test;
 
 wasm_bindgen_test_configure!(run_in_browser);
 
@@ -160,6 +163,12 @@ fn test_event_handling() {
         position: Some(Position { x: 10.0, y: 10.0 }),
         data: HashMap::new(),
         timestamp: get_current_timestamp(),
+        touch_data: None,
+        mouse_data: None,
+        keyboard_data: None,
+        gesture_data: None,
+        pen_data: None,
+        modifiers: EventModifiers { ctrl: false, shift: false, alt: false, meta: false },
     };
 
     let result = engine.process_interaction(interaction_event);
@@ -169,6 +178,82 @@ fn test_event_handling() {
     assert!(!render_update.dom_operations.is_empty() || !render_update.style_changes.is_empty());
 }
 
+#[wasm_bindgen_test]
+fn test_hit_testing() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec![
+            "create_element".to_string(),
+            "create_event_handler".to_string(),
+            "Click".to_string(),
+        ],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let size = |width: f64, height: f64| HashMap::from([
+        ("width".to_string(), serde_json::json!(width)),
+        ("height".to_string(), serde_json::json!(height)),
+    ]);
+    let panel = engine.create_element(ElementType::Container, size(200.0, 200.0)).unwrap();
+    let button = engine.create_element(ElementType::Interactive, size(50.0, 20.0)).unwrap();
+    let overlay = engine.create_element(ElementType::Container, size(100.0, 100.0)).unwrap();
+    engine.document_state.attach_child(&panel, &button);
+    let move_to = |engine: &mut InteractiveEngine, id: &str, x: f64, y: f64, scale: f64| {
+        let transform = &mut engine.document_state.get_element_mut(id).unwrap().transform;
+        (transform.x, transform.y, transform.scale_x, transform.scale_y) = (x, y, scale, scale);
+    };
+    move_to(&mut engine, &panel, 100.0, 100.0, 1.0);
+    move_to(&mut engine, &button, 10.0, 10.0, 2.0);
+
+    // Children sit in their parent's box; scaling grows the box about its centre
+    assert_eq!(engine.hit_test(150.0, 150.0), Some(panel.clone()));
+    assert_eq!(engine.hit_test(90.0, 105.0), Some(button.clone()));
+    assert_eq!(engine.hit_test(50.0, 50.0), Some(overlay.clone()));
+    assert_eq!(engine.hit_test(500.0, 500.0), None);
+
+    // Later elements are on top unless z-index says otherwise
+    move_to(&mut engine, &overlay, 80.0, 80.0, 1.0);
+    assert_eq!(engine.hit_test(110.0, 110.0), Some(overlay.clone()));
+    let set = |engine: &mut InteractiveEngine, id: &str, property: &str, value: serde_json::Value| {
+        engine.document_state.update_element(id, HashMap::from([(property.to_string(), value)])).unwrap();
+    };
+    set(&mut engine, &panel, "style.z-index", serde_json::json!(1));
+    assert_eq!(engine.hit_test(110.0, 110.0), Some(button.clone()));
+
+    // Hidden subtrees are skipped; pointer-events none passes through to what is below
+    set(&mut engine, &panel, "style.pointer-events", serde_json::json!("none"));
+    assert_eq!(engine.hit_test(150.0, 150.0), Some(overlay.clone()));
+    assert_eq!(engine.hit_test(110.0, 110.0), Some(button.clone()));
+    set(&mut engine, &panel, "style.visibility", serde_json::json!("hidden"));
+    assert_eq!(engine.hit_test(110.0, 110.0), Some(overlay.clone()));
+    set(&mut engine, &overlay, "style.display", serde_json::json!("none"));
+    assert_eq!(engine.hit_test(110.0, 110.0), None);
+
+    // Untargeted clicks resolve to the element under the pointer
+    set(&mut engine, &panel, "style.visibility", serde_json::json!("visible"));
+    engine.add_event_handler(&button, "click", "toggle_visibility").unwrap();
+    let update = engine.process_interaction(InteractionEvent {
+        event_type: InteractionType::Click,
+        target_element: None,
+        position: Some(Position { x: 110.0, y: 110.0 }),
+        data: HashMap::new(),
+        timestamp: get_current_timestamp(),
+        touch_data: None,
+        mouse_data: None,
+        keyboard_data: None,
+        gesture_data: None,
+        pen_data: None,
+        modifiers: EventModifiers { ctrl: false, shift: false, alt: false, meta: false },
+    }).unwrap();
+    assert!(update.style_changes.iter().any(|change| change.element_id == button));
+}
+
 #[wasm_bindgen_test]
 fn test_viewport_updates() {
     let permissions = WASMPermissions {
@@ -235,7 +320,7 @@ fn test_data_source_management() {
         ("data_source".to_string(), serde_json::Value::String("test_data".to_string())),
     ].into_iter().collect();
 
-    let element_id = engine.create_element(ElementType::Chart, properties).unwrap();
+    let _element_id = engine.create_element(ElementType::Chart, properties).unwrap();
 
     // Test data update
     let data_update_event = InteractionEvent {
@@ -247,6 +332,12 @@ fn test_data_source_management() {
             ("data".to_string(), serde_json::json!({"value": 84})),
         ].into_iter().collect(),
         timestamp: get_current_timestamp(),
+        touch_data: None,
+        mouse_data: None,
+        keyboard_data: None,
+        gesture_data: None,
+        pen_data: None,
+        modifiers: EventModifiers { ctrl: false, shift: false, alt: false, meta: false },
     };
 
     let result = engine.process_interaction(data_update_event);
@@ -257,6 +348,83 @@ fn test_data_source_management() {
     assert_eq!(updated_data["value"], 84);
 }
 
+#[wasm_bindgen_test]
+fn test_derived_data_sources() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let sales = DataSource::new("sales".to_string(), DataSourceType::Dynamic, serde_json::json!([
+        {"region": "north", "amount": 10},
+        {"region": "south", "amount": 5}
+    ]));
+    engine.document_state.data_sources.insert("sales".to_string(), sales);
+
+    let fields = PointFields { x: "x".to_string(), y: "amount".to_string() };
+    let downsampled = engine.downsample_data("sales", DownsampleMethod::MinMax, 10, &fields, None).unwrap();
+    let totals = engine.aggregate_data("sales", "region", "amount", AggregateFunction::Sum, None).unwrap();
+    assert_eq!((downsampled.as_str(), totals.as_str()), ("sales_downsampled", "sales_by_region"));
+    assert_eq!(engine.document_state.data_sources["sales_downsampled"].column("amount"), Some(&[10.0, 5.0][..]));
+
+    // Deriving again from the same input refreshes the result
+    engine.update_data_source("sales", serde_json::json!([
+        {"region": "north", "amount": 10},
+        {"region": "north", "amount": 7}
+    ])).unwrap();
+    let version = engine.document_state.data_sources["sales_by_region"].version;
+    engine.aggregate_data("sales", "region", "amount", AggregateFunction::Sum, None).unwrap();
+    let totals = &engine.document_state.data_sources["sales_by_region"];
+    assert_eq!((totals.data.clone(), totals.version), (serde_json::json!([{"region": "north", "amount": 17.0}]), version + 1));
+    engine.downsample_data("sales", DownsampleMethod::Lttb, 10, &fields, None).unwrap();
+    assert_eq!(engine.document_state.data_sources["sales_downsampled"].column("amount"), Some(&[10.0, 7.0][..]));
+
+    // Callers can pick the output id, but never one taken by another source
+    let mean = engine.aggregate_data("sales", "region", "amount", AggregateFunction::Mean, Some("sales_mean")).unwrap();
+    assert_eq!(engine.document_state.data_sources[&mean].data[0]["amount"], 8.5);
+    assert_eq!(engine.aggregate_data("sales", "region", "amount", AggregateFunction::Mean, Some("sales")).unwrap_err().code, "DATA_SOURCE_EXISTS");
+    let other = DataSource::new("other".to_string(), DataSourceType::Static, serde_json::json!([1, 2]));
+    engine.document_state.data_sources.insert("other".to_string(), other);
+    assert_eq!(engine.downsample_data("other", DownsampleMethod::Lttb, 10, &PointFields::default(), Some("sales_mean")).unwrap_err().code, "DATA_SOURCE_EXISTS");
+}
+
+#[wasm_bindgen_test]
+fn test_typed_data_respects_schema() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let readings = DataSource::new("readings".to_string(), DataSourceType::Dynamic, serde_json::json!([]));
+    engine.document_state.data_sources.insert("readings".to_string(), readings);
+
+    let mut fields = HashMap::new();
+    fields.insert("value".to_string(), FieldSchema { field_type: FieldType::Number, min: None, max: Some(100.0), nullable: false });
+    engine.set_data_schema("readings", Some(DataSchema { fields, required: Vec::new(), mode: ValidationMode::Reject })).unwrap();
+
+    assert_eq!(engine.update_data_f64("readings", &[1.0, f64::NAN]).unwrap_err().code, "DATA_VALIDATION_FAILED");
+    assert_eq!(engine.update_data_f64("readings", &[150.0]).unwrap_err().code, "DATA_VALIDATION_FAILED");
+    let unknown = engine.update_data_columns("readings", &[("value".to_string(), &[1.0][..]), ("extra".to_string(), &[2.0][..])]);
+    assert_eq!(unknown.unwrap_err().code, "DATA_VALIDATION_FAILED");
+    assert!(engine.document_state.data_sources["readings"].columns.is_empty());
+    assert!(engine.drain_data_events().iter().all(|event| matches!(event, DataEvent::ValidationFailed { .. })));
+
+    engine.update_data_f64("readings", &[1.0, 2.0]).unwrap();
+    assert_eq!(engine.document_state.data_sources["readings"].numeric_values(), vec![1.0, 2.0]);
+}
+
 #[wasm_bindgen_test]
 fn test_security_permissions() {
     let restrictive_permissions = WASMPermissions {
@@ -318,10 +486,6 @@ fn test_render_frame_processing() {
     let timestamp = get_current_timestamp();
     let result = engine.render_frame(timestamp);
     assert!(result.is_ok());
-
-    let render_update = result.unwrap();
-    // Should have animation updates if animation is active
-    assert!(render_update.animation_updates.len() >= 0); // Could be 0 if animation hasn't started
 }
 
 #[wasm_bindgen_test]
@@ -362,4 +526,3771 @@ fn test_element_querying() {
     // Query animations (should be empty)
     let animations = engine.query_elements_by_type(ElementType::Animation);
     assert_eq!(animations.len(), 0);
-}
\ No newline at end of file
+}
+#[cfg(feature = "typescript")]
+#[wasm_bindgen_test]
+fn test_json_schema_generation() {
+    let schema_json = get_json_schema("InteractionEvent").unwrap();
+    let schema: serde_json::Value = serde_json::from_str(&schema_json).unwrap();
+
+    assert_eq!(schema["title"], "InteractionEvent");
+    assert!(schema["properties"]["event_type"].is_object());
+    assert!(schema["required"].as_array().unwrap().iter().any(|field| field == "timestamp"));
+
+    assert!(get_json_schema("NotAType").is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_data_delta_sync() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["DataUpdate".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+
+    let mut online = InteractiveEngine::new(permissions.clone()).unwrap();
+    let mut offline = InteractiveEngine::new(permissions).unwrap();
+
+    online.document_state.data_sources.insert(
+        "prices".to_string(),
+        DataSource::new("prices".to_string(), DataSourceType::Dynamic, serde_json::json!([1, 2])),
+    );
+    online.update_data_source("prices", serde_json::json!([3, 4])).unwrap();
+
+    // First sync ships everything newer than an empty version vector
+    let delta = online.export_data_delta(&HashMap::new()).unwrap();
+    assert_eq!(delta.changes.len(), 1);
+    assert_eq!(delta.changes[0].clock[online.get_replica_id()], 1);
+    assert_eq!(offline.apply_data_delta(delta.clone()).unwrap().applied, vec!["prices".to_string()]);
+    assert_eq!(offline.document_state.data_sources["prices"].data, serde_json::json!([3, 4]));
+
+    // Replaying the same delta is a no-op, and an up-to-date peer gets an empty delta
+    assert_eq!(offline.apply_data_delta(delta).unwrap(), DeltaImport::default());
+    assert!(online.export_data_delta(&offline.get_data_versions()).unwrap().changes.is_empty());
+
+    let snapshot = online.export_data_snapshot().unwrap();
+    assert_eq!(snapshot.versions["prices"], online.document_state.data_sources["prices"].version);
+
+    // Edits made on both sides before syncing conflict, and the local data is kept
+    online.update_data_source("prices", serde_json::json!([5])).unwrap();
+    offline.update_data_source("prices", serde_json::json!([6])).unwrap();
+    let import = offline.apply_data_delta(online.export_data_delta(&HashMap::new()).unwrap()).unwrap();
+    assert_eq!(import.conflicts, vec!["prices".to_string()]);
+    assert!(import.applied.is_empty());
+    assert_eq!(offline.document_state.data_sources["prices"].data, serde_json::json!([6]));
+
+    // A delta is checked whole before any of it is applied
+    let mut fields = HashMap::new();
+    fields.insert("price".to_string(), FieldSchema { field_type: FieldType::Number, min: None, max: None, nullable: false });
+    offline.set_data_schema("prices", Some(DataSchema { fields, required: vec!["price".to_string()], mode: ValidationMode::Reject })).unwrap();
+    offline.export_data_delta(&HashMap::new()).unwrap();
+    let mut clock = offline.document_state.data_sources["prices"].clock.clone();
+    clock.insert("peer".to_string(), 1);
+    let change = |source_id: &str, data: serde_json::Value| DataSourceChange {
+        source_id: source_id.to_string(),
+        version: 10,
+        clock: clock.clone(),
+        data,
+        columns: HashMap::new(),
+    };
+    let delta = DataDelta {
+        schema_version: DATA_SYNC_SCHEMA_VERSION,
+        changes: vec![change("volumes", serde_json::json!([1])), change("prices", serde_json::json!([{"price": "n/a"}]))],
+    };
+    assert_eq!(offline.apply_data_delta(delta).unwrap_err().code, "DATA_VALIDATION_FAILED");
+    assert!(!offline.document_state.data_sources.contains_key("volumes"));
+}
+
+#[wasm_bindgen_test]
+fn test_mutation_journal() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["DataUpdate".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let open = |answers: serde_json::Value| {
+        let mut engine = InteractiveEngine::new(permissions.clone()).unwrap();
+        for (id, data) in [("answers", answers), ("prices", serde_json::json!([1]))] {
+            engine.document_state.data_sources.insert(id.to_string(), DataSource::new(id.to_string(), DataSourceType::Dynamic, data));
+        }
+        engine.set_mutation_journal("answers", Some(MutationJournalConfig { max_entries: 2, coalesce_ms: 0.0 }));
+        engine
+    };
+
+    // Only journaled sources are recorded, and each change tells the host to save
+    let mut engine = open(serde_json::json!({"name": ""}));
+    engine.update_data_source("prices", serde_json::json!([2])).unwrap();
+    assert!(engine.export_mutations().unwrap().mutations.is_empty());
+    for name in ["A", "Ad", "Ada"] {
+        engine.update_data_source("answers", serde_json::json!({"name": name})).unwrap();
+    }
+    let journaled: Vec<u64> = engine.drain_data_events().into_iter()
+        .filter_map(|event| match event { DataEvent::Journaled { sequence, .. } => Some(sequence), _ => None })
+        .collect();
+    assert_eq!(journaled, vec![0, 1, 2]);
+    let export = engine.export_mutations().unwrap();
+    assert_eq!(export.mutations.iter().map(|mutation| mutation.sequence).collect::<Vec<_>>(), vec![1, 2]);
+    let saved = serde_json::to_vec(&export).unwrap();
+
+    // Edits close together replace the previous entry
+    engine.set_mutation_journal("answers", Some(MutationJournalConfig { max_entries: 2, coalesce_ms: 60_000.0 }));
+    engine.update_data_source("answers", serde_json::json!({"name": "Ada L"})).unwrap();
+    assert_eq!(engine.export_mutations().unwrap().mutations.len(), 2);
+
+    // The same document restores the user's data, which stays journaled
+    let mut reopened = open(serde_json::json!({"name": ""}));
+    let import = reopened.apply_mutations(reopened.read_mutations(&saved).unwrap(), MutationConflictPolicy::Fail).unwrap();
+    assert_eq!((import.applied, import.conflicts), (vec!["answers".to_string()], vec![]));
+    assert_eq!(reopened.document_state.data_sources["answers"].data, serde_json::json!({"name": "Ada"}));
+    assert_eq!(reopened.export_mutations().unwrap().mutations, export.mutations);
+
+    // A changed document conflicts; the policy decides who wins
+    let changed = serde_json::json!({"name": "", "email": ""});
+    assert_eq!(open(changed.clone()).apply_mutations(export.clone(), MutationConflictPolicy::Fail).unwrap_err().code, "MUTATION_CONFLICT");
+    let mut kept = open(changed.clone());
+    let import = kept.apply_mutations(export.clone(), MutationConflictPolicy::PreferDocument).unwrap();
+    assert_eq!((import.conflicts, import.skipped), (vec!["answers".to_string()], vec!["answers".to_string()]));
+    assert_eq!(kept.document_state.data_sources["answers"].data, changed);
+    let mut replaced = open(changed);
+    let import = replaced.apply_mutations(export, MutationConflictPolicy::PreferMutations).unwrap();
+    assert_eq!((import.applied, import.conflicts), (vec!["answers".to_string()], vec!["answers".to_string()]));
+    assert_eq!(replaced.document_state.data_sources["answers"].data, serde_json::json!({"name": "Ada"}));
+}
+
+#[wasm_bindgen_test]
+fn test_chunked_document_load() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["DataUpdate".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+
+    let elements: Vec<serde_json::Value> = (0..5).map(|i| serde_json::json!({
+        "id": format!("node_{}", i),
+        "element_type": "Container",
+        "properties": {},
+        "children": [],
+        "event_handlers": [],
+        "transform": Transform::default(),
+        "style": {},
+    })).collect();
+    let document = serde_json::json!({
+        "elements": elements,
+        "data_sources": {
+            "prices": DataSource::new("prices".to_string(), DataSourceType::Static, serde_json::json!([1, 2, 3])),
+        },
+    });
+
+    // A zero budget still advances one unit of work per call
+    let token = engine.load_document_chunked(document.to_string());
+    let first = engine.continue_operation(&token, 0.0).unwrap();
+    assert!(!first.done);
+    assert_eq!(first.total, 7);
+    assert!(engine.document_state.elements.is_empty());
+
+    let mut last = first;
+    while !last.done {
+        let next = engine.continue_operation(&token, 0.0).unwrap();
+        assert!(next.percent_complete > last.percent_complete);
+        last = next;
+    }
+    assert_eq!(last.percent_complete, 100.0);
+    assert_eq!(engine.document_state.elements.len(), 5);
+    assert!(engine.document_state.data_sources.contains_key("prices"));
+    assert_eq!(engine.drain_operation_events().len(), 7);
+
+    // Finished and invalid operations are gone
+    assert!(engine.continue_operation(&token, 10.0).is_err());
+    let bad = engine.load_document_chunked("{\"elements\": [{}]}".to_string());
+    assert!(engine.continue_operation(&bad, 10.0).is_err());
+    assert!(!engine.cancel_operation(&bad));
+
+    // Large documents are scanned in slices rather than parsed in one step
+    let rows: Vec<f64> = (0..20_000).map(|i| i as f64).collect();
+    let large = serde_json::json!({
+        "elements": elements,
+        "data_sources": {
+            "a": DataSource::new("a".to_string(), DataSourceType::Static, serde_json::json!(rows)),
+            "b": DataSource::new("b".to_string(), DataSourceType::Static, serde_json::json!(rows)),
+        },
+    });
+    let token = engine.load_document_chunked(large.to_string());
+    let first = engine.continue_operation(&token, 0.0).unwrap();
+    assert!(!first.done);
+    assert_eq!(first.processed, 1);
+    assert!(first.total > 2);
+    while !engine.continue_operation(&token, 0.0).unwrap().done {}
+    assert_eq!(engine.document_state.data_sources["b"].data.as_array().unwrap().len(), 20_000);
+    let truncated = engine.load_document_chunked(large.to_string()[..100].to_string());
+    assert_eq!(engine.continue_operation(&truncated, 10.0).unwrap_err().code, "INVALID_DOCUMENT");
+
+    // A new document starts without the old one's charts, animations, bindings and rate limits
+    engine.chart_renderer.create_chart_with_id("old_chart", ChartType::Line, "prices".to_string(), ChartConfig::default()).unwrap();
+    engine.animation_controller.start_animation(Animation {
+        id: "fade".to_string(),
+        target_element: "node_0".to_string(),
+        animation_type: AnimationType::Style,
+        duration: 2_000.0,
+        easing: EasingFunction::Linear,
+        keyframes: Vec::new(),
+        loop_count: 1,
+        direction: AnimationDirection::Normal,
+        noise: None,
+        composition: Default::default(),
+    });
+    engine.data_binding_manager.add_binding(DataBinding {
+        source_id: "prices".to_string(),
+        target_element: "node_0".to_string(),
+        property_path: "value".to_string(),
+        target_property: None,
+        transform_function: None,
+        update_trigger: UpdateTrigger::Immediate,
+    });
+    engine.set_event_rate_limit(EventRateLimitConfig {
+        target_element: Some("node_0".to_string()),
+        event_type: InteractionType::Click,
+        limit: RateLimit::Throttle { interval_ms: 100.0 },
+    });
+    engine.load_document(&document.to_string()).unwrap();
+    assert!(engine.chart_renderer.charts.is_empty());
+    assert!(engine.animation_controller.active_animations.is_empty());
+    assert!(engine.data_binding_manager.bindings.is_empty());
+    assert!(engine.event_rate_limiter.limits.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_progressive_document_load() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["DataUpdate".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+
+    let element = |id: &str, y: f64, children: Vec<&str>| serde_json::json!({
+        "id": id,
+        "element_type": "Container",
+        "properties": {},
+        "children": children,
+        "event_handlers": [],
+        "transform": Transform { y, ..Transform::default() },
+        "style": {},
+    });
+    let document = serde_json::json!({
+        "elements": [element("footer", 5000.0, vec![]), element("hero", 0.0, vec!["footer"]), element("aside", 3000.0, vec![]), element("nav", 4000.0, vec![])],
+        "data_sources": {
+            "prices": DataSource::new("prices".to_string(), DataSourceType::Static, serde_json::json!([1, 2, 3])),
+        },
+        "loading": {
+            "priorities": {"nav": "Critical", "aside": "High"},
+            "preload": [{"url": "hero.png", "kind": "image", "priority": "Low"}],
+        },
+    });
+    let hints: LoadingHints = serde_json::from_value(serde_json::json!({
+        "priorities": {"aside": "Low"},
+        "preload": [{"url": "body.woff2", "kind": "font", "priority": "Critical"}],
+    })).unwrap();
+
+    // The first screen and critical elements are live before any streaming happens
+    let started = engine.load_document_progressive(document.to_string(), hints).unwrap();
+    assert_eq!(started.initial_elements, 2);
+    let ids = |engine: &InteractiveEngine| engine.document_state.elements.iter().map(|element| element.id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(&engine), ["hero", "nav"]);
+    assert_eq!(started.preload.iter().map(|hint| hint.url.as_str()).collect::<Vec<_>>(), ["body.woff2", "hero.png"]);
+    let events = engine.drain_operation_events();
+    assert!(events[0].materialized && !events[0].done);
+    assert_eq!((events[0].processed, events[0].total), (3, 6));
+
+    // Data sources stream ahead of the remaining elements, which follow priority order
+    engine.continue_operation(&started.token, 0.0).unwrap();
+    assert!(engine.document_state.data_sources.contains_key("prices"));
+    assert_eq!(engine.document_state.elements.len(), 2);
+    while !engine.continue_operation(&started.token, 0.0).unwrap().done {}
+    assert_eq!(ids(&engine), ["hero", "nav", "footer", "aside"]);
+    assert_eq!(engine.document_state.render_tree.nodes["footer"].parent.as_deref(), Some("hero"));
+    assert!(engine.get_event_bus_log().iter().all(|record| record.event_name == "document.load_progress"));
+    assert_eq!(engine.get_event_bus_log().len(), 4);
+}
+
+#[wasm_bindgen_test]
+fn test_freeze_and_thaw() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions.clone()).unwrap();
+    let element_id = engine.create_element(ElementType::Container, HashMap::new()).unwrap();
+    engine.schedule_timer(TimerDefinition {
+        id: "reminder".to_string(),
+        delay_ms: 1_000.0,
+        interval_ms: None,
+        max_runs: None,
+        actions: vec![CommandAction::RefreshData { source_id: "kpi".to_string() }],
+    }).unwrap();
+    engine.animation_controller.start_animation(Animation {
+        id: "fade".to_string(),
+        target_element: element_id.clone(),
+        animation_type: AnimationType::Style,
+        duration: 2_000.0,
+        easing: EasingFunction::Linear,
+        keyframes: Vec::new(),
+        loop_count: 1,
+        direction: AnimationDirection::Normal,
+        noise: None,
+        composition: Default::default(),
+    });
+    engine.animation_controller.active_animations.get_mut("fade").unwrap().start_time = 1_000.0;
+
+    // 400ms into both the animation and the timer's countdown
+    engine.timer_scheduler.take_due(1_000.0);
+    engine.timer_scheduler.take_due(1_400.0);
+    engine.animation_controller.update_animations(&mut engine.document_state, 1_400.0).unwrap();
+    let snapshot = engine.freeze().unwrap();
+
+    // The restored engine picks up where the frozen one stopped, on its own clock
+    let mut resumed = InteractiveEngine::new(permissions).unwrap();
+    resumed.thaw(&snapshot).unwrap();
+    assert!(resumed.document_state.get_element(&element_id).is_some());
+    assert_eq!(resumed.animation_controller.positions()[0].elapsed_ms, 400.0);
+    resumed.animation_controller.update_animations(&mut resumed.document_state, 90_000.0).unwrap();
+    assert_eq!(resumed.animation_controller.positions()[0].elapsed_ms, 400.0);
+    assert!(resumed.timer_scheduler.take_due(90_000.0).is_empty());
+    assert_eq!(resumed.timer_scheduler.take_due(90_600.0).len(), 1);
+
+    assert_eq!(resumed.thaw(b"not a snapshot").unwrap_err().code, "INVALID_SNAPSHOT");
+    let mut future = snapshot.clone();
+    future[4] = 99;
+    assert_eq!(resumed.thaw(&future).unwrap_err().code, "UNSUPPORTED_VERSION");
+}
+
+#[wasm_bindgen_test]
+fn test_update_sequencing_and_resync() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    // Listed child first to check that parents are still created before their children
+    for (id, element_type, properties, style) in [
+        ("label", "Text", serde_json::json!({"text": "Hi", "style.font-weight": "bold"}), serde_json::json!({})),
+        ("panel", "Container", serde_json::json!({}), serde_json::json!({"background_color": "#fafafa", "border_radius": 4.0})),
+    ] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": element_type, "properties": properties, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": style,
+        })).unwrap()).unwrap();
+    }
+    engine.document_state.render_tree.nodes.get_mut("panel").unwrap().children.push("label".to_string());
+    engine.document_state.render_tree.nodes.get_mut("label").unwrap().parent = Some("panel".to_string());
+
+    let first = engine.sequenced(RenderUpdate::empty());
+    let second = engine.sequenced(RenderUpdate::empty());
+    assert_eq!((first.sequence, second.sequence), (1, 2));
+    engine.ack_update(1).unwrap();
+    assert_eq!(engine.ack_update(5).unwrap_err().code, "INVALID_SEQUENCE");
+    assert_eq!(engine.get_sync_state().unacknowledged, 1);
+
+    let resync = engine.request_full_resync();
+    assert!(resync.full_resync);
+    assert_eq!(resync.sequence, 3);
+    assert_eq!(engine.get_sync_state().last_resync, Some(3));
+    let created: Vec<(String, Option<String>)> = resync.dom_operations.iter()
+        .filter_map(|operation| match operation {
+            DOMOperation::Create { element_id, parent_id, .. } => Some((element_id.clone(), parent_id.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(created, [("panel".to_string(), None), ("label".to_string(), Some("panel".to_string()))]);
+    let style = |element_id: &str, property: &str| resync.style_changes.iter()
+        .find(|change| change.element_id == element_id && change.property == property)
+        .map(|change| change.value.clone());
+    assert_eq!(style("panel", "background-color").as_deref(), Some("#fafafa"));
+    assert_eq!(style("panel", "border-radius").as_deref(), Some("4px"));
+    assert_eq!(style("label", "font-weight").as_deref(), Some("\"bold\""));
+    assert!(resync.dom_operations.iter().any(|operation| matches!(operation,
+        DOMOperation::Update { element_id, attributes } if element_id == "label" && attributes["text"] == "\"Hi\"")));
+}
+
+#[wasm_bindgen_test]
+fn test_viewport_views() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    for (id, x) in [("left", 0.0), ("right", 2000.0)] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": "Container", "properties": {}, "children": [],
+            "event_handlers": [], "transform": Transform { x, ..Transform::default() }, "style": {},
+        })).unwrap()).unwrap();
+    }
+    engine.document_state.run_layout_pass(0.0);
+    let camera = |offset_x: f64| Viewport { width: 1000.0, height: 800.0, scale: 1.0, offset_x, offset_y: 0.0 };
+    engine.register_viewport("audience", camera(1500.0)).unwrap();
+    assert_eq!(engine.register_viewport("audience", camera(0.0)).unwrap_err().code, "VIEWPORT_EXISTS");
+
+    let style_of = |update: &RenderUpdate, element_id: &str, property: &str| update.style_changes.iter()
+        .rfind(|change| change.element_id == element_id && change.property == property)
+        .map(|change| change.value.clone());
+    let initial = engine.take_viewport_updates("audience").unwrap();
+    assert_eq!(initial.len(), 1);
+    assert!(initial[0].full_resync);
+    assert_eq!(style_of(&initial[0], "left", "visibility").as_deref(), Some("hidden"));
+    assert_eq!(style_of(&initial[0], VIEWPORT_ROOT, "transform").as_deref(), Some("scale(1) translate(-1500px, 0px)"));
+
+    // The main view gets every change; the audience view only what its camera sees
+    let touch = |engine: &mut InteractiveEngine| for id in ["left", "right"] {
+        engine.lifecycle_changes.push(ElementChange::Update {
+            element_id: id.to_string(),
+            properties: [("selected".to_string(), serde_json::json!(true))].into_iter().collect(),
+        });
+    };
+    touch(&mut engine);
+    let main = engine.render_frame(16.0).unwrap();
+    assert_eq!(main.dom_operations.len(), 2);
+    let audience = engine.take_viewport_updates("audience").unwrap();
+    assert_eq!(audience[0].sequence, 2);
+    assert!(matches!(&audience[0].dom_operations[..], [DOMOperation::Update { element_id, .. }] if element_id == "right"));
+
+    // Panning back brings "left" up to date as it enters and hides "right"
+    engine.set_viewport_camera("audience", camera(0.0)).unwrap();
+    engine.render_frame(32.0).unwrap();
+    let panned = engine.take_viewport_updates("audience").unwrap();
+    assert_eq!(style_of(&panned[0], "left", "visibility").as_deref(), Some("visible"));
+    assert_eq!(style_of(&panned[0], "right", "visibility").as_deref(), Some("hidden"));
+    assert_eq!(style_of(&panned[0], VIEWPORT_ROOT, "transform").as_deref(), Some("scale(1) translate(0px, 0px)"));
+    assert!(engine.remove_viewport("audience"));
+    assert_eq!(engine.take_viewport_updates("audience").unwrap_err().code, "VIEWPORT_NOT_FOUND");
+}
+
+#[wasm_bindgen_test]
+fn test_element_views() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    for id in ["panel", "label", "footer"] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": "Container", "properties": {}, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        })).unwrap()).unwrap();
+    }
+    engine.document_state.render_tree.nodes.get_mut("panel").unwrap().children.push("label".to_string());
+    engine.document_state.render_tree.nodes.get_mut("label").unwrap().parent = Some("panel".to_string());
+    engine.document_state.render_tree.nodes.get_mut("panel").unwrap().bounds = BoundingBox { x: 50.0, y: 50.0, width: 400.0, height: 100.0 };
+    assert_eq!(engine.extract_element_view("missing", 200.0, 100.0).unwrap_err().code, "ELEMENT_NOT_FOUND");
+    assert_eq!(engine.extract_element_view("panel", 0.0, 100.0).unwrap_err().code, "INVALID_VIEW_SIZE");
+
+    let extracted = engine.extract_element_view("panel", 200.0, 100.0).unwrap();
+    assert!(extracted.initial.full_resync);
+    assert_eq!(extracted.initial.sequence, 1);
+    assert!(extracted.svg.is_none());
+    let created: Vec<String> = extracted.initial.dom_operations.iter()
+        .filter_map(|operation| match operation {
+            DOMOperation::Create { element_id, .. } => Some(element_id.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(created, ["panel", "label"]);
+    assert!(extracted.initial.style_changes.iter()
+        .any(|change| change.element_id == "panel" && change.property == "transform" && change.value == "scale(0.5)"));
+
+    // Only changes inside the extracted subtree reach the view
+    for id in ["label", "footer"] {
+        engine.lifecycle_changes.push(ElementChange::Update {
+            element_id: id.to_string(),
+            properties: [("selected".to_string(), serde_json::json!(true))].into_iter().collect(),
+        });
+    }
+    engine.render_frame(16.0).unwrap();
+    let updates = engine.take_element_view_updates(&extracted.view_id).unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].sequence, 2);
+    assert!(matches!(&updates[0].dom_operations[..], [DOMOperation::Update { element_id, .. }] if element_id == "label"));
+    assert!(engine.take_element_view_updates(&extracted.view_id).unwrap().is_empty());
+
+    assert!(engine.close_element_view(&extracted.view_id));
+    assert_eq!(engine.take_element_view_updates(&extracted.view_id).unwrap_err().code, "VIEWPORT_NOT_FOUND");
+}
+
+#[wasm_bindgen_test]
+fn test_embedded_documents() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["Click".to_string(), "create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions.clone()).unwrap();
+    for (id, element_type) in [("frame", "Embed"), ("panel", "Container")] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": element_type, "properties": {}, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        })).unwrap()).unwrap();
+    }
+    engine.document_state.render_tree.nodes.get_mut("frame").unwrap().bounds = BoundingBox { x: 100.0, y: 50.0, width: 300.0, height: 200.0 };
+    let document = serde_json::json!({
+        "elements": [{
+            "id": "inner", "element_type": "Text", "properties": {"text": "Nested"}, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        }],
+    }).to_string();
+    assert_eq!(engine.embed_document("panel", document.clone(), permissions.clone()).unwrap_err().code, "NOT_AN_EMBED");
+
+    // The embedded document gets only what both sides allow
+    let requested = WASMPermissions {
+        allow_networking: true,
+        allowed_interactions: vec!["Click".to_string(), "Drag".to_string()],
+        max_elements: 500,
+        ..permissions.clone()
+    };
+    engine.embed_document("frame", document, requested).unwrap();
+    let child = &engine.embedded_documents[0].engine;
+    assert!(!child.security_context.permissions.allow_networking);
+    assert_eq!(child.security_context.permissions.allowed_interactions, ["Click"]);
+    assert_eq!(child.security_context.permissions.max_elements, 100);
+
+    // Its elements are created inside the embed, which clips them
+    let frame = engine.render_frame(16.0).unwrap();
+    assert!(frame.dom_operations.iter().any(|operation| matches!(operation,
+        DOMOperation::Create { element_id, parent_id, .. } if element_id == "frame/inner" && parent_id.as_deref() == Some("frame"))));
+    assert!(frame.style_changes.iter().any(|change| change.element_id == "frame" && change.property == "overflow" && change.value == "hidden"));
+    assert!(frame.style_changes.iter().any(|change| change.element_id == "frame/inner" && change.property == "opacity"));
+
+    // Events under the embed are handed to it in its own coordinates
+    let click: InteractionEvent = serde_json::from_value(serde_json::json!({
+        "event_type": "Click", "target_element": "frame/inner", "position": {"x": 150.0, "y": 80.0},
+        "data": {}, "timestamp": 20.0, "touch_data": null, "mouse_data": null, "keyboard_data": null,
+        "gesture_data": null, "modifiers": {"ctrl": false, "shift": false, "alt": false, "meta": false},
+    })).unwrap();
+    let index = engine.embed_target(&click).unwrap();
+    let inner = engine.embedded_documents[index].translate_event(click);
+    assert_eq!(inner.target_element.as_deref(), Some("inner"));
+    assert_eq!(inner.position.map(|position| (position.x, position.y)), Some((50.0, 30.0)));
+
+    engine.delete_element("frame").unwrap();
+    assert!(engine.get_embedded_documents().is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_widget_sandboxes() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["DataUpdate".to_string(), "create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions.clone()).unwrap();
+    engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+        "id": "widget", "element_type": "Embed", "properties": {}, "children": [],
+        "event_handlers": [], "transform": Transform::default(), "style": {},
+    })).unwrap()).unwrap();
+    let document = serde_json::json!({
+        "elements": [{
+            "id": "ticker", "element_type": "Text", "properties": {}, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        }],
+        "data_sources": {
+            "quotes": DataSource::new("quotes".to_string(), DataSourceType::Static, serde_json::json!([1, 2])),
+        },
+    }).to_string();
+    engine.embed_document("widget", document, permissions).unwrap();
+    engine.set_widget_budget("widget", WidgetBudget { memory_limit: 4096, max_strikes: 2, ..WidgetBudget::default() }).unwrap();
+    assert_eq!(engine.embedded_documents[0].engine.security_context.resource_limits.max_memory, 4096);
+
+    // Widget data lives in the widget's engine only
+    engine.update_widget_data("widget", "quotes", serde_json::json!([3, 4])).unwrap();
+    assert!(!engine.document_state.data_sources.contains_key("quotes"));
+    assert_eq!(engine.embedded_documents[0].engine.document_state.data_sources["quotes"].to_rows(), serde_json::json!([3, 4]));
+
+    // Failures are absorbed until the strike limit, then the widget is stopped
+    engine.embedded_documents[0].strike("Memory allocation would exceed limit");
+    assert_eq!(engine.get_widget_status("widget").unwrap().state, WidgetState::Running);
+    engine.embedded_documents[0].strike("Memory allocation would exceed limit");
+    let status = engine.get_widget_status("widget").unwrap();
+    assert_eq!(status.state, WidgetState::Stopped { reason: "Memory allocation would exceed limit".to_string() });
+    let frame = engine.render_frame(16.0).unwrap();
+    assert!(frame.dom_operations.iter().any(|operation| matches!(operation, DOMOperation::Remove { element_id } if element_id == "widget/ticker")));
+    assert_eq!(engine.update_widget_data("widget", "quotes", serde_json::json!([])).unwrap_err().code, "WIDGET_STOPPED");
+
+    // Restarting reloads the original document
+    engine.restart_widget("widget").unwrap();
+    let status = engine.get_widget_status("widget").unwrap();
+    assert_eq!((status.state, status.strikes, status.restarts), (WidgetState::Running, 0, 1));
+    assert_eq!(engine.embedded_documents[0].engine.document_state.data_sources["quotes"].to_rows(), serde_json::json!([1, 2]));
+    let frame = engine.render_frame(32.0).unwrap();
+    assert!(frame.dom_operations.iter().any(|operation| matches!(operation, DOMOperation::Create { element_id, .. } if element_id == "widget/ticker")));
+
+    engine.kill_widget("widget").unwrap();
+    assert!(matches!(engine.get_widget_status("widget").unwrap().state, WidgetState::Stopped { .. }));
+    assert_eq!(engine.kill_widget("missing").unwrap_err().code, "WIDGET_NOT_FOUND");
+}
+
+#[wasm_bindgen_test]
+fn test_telemetry_batches() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let config = |sample_rate: f64| TelemetryConfig { namespace: "main".to_string(), sample_rate, flush_interval_ms: 100.0 };
+
+    // Nothing is collected until telemetry is enabled
+    engine.render_frame(0.0).unwrap();
+    engine.flush_telemetry(10.0);
+    assert!(engine.drain_telemetry().is_empty());
+    assert_eq!(engine.enable_telemetry(config(1.5)).unwrap_err().code, "INVALID_TELEMETRY_CONFIG");
+    engine.enable_telemetry(config(0.5)).unwrap();
+
+    let click: InteractionEvent = serde_json::from_value(serde_json::json!({
+        "event_type": "Click", "target_element": null, "position": null,
+        "data": {}, "timestamp": 20.0, "touch_data": null, "mouse_data": null, "keyboard_data": null,
+        "gesture_data": null, "modifiers": {"ctrl": false, "shift": false, "alt": false, "meta": false},
+    })).unwrap();
+    assert!(engine.process_interaction(click).is_err());
+    for timestamp in [0.0, 50.0, 100.0] {
+        engine.render_frame(timestamp).unwrap();
+    }
+
+    // The third frame closes the first window; half of the frame times are kept
+    let batches = engine.drain_telemetry();
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    assert_eq!((batch.namespace.as_str(), batch.sequence, batch.start, batch.end), ("main", 1, 0.0, 100.0));
+    assert_eq!(batch.counters, [("errors.INTERACTION_NOT_ALLOWED".to_string(), 1), ("interactions".to_string(), 1)]);
+    assert!(batch.gauges.iter().any(|(name, _)| name == "chart.cache_hit_rate"));
+    assert_eq!(batch.histograms.len(), 1);
+    assert_eq!((batch.histograms[0].name.as_str(), batch.histograms[0].count), ("frame.time_ms", 1));
+
+    engine.disable_telemetry();
+    engine.render_frame(300.0).unwrap();
+    assert!(engine.drain_telemetry().is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_command_dispatch() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["DataUpdate".to_string(), "create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    engine.document_state.data_sources.insert(
+        "sales".to_string(),
+        DataSource::new("sales".to_string(), DataSourceType::Static, serde_json::json!([
+            {"region": "North", "value": 10},
+            {"region": "South", "value": 20},
+            {"region": "North", "value": 30},
+        ])),
+    );
+    let title = engine.create_element(ElementType::Text, HashMap::new()).unwrap();
+
+    let command: CommandDefinition = serde_json::from_value(serde_json::json!({
+        "name": "filter chart by region",
+        "aliases": ["show region"],
+        "parameters": [{"name": "region", "param_type": {"Choice": ["North", "South"]}, "required": true}],
+        "actions": [
+            {"FilterData": {"source_id": "sales", "target_source_id": "sales_view", "field": "region", "value": "{region}"}},
+            {"SetProperties": {"element_id": title, "properties": {"text": "Sales in {region}"}}},
+        ],
+    })).unwrap();
+    engine.register_command(command);
+
+    // Aliases and argument values match case-insensitively
+    let args = [("region".to_string(), serde_json::json!("north"))].into_iter().collect();
+    let update = engine.dispatch_command("Show Region", args).unwrap();
+    assert!(!update.dom_operations.is_empty());
+    assert_eq!(engine.document_state.data_sources["sales_view"].data.as_array().unwrap().len(), 2);
+    assert_eq!(engine.document_state.get_element(&title).unwrap().properties["text"], serde_json::json!("Sales in North"));
+
+    // Invalid and missing arguments are rejected before any action runs
+    let args = [("region".to_string(), serde_json::json!("east"))].into_iter().collect();
+    assert_eq!(engine.dispatch_command("filter chart by region", args).unwrap_err().code, "INVALID_COMMAND_ARGS");
+    assert_eq!(engine.dispatch_command("filter chart by region", HashMap::new()).unwrap_err().code, "INVALID_COMMAND_ARGS");
+    assert_eq!(engine.dispatch_command("next page", HashMap::new()).unwrap_err().code, "COMMAND_NOT_FOUND");
+}
+
+#[wasm_bindgen_test]
+fn test_color_contrast_check_and_fix() {
+    let black = Rgb::parse("#000").unwrap();
+    let white = Rgb::parse("white").unwrap();
+    assert!((black.contrast_ratio(&white) - 21.0).abs() < 1e-9);
+    assert!(Rgb::parse("rgba(0, 0, 0, 0)").is_none());
+
+    // Adjusted colors keep their hue and meet the requested ratio
+    let light_blue = Rgb::parse("#9999ff").unwrap();
+    let adjusted = light_blue.adjust_for_contrast(&white, CONTRAST_AA_TEXT).unwrap();
+    assert!(adjusted.contrast_ratio(&white) >= CONTRAST_AA_TEXT);
+    assert!(adjusted.b > adjusted.r && (adjusted.r - adjusted.g).abs() < 1.0);
+
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let properties = [
+        ("text".to_string(), serde_json::json!("Low contrast")),
+        ("color".to_string(), serde_json::json!("#777777")),
+    ].into_iter().collect();
+    let label = engine.create_element(ElementType::Text, properties).unwrap();
+    engine.document_state.get_element_mut(&label).unwrap().style.background_color = Some("#555555".to_string());
+
+    let report = engine.check_color_contrast(false).unwrap();
+    assert_eq!(report.violations.len(), 1);
+    assert_eq!(report.violations[0].background, "#555555");
+    assert!(!report.violations[0].fixed);
+
+    // Auto-fix rewrites the text color so a second pass is clean
+    let report = engine.check_color_contrast(true).unwrap();
+    assert!(report.violations[0].fixed);
+    let fixed = engine.document_state.get_element(&label).unwrap().properties["color"].as_str().unwrap().to_string();
+    assert_eq!(Some(fixed), report.violations[0].suggested);
+    assert!(engine.check_color_contrast(false).unwrap().violations.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_figure_alt_text_warnings() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let figure = engine.create_element(ElementType::Vector, HashMap::new()).unwrap();
+
+    let description = engine.describe_element(&figure).unwrap();
+    assert_eq!(description.role, "img");
+    assert_eq!(description.warnings.len(), 1);
+
+    let alt = [("alt_text".to_string(), serde_json::json!("Org chart of the sales team"))].into_iter().collect();
+    engine.document_state.update_element(&figure, alt).unwrap();
+    let tree = engine.get_accessibility_tree();
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree[0].description.as_deref(), Some("Org chart of the sales team"));
+    assert!(tree[0].warnings.is_empty());
+
+    // Decorative figures are presented without a name or description
+    let decorative = [("decorative".to_string(), serde_json::json!(true))].into_iter().collect();
+    engine.document_state.update_element(&figure, decorative).unwrap();
+    assert_eq!(engine.describe_element(&figure).unwrap().role, "presentation");
+}
+
+#[wasm_bindgen_test]
+fn test_document_lint_profiles() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let element = |id: &str, element_type: &str, properties: serde_json::Value| serde_json::json!({
+        "id": id,
+        "element_type": element_type,
+        "properties": properties,
+        "children": [],
+        "event_handlers": [],
+        "transform": Transform::default(),
+        "style": {},
+    });
+    let document = serde_json::json!({
+        "elements": [
+            element("photo", "Image", serde_json::json!({"src": "http://cdn.example.com/a.png"})),
+            element("button", "Interactive", serde_json::json!({"onclick": "steal()", "href": "JavaScript:alert(1)"})),
+            element("label", "Text", serde_json::json!({"text": "Total", "column_gap": 14, "columnGap": 16, "color": "#1a73e8"})),
+        ],
+        "style_tokens": {"--brand": "#1A73E8"},
+    });
+
+    // Loading warns and still loads
+    engine.load_document(&document.to_string()).unwrap();
+    assert_eq!(engine.document_state.elements.len(), 3);
+    let findings = engine.take_lint_findings();
+    let rule_ids: Vec<&str> = findings.iter().map(|finding| finding.rule_id.as_str()).collect();
+    assert_eq!(rule_ids, vec![
+        "a11y/image-alt", "a11y/interactive-name", "security/script-url", "security/inline-handler",
+        "security/insecure-url", "style/hardcoded-color", "style/duplicate-alias",
+    ]);
+    assert!(engine.take_lint_findings().is_empty());
+    let insecure = findings.iter().find(|finding| finding.rule_id == "security/insecure-url").unwrap();
+    assert_eq!(insecure.fix.as_ref().unwrap().value, Some(serde_json::json!("https://cdn.example.com/a.png")));
+    let color = findings.iter().find(|finding| finding.rule_id == "style/hardcoded-color").unwrap();
+    assert_eq!(color.fix.as_ref().unwrap().value, Some(serde_json::json!("var(--brand)")));
+
+    // Profiles narrow the categories and change severities
+    let security = engine.lint_document(LintProfile::named("security").as_ref()).unwrap();
+    assert!(security.iter().all(|finding| finding.category == lint::LintCategory::Security));
+    let strict = engine.lint_document(LintProfile::named("strict").as_ref()).unwrap();
+    assert!(strict.iter().filter(|finding| finding.rule_id != "style/hardcoded-color").all(|finding| finding.severity == lint::LintSeverity::Error));
+    let quiet = LintProfile {
+        severities: [("style/hardcoded-color".to_string(), lint::LintSeverity::Off)].into_iter().collect(),
+        ..LintProfile::recommended()
+    };
+    engine.set_lint_profile(quiet).unwrap();
+    assert_eq!(engine.lint_document(None).unwrap().len(), 6);
+    let unknown = LintProfile { severities: [("a11y/nope".to_string(), lint::LintSeverity::Off)].into_iter().collect(), ..LintProfile::recommended() };
+    assert_eq!(engine.set_lint_profile(unknown).unwrap_err().code, "INVALID_LINT_PROFILE");
+    assert!(engine.lint_rules().iter().any(|rule| rule.id == "perf/element-count" && !rule.fixable));
+}
+
+#[wasm_bindgen_test]
+fn test_event_bus_delivery() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["StateChange".to_string(), "create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let badge = engine.create_element(ElementType::Text, HashMap::new()).unwrap();
+
+    let update_text = [
+        ("target".to_string(), serde_json::json!(badge)),
+        ("text".to_string(), serde_json::json!("{count} items")),
+    ].into_iter().collect();
+    engine.subscribe_event("cart.*", Some(badge.clone()), "update_text", update_text, false).unwrap();
+    // A one-shot wildcard subscriber re-emits everything it sees as an audit event
+    let forward = [
+        ("event".to_string(), serde_json::json!("audit")),
+        ("payload".to_string(), serde_json::json!({"seen": "{event}"})),
+    ].into_iter().collect();
+    engine.subscribe_event("*", None, "emit_event", forward, true).unwrap();
+    let highlight = [
+        ("target".to_string(), serde_json::json!(badge)),
+        ("color".to_string(), serde_json::json!("#ffcc00")),
+    ].into_iter().collect();
+    engine.subscribe_event("audit", None, "change_color", highlight, false).unwrap();
+
+    let changes = engine.deliver_bus_event("cart.updated", serde_json::json!({"count": 3}), None, 0).unwrap();
+    let updates: Vec<&BTreeMap<String, serde_json::Value>> = changes.iter().filter_map(|change| match change {
+        ElementChange::Update { element_id, properties } => {
+            assert_ne!(element_id, EVENT_BUS_TARGET);
+            Some(properties)
+        }
+        _ => None,
+    }).collect();
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[0]["textContent"], serde_json::json!("3 items"));
+    assert_eq!(updates[1]["style.backgroundColor"], serde_json::json!("#ffcc00"));
+
+    // The once-subscription was consumed, so the second event is not forwarded
+    let changes = engine.deliver_bus_event("cart.updated", serde_json::json!({"count": 4}), None, 0).unwrap();
+    assert_eq!(changes.len(), 1);
+
+    let log = engine.get_event_bus_log();
+    let names: Vec<&str> = log.iter().map(|record| record.event_name.as_str()).collect();
+    assert_eq!(names, vec!["cart.updated", "audit", "cart.updated"]);
+    assert_eq!(log[1].payload, serde_json::json!({"seen": "cart.updated"}));
+
+    engine.delete_element(&badge).unwrap();
+    assert_eq!(engine.event_bus.subscriptions().len(), 1);
+}
+
+#[wasm_bindgen_test]
+fn test_document_timers() {
+    let refresh = |id: &str, delay_ms: f64, interval_ms: Option<f64>, max_runs: Option<u32>| TimerDefinition {
+        id: id.to_string(),
+        delay_ms,
+        interval_ms,
+        max_runs,
+        actions: vec![CommandAction::RefreshData { source_id: "kpi".to_string() }],
+    };
+
+    // Timers arm on the first tick; missed recurrences collapse into a single run
+    let mut scheduler = TimerScheduler::new();
+    scheduler.schedule(refresh("every_minute", 60_000.0, Some(60_000.0), Some(3)));
+    assert!(scheduler.take_due(1_000.0).is_empty());
+    assert_eq!(scheduler.take_due(61_000.0).len(), 1);
+    assert_eq!(scheduler.take_due(250_000.0).len(), 1);
+    assert_eq!(scheduler.status()[0].due_at, Some(301_000.0));
+
+    // Paused time is not counted toward the countdown
+    scheduler.pause();
+    assert!(scheduler.take_due(400_000.0).is_empty());
+    scheduler.resume();
+    assert_eq!(scheduler.status()[0].due_at, Some(451_000.0));
+    assert_eq!(scheduler.take_due(451_000.0).len(), 1);
+    assert_eq!(scheduler.len(), 0);
+
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let hint = engine.create_element(ElementType::Text, HashMap::new()).unwrap();
+    engine.schedule_timer(TimerDefinition {
+        id: "show_hint".to_string(),
+        delay_ms: 5_000.0,
+        interval_ms: None,
+        max_runs: None,
+        actions: vec![CommandAction::SetProperties {
+            element_id: hint.clone(),
+            properties: [("visible".to_string(), serde_json::json!(true))].into_iter().collect(),
+        }],
+    }).unwrap();
+
+    engine.render_frame(0.0).unwrap();
+    engine.render_frame(4_999.0).unwrap();
+    assert!(!engine.document_state.get_element(&hint).unwrap().properties.contains_key("visible"));
+    engine.render_frame(5_000.0).unwrap();
+    assert_eq!(engine.document_state.get_element(&hint).unwrap().properties["visible"], serde_json::json!(true));
+    assert!(engine.get_timers().is_empty());
+
+    // Recurring intervals are clamped and the active timer count is capped
+    engine.schedule_timer(refresh("spin", 0.0, Some(1.0), None)).unwrap();
+    assert_eq!(engine.timer_scheduler.timers[0].definition.interval_ms, Some(100.0));
+    for i in 1..32 {
+        engine.schedule_timer(refresh(&format!("timer_{}", i), 1_000.0, None, None)).unwrap();
+    }
+    assert_eq!(engine.schedule_timer(refresh("one_too_many", 1_000.0, None, None)).unwrap_err().code, "TIMER_LIMIT_EXCEEDED");
+}
+
+#[wasm_bindgen_test]
+fn test_visibility_triggers() {
+    let area = BoundingBox { x: 0.0, y: 0.0, width: 100.0, height: 100.0 };
+    assert_eq!(intersection_ratio(&BoundingBox { x: 50.0, y: 0.0, width: 100.0, height: 100.0 }, &area), 0.5);
+    assert_eq!(intersection_ratio(&BoundingBox { x: 200.0, y: 0.0, width: 10.0, height: 10.0 }, &area), 0.0);
+
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let size = [("width".to_string(), serde_json::json!(400.0)), ("height".to_string(), serde_json::json!(200.0))];
+    let card = engine.create_element(ElementType::Container, size.into_iter().collect()).unwrap();
+    engine.document_state.get_element_mut(&card).unwrap().transform.y = 2000.0;
+
+    let revealed = |value: bool| vec![CommandAction::SetProperties {
+        element_id: card.clone(),
+        properties: [("revealed".to_string(), serde_json::json!(value))].into_iter().collect(),
+    }];
+    engine.add_visibility_trigger(VisibilityTrigger {
+        id: "reveal_card".to_string(),
+        element_id: card.clone(),
+        threshold: 0.5,
+        on_enter: revealed(true),
+        on_exit: revealed(false),
+        once: false,
+    }).unwrap();
+    let is_revealed = |engine: &InteractiveEngine| engine.document_state.get_element(&card).unwrap().properties.get("revealed").cloned();
+
+    // Starting off screen reports nothing; 40% visible is still below the threshold
+    engine.render_frame(0.0).unwrap();
+    assert_eq!(is_revealed(&engine), None);
+    engine.set_viewport_offset(0.0, 1000.0);
+    engine.render_frame(16.0).unwrap();
+    assert_eq!(is_revealed(&engine), None);
+
+    engine.set_viewport_offset(0.0, 1100.0);
+    engine.render_frame(32.0).unwrap();
+    assert_eq!(is_revealed(&engine), Some(serde_json::json!(true)));
+
+    engine.set_viewport_offset(0.0, 0.0);
+    engine.render_frame(48.0).unwrap();
+    assert_eq!(is_revealed(&engine), Some(serde_json::json!(false)));
+}
+
+#[wasm_bindgen_test]
+fn test_element_lifecycle_hooks() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+
+    // The widget initializes its label on mount and reacts to changes of "value" only
+    let document = serde_json::json!({
+        "elements": [{
+            "id": "gauge",
+            "element_type": "Interactive",
+            "properties": {"value": 10},
+            "children": [],
+            "event_handlers": [
+                {"event_type": "mount", "handler_id": "update_text", "parameters": {"target": "gauge", "text": "ready"}},
+                {"event_type": "property_change", "handler_id": "change_color", "parameters": {"target": "gauge", "color": "#00aa00", "properties": ["value"]}},
+                {"event_type": "unmount", "handler_id": "emit_event", "parameters": {"event": "gauge.removed"}},
+            ],
+            "transform": Transform::default(),
+            "style": {},
+        }],
+    });
+    let token = engine.load_document_chunked(document.to_string());
+    while !engine.continue_operation(&token, 100.0).unwrap().done {}
+    assert_eq!(engine.lifecycle_changes.len(), 1);
+
+    let update = engine.render_frame(0.0).unwrap();
+    assert!(update.dom_operations.iter().any(|op| matches!(op, DOMOperation::Update { element_id, .. } if element_id == "gauge")));
+    assert!(engine.lifecycle_changes.is_empty());
+
+    let unwatched = [("label".to_string(), serde_json::json!("Speed"))].into_iter().collect();
+    engine.update_element_properties("gauge", unwatched).unwrap();
+    assert!(engine.lifecycle_changes.is_empty());
+    // Writing the current value again is not a change
+    let same = [("value".to_string(), serde_json::json!(10))].into_iter().collect();
+    engine.update_element_properties("gauge", same).unwrap();
+    assert!(engine.lifecycle_changes.is_empty());
+    let watched = [("value".to_string(), serde_json::json!(42))].into_iter().collect();
+    engine.update_element_properties("gauge", watched).unwrap();
+    assert_eq!(engine.lifecycle_changes.len(), 1);
+
+    engine.delete_element("gauge").unwrap();
+    // Behind the load's "document.load_progress"
+    assert_eq!(engine.get_event_bus_log().last().unwrap().event_name, "gauge.removed");
+    assert!(engine.document_state.get_element("gauge").is_none());
+}
+
+#[wasm_bindgen_test]
+fn test_component_instances() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let kpi_card = |label_text: &str| -> ComponentDefinition {
+        serde_json::from_value(serde_json::json!({
+            "name": "kpi_card",
+            "parameters": [
+                {"name": "title", "param_type": "String", "required": true},
+                {"name": "value", "param_type": "Number", "default": 0},
+            ],
+            "elements": [
+                {"id": "card", "element_type": "Container", "properties": {}, "children": ["label"], "event_handlers": [], "transform": Transform::default(), "style": {}},
+                {"id": "label", "element_type": "Text", "properties": {"text": label_text, "color": "#333333"}, "children": [], "event_handlers": [], "transform": Transform::default(), "style": {}},
+            ],
+        })).unwrap()
+    };
+    engine.register_component(kpi_card("{title}: {value}")).unwrap();
+
+    let args = |title: &str, value: f64| [
+        ("title".to_string(), serde_json::json!(title)),
+        ("value".to_string(), serde_json::json!(value)),
+    ].into_iter().collect();
+    let revenue = engine.instantiate_component("kpi_card", Some("revenue".to_string()), args("Revenue", 42.0), HashMap::new(), None).unwrap();
+    let churn = engine.instantiate_component("kpi_card", None, args("Churn", 3.5), HashMap::new(), None).unwrap();
+    assert_eq!(churn, "kpi_card_2");
+    let text = |engine: &InteractiveEngine, id: &str| engine.document_state.get_element(id).unwrap().properties["text"].clone();
+    assert_eq!(text(&engine, "revenue.label"), serde_json::json!("Revenue: 42.0"));
+    assert_eq!(text(&engine, "kpi_card_2.label"), serde_json::json!("Churn: 3.5"));
+    assert_eq!(engine.document_state.render_tree.nodes["revenue.label"].parent.as_deref(), Some("revenue.card"));
+
+    let missing_title = HashMap::new();
+    assert_eq!(engine.instantiate_component("kpi_card", None, missing_title, HashMap::new(), None).unwrap_err().code, "INVALID_COMPONENT_ARGS");
+
+    // Template edits reach every instance while local overrides stay in place
+    let red = [("color".to_string(), serde_json::json!("#cc0000"))].into_iter().collect();
+    engine.set_component_override(&revenue, "label", red).unwrap();
+    engine.register_component(kpi_card("{title} = {value}")).unwrap();
+    assert_eq!(text(&engine, "revenue.label"), serde_json::json!("Revenue = 42.0"));
+    assert_eq!(engine.document_state.get_element("revenue.label").unwrap().properties["color"], serde_json::json!("#cc0000"));
+    assert_eq!(engine.document_state.get_element("kpi_card_2.label").unwrap().properties["color"], serde_json::json!("#333333"));
+    assert_eq!(engine.document_state.elements.len(), 4);
+
+    engine.remove_component_instance(&churn).unwrap();
+    assert_eq!(engine.document_state.elements.len(), 2);
+    assert!(engine.document_state.get_element("kpi_card_2.card").is_none());
+}
+
+#[wasm_bindgen_test]
+fn test_component_slots() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let element = |id: &str, element_type: &str, children: Vec<&str>| serde_json::json!({
+        "id": id, "element_type": element_type, "properties": {}, "children": children,
+        "event_handlers": [], "transform": Transform::default(), "style": {},
+    });
+    let card: ComponentDefinition = serde_json::from_value(serde_json::json!({
+        "name": "card",
+        "elements": [element("frame", "Container", vec!["header", "body"]), element("header", "Text", vec![]), element("body", "Container", vec![])],
+        "slots": [{"name": "content", "host": "body", "required": true}, {"name": "actions", "host": "frame"}],
+    })).unwrap();
+    engine.register_component(card).unwrap();
+    for id in ["chart_a", "chart_b"] {
+        engine.document_state.add_element(serde_json::from_value(element(id, "Chart", vec![])).unwrap()).unwrap();
+    }
+
+    assert_eq!(engine.instantiate_component("card", None, HashMap::new(), HashMap::new(), None).unwrap_err().code, "INVALID_COMPONENT_ARGS");
+    let unknown_slot = [("footer".to_string(), vec!["chart_a".to_string()])].into_iter().collect();
+    assert_eq!(engine.instantiate_component("card", None, HashMap::new(), unknown_slot, None).unwrap_err().code, "SLOT_NOT_FOUND");
+
+    let slots = [("content".to_string(), vec!["chart_a".to_string()])].into_iter().collect();
+    let instance = engine.instantiate_component("card", Some("sales".to_string()), HashMap::new(), slots, None).unwrap();
+    assert_eq!(engine.document_state.get_element("sales.body").unwrap().children, vec!["chart_a".to_string()]);
+    assert_eq!(engine.document_state.render_tree.nodes["chart_a"].parent.as_deref(), Some("sales.body"));
+
+    // Swapping slot content recomposes the instance and detaches the old content
+    engine.set_component_slot(&instance, "content", vec!["chart_b".to_string()]).unwrap();
+    assert_eq!(engine.document_state.get_element("sales.body").unwrap().children, vec!["chart_b".to_string()]);
+    assert_eq!(engine.document_state.render_tree.nodes["chart_a"].parent, None);
+    assert_eq!(engine.document_state.render_tree.nodes["chart_b"].parent.as_deref(), Some("sales.body"));
+
+    engine.remove_component_instance(&instance).unwrap();
+    assert!(engine.document_state.get_element("chart_b").is_some());
+    assert_eq!(engine.document_state.render_tree.nodes["chart_b"].parent, None);
+}
+
+#[wasm_bindgen_test]
+fn test_component_scoped_styles() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let badge: ComponentDefinition = serde_json::from_value(serde_json::json!({
+        "name": "badge",
+        "elements": [
+            {"id": "frame", "element_type": "Container", "properties": {}, "children": ["label"], "event_handlers": [], "transform": Transform::default(), "style": {}},
+            {"id": "label", "element_type": "Text", "properties": {"class": "label"}, "children": [], "event_handlers": [], "transform": Transform::default(), "style": {}},
+        ],
+        "styles": [
+            {"selector": ":host", "declarations": {"background": "var(--badge-bg)"}},
+            {"selector": "#label:hover, :host(.active) > .label", "declarations": {"color": "var(--badge-fg)"}},
+        ],
+        "tokens": {"--badge-bg": "#eeeeee", "--badge-fg": "#222222"},
+    })).unwrap();
+    let scope = badge.scope_class();
+    engine.register_component(badge).unwrap();
+    let first = engine.instantiate_component("badge", Some("first".to_string()), HashMap::new(), HashMap::new(), None).unwrap();
+    engine.instantiate_component("badge", Some("second".to_string()), HashMap::new(), HashMap::new(), None).unwrap();
+
+    let class = |id: &str| engine.document_state.get_element(id).unwrap().properties["class"].as_str().unwrap().to_string();
+    assert_eq!(class("first.frame"), format!("{0} {0}__frame {0}-host liv-instance-first", scope));
+    assert_eq!(class("first.label"), format!("label {0} {0}__label", scope));
+
+    // Document tokens theme every instance; instance tokens win over them
+    engine.set_style_token("--badge-bg", Some("#000000".to_string())).unwrap();
+    engine.set_component_token(&first, "--badge-fg", Some("#ffcc00".to_string())).unwrap();
+    assert_eq!(engine.set_component_token(&first, "--unknown", Some("red".to_string())).unwrap_err().code, "TOKEN_NOT_FOUND");
+
+    let css = engine.component_stylesheet();
+    assert!(css.contains(&format!(".{}-host {{ background: var(--badge-bg); }}", scope)));
+    assert!(css.contains(&format!(".{0}__label.{0}:hover, .{0}-host.active > .label.{0} {{ color: var(--badge-fg); }}", scope)));
+    assert!(css.contains(".liv-instance-first { --badge-bg: #000000; --badge-fg: #ffcc00; }"));
+    assert!(css.contains(".liv-instance-second { --badge-bg: #000000; --badge-fg: #222222; }"));
+}
+
+#[wasm_bindgen_test]
+fn test_typed_errors() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let engine = InteractiveEngine::new(permissions).unwrap();
+
+    // Typed errors keep the old code and message, with their fields as details
+    let error = engine.get_element_bounds("missing").unwrap_err();
+    assert_eq!(error.code, "ELEMENT_NOT_FOUND");
+    assert_eq!(error.message, "Element 'missing' not found");
+    let details = error.details.clone().unwrap();
+    assert_eq!(details["element_id"], serde_json::json!("missing"));
+    assert_eq!(details["subsystem"], serde_json::json!("layout"));
+    assert!(!details.contains_key("kind"));
+
+    let error: WASMError = SecurityError::MemoryLimitExceeded { requested: 2048, limit: 1024 }.into();
+    assert_eq!(error.code, "MEMORY_LIMIT_EXCEEDED");
+    assert_eq!(error.details.as_ref().unwrap()["limit"], serde_json::json!(1024));
+
+    // Context accumulates innermost first
+    let error = error.with_context("creating chart").with_context("loading document");
+    assert_eq!(error.context(), vec!["creating chart".to_string(), "loading document".to_string()]);
+    let round_trip: WASMError = serde_json::from_str(&serde_json::to_string(&error).unwrap()).unwrap();
+    assert_eq!(round_trip.context(), error.context());
+
+    // The registry lists each code once, and covers codes produced above
+    let registry = WASMError::registry();
+    let mut codes: Vec<&str> = registry.iter().map(|info| info.code.as_str()).collect();
+    assert!(codes.contains(&"ELEMENT_NOT_FOUND"));
+    assert!(codes.contains(&"DATA_VALIDATION_FAILED"));
+    assert_eq!(registry.iter().find(|info| info.code == "CHART_NOT_FOUND").unwrap().subsystem, "chart");
+    let total = codes.len();
+    codes.sort();
+    codes.dedup();
+    assert_eq!(codes.len(), total);
+}
+
+#[wasm_bindgen_test]
+fn test_input_transform_and_point_conversion() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "Click".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let close = |position: &Position, x: f64, y: f64| (position.x - x).abs() < 1e-9 && (position.y - y).abs() < 1e-9;
+
+    assert_eq!(InputTransform::new(1.0, 2.0, 2.0, 4.0, 0.0, 0.0).unwrap_err().code, "INVALID_INPUT_TRANSFORM");
+    assert_eq!(InputTransform::new(f64::NAN, 0.0, 0.0, 1.0, 0.0, 0.0).unwrap_err().code, "INVALID_INPUT_TRANSFORM");
+
+    // Device pixel ratio 2 with the document scrolled by 1,000,000 CSS pixels
+    let transform = InputTransform::new(0.5, 0.0, 0.0, 0.5, 0.0, 1.0e6).unwrap();
+    let document = transform.apply(&Position { x: 300.0, y: 80.0 });
+    assert!(close(&document, 150.0, 1_000_040.0));
+    assert!(close(&transform.inverse().apply(&document), 300.0, 80.0));
+    assert_eq!(transform.scale_factor(), 0.5);
+
+    let mut event: InteractionEvent = serde_json::from_value(serde_json::json!({
+        "event_type": "Click", "target_element": null, "position": {"x": 20.0, "y": 40.0},
+        "data": {}, "timestamp": 5.0, "keyboard_data": null, "gesture_data": null,
+        "touch_data": {"touches": [{"identifier": 1, "position": {"x": 20.0, "y": 40.0}, "radius": 10.0, "rotation_angle": null, "force": null}],
+            "changed_touches": [], "target_touches": [], "force": null, "rotation_angle": null, "scale": null},
+        "mouse_data": {"button": "Left", "buttons": 1, "position": {"x": 20.0, "y": 40.0}, "movement": {"x": 4.0, "y": -2.0}, "wheel_delta": null},
+        "modifiers": {"ctrl": false, "shift": false, "alt": false, "meta": false},
+    })).unwrap();
+    transform.apply_to_event(&mut event);
+    assert!(close(event.position.as_ref().unwrap(), 10.0, 1_000_020.0));
+    assert_eq!(event.touch_data.as_ref().unwrap().touches[0].radius, Some(5.0));
+    assert!(close(event.mouse_data.as_ref().unwrap().movement.as_ref().unwrap(), 2.0, -1.0));
+
+    let card = engine.create_element(ElementType::Container, HashMap::new()).unwrap();
+    engine.document_state.render_tree.nodes.get_mut(&card).unwrap().bounds = BoundingBox { x: 100.0, y: 50.0, width: 200.0, height: 100.0 };
+    engine.update_viewport(800.0, 600.0, 2.0).unwrap();
+    engine.set_input_transform(InputTransform::new(0.5, 0.0, 0.0, 0.5, 10.0, 20.0).unwrap());
+
+    let client = Position { x: 400.0, y: 200.0 };
+    let element = CoordinateSpace::Element { element_id: card.clone() };
+    let document = engine.convert_point(&client, &CoordinateSpace::Client, &CoordinateSpace::Document).unwrap();
+    assert!(close(&document, 210.0, 120.0));
+    assert!(close(&engine.convert_point(&client, &CoordinateSpace::Client, &element).unwrap(), 110.0, 70.0));
+    let viewport = engine.convert_point(&document, &CoordinateSpace::Document, &CoordinateSpace::Viewport).unwrap();
+    let back = engine.convert_point(&viewport, &CoordinateSpace::Viewport, &CoordinateSpace::Client).unwrap();
+    assert!(close(&back, 400.0, 200.0));
+
+    assert_eq!(CoordinateSpace::parse("element", None).unwrap_err().code, "UNKNOWN_COORDINATE_SPACE");
+    assert_eq!(CoordinateSpace::parse("page", None).unwrap_err().code, "UNKNOWN_COORDINATE_SPACE");
+    let missing = CoordinateSpace::Element { element_id: "missing".to_string() };
+    assert_eq!(engine.convert_point(&client, &CoordinateSpace::Client, &missing).unwrap_err().code, "ELEMENT_NOT_FOUND");
+}
+
+#[wasm_bindgen_test]
+fn test_interaction_event_validation() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["Click".to_string(), "TouchStart".to_string(), "PenDown".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let event = |value: serde_json::Value| -> InteractionEvent {
+        let mut base = serde_json::json!({
+            "event_type": "Click", "target_element": null, "position": {"x": 10.0, "y": 10.0},
+            "data": {}, "timestamp": 5.0, "touch_data": null, "mouse_data": null, "keyboard_data": null,
+            "gesture_data": null, "modifiers": {"ctrl": false, "shift": false, "alt": false, "meta": false},
+        });
+        for (key, field) in value.as_object().unwrap() {
+            base[key] = field.clone();
+        }
+        serde_json::from_value(base).unwrap()
+    };
+    let touch = |id: u32, x: f64| serde_json::json!({"identifier": id, "position": {"x": x, "y": 0.0}, "radius": null, "rotation_angle": null, "force": 3.0});
+
+    let error = engine.process_interaction(event(serde_json::json!({"timestamp": -1.0}))).unwrap_err();
+    assert_eq!(error.code, "INVALID_TIMESTAMP");
+
+    // serde_json cannot carry NaN, so set it after parsing
+    let mut nan = event(serde_json::json!({}));
+    nan.position = Some(Position { x: f64::NAN, y: 0.0 });
+    let error = engine.process_interaction(nan).unwrap_err();
+    assert_eq!(error.code, "INVALID_POSITION");
+    assert_eq!(error.details.unwrap()["field"], serde_json::json!("position"));
+
+    let error = engine.process_interaction(event(serde_json::json!({"event_type": "TouchStart"}))).unwrap_err();
+    assert_eq!(error.code, "INCONSISTENT_EVENT");
+    let duplicate = serde_json::json!({
+        "event_type": "TouchStart",
+        "touch_data": {"touches": [touch(1, 0.0), touch(1, 5.0)], "changed_touches": [touch(1, 0.0)], "target_touches": [], "force": null, "rotation_angle": null, "scale": null},
+    });
+    assert_eq!(engine.process_interaction(event(duplicate)).unwrap_err().code, "INCONSISTENT_EVENT");
+    assert_eq!(engine.process_interaction(event(serde_json::json!({"event_type": "PenDown"}))).unwrap_err().code, "INCONSISTENT_EVENT");
+    assert_eq!(engine.get_interaction_metrics().rejected_events, 5);
+
+    // Out-of-range values are clamped rather than rejected
+    let mut wild = event(serde_json::json!({
+        "event_type": "TouchStart", "position": {"x": 1.0e9, "y": -4.0},
+        "touch_data": {"touches": [touch(1, 0.0)], "changed_touches": [touch(1, 0.0)], "target_touches": [], "force": null, "rotation_angle": null, "scale": 0.0},
+    }));
+    assert_eq!(wild.normalize().unwrap(), 4);
+    assert_eq!(wild.position.as_ref().unwrap().x, 1.0e6);
+    let touch_data = wild.touch_data.as_ref().unwrap();
+    assert_eq!(touch_data.touches[0].force, Some(1.0));
+    assert_eq!(touch_data.scale, Some(0.01));
+
+    // Without changed touches the active touches stand in for them
+    let mut active_only = event(serde_json::json!({
+        "event_type": "TouchStart",
+        "touch_data": {"touches": [touch(2, 5.0)], "changed_touches": [], "target_touches": [], "force": null, "rotation_angle": null, "scale": null},
+    }));
+    active_only.normalize().unwrap();
+    assert_eq!(active_only.touch_data.as_ref().unwrap().changed_touches[0].identifier, 2);
+
+    assert!(engine.process_interaction(wild).is_ok());
+    assert_eq!(engine.get_interaction_metrics().sanitized_events, 0);
+    assert!(engine.process_interaction(event(serde_json::json!({"position": {"x": -2.0e6, "y": 0.0}}))).is_ok());
+    assert_eq!(engine.get_interaction_metrics().sanitized_events, 1);
+}
+
+#[wasm_bindgen_test]
+fn test_property_schemas() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let properties = |value: serde_json::Value| -> HashMap<String, serde_json::Value> { serde_json::from_value(value).unwrap() };
+
+    let schema = engine.get_property_schema(&ElementType::Text);
+    assert!(schema.iter().any(|property| property.name == "width"));
+    let font_size = schema.iter().find(|property| property.name == "font_size").unwrap();
+    assert_eq!(font_size.default, Some(serde_json::json!(16.0)));
+
+    // Warn mode keeps the element and reports typos with a suggestion
+    let text_id = engine.create_element(ElementType::Text, properties(serde_json::json!({
+        "text": "Hello", "widht": 120, "color": "not-a-color", "style.margin": "4px", "data-test": "x",
+    }))).unwrap();
+    let warnings = engine.take_property_warnings();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].property, "color");
+    assert_eq!(warnings[1].property, "widht");
+    assert_eq!(warnings[1].suggestion.as_deref(), Some("width"));
+    assert!(engine.take_property_warnings().is_empty());
+
+    engine.set_property_validation(PropertyValidation::Strict);
+    let error = engine.update_element_properties(&text_id, properties(serde_json::json!({"font_size": -3}))).unwrap_err();
+    assert_eq!(error.code, "INVALID_PROPERTIES");
+    assert_eq!(error.details.unwrap()["issues"][0]["property"], serde_json::json!("font_size"));
+    assert!(engine.update_element_properties(&text_id, properties(serde_json::json!({"font_size": 24, "color": "var(--accent)"}))).is_ok());
+
+    // Hosts can describe their own properties
+    let variant = PropertySchema {
+        name: "variant".to_string(),
+        property_type: PropertyType::Choice(vec!["primary".to_string(), "ghost".to_string()]),
+        default: Some(serde_json::json!("primary")),
+        min: None,
+        max: None,
+        description: "Heading style".to_string(),
+    };
+    engine.register_property_schema(ElementType::Text, variant);
+    assert!(engine.get_property_schema(&ElementType::Interactive).iter().all(|property| property.name != "variant"));
+    assert_eq!(engine.update_element_properties(&text_id, properties(serde_json::json!({"variant": "loud"}))).unwrap_err().code, "INVALID_PROPERTIES");
+    assert!(engine.update_element_properties(&text_id, properties(serde_json::json!({"variant": "ghost"}))).is_ok());
+
+    engine.set_property_validation(PropertyValidation::Off);
+    assert!(engine.update_element_properties(&text_id, properties(serde_json::json!({"variant": "loud"}))).is_ok());
+    assert!(engine.take_property_warnings().is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_element_presets() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    engine.document_state.data_sources.insert(
+        "revenue".to_string(),
+        DataSource::new("revenue".to_string(), DataSourceType::Static, serde_json::json!({"total": 42})),
+    );
+
+    let options = |value: serde_json::Value| -> PresetOptions { serde_json::from_value(value).unwrap() };
+    let kpi = engine.create_preset("kpi_card", options(serde_json::json!({"data_source": "revenue", "value_field": "total", "title": "Revenue"}))).unwrap();
+    assert_eq!(kpi.root_id, "kpi_card_1");
+    assert_eq!(kpi.element_ids, vec!["kpi_card_1", "kpi_card_1_title", "kpi_card_1_value"]);
+    assert_eq!(kpi.binding_ids.len(), 1);
+    assert_eq!(engine.document_state.get_element("kpi_card_1").unwrap().children, vec!["kpi_card_1_title", "kpi_card_1_value"]);
+    assert_eq!(engine.document_state.render_tree.nodes["kpi_card_1_value"].parent.as_deref(), Some("kpi_card_1"));
+    assert!(engine.take_property_warnings().is_empty());
+
+    // The binding writes the data field into the value's text
+    let update = engine.render_frame(0.0).unwrap();
+    assert!(update.dom_operations.iter().any(|operation| matches!(operation,
+        DOMOperation::Update { element_id, attributes } if element_id == "kpi_card_1_value" && attributes.get("text").map(|text| text.as_str()) == Some("42"))));
+
+    // Chart presets create the chart sized to its element, under the given parent
+    let chart = engine.create_chart_element(ChartType::Bar, "revenue", options(serde_json::json!({"parent_id": "kpi_card_1", "width": 320, "height": 200}))).unwrap();
+    assert_eq!(chart.root_id, "chart_1");
+    assert_eq!(chart.chart_ids, vec!["chart_1_chart"]);
+    let config = &engine.chart_renderer.charts["chart_1_chart"].config;
+    assert_eq!((config.width, config.height), (320.0, 200.0));
+    assert_eq!(engine.document_state.get_element("chart_1").unwrap().properties["chart_id"], serde_json::json!("chart_1_chart"));
+    assert!(engine.document_state.get_element("kpi_card_1").unwrap().children.contains(&"chart_1".to_string()));
+
+    let divider = engine.create_preset("divider", PresetOptions::default()).unwrap();
+    assert_eq!(divider.shape_ids, vec!["divider_1_line"]);
+    assert_eq!(engine.create_preset("kpi_card", PresetOptions::default()).unwrap().root_id, "kpi_card_2");
+
+    assert_eq!(engine.create_preset("chart_card", PresetOptions::default()).unwrap_err().code, "INVALID_PRESET_OPTIONS");
+    assert_eq!(engine.create_preset("carousel", PresetOptions::default()).unwrap_err().code, "UNKNOWN_PRESET");
+    assert_eq!(engine.create_preset("button", options(serde_json::json!({"data_source": "missing"}))).unwrap_err().code, "DATA_SOURCE_NOT_FOUND");
+    assert!(engine.document_state.get_element("chart_card_1").is_none());
+}
+
+#[wasm_bindgen_test]
+fn test_kpi_elements() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    engine.document_state.data_sources.insert(
+        "revenue".to_string(),
+        DataSource::new("revenue".to_string(), DataSourceType::Dynamic, serde_json::json!([
+            {"month": "Jan", "total": 900.0},
+            {"month": "Feb", "total": 1000.0},
+            {"month": "Mar", "total": 1250.6},
+        ])),
+    );
+    let properties: HashMap<String, serde_json::Value> = serde_json::from_value(serde_json::json!({
+        "title": "Revenue",
+        "data_source": "revenue",
+        "value_field": "total",
+        "prefix": "$",
+        "thresholds": [{"at": 1200.0, "color": "#1a7f37", "label": "On target"}, {"at": 0.0, "color": "#cf222e", "label": "Behind"}],
+    })).unwrap();
+    let kpi = engine.create_element(ElementType::Kpi, properties).unwrap();
+    assert!(engine.take_property_warnings().is_empty());
+
+    // The document's properties alone configure the figure
+    let update = engine.render_frame(0.0).unwrap();
+    assert!(update.dom_operations.iter().any(|operation| matches!(operation, DOMOperation::Update { element_id, .. } if *element_id == kpi)));
+    let shown = |engine: &InteractiveEngine, key: &str| engine.document_state.get_element(&kpi).unwrap().properties.get(key).cloned();
+    assert_eq!(shown(&engine, "text"), Some(serde_json::json!("$1,251")));
+    assert_eq!(shown(&engine, "delta_text"), Some(serde_json::json!("+25.1%")));
+    assert_eq!(shown(&engine, "trend"), Some(serde_json::json!("up")));
+    assert_eq!(shown(&engine, "delta_color"), Some(serde_json::json!("#1a7f37")));
+    assert_eq!(shown(&engine, "status"), Some(serde_json::json!("On target")));
+    assert!(shown(&engine, "sparkline").unwrap().as_str().unwrap().contains("<polyline"));
+    let description = engine.describe_element(&kpi).unwrap();
+    assert_eq!((description.role.as_str(), description.name.as_deref()), ("status", Some("Revenue")));
+    assert_eq!(description.description.as_deref(), Some("$1,251, +25.1% on the previous period"));
+
+    // Nothing changed, nothing is redrawn
+    assert!(!engine.render_frame(16.0).unwrap().dom_operations.iter().any(|operation| operation.element_id() == kpi));
+
+    // New data redraws it; a fall is bad news unless lower is better
+    engine.update_data_source("revenue", serde_json::json!([{"total": 1000.0}, {"total": 800.0}])).unwrap();
+    engine.render_frame(32.0).unwrap();
+    assert_eq!((shown(&engine, "delta_text"), shown(&engine, "trend")), (Some(serde_json::json!("-20.0%")), Some(serde_json::json!("down"))));
+    assert_eq!((shown(&engine, "delta_color"), shown(&engine, "status")), (Some(serde_json::json!("#cf222e")), Some(serde_json::json!("Behind"))));
+    engine.update_element_properties(&kpi, HashMap::from([
+        ("higher_is_better".to_string(), serde_json::json!(false)),
+        ("delta_format".to_string(), serde_json::json!("absolute")),
+    ])).unwrap();
+    engine.render_frame(48.0).unwrap();
+    assert_eq!((shown(&engine, "delta_text"), shown(&engine, "delta_color")), (Some(serde_json::json!("-$200")), Some(serde_json::json!("#1a7f37"))));
+
+    // Without data there is nothing to compare or draw
+    engine.update_data_source("revenue", serde_json::json!([])).unwrap();
+    engine.render_frame(64.0).unwrap();
+    assert_eq!(shown(&engine, "text"), Some(serde_json::json!("-")));
+    assert!(shown(&engine, "delta_text").is_none() && shown(&engine, "sparkline").is_none());
+
+    // Number formats
+    let spec = |properties: serde_json::Value| KpiSpec::from_properties(&serde_json::from_value(properties).unwrap());
+    assert_eq!(spec(serde_json::json!({"format": "compact"})).format_value(1_234_567.0), "1.2M");
+    assert_eq!(spec(serde_json::json!({"format": "compact", "suffix": " users"})).format_value(-12_000.0), "-12K users");
+    assert_eq!(spec(serde_json::json!({"format": "percent"})).format_value(0.4567), "45.7%");
+    assert_eq!(spec(serde_json::json!({"decimals": 2})).format_value(1234.5), "1,234.50");
+    let compared = spec(serde_json::json!({"previous_field": "target", "delta_format": "absolute"}))
+        .display(&serde_json::json!([{"value": 95.0, "target": 100.0}]));
+    assert_eq!(compared["delta_text"], serde_json::json!("-5"));
+}
+
+#[wasm_bindgen_test]
+fn test_seeded_random() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions.clone()).unwrap();
+    let mut other = InteractiveEngine::new(permissions.clone()).unwrap();
+    engine.set_random_seed(42);
+    other.set_random_seed(42);
+    let uniform = Distribution::Uniform { min: -1.0, max: 1.0 };
+
+    // The same seed draws the same numbers, and draws from one stream leave the others alone
+    other.random_sample("particles", &uniform, 10).unwrap();
+    let jitter = engine.random_sample("jitter", &uniform, 5).unwrap();
+    assert_eq!(jitter, other.random_sample("jitter", &uniform, 5).unwrap());
+    assert!(jitter.iter().all(|value| (-1.0..1.0).contains(value)));
+    assert_ne!(jitter, engine.random_sample("jitter", &uniform, 5).unwrap());
+    other.set_random_seed(7);
+    assert_ne!(other.random_sample("jitter", &uniform, 5).unwrap(), jitter);
+
+    let dice = engine.random_sample("dice", &Distribution::Integer { min: 1, max: 6 }, 600).unwrap();
+    assert!(dice.iter().all(|roll| (1.0..=6.0).contains(roll) && roll.fract() == 0.0));
+    assert!((1..=6).all(|face| dice.contains(&(face as f64))));
+    let picks = engine.random_sample("dice", &Distribution::Weighted { weights: vec![1.0, 0.0, 3.0] }, 400).unwrap();
+    assert!(!picks.contains(&1.0));
+    assert!(picks.iter().filter(|pick| **pick == 2.0).count() > picks.iter().filter(|pick| **pick == 0.0).count());
+    let normal = engine.random_sample("normal", &Distribution::Normal { mean: 10.0, std_dev: 2.0 }, 2000).unwrap();
+    let mean = normal.iter().sum::<f64>() / normal.len() as f64;
+    assert!((mean - 10.0).abs() < 0.3, "mean {}", mean);
+    let arrivals = engine.random_sample("normal", &Distribution::Poisson { lambda: 4.0 }, 2000).unwrap();
+    assert!((arrivals.iter().sum::<f64>() / 2000.0 - 4.0).abs() < 0.3);
+
+    let invalid = engine.random_sample("jitter", &Distribution::Bernoulli { p: 1.5 }, 1).unwrap_err();
+    assert_eq!(invalid.code, "INVALID_DISTRIBUTION");
+    let too_many = engine.random_sample("jitter", &uniform, random::MAX_RANDOM_SAMPLES + 1).unwrap_err();
+    assert_eq!(too_many.code, "INVALID_DISTRIBUTION");
+
+    // Noise is a smooth function of the point and the seed
+    for kind in [NoiseKind::Perlin, NoiseKind::Simplex] {
+        let here = engine.noise(kind, 3.3, 1.7, 0.2, 4);
+        assert!((-1.0..=1.0).contains(&here));
+        assert_eq!(here, engine.noise(kind, 3.3, 1.7, 0.2, 4));
+        assert!((here - engine.noise(kind, 3.301, 1.7, 0.2, 4)).abs() < 0.05);
+        let samples: Vec<f64> = (0..50).map(|i| engine.noise(kind, i as f64 * 0.37, 0.5, 0.0, 1)).collect();
+        assert!(samples.iter().any(|value| value.abs() > 0.05));
+    }
+    other.set_random_seed(42);
+    assert_eq!(engine.noise(NoiseKind::Perlin, 0.4, 0.6, 0.0, 2), other.noise(NoiseKind::Perlin, 0.4, 0.6, 0.0, 2));
+    other.set_random_seed(43);
+    assert_ne!(engine.noise(NoiseKind::Perlin, 0.4, 0.6, 0.0, 2), other.noise(NoiseKind::Perlin, 0.4, 0.6, 0.0, 2));
+
+    // A snapshot keeps the seed and where each stream is
+    let snapshot = engine.freeze().unwrap();
+    let next = engine.random_sample("jitter", &uniform, 3).unwrap();
+    let mut resumed = InteractiveEngine::new(permissions).unwrap();
+    resumed.thaw(&snapshot).unwrap();
+    assert_eq!(resumed.random_seed(), 42);
+    assert_eq!(resumed.random_sample("jitter", &uniform, 3).unwrap(), next);
+}
+
+#[wasm_bindgen_test]
+fn test_noise_driven_animation() {
+    let mut document = DocumentState { random_seed: Some(9), ..DocumentState::default() };
+    let mut controller = AnimationController::new();
+    let keyframe = |time: f64, x: f64| Keyframe { time, properties: [("x".to_string(), serde_json::json!(x))].into_iter().collect() };
+    let animation = Animation {
+        id: "wobble".to_string(),
+        target_element: "leaf".to_string(),
+        animation_type: AnimationType::Transform,
+        duration: 10_000.0,
+        easing: EasingFunction::Linear,
+        keyframes: vec![keyframe(0.0, 100.0), keyframe(1.0, 100.0)],
+        loop_count: 1,
+        direction: AnimationDirection::Normal,
+        noise: Some(NoiseDriver {
+            amplitudes: [("x".to_string(), 20.0), ("rotation".to_string(), 5.0)].into_iter().collect(),
+            frequency: 0.5,
+            octaves: 2,
+            kind: NoiseKind::Perlin,
+        }),
+        composition: Default::default(),
+    };
+    let values_at = |controller: &mut AnimationController, document: &mut DocumentState, timestamp: f64| {
+        let changes = controller.update_animations(document, timestamp).unwrap();
+        let Some(ElementChange::AnimationUpdate { values, .. }) = changes.into_iter().next() else { panic!("no animation update") };
+        (values["x"].as_f64().unwrap(), values["rotation"].as_f64().unwrap())
+    };
+
+    controller.start_animation(animation.clone());
+    controller.active_animations.get_mut("wobble").unwrap().start_time = 0.0;
+    let frames: Vec<(f64, f64)> = (1..20).map(|i| values_at(&mut controller, &mut document, i as f64 * 450.0)).collect();
+    assert!(frames.iter().all(|(x, rotation)| (80.0..=120.0).contains(x) && (-5.0..=5.0).contains(rotation)));
+    assert!(frames.iter().any(|(x, _)| (x - 100.0).abs() > 0.5));
+    assert!(frames.windows(2).any(|pair| pair[0].1 != pair[1].1));
+
+    // The same seed plays the same motion again
+    let mut replay = AnimationController::new();
+    replay.start_animation(animation);
+    replay.active_animations.get_mut("wobble").unwrap().start_time = 0.0;
+    assert_eq!(values_at(&mut replay, &mut document, 450.0), frames[0]);
+}
+
+#[wasm_bindgen_test]
+fn test_animation_timeline() {
+    let mut document = DocumentState::default();
+    let mut controller = AnimationController::new();
+    let keyframe = |time: f64, x: f64| Keyframe { time, properties: [("x".to_string(), serde_json::json!(x))].into_iter().collect() };
+    let animation = |id: &str, duration: f64, direction: AnimationDirection| Animation {
+        id: id.to_string(),
+        target_element: format!("{}_box", id),
+        animation_type: AnimationType::Transform,
+        duration,
+        easing: EasingFunction::Linear,
+        keyframes: vec![keyframe(0.0, 0.0), keyframe(1.0, 100.0)],
+        loop_count: 1,
+        direction,
+        noise: None,
+        composition: Default::default(),
+    };
+    let mut timeline = AnimationTimeline::new("intro".to_string());
+    assert_eq!(timeline.add(vec![animation("a", 1000.0, AnimationDirection::Normal)], &TimelinePlacement::default()).unwrap(), 0.0);
+    let group = vec![animation("b", 500.0, AnimationDirection::Normal), animation("c", 1000.0, AnimationDirection::Reverse)];
+    assert_eq!(timeline.add(group, &TimelinePlacement::Sequence { delay_ms: 200.0 }).unwrap(), 1200.0);
+    assert_eq!(timeline.add(vec![animation("d", 100.0, AnimationDirection::Normal)], &TimelinePlacement::WithPrevious { delay_ms: 50.0 }).unwrap(), 1250.0);
+    let endless = Animation { loop_count: -1, ..animation("e", 100.0, AnimationDirection::Normal) };
+    assert_eq!(timeline.add(vec![endless], &TimelinePlacement::default()).unwrap_err().code, "INVALID_TIMELINE_ITEM");
+    assert_eq!(timeline.duration_ms(), 2200.0);
+    controller.add_timeline(timeline);
+
+    let frame = |controller: &mut AnimationController, document: &mut DocumentState, timestamp: f64| -> HashMap<String, f64> {
+        controller.update_animations(document, timestamp).unwrap().into_iter()
+            .map(|change| match change {
+                ElementChange::AnimationUpdate { animation_id, values, .. } => (animation_id, values["x"].as_f64().unwrap()),
+                other => panic!("unexpected change {:?}", other),
+            })
+            .collect()
+    };
+    // Paused: nothing moves until played
+    assert_eq!(frame(&mut controller, &mut document, 0.0).get("a"), Some(&0.0));
+    assert!(frame(&mut controller, &mut document, 100.0).is_empty());
+    controller.timeline_mut("intro").unwrap().play();
+    frame(&mut controller, &mut document, 1000.0);
+    assert_eq!(frame(&mut controller, &mut document, 1500.0), [("a".to_string(), 50.0)].into_iter().collect());
+
+    // Seeking past the first animation settles it on its last keyframe and starts the group
+    controller.timeline_mut("intro").unwrap().seek(1450.0);
+    let values = frame(&mut controller, &mut document, 1500.0);
+    assert_eq!(values["a"], 100.0);
+    assert_eq!(values["b"], 50.0);
+    assert_eq!(values["c"], 75.0);
+    assert_eq!(values["d"], 100.0);
+
+    // Reversed, the clock runs back to 0 and stops there
+    controller.timeline_mut("intro").unwrap().reverse();
+    let values = frame(&mut controller, &mut document, 1600.0);
+    assert_eq!(values["b"], 30.0);
+    frame(&mut controller, &mut document, 5000.0);
+    let state = controller.timeline("intro").unwrap().state();
+    assert_eq!((state.position_ms, state.playing, state.reversed), (0.0, false, true));
+    assert_eq!(state.animation_ids, vec!["a", "b", "c", "d"]);
+
+    // Timelines whose targets are removed lose those animations
+    assert_eq!(controller.release_targets(&|target| target == "d_box"), 1);
+    assert_eq!(controller.target_map().get("b"), Some(&"b_box".to_string()));
+}
+
+#[wasm_bindgen_test]
+fn test_property_interpolators() {
+    use crate::interpolate::InterpolatorKind;
+    let json = |text: &str| serde_json::json!(text);
+    let mut interpolators = Interpolators::default();
+    // Built in: numbers, colors, same-shape strings, transforms and paths
+    assert_eq!(interpolators.interpolate("x", &serde_json::json!(10), &serde_json::json!(20), 0.25), serde_json::json!(12.5));
+    assert_eq!(interpolators.interpolate("style.fill", &json("#000000"), &json("rgb(255, 255, 255)"), 0.5), json("#808080"));
+    assert_eq!(interpolators.interpolate("style.width", &json("10px"), &json("20px"), 0.5), json("15px"));
+    assert_eq!(interpolators.interpolate("style.transform", &json("none"), &json("translate(10px, 20px) scale(3)"), 0.5), json("translate(5px, 10px) scale(2)"));
+    assert_eq!(interpolators.interpolate("d", &json("M0,0 L10,10"), &json("M 10 20 L 30 40"), 0.5), json("M5,10 L20,25"));
+    assert_eq!(interpolators.interpolate("gradient", &serde_json::json!(["#ff0000", 0]), &serde_json::json!(["#0000ff", 1]), 0.5), serde_json::json!(["#800080", 0.5]));
+    // Unblendable values switch halfway
+    assert_eq!(interpolators.interpolate("d", &json("M0 0 L1 1"), &json("M0 0 C1 1 2 2 3 3"), 0.4), json("M0 0 L1 1"));
+    assert_eq!(interpolators.interpolate("label", &json("start"), &json("end"), 0.6), json("end"));
+
+    // Registered kinds override the defaults, matching a full name or its last segment
+    interpolators.register("rotation", Arc::new(InterpolatorKind::Angle));
+    assert_eq!(interpolators.interpolate("transform.rotation", &serde_json::json!(350), &serde_json::json!(10), 0.75), serde_json::json!(5.0));
+    interpolators.register("style.width", Arc::new(InterpolatorKind::Discrete));
+    assert_eq!(interpolators.interpolate("style.width", &json("10px"), &json("20px"), 0.4), json("10px"));
+
+    // Plugins supply their own
+    struct Stepped;
+    impl PropertyInterpolator for Stepped {
+        fn interpolate(&self, from: &serde_json::Value, to: &serde_json::Value, progress: f64) -> Option<serde_json::Value> {
+            let (from, to) = (from.as_f64()?, to.as_f64()?);
+            Some(serde_json::json!(from + ((to - from) * progress / 10.0).floor() * 10.0))
+        }
+    }
+    let mut controller = AnimationController::new();
+    controller.interpolators.register("count", Arc::new(Stepped));
+    let keyframe = |time: f64, count: f64| Keyframe { time, properties: [("count".to_string(), serde_json::json!(count))].into_iter().collect() };
+    controller.start_animation(Animation {
+        id: "counter".to_string(),
+        target_element: "total".to_string(),
+        animation_type: AnimationType::Style,
+        duration: 1000.0,
+        easing: EasingFunction::Linear,
+        keyframes: vec![keyframe(0.0, 0.0), keyframe(1.0, 100.0)],
+        loop_count: 1,
+        direction: AnimationDirection::Normal,
+        noise: None,
+        composition: Default::default(),
+    });
+    controller.active_animations.get_mut("counter").unwrap().start_time = 0.0;
+    let changes = controller.update_animations(&mut DocumentState::default(), 456.0).unwrap();
+    let Some(ElementChange::AnimationUpdate { values, .. }) = changes.first() else { panic!("no animation update") };
+    assert_eq!(values["count"], serde_json::json!(40.0));
+    assert!(interpolators.unregister("style.width"));
+}
+
+#[wasm_bindgen_test]
+fn test_streaming_sources() {
+    let mut permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string()],
+        max_data_size: 4096,
+        max_elements: 100,
+    };
+    let mut offline = InteractiveEngine::new(permissions.clone()).unwrap();
+    assert_eq!(offline.open_stream("ticks", StreamConfig::default()).unwrap_err().code, "NETWORKING_NOT_ALLOWED");
+
+    permissions.allow_networking = true;
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    engine.document_state.data_sources.insert(
+        "labels".to_string(),
+        DataSource::new("labels".to_string(), DataSourceType::Static, serde_json::json!([])),
+    );
+    assert_eq!(engine.open_stream("labels", StreamConfig::default()).unwrap_err().code, "NOT_A_STREAM");
+    assert_eq!(engine.push_stream_frame("ticks", b"[]").unwrap_err().code, "STREAM_NOT_OPEN");
+    let config = StreamConfig { max_held_frames: 2, max_reconnect_attempts: 2, ..StreamConfig::default() };
+    engine.open_stream("ticks", config).unwrap();
+    engine.chart_renderer.create_chart_with_id("ticks", ChartType::Line, "ticks".to_string(), ChartConfig::default()).unwrap();
+    let properties: HashMap<String, serde_json::Value> = [("chart_id".to_string(), serde_json::json!("ticks"))].into_iter().collect();
+    let chart = engine.create_element(ElementType::Chart, properties).unwrap();
+    let rows = |engine: &InteractiveEngine| engine.document_state.data_sources["ticks"].data.as_array().unwrap().iter()
+        .map(|row| row["price"].as_f64().unwrap())
+        .collect::<Vec<f64>>();
+
+    // Frames wait for the next render frame and for the sequence numbers before them
+    engine.push_stream_frame("ticks", br#"{"seq": 1, "rows": [{"price": 1.0}]}"#).unwrap();
+    let status = engine.push_stream_frame("ticks", br#"{"seq": 3, "rows": [{"price": 3.0}]}"#).unwrap();
+    assert_eq!((status.last_sequence, status.held_frames), (Some(1), 1));
+    assert!(rows(&engine).is_empty());
+    engine.push_stream_frame("ticks", br#"{"seq": 2, "rows": [{"price": 2.0}, {"price": 2.5}]}"#).unwrap();
+    let status = engine.push_stream_frame("ticks", br#"{"seq": 2, "rows": [{"price": 2.0}]}"#).unwrap();
+    assert_eq!((status.last_sequence, status.held_frames, status.frames_dropped), (Some(3), 0, 1));
+    let update = engine.render_frame(16.0).unwrap();
+    assert_eq!(rows(&engine), vec![1.0, 2.0, 2.5, 3.0]);
+    let streamed = update.dom_operations.iter().find_map(|operation| match operation {
+        DOMOperation::Update { element_id, attributes } if *element_id == chart => attributes.get("stream_rows").cloned(),
+        _ => None,
+    }).unwrap();
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&streamed).unwrap().as_array().unwrap().len(), 4);
+    assert_eq!(engine.stream_status("ticks").unwrap().buffered_bytes, 0);
+
+    // A gap that outlasts max_held_frames is skipped
+    for seq in [5, 6, 7] {
+        engine.push_stream_frame("ticks", format!(r#"{{"seq": {}, "rows": [{{"price": {}.0}}]}}"#, seq, seq).as_bytes()).unwrap();
+    }
+    let status = engine.stream_status("ticks").unwrap();
+    assert_eq!((status.last_sequence, status.frames_lost), (Some(7), 1));
+
+    // Binary frames carry typed columns
+    let mut frame = stream::STREAM_FRAME_MAGIC.to_vec();
+    frame.extend_from_slice(&8u64.to_le_bytes());
+    frame.extend_from_slice(&1u16.to_le_bytes());
+    frame.extend_from_slice(&5u16.to_le_bytes());
+    frame.extend_from_slice(b"price");
+    frame.extend_from_slice(&2u32.to_le_bytes());
+    frame.extend_from_slice(&8.0f64.to_le_bytes());
+    frame.extend_from_slice(&8.5f64.to_le_bytes());
+    engine.push_stream_frame("ticks", &frame).unwrap();
+    assert_eq!(engine.push_stream_frame("ticks", &frame[..frame.len() - 3]).unwrap_err().code, "INVALID_STREAM_FRAME");
+    engine.render_frame(32.0).unwrap();
+    assert_eq!(engine.document_state.data_sources["ticks"].column("price"), Some(&[8.0, 8.5][..]));
+    assert_eq!(rows(&engine), vec![1.0, 2.0, 2.5, 3.0, 5.0, 6.0, 7.0]);
+
+    // Unapplied frames past the buffer limit are refused, and the host is asked to pause first
+    let big = serde_json::to_vec(&vec![serde_json::json!({"price": 1.0}); 240]).unwrap();
+    let status = engine.push_stream_frame("ticks", &big).unwrap();
+    assert!(status.paused);
+    assert_eq!(engine.push_stream_frame("ticks", &big).unwrap_err().code, "STREAM_BUFFER_FULL");
+    engine.render_frame(48.0).unwrap();
+    assert!(!engine.stream_status("ticks").unwrap().paused);
+
+    // Reconnect delays double until the attempts run out
+    assert_eq!(engine.stream_disconnected("ticks").unwrap().reconnect_in_ms, Some(500.0));
+    assert_eq!(engine.stream_disconnected("ticks").unwrap().reconnect_in_ms, Some(1000.0));
+    let closed = engine.stream_disconnected("ticks").unwrap();
+    assert_eq!((closed.state, closed.reconnect_in_ms), (StreamState::Closed, None));
+    assert_eq!(engine.push_stream_frame("ticks", b"[]").unwrap_err().code, "STREAM_NOT_OPEN");
+    let reopened = engine.open_stream("ticks", StreamConfig::default()).unwrap();
+    assert_eq!((reopened.state, reopened.last_sequence), (StreamState::Connected, Some(8)));
+    assert!(engine.close_stream("ticks"));
+}
+
+#[wasm_bindgen_test]
+fn test_schema_migrations() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions.clone()).unwrap();
+    let versions = engine.get_supported_versions();
+    let document = versions.iter().find(|versions| versions.kind == SchemaKind::Document).unwrap();
+    assert_eq!((document.oldest, document.current), (1, DOCUMENT_SCHEMA_VERSION));
+
+    // Unversioned documents are version 1 and get their camelCase font properties renamed
+    let legacy = serde_json::json!({
+        "elements": [{
+            "id": "heading", "element_type": "Text", "properties": {"text": "Hi", "fontSize": 28, "fontWeight": "bold"},
+            "children": [], "event_handlers": [],
+            "transform": {"x": 0.0, "y": 0.0, "scale_x": 1.0, "scale_y": 1.0, "rotation": 0.0, "opacity": 1.0},
+            "style": {"background_color": null, "border_color": null, "border_width": null, "border_radius": null, "shadow": null},
+        }],
+    });
+    let token = engine.load_document_chunked(legacy.to_string());
+    engine.continue_operation(&token, f64::INFINITY).unwrap();
+    let properties = &engine.document_state.get_element("heading").unwrap().properties;
+    assert_eq!(properties.get("font_size"), Some(&serde_json::json!(28)));
+    assert_eq!(properties.get("font_weight"), Some(&serde_json::json!("bold")));
+    assert!(!properties.contains_key("fontSize"));
+
+    let mut future = legacy.clone();
+    future["schema_version"] = serde_json::json!(DOCUMENT_SCHEMA_VERSION + 1);
+    let token = engine.load_document_chunked(future.to_string());
+    assert_eq!(engine.continue_operation(&token, f64::INFINITY).unwrap_err().code, "UNSUPPORTED_VERSION");
+
+    // Version 1 snapshots go through the same rename
+    let snapshot = engine.freeze().unwrap();
+    let mut body: serde_json::Value = serde_json::from_slice(&snapshot[5..]).unwrap();
+    body["document"]["elements"][0]["properties"] = serde_json::json!({"fontSize": 12});
+    let mut old_snapshot = snapshot[..4].to_vec();
+    old_snapshot.push(1);
+    old_snapshot.extend(serde_json::to_vec(&body).unwrap());
+    let mut resumed = InteractiveEngine::new(permissions).unwrap();
+    resumed.thaw(&old_snapshot).unwrap();
+    assert_eq!(resumed.document_state.get_element("heading").unwrap().properties.get("font_size"), Some(&serde_json::json!(12)));
+
+    // Deltas from before schema_version existed still read
+    let delta = resumed.read_data_delta(br#"{"changes": []}"#).unwrap();
+    assert_eq!(delta.schema_version, DATA_SYNC_SCHEMA_VERSION);
+
+    let step = MigrationStep {
+        kind: SchemaKind::Document,
+        from_version: DOCUMENT_SCHEMA_VERSION,
+        description: "Nothing yet".to_string(),
+        migrate: |_| Ok(()),
+    };
+    assert_eq!(resumed.register_migration(step).unwrap_err().code, "INVALID_MIGRATION");
+    let failing = MigrationStep {
+        kind: SchemaKind::Document,
+        from_version: 1,
+        description: "Always fails".to_string(),
+        migrate: |_| Err("unreadable".to_string()),
+    };
+    resumed.register_migration(failing).unwrap();
+    let token = resumed.load_document_chunked(legacy.to_string());
+    let error = resumed.continue_operation(&token, f64::INFINITY).unwrap_err();
+    assert_eq!(error.code, "MIGRATION_FAILED");
+    assert_eq!(error.details.unwrap()["from_version"], serde_json::json!(1));
+}
+
+#[wasm_bindgen_test]
+fn test_garbage_collection() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    engine.document_state.data_sources.insert(
+        "revenue".to_string(),
+        DataSource::new("revenue".to_string(), DataSourceType::Static, serde_json::json!({"total": 42})),
+    );
+    let options = |value: serde_json::Value| -> PresetOptions { serde_json::from_value(value).unwrap() };
+    engine.create_preset("kpi_card", options(serde_json::json!({"data_source": "revenue", "value_field": "total", "title": "Revenue"}))).unwrap();
+    engine.create_chart_element(ChartType::Bar, "revenue", options(serde_json::json!({"parent_id": "kpi_card_1"}))).unwrap();
+
+    // Deleting the bound element takes its binding and its place in the parent with it
+    let report = engine.delete_element("kpi_card_1_value").unwrap();
+    assert_eq!(report.bindings, 1);
+    assert!(report.dangling_links >= 1);
+    assert!(!engine.document_state.get_element("kpi_card_1").unwrap().children.contains(&"kpi_card_1_value".to_string()));
+
+    // A chart only its element drew goes with the element
+    let report = engine.delete_element("chart_1").unwrap();
+    assert_eq!(report.charts, 1);
+    assert!(engine.chart_renderer.charts.is_empty());
+
+    // Elements removed behind the engine's back are swept by a full pass
+    engine.subscribe_event("title.*", Some("kpi_card_1_title".to_string()), "on_title", HashMap::new(), false).unwrap();
+    engine.set_event_rate_limit(EventRateLimitConfig {
+        target_element: Some("kpi_card_1_title".to_string()),
+        event_type: InteractionType::Click,
+        limit: RateLimit::Throttle { interval_ms: 100.0 },
+    });
+    engine.document_state.remove_element("kpi_card_1_title").unwrap();
+    let report = engine.collect_garbage();
+    assert_eq!((report.subscriptions, report.rate_limits), (1, 1));
+    assert!(engine.document_state.get_element("kpi_card_1").unwrap().children.is_empty());
+    assert_eq!(engine.collect_garbage().total(), 0);
+    assert_eq!(engine.gc_totals().bindings, 1);
+    assert_eq!(engine.gc_totals().charts, 1);
+}
+
+#[wasm_bindgen_test]
+fn test_upserts() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let spec = |properties: serde_json::Value| -> ElementSpec {
+        serde_json::from_value(serde_json::json!({"element_type": "Text", "properties": properties})).unwrap()
+    };
+
+    let created = engine.upsert_element("label", spec(serde_json::json!({"text": "Hello", "font_size": 14}))).unwrap();
+    assert!(created.created);
+    assert_eq!(created.changed, vec!["font_size", "text"]);
+    let update = engine.render_frame(0.0).unwrap();
+    assert!(matches!(&update.dom_operations[0], DOMOperation::Create { element_id, .. } if element_id == "label"));
+
+    // The same spec again is a no-op
+    assert!(engine.upsert_element("label", spec(serde_json::json!({"text": "Hello", "font_size": 14}))).unwrap().is_unchanged());
+    assert!(engine.render_frame(16.0).unwrap().dom_operations.is_empty());
+
+    // Only the changed property is emitted; null removes a property
+    let result = engine.upsert_element("label", spec(serde_json::json!({"text": "World", "font_size": 14, "color": null}))).unwrap();
+    assert_eq!(result.changed, vec!["text"]);
+    assert!(result.removed.is_empty());
+    // The re-wrapped lines of the new text follow in their own update
+    let update = engine.render_frame(32.0).unwrap();
+    assert_eq!(update.dom_operations.len(), 2);
+    assert!(update.dom_operations.iter().all(|operation| matches!(operation,
+        DOMOperation::Update { element_id, attributes } if element_id == "label" && !attributes.contains_key("font_size"))));
+    let result = engine.upsert_element("label", spec(serde_json::json!({"font_size": null}))).unwrap();
+    assert_eq!(result.removed, vec!["font_size"]);
+    assert!(!engine.document_state.get_element("label").unwrap().properties.contains_key("font_size"));
+
+    let image: ElementSpec = serde_json::from_value(serde_json::json!({"element_type": "Image"})).unwrap();
+    assert_eq!(engine.upsert_element("label", image).unwrap_err().code, "ELEMENT_TYPE_MISMATCH");
+
+    // Unchanged data keeps the source version
+    let source = |data: serde_json::Value| -> DataSourceSpec {
+        serde_json::from_value(serde_json::json!({"source_type": "Static", "data": data})).unwrap()
+    };
+    assert!(engine.upsert_data_source("sales", source(serde_json::json!([1, 2]))).unwrap().created);
+    let version = engine.document_state.data_sources["sales"].version;
+    assert!(engine.upsert_data_source("sales", source(serde_json::json!([1, 2]))).unwrap().is_unchanged());
+    assert_eq!(engine.document_state.data_sources["sales"].version, version);
+    assert_eq!(engine.upsert_data_source("sales", source(serde_json::json!([1, 2, 3]))).unwrap().changed, vec!["data"]);
+    assert!(engine.document_state.data_sources["sales"].version > version);
+}
+
+#[wasm_bindgen_test]
+fn test_linked_text_frames() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    for (id, element_type, properties) in [
+        ("page1", "Text", serde_json::json!({"columns": 2, "column_gap": 0, "width": 120, "height": 40})),
+        ("page2", "Text", serde_json::json!({"width": 120, "height": 40})),
+        ("box", "Container", serde_json::json!({"width": 120, "height": 40})),
+    ] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": element_type, "properties": properties, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        })).unwrap()).unwrap();
+    }
+    engine.document_state.run_layout_pass(0.0);
+
+    // 10px text: 6px per character and 12px lines, so three lines per frame,
+    // two words per 60px column and four per 120px frame
+    let words: Vec<String> = (0..26).map(|index| format!("w{:03}", index)).collect();
+    let story: TextStory = serde_json::from_value(serde_json::json!({
+        "id": "article", "font_size": 10.0, "frames": ["page1", "page2"],
+        "runs": [{"text": format!("{} ", words[..3].join(" "))}, {"text": words[3..].join(" "), "font_weight": "bold"}],
+    })).unwrap();
+    engine.set_text_story(story.clone()).unwrap();
+
+    let layout = engine.get_story_layout("article").unwrap();
+    let first = &layout.frames[0];
+    assert_eq!(first.lines.len(), 6);
+    assert_eq!((first.lines[3].column, first.lines[3].y), (1, 0.0));
+    assert_eq!(first.lines[3].spans[0].x, 60.0);
+    let spans: Vec<(usize, &str, f64)> = first.lines[1].spans.iter().map(|span| (span.run, span.text.as_str(), span.x)).collect();
+    assert_eq!(spans, vec![(0, "w002 ", 0.0), (1, "w003", 30.0)]);
+    assert_eq!(layout.frames[1].lines.len(), 3);
+    assert!(layout.frames[1].text().starts_with("w012 w013 w014 w015\n"));
+    assert!(layout.overflow);
+    assert_eq!(engine.get_overflow_text("page2").unwrap(), "w024 w025");
+    assert!(engine.get_overflow_text("page1").unwrap().starts_with("w012 "));
+
+    engine.render_frame(16.0).unwrap();
+    let property = |engine: &InteractiveEngine, id: &str, name: &str| engine.document_state.get_element(id).unwrap().properties[name].clone();
+    assert_eq!(property(&engine, "page1", "text_overflow"), serde_json::json!(false));
+    assert_eq!(property(&engine, "page2", "text_overflow"), serde_json::json!(true));
+    assert!(property(&engine, "page1", "text").as_str().unwrap().starts_with("w000 w001\nw002 w003\n"));
+
+    // Growing the last frame reflows only what changed
+    engine.document_state.update_element("page2", [("height".to_string(), serde_json::json!(60))].into_iter().collect()).unwrap();
+    let update = engine.render_frame(32.0).unwrap();
+    assert!(update.dom_operations.iter().all(|operation| matches!(operation, DOMOperation::Update { element_id, .. } if element_id == "page2")));
+    assert_eq!(property(&engine, "page2", "text_overflow"), serde_json::json!(false));
+    assert_eq!(engine.get_overflow_text("page2").unwrap(), "");
+    assert!(engine.render_frame(48.0).unwrap().dom_operations.is_empty());
+
+    let mut other = story.clone();
+    other.id = "sidebar".to_string();
+    assert_eq!(engine.set_text_story(other.clone()).unwrap_err().code, "FRAME_ALREADY_LINKED");
+    other.frames = vec!["box".to_string()];
+    assert_eq!(engine.set_text_story(other).unwrap_err().code, "NOT_A_TEXT_FRAME");
+    assert_eq!(engine.get_overflow_text("box").unwrap_err().code, "FRAME_NOT_LINKED");
+    assert_eq!(engine.link_text_frame("missing", "box").unwrap_err().code, "STORY_NOT_FOUND");
+
+    let report = engine.delete_element("page2").unwrap();
+    assert!(report.dangling_links >= 1);
+    assert_eq!(engine.document_state.text_stories[0].frames, vec!["page1"]);
+    assert!(engine.remove_text_story("article").unwrap());
+    assert!(!engine.document_state.get_element("page1").unwrap().properties.contains_key("text_lines"));
+}
+
+#[wasm_bindgen_test]
+fn test_text_justification_and_hyphenation() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    for id in ["left", "right"] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": "Text", "properties": {}, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        })).unwrap()).unwrap();
+        engine.document_state.render_tree.nodes.get_mut(id).unwrap().bounds = BoundingBox { x: 0.0, y: 0.0, width: 150.0, height: 60.0 };
+    }
+
+    let english = Hyphenator::english();
+    assert_eq!(english.hyphenate("hyphenation"), vec![2, 5, 7]);
+    assert!(english.hyphenate("the").is_empty());
+    assert_eq!(Hyphenator::from_patterns("1ba 2").unwrap_err().code, "INVALID_HYPHENATION_PATTERNS");
+
+    // 10px text in 150px frames: 25 characters and five lines each
+    let story = |options: serde_json::Value| -> TextStory {
+        let mut story = serde_json::json!({
+            "id": "essay", "font_size": 10.0, "frames": ["left", "right"],
+            "runs": [{"text": "The quick brown fox jumps over the lazy dog. Typography and hyphenation make justified paragraphs look even.\nSecond paragraph here with several words to wrap around nicely."}],
+        });
+        for (key, value) in options.as_object().unwrap() {
+            story[key] = value.clone();
+        }
+        serde_json::from_value(story).unwrap()
+    };
+    let lines = |engine: &InteractiveEngine, frame: usize| -> Vec<(String, f64, bool)> {
+        engine.get_story_layout("essay").unwrap().frames[frame].lines.iter()
+            .map(|line| (line.spans.iter().map(|span| span.text.as_str()).collect(), line.word_spacing, line.hyphenated))
+            .collect()
+    };
+
+    engine.set_text_story(story(serde_json::json!({"align": "Justify", "hyphenation": "en-US"}))).unwrap();
+    let greedy = lines(&engine, 0);
+    assert_eq!(greedy[1], ("over the lazy dog. Typo-".to_string(), 1.5, true));
+    assert_eq!(greedy[2].1, 9.0);
+    assert_eq!(greedy[4], ("look even.".to_string(), 0.0, false));
+
+    // Optimal breaking evens out the spacing of the paragraph
+    engine.set_text_story(story(serde_json::json!({"align": "Justify", "hyphenation": "en", "line_breaking": "Optimal"}))).unwrap();
+    let optimal = lines(&engine, 0);
+    assert_eq!(optimal[1].0, "over the lazy dog. Ty-");
+    let widest = |lines: &[(String, f64, bool)]| lines.iter().map(|line| line.1).fold(0.0, f64::max);
+    assert!(widest(&optimal) < widest(&greedy));
+
+    engine.set_text_story(story(serde_json::json!({"align": "Right"}))).unwrap();
+    let layout = engine.get_story_layout("essay").unwrap();
+    assert_eq!(layout.frames[0].lines[1].spans[0].x, 150.0 - 18.0 * 6.0);
+
+    // A single line left at the top of the next frame pulls another one with it
+    engine.set_text_story(story(serde_json::json!({}))).unwrap();
+    assert_eq!(lines(&engine, 0).len(), 5);
+    engine.set_text_story(story(serde_json::json!({"widows": 2}))).unwrap();
+    assert_eq!(lines(&engine, 0).len(), 4);
+    assert_eq!(lines(&engine, 1)[0].0, "justified paragraphs look");
+
+    // A paragraph whose first line would sit alone at the bottom starts in the next frame
+    engine.document_state.render_tree.nodes.get_mut("left").unwrap().bounds.height = 84.0;
+    assert_eq!(lines(&engine, 0).len(), 7);
+    engine.set_text_story(story(serde_json::json!({"orphans": 2}))).unwrap();
+    assert_eq!(lines(&engine, 0).len(), 6);
+    assert_eq!(lines(&engine, 1)[0].0, "Second paragraph here");
+
+    assert_eq!(engine.set_text_story(story(serde_json::json!({"hyphenation": "nl"}))).unwrap_err().code, "UNKNOWN_HYPHENATION_LANGUAGE");
+    engine.register_hyphenation_patterns("nl", "1ba 1be 1ka 1ke").unwrap();
+    assert!(engine.set_text_story(story(serde_json::json!({"hyphenation": "nl"}))).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_text_shaping_and_bidi() {
+    let shape = |text: &str| -> Vec<(usize, f64)> {
+        let request = ShapingRequest { text: text.to_string(), font_size: 10.0, font_family: None, font_weight: None, italic: false, rtl: false };
+        EstimatedShaper.shape(&request).iter().map(|cluster| (cluster.start, cluster.advance)).collect()
+    };
+    // Combining marks, skin tones, ZWJ families, flags and emoji presentation stay with their base
+    assert_eq!(shape("e\u{301}t"), vec![(0, 6.0), (2, 6.0)]);
+    assert_eq!(shape("👍🏽👨\u{200D}👩\u{200D}👧"), vec![(0, 10.0), (2, 10.0)]);
+    assert_eq!(shape("🇫🇷🇩🇪"), vec![(0, 10.0), (2, 10.0)]);
+    assert_eq!(shape("❤\u{FE0F}"), vec![(0, 10.0)]);
+
+    let geometry = [FrameGeometry { width: 200.0, height: 100.0, columns: 1, column_gap: 0.0 }];
+    let story = |text: &str| -> TextStory {
+        serde_json::from_value(serde_json::json!({"id": "mixed", "font_size": 10.0, "frames": ["frame"], "runs": [{"text": text}]})).unwrap()
+    };
+    let spans = |layout: &StoryLayout| -> Vec<(String, f64, bool)> {
+        layout.frames[0].lines[0].spans.iter().map(|span| (span.text.clone(), span.x, span.rtl)).collect()
+    };
+
+    // An embedded right-to-left phrase is reversed as a whole; its text stays in logical order
+    let layout = layout_story(&story("hello שלום עולם world"), &geometry, None, &EstimatedShaper);
+    assert_eq!(spans(&layout), vec![
+        ("hello ".to_string(), 0.0, false),
+        ("שלום עולם".to_string(), 36.0, true),
+        (" world".to_string(), 90.0, false),
+    ]);
+
+    // A right-to-left paragraph runs from the right, with numbers kept left to right
+    let layout = layout_story(&story("אבג 1,000 דהו"), &geometry, None, &EstimatedShaper);
+    assert_eq!(spans(&layout), vec![
+        (" דהו".to_string(), 0.0, true),
+        ("1,000".to_string(), 24.0, false),
+        ("אבג ".to_string(), 54.0, true),
+    ]);
+    assert_eq!(layout.frames[0].text(), "אבג 1,000 דהו");
+
+    // A host shaper's clusters decide the advances and where lines may break
+    struct Ligatures;
+    impl TextShaper for Ligatures {
+        fn shape(&self, request: &ShapingRequest) -> Vec<ShapedCluster> {
+            (0..request.text.chars().count()).step_by(2).map(|start| ShapedCluster { start, advance: 15.0 }).collect()
+        }
+    }
+    let narrow = [FrameGeometry { width: 40.0, height: 100.0, columns: 1, column_gap: 0.0 }];
+    let layout = layout_story(&story("abcdefgh"), &narrow, None, &Ligatures);
+    let lines: Vec<String> = layout.frames[0].lines.iter().map(|line| line.spans[0].text.clone()).collect();
+    assert_eq!(lines, vec!["abcd", "efgh"]);
+    assert_eq!(layout.frames[0].lines[0].spans[0].width, 30.0);
+}
+
+#[wasm_bindgen_test]
+fn test_text_layout() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    for (id, element_type, properties) in [
+        ("label", "Text", serde_json::json!({"text": "hello world", "font_size": 10})),
+        ("rich", "Text", serde_json::json!({"font_size": 10, "spans": [{"text": "Big ", "font_size": 20}, {"text": "small", "color": "#ff0000"}]})),
+        ("column", "Container", serde_json::json!({"layout": "flex-column", "width": 60})),
+        ("para", "Text", serde_json::json!({"text": "aaaa bbbb cccc", "font_size": 10, "text_align": "center"})),
+    ] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": element_type, "properties": properties, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        })).unwrap()).unwrap();
+    }
+    engine.document_state.attach_child("column", "para");
+    engine.render_frame(0.0).unwrap();
+    let size = |engine: &InteractiveEngine, id: &str| {
+        let bounds = &engine.document_state.render_tree.nodes[id].bounds;
+        (bounds.width, bounds.height)
+    };
+
+    // Without a width, text is as wide as its longest line; lines are 1.2 times the largest font size
+    assert_eq!(size(&engine, "label"), (66.0, 12.0));
+    assert_eq!(size(&engine, "rich"), (78.0, 24.0));
+    // Stretched across a 60px column, the paragraph wraps onto a second, centered line
+    assert_eq!(size(&engine, "para"), (60.0, 24.0));
+    assert_eq!(size(&engine, "column").1, 24.0);
+    let lines: Vec<FlowLine> = serde_json::from_value(engine.document_state.get_element("para").unwrap().properties["text_lines"].clone()).unwrap();
+    assert_eq!(lines.len(), 2);
+    assert_eq!((lines[1].spans[0].text.as_str(), lines[1].spans[0].x, lines[1].y), ("cccc", 18.0, 12.0));
+    let lines: Vec<FlowLine> = serde_json::from_value(engine.document_state.get_element("rich").unwrap().properties["text_lines"].clone()).unwrap();
+    let runs: Vec<(usize, f64)> = lines[0].spans.iter().map(|span| (span.run, span.x)).collect();
+    assert_eq!(runs, vec![(0, 0.0), (1, 48.0)]);
+    assert!(engine.render_frame(16.0).unwrap().dom_operations.is_empty());
+
+    // A font table gives the family's advances and line height
+    let table = |units_per_em: f64| -> FontTable {
+        serde_json::from_value(serde_json::json!({
+            "family": "Mono", "units_per_em": units_per_em, "ascent": 800, "descent": 200, "default_advance": 500, "advances": {"i": 250},
+        })).unwrap()
+    };
+    assert_eq!(engine.register_font_table(table(0.0)).unwrap_err().code, "INVALID_FONT_TABLE");
+    engine.register_font_table(table(1000.0)).unwrap();
+    let properties = [("text".to_string(), serde_json::json!("hi")), ("font_family".to_string(), serde_json::json!("Mono"))];
+    engine.document_state.update_element("label", properties.into_iter().collect()).unwrap();
+    let update = engine.render_frame(32.0).unwrap();
+    // 7.5px rounded up, and one em per line
+    assert_eq!(size(&engine, "label"), (8.0, 10.0));
+    assert!(update.dom_operations.iter().any(|operation| matches!(operation,
+        DOMOperation::Update { element_id, attributes } if element_id == "label" && attributes.contains_key("text_lines"))));
+
+    // A story frame keeps the box it is given
+    let story: TextStory = serde_json::from_value(serde_json::json!({"id": "story", "frames": ["label"], "runs": [{"text": "flowed"}]})).unwrap();
+    engine.set_text_story(story).unwrap();
+    engine.render_frame(48.0).unwrap();
+    assert_eq!(size(&engine, "label"), (100.0, 100.0));
+}
+
+#[wasm_bindgen_test]
+fn test_quiz_scoring_and_attempts() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    for (id, element_type) in [("capital", "Question"), ("primes", "Question"), ("pi", "Question"), ("steps", "Question"), ("note", "Text")] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": element_type, "properties": {}, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        })).unwrap()).unwrap();
+    }
+    let question = |value: serde_json::Value| -> Question { serde_json::from_value(value).unwrap() };
+    engine.set_question(question(serde_json::json!({
+        "element_id": "capital", "prompt": "Capital of France?", "max_attempts": 2,
+        "kind": {"MultipleChoice": {"options": ["Lyon", "Paris", "Nice"], "correct": 1}},
+    }))).unwrap();
+    engine.set_question(question(serde_json::json!({
+        "element_id": "primes", "prompt": "Pick the primes", "points": 2.0,
+        "kind": {"MultiSelect": {"options": ["2", "4", "5", "9"], "correct": [0, 2], "partial_credit": true}},
+    }))).unwrap();
+    engine.set_question(question(serde_json::json!({
+        "element_id": "pi", "prompt": "Pi to one place",
+        "kind": {"Numeric": {"answer": 3.1, "tolerance": 0.05}},
+    }))).unwrap();
+    engine.set_question(question(serde_json::json!({
+        "element_id": "steps", "prompt": "Order the steps",
+        "kind": {"Ordering": {"items": ["plan", "build", "ship"]}},
+    }))).unwrap();
+
+    // Answer keys are checked up front
+    let bad = question(serde_json::json!({"element_id": "note", "prompt": "?", "kind": {"Numeric": {"answer": 1.0}}}));
+    assert_eq!(engine.set_question(bad).unwrap_err().code, "INVALID_QUESTION");
+    let bad = question(serde_json::json!({"element_id": "pi", "prompt": "?", "kind": {"MultipleChoice": {"options": ["a"], "correct": 3}}}));
+    assert_eq!(engine.set_question(bad).unwrap_err().code, "INVALID_QUESTION");
+    assert_eq!(engine.submit_answer("pi", QuestionAnswer::Choice(0)).unwrap_err().code, "INVALID_ANSWER");
+    assert_eq!(engine.submit_answer("steps", QuestionAnswer::Order(vec![0, 0, 1])).unwrap_err().code, "INVALID_ANSWER");
+    assert_eq!(engine.submit_answer("note", QuestionAnswer::Number(1.0)).unwrap_err().code, "QUESTION_NOT_FOUND");
+
+    engine.subscribe_event(QUIZ_COMPLETED_EVENT, Some("note".to_string()), "update_text", [
+        ("target".to_string(), serde_json::json!("note")),
+        ("text".to_string(), serde_json::json!("Scored {score} of {max_score}")),
+    ].into_iter().collect(), false).unwrap();
+
+    // A wrong first try leaves one more attempt
+    engine.submit_answer("capital", QuestionAnswer::Choice(0)).unwrap();
+    let properties = &engine.document_state.get_element("capital").unwrap().properties;
+    assert_eq!(properties.get("quiz_correct"), Some(&serde_json::json!(false)));
+    assert_eq!(properties.get("quiz_closed"), Some(&serde_json::json!(false)));
+    engine.submit_answer("capital", QuestionAnswer::Choice(1)).unwrap();
+    assert_eq!(engine.submit_answer("capital", QuestionAnswer::Choice(1)).unwrap_err().code, "QUESTION_CLOSED");
+
+    // One right and one wrong pick of two primes earns nothing; numeric answers allow a tolerance
+    engine.submit_answer("primes", QuestionAnswer::Choices(vec![0, 1])).unwrap();
+    engine.submit_answer("pi", QuestionAnswer::Number(3.12)).unwrap();
+    let score = engine.get_quiz_score();
+    assert_eq!((score.score, score.max_score, score.answered, score.total, score.completed), (2.0, 5.0, 3, 4, false));
+
+    engine.submit_answer("steps", QuestionAnswer::Order(vec![0, 1, 2])).unwrap();
+    assert!(engine.get_quiz_score().completed);
+    let events = engine.drain_quiz_events();
+    assert_eq!(events.len(), 6);
+    assert!(matches!(&events[5], QuizEvent::Completed { score, max_score, .. } if *score == 3.0 && *max_score == 5.0));
+    let completed = engine.get_event_bus_log().into_iter().last().unwrap();
+    assert_eq!((completed.event_name.as_str(), completed.delivered_to.len()), (QUIZ_COMPLETED_EVENT, 1));
+
+    // Attempts survive a freeze and thaw, and can be saved and restored by the host
+    let saved = engine.get_quiz_attempts();
+    let snapshot = engine.freeze().unwrap();
+    engine.reset_quiz().unwrap();
+    assert_eq!(engine.get_quiz_score().answered, 0);
+    engine.thaw(&snapshot).unwrap();
+    assert_eq!(engine.get_quiz_attempts(), saved);
+    engine.reset_quiz().unwrap();
+    assert_eq!(engine.restore_quiz_attempts(saved).unwrap(), 4);
+    assert_eq!(engine.get_quiz_score().score, 3.0);
+    assert!(engine.drain_quiz_events().is_empty());
+
+    // Documents carry their questions
+    let questions = engine.document_state.questions.clone();
+    let token = engine.load_document_chunked(serde_json::json!({"questions": questions}).to_string());
+    while !engine.continue_operation(&token, 100.0).unwrap().done {}
+    assert_eq!(engine.document_state.questions.len(), 4);
+}
+
+#[wasm_bindgen_test]
+fn test_guided_tour() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string(), "Click".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    for (id, x, y, width, height) in [("toolbar", 20.0, 30.0, 200.0, 40.0), ("hidden", 0.0, 0.0, 0.0, 0.0), ("sidebar", 300.0, 100.0, 100.0, 400.0)] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": "Container", "properties": {}, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        })).unwrap()).unwrap();
+        engine.document_state.render_tree.nodes.get_mut(id).unwrap().bounds = BoundingBox { x, y, width, height };
+    }
+    let tour = |value: serde_json::Value| -> Tour { serde_json::from_value(value).unwrap() };
+
+    assert_eq!(engine.define_tour(tour(serde_json::json!({"id": "empty", "steps": []}))).unwrap_err().code, "INVALID_TOUR");
+    assert_eq!(engine.start_tour("intro").unwrap_err().code, "TOUR_NOT_FOUND");
+    assert_eq!(engine.tour_next().unwrap_err().code, "NO_ACTIVE_TOUR");
+    engine.define_tour(tour(serde_json::json!({"id": "nothing", "steps": [{"target": "hidden", "title": "Gone"}]}))).unwrap();
+    assert_eq!(engine.start_tour("nothing").unwrap_err().code, "INVALID_TOUR");
+    engine.define_tour(tour(serde_json::json!({"id": "intro", "steps": [
+        {"target": "toolbar", "title": "Tools", "body": "Everything starts here"},
+        {"target": "hidden", "title": "Hidden"},
+        {"target": "sidebar", "title": "Outline", "placement": "Left"},
+    ]}))).unwrap();
+
+    // The overlay is created once and the spotlight is padded around the target
+    let update = engine.start_tour("intro").unwrap();
+    assert!(update.dom_operations.iter().any(|op| matches!(op, DOMOperation::Create { element_id, .. } if element_id == TourLayer::LAYER_ID)));
+    let spotlight = |update: &RenderUpdate| update.dom_operations.iter().rev().find_map(|op| match op {
+        DOMOperation::Update { element_id, attributes } if element_id == TourLayer::SPOTLIGHT_ID && attributes.contains_key("x") => {
+            Some((attributes["x"].clone(), attributes["y"].clone()))
+        }
+        _ => None,
+    });
+    assert_eq!(spotlight(&update), Some(("12.00".to_string(), "22.00".to_string())));
+    let progress = engine.get_tour_progress().unwrap();
+    assert_eq!((progress.step, progress.step_count, progress.target.as_str()), (0, 3, "toolbar"));
+
+    // Back on the first step does nothing
+    engine.tour_back().unwrap();
+    assert_eq!(engine.get_tour_progress().unwrap().step, 0);
+
+    // The callout's Next button advances, passing over the step whose target is not laid out
+    let click: InteractionEvent = serde_json::from_value(serde_json::json!({
+        "event_type": "Click", "target_element": TourLayer::NEXT_ID, "position": null,
+        "data": {}, "timestamp": 20.0, "touch_data": null, "mouse_data": null, "keyboard_data": null,
+        "gesture_data": null, "modifiers": {"ctrl": false, "shift": false, "alt": false, "meta": false},
+    })).unwrap();
+    let update = engine.process_interaction(click).unwrap();
+    assert!(!update.dom_operations.iter().any(|op| matches!(op, DOMOperation::Create { .. })));
+    assert_eq!(spotlight(&update), Some(("292.00".to_string(), "92.00".to_string())));
+    assert_eq!(engine.get_tour_progress().unwrap().step, 2);
+    let step = engine.get_event_bus_log().into_iter().last().unwrap();
+    assert_eq!((step.event_name.as_str(), step.payload["step"].clone()), (TOUR_STEP_EVENT, serde_json::json!(2)));
+    engine.tour_back().unwrap();
+    assert_eq!(engine.get_tour_progress().unwrap().target, "toolbar");
+
+    // Finishing removes the overlay
+    engine.tour_next().unwrap();
+    let update = engine.tour_next().unwrap();
+    assert!(update.dom_operations.iter().any(|op| matches!(op, DOMOperation::Remove { element_id } if element_id == TourLayer::LAYER_ID)));
+    assert!(engine.get_tour_progress().is_none());
+    assert_eq!(engine.get_event_bus_log().into_iter().last().unwrap().event_name, TOUR_COMPLETED_EVENT);
+
+    engine.start_tour("intro").unwrap();
+    engine.skip_tour().unwrap();
+    assert!(engine.get_tour_progress().is_none());
+    assert_eq!(engine.get_event_bus_log().into_iter().last().unwrap().event_name, TOUR_SKIPPED_EVENT);
+}
+
+#[wasm_bindgen_test]
+fn test_branching_pages() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    for (id, element_type) in [("intro", "Container"), ("quiz", "Container"), ("q1", "Question"), ("remedial", "Container"), ("done", "Container")] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": element_type, "properties": {}, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        })).unwrap()).unwrap();
+    }
+    engine.set_question(serde_json::from_value(serde_json::json!({
+        "element_id": "q1", "prompt": "2 + 2?", "kind": {"Numeric": {"answer": 4.0}},
+    })).unwrap()).unwrap();
+    let pages = |value: serde_json::Value| -> Vec<Page> { serde_json::from_value(value).unwrap() };
+
+    assert_eq!(engine.next_page().unwrap_err().code, "NO_CURRENT_PAGE");
+    let duplicate = pages(serde_json::json!([{"id": "a", "elements": []}, {"id": "a", "elements": []}]));
+    assert_eq!(engine.set_pages(duplicate).unwrap_err().code, "INVALID_PAGES");
+    let broken = pages(serde_json::json!([{"id": "a", "elements": [], "routes": [{"to": "b"}]}]));
+    assert_eq!(engine.set_pages(broken).unwrap_err().code, "INVALID_PAGES");
+
+    // Remediation only leads back once the learner has reviewed it, so it can strand them
+    let reachability = engine.set_pages(pages(serde_json::json!([
+        {"id": "intro", "elements": ["intro"], "routes": [{"to": "quiz"}]},
+        {"id": "quiz", "elements": ["quiz", "q1"], "routes": [
+            {"to": "done", "when": {"QuizPercent": {"comparison": "GreaterOrEqual", "percent": 80.0}}},
+            {"to": "remedial"},
+        ]},
+        {"id": "remedial", "elements": ["remedial"], "routes": [
+            {"to": "quiz", "when": {"Data": {"source_id": "progress", "field": "reviewed", "comparison": "Equal", "value": true}}},
+        ]},
+        {"id": "done", "elements": ["done"], "end": true},
+        {"id": "orphan", "elements": []},
+    ]))).unwrap();
+    assert_eq!(reachability.start.as_deref(), Some("intro"));
+    assert_eq!(reachability.reachable, vec!["intro", "quiz", "remedial", "done"]);
+    assert_eq!(reachability.unreachable, vec!["orphan"]);
+    assert_eq!(reachability.dead_ends, vec!["remedial"]);
+    let visible = |engine: &InteractiveEngine, id: &str| engine.document_state.render_tree.nodes[id].visible;
+    assert!(visible(&engine, "intro") && !visible(&engine, "quiz") && !visible(&engine, "done"));
+
+    // A wrong answer routes to remediation
+    engine.next_page().unwrap();
+    assert!(visible(&engine, "quiz") && visible(&engine, "q1") && !visible(&engine, "intro"));
+    engine.submit_answer("q1", QuestionAnswer::Number(5.0)).unwrap();
+    engine.next_page().unwrap();
+    assert_eq!(engine.get_current_page().as_deref(), Some("remedial"));
+    assert_eq!(engine.next_page().unwrap_err().code, "NO_MATCHING_ROUTE");
+    engine.document_state.data_sources.insert(
+        "progress".to_string(),
+        DataSource::new("progress".to_string(), DataSourceType::Dynamic, serde_json::json!({"reviewed": true})),
+    );
+    engine.next_page().unwrap();
+    assert_eq!(engine.get_page_history(), vec!["intro", "quiz", "remedial", "quiz"]);
+
+    // Back walks the history, and a navigation Back does the same
+    engine.page_back().unwrap();
+    let changed = engine.get_event_bus_log().into_iter().last().unwrap();
+    assert_eq!(changed.event_name, PAGE_CHANGED_EVENT);
+    assert_eq!((changed.payload["from"].clone(), changed.payload["to"].clone(), changed.payload["back"].clone()), (serde_json::json!("quiz"), serde_json::json!("remedial"), serde_json::json!(true)));
+    engine.perform_navigation_action(&NavigationAction::Back).unwrap();
+    assert_eq!(engine.get_page_history(), vec!["intro", "quiz"]);
+
+    assert_eq!(engine.go_to_page("nowhere").unwrap_err().code, "PAGE_NOT_FOUND");
+    engine.go_to_page("done").unwrap();
+    assert!(visible(&engine, "done") && !visible(&engine, "quiz"));
+
+    // History survives a freeze and thaw
+    let snapshot = engine.freeze().unwrap();
+    engine.page_back().unwrap();
+    engine.page_back().unwrap();
+    engine.page_back().unwrap();
+    assert_eq!(engine.get_page_history(), vec!["intro"]);
+    engine.thaw(&snapshot).unwrap();
+    assert_eq!(engine.get_current_page().as_deref(), Some("done"));
+}
+
+// Markers are only drawn, and only spiderfied with legs, when charts are built
+#[cfg(feature = "charts")]
+#[wasm_bindgen_test]
+fn test_map_markers_and_clustering() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string(), "Click".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    engine.document_state.data_sources.insert(
+        "sites".to_string(),
+        DataSource::new("sites".to_string(), DataSourceType::Static, serde_json::json!([
+            {"lat": 0.0, "lon": 0.0, "label": "A"},
+            {"lat": 0.1, "lon": 0.1, "label": "B"},
+            {"lat": -0.05, "lon": 0.05, "label": "C"},
+            {"lat": 40.0, "lon": 60.0, "label": "D"},
+            {"label": "no coordinates"},
+        ])),
+    );
+    let config = ChartConfig {
+        width: 400.0,
+        height: 400.0,
+        margin: ChartMargin { top: 0.0, right: 0.0, bottom: 0.0, left: 0.0 },
+        ..ChartConfig::default()
+    };
+    engine.chart_renderer.create_chart_with_id("map", ChartType::Map, "sites".to_string(), config).unwrap();
+    engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+        "id": "map_element", "element_type": "Chart", "properties": {"chart_id": "map"}, "children": [],
+        "event_handlers": [], "transform": Transform::default(), "style": {},
+    })).unwrap()).unwrap();
+    engine.document_state.render_tree.nodes.get_mut("map_element").unwrap().bounds = BoundingBox { x: 0.0, y: 0.0, width: 400.0, height: 400.0 };
+
+    // Zoomed out, the three nearby sites are one cluster
+    let rendered = engine.render_chart_from_source("map").unwrap();
+    let series: Vec<&str> = rendered.data_points.iter().map(|point| point.series_id.as_str()).collect();
+    assert_eq!(series, vec!["clusters", "markers"]);
+    assert!(rendered.svg_content.contains(r#"class="cluster" data-rows="0,1,2""#));
+    assert_eq!(rendered.data_points[1].label.as_deref(), Some("D"));
+
+    let data = engine.chart_source_data("map").unwrap();
+    let marks = |engine: &InteractiveEngine| {
+        let state = engine.chart_renderer.map_states.get("map").cloned().unwrap_or_default();
+        map_layout(&engine.chart_renderer.charts["map"], &data, &state)
+    };
+    let click = |x: f64, y: f64| -> InteractionEvent { serde_json::from_value(serde_json::json!({
+        "event_type": "Click", "target_element": "map_element", "position": {"x": x, "y": y},
+        "data": {}, "timestamp": 20.0, "touch_data": null, "mouse_data": null, "keyboard_data": null,
+        "gesture_data": null, "modifiers": {"ctrl": false, "shift": false, "alt": false, "meta": false},
+    })).unwrap() };
+
+    // Clicking the cluster spiderfies it and redraws the chart element
+    let cluster = marks(&engine).into_iter().find(|mark| mark.rows.len() == 3).unwrap();
+    let update = engine.process_interaction(click(cluster.x, cluster.y)).unwrap();
+    assert_eq!(engine.chart_renderer.map_states["map"].spiderfied, vec![0, 1, 2]);
+    assert!(update.dom_operations.iter().any(|operation| matches!(operation,
+        DOMOperation::Update { element_id, attributes } if element_id == "map_element" && attributes["svg_content"].contains(r#"class=\"leg\""#))));
+    let published = engine.get_event_bus_log().into_iter().last().unwrap();
+    assert_eq!((published.event_name.as_str(), published.payload["count"].clone()), (CHART_CLUSTER_EVENT, serde_json::json!(3)));
+
+    // A spiderfied marker opens its popup, styled by the chart's tooltip settings
+    let marker = marks(&engine).into_iter().find(|mark| mark.rows == [1]).unwrap();
+    assert!(marker.leg.is_some());
+    let (element_id, chart_id, hit) = engine.chart_map_hit(&click(marker.x, marker.y)).unwrap();
+    assert_eq!(hit, MapHit::Marker(1));
+    engine.handle_map_click(&element_id, &chart_id, hit).unwrap();
+    assert_eq!(engine.chart_renderer.map_states["map"].popup, Some(1));
+    let published = engine.get_event_bus_log().into_iter().last().unwrap();
+    assert_eq!((published.event_name.as_str(), published.payload["label"].clone()), (CHART_MARKER_EVENT, serde_json::json!("B")));
+    let svg = engine.render_chart_from_source("map").unwrap().svg_content;
+    assert!(svg.contains(r#"class="popup" data-row="1""#) && svg.contains(r##"fill="#000000""##));
+
+    // A click on empty map closes both
+    let (element_id, chart_id, hit) = engine.chart_map_hit(&click(5.0, 395.0)).unwrap();
+    assert_eq!(hit, MapHit::Background);
+    engine.handle_map_click(&element_id, &chart_id, hit).unwrap();
+    assert_eq!(engine.chart_renderer.map_states["map"], MapState::default());
+
+    // Zoomed in, the sites separate and the far one is out of view
+    let view = |west: f64, south: f64, east: f64, north: f64| GeoBounds { west, south, east, north };
+    let rendered = engine.set_map_view("map", view(-0.2, -0.2, 0.2, 0.2)).unwrap();
+    assert_eq!(rendered.data_points.len(), 3);
+    assert!(rendered.data_points.iter().all(|point| point.series_id == "markers"));
+    assert_eq!(engine.set_map_view("map", view(10.0, 0.0, -10.0, 5.0)).unwrap_err().code, "INVALID_MAP_VIEW");
+    engine.chart_renderer.create_chart_with_id("bars", ChartType::Bar, "sites".to_string(), ChartConfig::default()).unwrap();
+    assert_eq!(engine.set_map_view("bars", view(-1.0, -1.0, 1.0, 1.0)).unwrap_err().code, "INVALID_MAP_VIEW");
+}
+
+#[wasm_bindgen_test]
+fn test_chart_facets() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string(), "Click".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    engine.document_state.data_sources.insert(
+        "sales".to_string(),
+        DataSource::new("sales".to_string(), DataSourceType::Static, serde_json::json!([
+            {"region": "North", "value": 10.0},
+            {"region": "South", "value": 40.0},
+            {"region": "North", "value": 20.0},
+            {"region": "East", "value": 30.0},
+            {"value": 99.0},
+        ])),
+    );
+    let config = ChartConfig {
+        width: 400.0,
+        height: 400.0,
+        legend: Some(ChartLegend { position: LegendPosition::Bottom, show: true, font_size: 12.0, color: "#333333".to_string() }),
+        ..ChartConfig::default()
+    };
+    engine.chart_renderer.create_chart_with_id("sales", ChartType::Bar, "sales".to_string(), config).unwrap();
+    engine.chart_renderer.add_series("sales", ChartSeries {
+        id: "value".to_string(),
+        name: "Sales".to_string(),
+        data_field: "value".to_string(),
+        color: "#1f77b4".to_string(),
+        line_width: None,
+        fill_opacity: None,
+        marker_size: None,
+        marker_shape: None,
+        markers: None,
+        line_style: None,
+        visible: true,
+        y_axis: AxisReference::Primary,
+        error: None,
+        pattern: None,
+    }).unwrap();
+    let chart = engine.chart_renderer.charts.get_mut("sales").unwrap();
+    chart.axes.x_axis.as_mut().unwrap().label = Some("Month".to_string());
+    chart.axes.y_axis.as_mut().unwrap().label = Some("Revenue".to_string());
+
+    let facet = FacetOptions { field: "region".to_string(), columns: None, shared_scales: true, gap: 10.0 };
+    assert_eq!(engine.chart_renderer.set_chart_facet("sales", Some(FacetOptions { columns: Some(0), ..facet.clone() })).unwrap_err().code, "INVALID_FACET");
+    engine.chart_renderer.set_chart_facet("sales", Some(facet.clone())).unwrap();
+
+    // One cell per region in first-seen order, with the legend and axis labels drawn once
+    let rendered = engine.render_chart_from_source("sales").unwrap();
+    for region in ["North", "South", "East"] {
+        assert!(rendered.svg_content.contains(&format!(r#"data-facet="{}""#, region)));
+    }
+    assert_eq!(rendered.svg_content.matches(r#"class="chart-facet""#).count(), 3);
+    assert_eq!(rendered.svg_content.matches(r#"class="legend""#).count(), 1);
+    assert_eq!(rendered.svg_content.matches(r#"class="axis-label""#).count(), 2);
+    assert_eq!(rendered.data_points.len(), 4);
+
+    // Three facets make a 2x2 grid; shared scales give every cell the whole value range
+    let data = engine.chart_source_data("sales").unwrap();
+    let chart = engine.chart_renderer.charts["sales"].clone();
+    let cells = facet_layout(&chart, &facet, &data);
+    assert_eq!(cells.iter().map(|cell| cell.rows.len()).collect::<Vec<_>>(), vec![2, 1, 1]);
+    assert_eq!((cells[2].x, cells[1].y), (cells[0].x, cells[0].y));
+    assert!(cells[1].x > cells[0].x + cells[0].width && cells[2].y > cells[0].y + cells[0].height);
+    let y_range = |facet: &FacetOptions| {
+        let axis = facet_cell_chart(&chart, facet, &cells[0], &data).axes.y_axis.unwrap();
+        (axis.min_value, axis.max_value)
+    };
+    assert_eq!(y_range(&facet), (Some(0.0), Some(40.0)));
+    assert_eq!(y_range(&FacetOptions { shared_scales: false, ..facet.clone() }), (None, None));
+
+    // A click in a cell publishes its facet key
+    engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+        "id": "sales_element", "element_type": "Chart", "properties": {"chart_id": "sales"}, "children": [],
+        "event_handlers": [], "transform": Transform::default(), "style": {},
+    })).unwrap()).unwrap();
+    engine.document_state.render_tree.nodes.get_mut("sales_element").unwrap().bounds = BoundingBox { x: 0.0, y: 0.0, width: 400.0, height: 400.0 };
+    let click: InteractionEvent = serde_json::from_value(serde_json::json!({
+        "event_type": "Click", "target_element": "sales_element",
+        "position": {"x": cells[1].x + 5.0, "y": cells[1].y + FACET_LABEL_HEIGHT + 5.0},
+        "data": {}, "timestamp": 20.0, "touch_data": null, "mouse_data": null, "keyboard_data": null,
+        "gesture_data": null, "modifiers": {"ctrl": false, "shift": false, "alt": false, "meta": false},
+    })).unwrap();
+    engine.process_interaction(click).unwrap();
+    let published = engine.get_event_bus_log().into_iter().last().unwrap();
+    assert_eq!(published.event_name, CHART_FACET_EVENT);
+    assert_eq!((published.payload["facet"].clone(), published.payload["x"].clone()), (serde_json::json!("South"), serde_json::json!(5.0)));
+
+    // Without facets the chart is drawn whole again
+    engine.chart_renderer.set_chart_facet("sales", None).unwrap();
+    assert!(!engine.render_chart_from_source("sales").unwrap().svg_content.contains("chart-facet"));
+}
+
+#[wasm_bindgen_test]
+fn test_geo_projections() {
+    use crate::geo::{great_circle_distance, GeoBounds, GeoViewport, Projection};
+    let close = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9;
+
+    // Both projections round-trip and put the equator and meridian in the middle
+    for projection in [Projection::Mercator, Projection::Equirectangular] {
+        assert!(close(projection.project(0.0, 0.0), (0.5, 0.5)));
+        let (x, y) = projection.project(51.5074, -0.1278);
+        assert!(close(projection.unproject(x, y), (51.5074, -0.1278)));
+    }
+    assert!(close(Projection::Equirectangular.project(45.0, 90.0), (0.75, 0.25)));
+    // Mercator's square world keeps mid latitudes nearer the equator than equirectangular does,
+    // and clamps beyond its limit
+    assert!(Projection::Mercator.project(45.0, 0.0).1 > Projection::Equirectangular.project(45.0, 0.0).1);
+    assert!(close(Projection::Mercator.project(89.0, 0.0), (0.5, 0.0)));
+
+    // London to Paris is about 343.5 km
+    let distance = great_circle_distance((51.5074, -0.1278), (48.8566, 2.3522));
+    assert!((distance - 343_500.0).abs() < 1_000.0, "{}", distance);
+    assert_eq!(great_circle_distance((10.0, 20.0), (10.0, 20.0)), 0.0);
+
+    // Fitting keeps the points in view, centred, with the viewport's aspect ratio
+    let points = [(48.8566, 2.3522), (51.5074, -0.1278), (52.52, 13.405)];
+    let bounds = GeoBounds::around(&points).unwrap();
+    assert_eq!((bounds.west, bounds.north), (-0.1278, 52.52));
+    assert!(GeoBounds::around(&[]).is_none());
+    let fitted = bounds.fit(Projection::Mercator, 400.0, 200.0, 10.0);
+    let viewport = GeoViewport { projection: Projection::Mercator, bounds: fitted, x: 0.0, y: 0.0, width: 400.0, height: 200.0 };
+    for (latitude, longitude) in points {
+        let (x, y) = viewport.to_pixels(latitude, longitude);
+        assert!((10.0 - 1e-6..=390.0 + 1e-6).contains(&x) && (10.0 - 1e-6..=190.0 + 1e-6).contains(&y), "{} {}", x, y);
+        assert!(close(viewport.to_geo(x, y), (latitude, longitude)));
+    }
+    let (left, _) = viewport.to_pixels(52.0, -0.1278);
+    let (right, _) = viewport.to_pixels(52.0, 13.405);
+    assert!((left - 10.0).abs() < 1e-6 && (right - 390.0).abs() < 1e-6);
+    // A single point still gets an area around it
+    let single = GeoBounds::around(&points[..1]).unwrap().fit(Projection::Equirectangular, 100.0, 100.0, 0.0);
+    assert!(single.validate().is_ok() && single.contains(48.8566, 2.3522));
+
+    // Map charts fit their view to all markers
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    engine.document_state.data_sources.insert(
+        "sites".to_string(),
+        DataSource::new("sites".to_string(), DataSourceType::Static, serde_json::json!([
+            {"lat": 0.0, "lon": 0.0, "label": "A"},
+            {"lat": 40.0, "lon": 60.0, "label": "D"},
+        ])),
+    );
+    engine.document_state.data_sources.insert(
+        "empty".to_string(),
+        DataSource::new("empty".to_string(), DataSourceType::Static, serde_json::json!([])),
+    );
+    let config = ChartConfig {
+        width: 400.0,
+        height: 400.0,
+        margin: ChartMargin { top: 0.0, right: 0.0, bottom: 0.0, left: 0.0 },
+        ..ChartConfig::default()
+    };
+    engine.chart_renderer.create_chart_with_id("map", ChartType::Map, "sites".to_string(), config.clone()).unwrap();
+    engine.set_map_view("map", GeoBounds { west: -1.0, south: -1.0, east: 1.0, north: 1.0 }).unwrap();
+    assert_eq!(engine.render_chart_from_source("map").unwrap().data_points.len(), 1);
+    let rendered = engine.fit_map_view("map", 20.0).unwrap();
+    assert_eq!(rendered.data_points.len(), 2);
+    let view = &engine.chart_renderer.charts["map"].config.map.view;
+    assert!(view.contains(0.0, 0.0) && view.contains(40.0, 60.0));
+
+    engine.chart_renderer.create_chart_with_id("nothing", ChartType::Map, "empty".to_string(), config).unwrap();
+    assert_eq!(engine.fit_map_view("nothing", 20.0).unwrap_err().code, "NO_DATA");
+}
+
+#[wasm_bindgen_test]
+fn test_view_history() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    for (id, element_type, properties) in [
+        ("a", "Container", serde_json::json!({})),
+        ("b", "Container", serde_json::json!({})),
+        ("map_element", "Chart", serde_json::json!({"chart_id": "sites map"})),
+    ] {
+        engine.document_state.add_element(serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": element_type, "properties": properties, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        })).unwrap()).unwrap();
+    }
+    engine.document_state.data_sources.insert(
+        "sites".to_string(),
+        DataSource::new("sites".to_string(), DataSourceType::Static, serde_json::json!([{"lat": 10.0, "lon": 10.0}])),
+    );
+    engine.chart_renderer.create_chart_with_id("sites map", ChartType::Map, "sites".to_string(), ChartConfig::default()).unwrap();
+    let pages: Vec<Page> = serde_json::from_value(serde_json::json!([
+        {"id": "a", "elements": ["a"], "routes": [{"to": "b"}]},
+        {"id": "b", "elements": ["b"], "routes": [{"to": "c"}]},
+        {"id": "c", "elements": [], "end": true},
+    ])).unwrap();
+    engine.set_pages(pages).unwrap();
+    let history = |engine: &InteractiveEngine| engine.get_view_history();
+    assert_eq!((history(&engine).entries.len(), history(&engine).index), (1, 0));
+
+    // Scrolling follows in the current step; page changes, zooms and map views add steps
+    engine.set_viewport_offset(0.0, 300.0);
+    assert_eq!(history(&engine).entries.len(), 1);
+    assert_eq!(history(&engine).entries[0].offset_y, 300.0);
+    engine.go_to_page("b").unwrap();
+    engine.set_viewport_offset(0.0, 50.0);
+    engine.update_viewport(1920.0, 1080.0, 2.0).unwrap();
+    engine.update_viewport(1280.0, 720.0, 2.0).unwrap();
+    let world = engine.chart_renderer.charts["sites map"].config.map.view.clone();
+    let zoomed = GeoBounds { west: 0.0, south: 0.0, east: 20.0, north: 20.0 };
+    engine.set_map_view("sites map", zoomed.clone()).unwrap();
+    assert_eq!((history(&engine).entries.len(), history(&engine).index), (4, 3));
+    assert!(history(&engine).can_go_back() && !history(&engine).can_go_forward());
+
+    // Back restores the map view and redraws its element, then the zoom, then the first page
+    let update = engine.navigate_back().unwrap();
+    assert_eq!(engine.chart_renderer.charts["sites map"].config.map.view, world);
+    assert!(update.dom_operations.iter().any(|operation| matches!(operation,
+        DOMOperation::Update { element_id, attributes } if element_id == "map_element" && attributes.contains_key("svg_content"))));
+    engine.navigate_back().unwrap();
+    assert_eq!((engine.document_state.viewport.scale, engine.document_state.viewport.offset_y), (1.0, 50.0));
+    assert_eq!(engine.document_state.viewport.width, 1280.0);
+    engine.navigate_back().unwrap();
+    assert_eq!(engine.get_current_page().as_deref(), Some("a"));
+    assert_eq!(engine.get_page_history(), vec!["a"]);
+    assert_eq!(engine.document_state.viewport.offset_y, 300.0);
+    assert!(!engine.document_state.render_tree.nodes["b"].visible);
+    engine.navigate_back().unwrap();
+    assert_eq!((history(&engine).entries.len(), history(&engine).index), (4, 0));
+
+    // Forward replays; navigating from the middle drops the steps ahead
+    engine.navigate_forward().unwrap();
+    assert_eq!(engine.get_page_history(), vec!["a", "b"]);
+    engine.next_page().unwrap();
+    assert_eq!((history(&engine).entries.len(), history(&engine).index), (3, 2));
+    assert!(!history(&engine).can_go_forward());
+
+    // Deep links encode the view and open as a new step
+    let link = engine.encode_view_link();
+    assert!(link.starts_with("x=0&y=50&s=1&p=c&m.sites%20map="), "{}", link);
+    assert_eq!(engine.current_view().with_link(&link).unwrap(), engine.current_view());
+    engine.open_view_link("#p=a&y=10&m.sites%20map=0,0,20,20&future=1").unwrap();
+    assert_eq!(engine.get_current_page().as_deref(), Some("a"));
+    assert_eq!(engine.chart_renderer.charts["sites map"].config.map.view, zoomed);
+    assert_eq!((engine.document_state.viewport.offset_y, history(&engine).entries.len()), (10.0, 4));
+    for link in ["s=0", "y=far", "m.sites=1,2", "p=%zz", "m.sites=10,0,0,5"] {
+        assert_eq!(engine.open_view_link(link).unwrap_err().code, "INVALID_VIEW_LINK", "{}", link);
+    }
+
+    // The history is kept in snapshots
+    let snapshot = engine.freeze().unwrap();
+    engine.navigate_back().unwrap();
+    engine.thaw(&snapshot).unwrap();
+    assert_eq!(history(&engine).index, 3);
+}
+
+#[cfg(feature = "native")]
+#[wasm_bindgen_test]
+fn test_headless_processing() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let sales = DataSource::new("sales".to_string(), DataSourceType::Static, serde_json::json!([{"x": 1, "y": 2}])).with_schema(DataSchema {
+        fields: [("y".to_string(), FieldSchema { field_type: FieldType::Number, min: None, max: None, nullable: false })].into_iter().collect(),
+        required: vec!["y".to_string()],
+        mode: ValidationMode::Reject,
+    });
+    let document = serde_json::json!({
+        "elements": [{
+            "id": "node", "element_type": "Container", "properties": {}, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        }],
+        "data_sources": {"sales": sales},
+    });
+
+    let mut engine = InteractiveEngine::open_document(permissions.clone(), &document.to_string()).unwrap();
+    assert!(InteractiveEngine::open_document(permissions, "{\"elements\": [{}]}").is_err());
+    engine.chart_renderer.create_chart_with_id("sales_chart", ChartType::Bar, "sales".to_string(), ChartConfig::default()).unwrap();
+    let report = engine.validate_document().unwrap();
+    assert_eq!((report.elements, report.charts.clone()), (1, vec!["sales_chart".to_string()]));
+    assert!(report.is_clean(), "{:?}", report);
+
+    // Static export and thumbnails come out as SVG
+    let exports = engine.export_all_charts(ExportFormat::Svg, 1.0).unwrap();
+    assert_eq!(exports.len(), 1);
+    assert!(exports[0].content.starts_with("<svg"));
+    let thumbnail = engine.chart_thumbnail("sales_chart", 160.0, 90.0).unwrap();
+    assert!(thumbnail.contains(r#"width="160""#));
+
+    // A batch stops at the first source it cannot bind
+    let version = engine.document_state.data_sources["sales"].version;
+    engine.bind_data_batch(vec![("sales".to_string(), serde_json::json!([{"x": 1, "y": 5}]))], 16.0).unwrap();
+    assert_eq!(engine.document_state.data_sources["sales"].version, version + 1);
+    let invalid = engine.bind_data_batch(vec![("sales".to_string(), serde_json::json!([{"x": 2}]))], 32.0);
+    assert!(invalid.is_err());
+    let missing = engine.bind_data_batch(vec![("missing".to_string(), serde_json::json!([]))], 48.0);
+    assert_eq!(missing.unwrap_err().code, "DATA_SOURCE_NOT_FOUND");
+}
+
+#[cfg(feature = "ffi")]
+#[wasm_bindgen_test]
+fn test_ffi_engine_handle() {
+    use crate::ffi::*;
+    use flatbuffers::{ForwardsUOffset, Table, Vector};
+    use std::ffi::{CStr, CString};
+    type Tables<'a> = ForwardsUOffset<Vector<'a, ForwardsUOffset<Table<'a>>>>;
+    let text = |value: &str| CString::new(value).unwrap();
+    // Reads fields the way flatc-generated code for include/liv_update.fbs does
+    let root = |bytes: &'static [u8]| -> Table<'static> {
+        assert!(flatbuffers::buffer_has_identifier(bytes, UPDATE_FILE_IDENTIFIER, false));
+        unsafe { flatbuffers::root_unchecked::<Table>(bytes) }
+    };
+    let string = |table: &Table, field: u16| unsafe { table.get::<ForwardsUOffset<&str>>(field_slot(field), None) }.map(str::to_string);
+    let tables = |table: &Table<'static>, field: u16| -> Vec<Table<'static>> {
+        unsafe { table.get::<Tables>(field_slot(field), None) }.map_or_else(Vec::new, |vector| vector.iter().collect())
+    };
+    let sequence = |buffer: &LivBuffer| {
+        let bytes: &'static [u8] = unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) };
+        unsafe { root(bytes).get::<u64>(field_slot(4), Some(0)) }.unwrap()
+    };
+
+    // Every RenderUpdate field survives the flatbuffer encoding
+    let update = RenderUpdate {
+        dom_operations: vec![
+            DOMOperation::Create { element_id: "card".to_string(), tag: "div".to_string(), parent_id: None },
+            DOMOperation::Update { element_id: "card".to_string(), attributes: [("width".to_string(), "10".to_string()), ("fill".to_string(), "red".to_string())].into_iter().collect() },
+            DOMOperation::Move { element_id: "card".to_string(), new_parent_id: "page".to_string(), index: 2 },
+            DOMOperation::Remove { element_id: "old".to_string() },
+        ],
+        style_changes: vec![StyleChange { element_id: "card".to_string(), property: "opacity".to_string(), value: "0.5".to_string() }],
+        animation_updates: vec![AnimationUpdate {
+            animation_id: "pulse".to_string(),
+            progress: 0.25,
+            current_values: [("x".to_string(), serde_json::json!(12.5)), ("fill".to_string(), serde_json::json!("#ff0000"))].into_iter().collect(),
+        }],
+        timestamp: 16.0,
+        sequence: 7,
+        full_resync: true,
+        responses: Vec::new(),
+        dirty_regions: vec![BoundingBox { x: 1.0, y: 2.0, width: 3.0, height: 4.0 }],
+    };
+    let bytes: &'static [u8] = Box::leak(encode_render_update(&update).unwrap().into_boxed_slice());
+    let table = root(bytes);
+    unsafe {
+        assert_eq!(table.get::<f64>(field_slot(3), Some(0.0)), Some(16.0));
+        assert_eq!(table.get::<u64>(field_slot(4), Some(0)), Some(7));
+        assert_eq!(table.get::<bool>(field_slot(5), Some(false)), Some(true));
+        let operations = tables(&table, 0);
+        let kinds: Vec<u8> = operations.iter().map(|operation| operation.get::<u8>(field_slot(0), Some(0)).unwrap()).collect();
+        assert_eq!(kinds, vec![0, 1, 3, 2]);
+        assert_eq!(string(&operations[0], 2).as_deref(), Some("div"));
+        assert_eq!(string(&operations[0], 3), None);
+        let attributes: Vec<(String, String)> = tables(&operations[1], 5).iter().map(|attribute| (string(attribute, 0).unwrap(), string(attribute, 1).unwrap())).collect();
+        assert_eq!(attributes, vec![("fill".to_string(), "red".to_string()), ("width".to_string(), "10".to_string())]);
+        assert_eq!(string(&operations[2], 3).as_deref(), Some("page"));
+        assert_eq!(operations[2].get::<u64>(field_slot(4), Some(0)), Some(2));
+        assert_eq!(string(&operations[3], 1).as_deref(), Some("old"));
+        assert_eq!(string(&tables(&table, 1)[0], 2).as_deref(), Some("0.5"));
+        let animation = &tables(&table, 2)[0];
+        assert_eq!(animation.get::<f64>(field_slot(1), Some(0.0)), Some(0.25));
+        let values: Vec<(String, String)> = tables(animation, 2).iter().map(|value| (string(value, 0).unwrap(), string(value, 1).unwrap())).collect();
+        assert_eq!(values, vec![("fill".to_string(), "\"#ff0000\"".to_string()), ("x".to_string(), "12.5".to_string())]);
+        assert_eq!(string(&table, 6).as_deref(), Some("[]"));
+        let region = &tables(&table, 7)[0];
+        assert_eq!(region.get::<f64>(field_slot(3), Some(0.0)), Some(4.0));
+    }
+    let permissions = serde_json::json!({
+        "memory_limit": 1024 * 1024, "allowed_imports": ["console"], "cpu_time_limit": 5000,
+        "allow_networking": false, "allow_file_system": false, "allowed_interactions": ["StateChange"],
+        "max_data_size": 1024 * 1024, "max_elements": 100,
+    });
+
+    unsafe {
+        assert!(liv_engine_new(text("{}").as_ptr()).is_null());
+        assert!(CStr::from_ptr(liv_last_error()).to_str().unwrap().starts_with("Failed to parse permissions"));
+        let engine = liv_engine_new(text(&permissions.to_string()).as_ptr());
+        assert!(!engine.is_null());
+
+        let document = serde_json::json!({
+            "elements": [],
+            "data_sources": {"prices": DataSource::new("prices".to_string(), DataSourceType::Static, serde_json::json!([1]))},
+        });
+        assert_eq!(liv_engine_load_document(engine, text(&document.to_string()).as_ptr()), 0);
+        assert_eq!(liv_engine_load_document(engine, text("{\"elements\": [{}]}").as_ptr()), -1);
+        assert_eq!(liv_engine_update_data_source(engine, text("prices").as_ptr(), text("[1, 2]").as_ptr()), 0);
+        assert_eq!(liv_engine_update_data_source(engine, text("missing").as_ptr(), text("[]").as_ptr()), -1);
+
+        let frame = liv_engine_render_frame(engine, 16.0);
+        let first = sequence(&frame);
+        liv_buffer_free(frame);
+        let emitted = liv_engine_emit_event(engine, text("app.ready").as_ptr(), text("{\"ok\": true}").as_ptr(), std::ptr::null());
+        assert_eq!(sequence(&emitted), first + 1);
+        liv_buffer_free(emitted);
+        let log = liv_engine_event_log(engine);
+        let records: Vec<BusEventRecord> = serde_json::from_slice(std::slice::from_raw_parts(log.data, log.len)).unwrap();
+        assert_eq!(records.last().unwrap().event_name, "app.ready");
+        liv_buffer_free(log);
+
+        // Failures come back empty, with the reason
+        let failed = liv_engine_process_interaction(engine, text("not json").as_ptr());
+        assert!(failed.data.is_null() && failed.len == 0);
+        assert!(CStr::from_ptr(liv_last_error()).to_str().unwrap().contains("interaction event"));
+
+        // A panic inside the engine fails the call instead of unwinding into the host
+        assert_eq!(with_engine(engine, -1, |_| -> Result<i32, String> { panic!("layout exploded") }), -1);
+        assert_eq!(CStr::from_ptr(liv_last_error()).to_str().unwrap(), "Engine panicked: layout exploded");
+        liv_engine_free(engine);
+        assert_eq!(liv_engine_load_document(std::ptr::null_mut(), text("{}").as_ptr()), -1);
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_incremental_layout() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let page = engine.create_element(ElementType::Container, HashMap::new()).unwrap();
+    let card = engine.create_element(ElementType::Container, HashMap::new()).unwrap();
+    let label = engine.create_element(ElementType::Text, HashMap::new()).unwrap();
+    let sidebar = engine.create_element(ElementType::Container, HashMap::new()).unwrap();
+    engine.document_state.attach_child(&page, &card);
+    engine.document_state.attach_child(&card, &label);
+
+    // New elements are laid out under their top-level ancestors
+    let mut roots = vec![page.clone(), sidebar.clone()];
+    roots.sort();
+    assert_eq!(engine.document_state.render_tree.layout_roots(), roots);
+    let pass = engine.document_state.run_layout_pass(16.0).unwrap();
+    assert_eq!(pass.nodes, 4);
+    assert!(engine.document_state.run_layout_pass(32.0).is_none());
+
+    // A paint-only edit needs no layout; a size edit lays out just that subtree
+    let color = [("color".to_string(), serde_json::json!("#ff0000"))].into_iter().collect();
+    engine.document_state.update_element(&label, color).unwrap();
+    assert!(engine.document_state.render_tree.layout_dirty.is_empty());
+    let width = [("width".to_string(), serde_json::json!(240.0))].into_iter().collect();
+    engine.document_state.update_element(&card, width).unwrap();
+    engine.document_state.render_tree.mark_layout_dirty(&label);
+    assert_eq!(engine.document_state.render_tree.layout_roots(), vec![card.clone()]);
+    assert_eq!(engine.document_state.render_tree.subtree(&card), vec![card.clone(), label.clone()]);
+
+    // Zooming repaints without layout; resizing relayouts from the top-level elements
+    engine.document_state.render_tree.layout_dirty.clear();
+    let (width, height) = (engine.document_state.viewport.width, engine.document_state.viewport.height);
+    engine.update_viewport(width, height, 2.0).unwrap();
+    assert!(engine.document_state.render_tree.layout_dirty.is_empty());
+    engine.update_viewport(width + 100.0, height, 2.0).unwrap();
+    assert_eq!(engine.document_state.render_tree.layout_roots(), roots);
+
+    // Each pass is timed in the frame report
+    engine.render_frame(48.0).unwrap();
+    let report = engine.get_frame_report();
+    let pass = report.layout_passes.last().unwrap();
+    assert_eq!((pass.timestamp, pass.nodes), (48.0, 4));
+    assert!(pass.duration >= 0.0);
+}
+
+#[wasm_bindgen_test]
+fn test_container_layout() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    engine.update_viewport(800.0, 600.0, 1.0).unwrap();
+    let add = |engine: &mut InteractiveEngine, parent: Option<&str>, properties: serde_json::Value, (x, y): (f64, f64)| {
+        let properties = serde_json::from_value(properties).unwrap();
+        let id = engine.create_element(ElementType::Container, properties).unwrap();
+        let element = engine.document_state.get_element_mut(&id).unwrap();
+        (element.transform.x, element.transform.y) = (x, y);
+        if let Some(parent) = parent {
+            engine.document_state.attach_child(parent, &id);
+        }
+        id
+    };
+    let toolbar = add(&mut engine, None, serde_json::json!({
+        "layout": "flex-row", "width": "50%", "height": 50, "padding": 5, "gap": 10, "align_items": "center",
+    }), (20.0, 10.0));
+    let back = add(&mut engine, Some(&toolbar), serde_json::json!({"width": 100, "height": 20}), (0.0, 0.0));
+    let title = add(&mut engine, Some(&toolbar), serde_json::json!({"width": 50, "height": 20, "flex_grow": 1}), (0.0, 0.0));
+    let menu = add(&mut engine, Some(&toolbar), serde_json::json!({"width": 60, "height": 30}), (0.0, 0.0));
+    let hidden = add(&mut engine, Some(&toolbar), serde_json::json!({"style.display": "none"}), (0.0, 0.0));
+    let stack = add(&mut engine, None, serde_json::json!({"layout": "flex-column", "gap": 4}), (300.0, 200.0));
+    let heading = add(&mut engine, Some(&stack), serde_json::json!({"height": 30}), (0.0, 0.0));
+    let note = add(&mut engine, Some(&stack), serde_json::json!({"width": 80, "height": 20}), (0.0, 0.0));
+    let grid = add(&mut engine, None, serde_json::json!({"layout": "grid", "grid_columns": 2, "gap": 10, "width": 210}), (0.0, 200.0));
+    let cells: Vec<String> = [40, 40, 60].into_iter()
+        .map(|height| add(&mut engine, Some(&grid), serde_json::json!({"height": height}), (0.0, 0.0)))
+        .collect();
+    assert!(engine.take_property_warnings().is_empty());
+
+    let update = engine.render_frame(16.0).unwrap();
+    let bounds = |engine: &InteractiveEngine, id: &str| {
+        let bounds = engine.get_element_bounds(id).unwrap();
+        (bounds.x, bounds.y, bounds.width, bounds.height)
+    };
+    // A flex row: padding, gaps, the spare room to the growing child, centred across the line
+    assert_eq!(bounds(&engine, &toolbar), (20.0, 10.0, 400.0, 50.0));
+    assert_eq!(bounds(&engine, &back), (25.0, 25.0, 100.0, 20.0));
+    assert_eq!(bounds(&engine, &title), (135.0, 25.0, 210.0, 20.0));
+    assert_eq!(bounds(&engine, &menu), (355.0, 20.0, 60.0, 30.0));
+    assert_eq!(bounds(&engine, &hidden), (25.0, 15.0, 0.0, 0.0));
+    // A flex column fits its children and stretches those without a width
+    assert_eq!(bounds(&engine, &stack), (300.0, 200.0, 100.0, 54.0));
+    assert_eq!(bounds(&engine, &heading), (300.0, 200.0, 100.0, 30.0));
+    assert_eq!(bounds(&engine, &note), (300.0, 234.0, 80.0, 20.0));
+    // A grid fills its columns row by row, each row as tall as its tallest cell
+    assert_eq!(bounds(&engine, &grid), (0.0, 200.0, 210.0, 110.0));
+    assert_eq!(bounds(&engine, &cells[1]), (110.0, 200.0, 100.0, 40.0));
+    assert_eq!(bounds(&engine, &cells[2]), (0.0, 250.0, 100.0, 60.0));
+
+    // Flex and grid children are sent to the host, relative to their parent
+    let style = |update: &RenderUpdate, element_id: &str, property: &str| update.style_changes.iter()
+        .rfind(|change| change.element_id == element_id && change.property == property)
+        .map(|change| change.value.trim_matches('"').to_string());
+    assert_eq!(style(&update, &title, "left").as_deref(), Some("115px"));
+    assert_eq!(style(&update, &title, "width").as_deref(), Some("210px"));
+    assert_eq!(style(&update, &cells[2], "top").as_deref(), Some("50px"));
+
+    // A resize moves what is sized in percent, and only that
+    engine.update_viewport(1000.0, 600.0, 1.0).unwrap();
+    let update = engine.render_frame(32.0).unwrap();
+    assert_eq!(bounds(&engine, &title), (135.0, 25.0, 310.0, 20.0));
+    assert_eq!(style(&update, &toolbar, "width").as_deref(), Some("500px"));
+    assert_eq!(style(&update, &menu, "left").as_deref(), Some("435px"));
+    assert_eq!(style(&update, &back, "left"), None);
+    assert_eq!(style(&update, &grid, "width"), None);
+
+    // Changing the layout mode relays out the children
+    let absolute = [("layout".to_string(), serde_json::json!("absolute"))].into_iter().collect();
+    engine.update_element_properties(&toolbar, absolute).unwrap();
+    engine.render_frame(48.0).unwrap();
+    assert_eq!(bounds(&engine, &menu), (25.0, 15.0, 60.0, 30.0));
+}
+
+#[wasm_bindgen_test]
+fn test_dirty_regions() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let rect = |x: f64, y: f64, width: f64, height: f64| BoundingBox { x, y, width, height };
+    let add = |engine: &mut InteractiveEngine, (x, y): (f64, f64), size: f64| {
+        let properties = [("width".to_string(), serde_json::json!(size)), ("height".to_string(), serde_json::json!(size))];
+        let id = engine.create_element(ElementType::Container, properties.into_iter().collect()).unwrap();
+        let element = engine.document_state.get_element_mut(&id).unwrap();
+        (element.transform.x, element.transform.y) = (x, y);
+        id
+    };
+    let first = add(&mut engine, (0.0, 0.0), 100.0);
+    let second = add(&mut engine, (50.0, 50.0), 100.0);
+    let badge = add(&mut engine, (400.0, 400.0), 50.0);
+
+    // New boxes: the overlapping pair merge into one region
+    let update = engine.render_frame(16.0).unwrap();
+    assert_eq!(update.dirty_regions, vec![rect(0.0, 0.0, 150.0, 150.0), rect(400.0, 400.0, 50.0, 50.0)]);
+
+    // A change repaints its element's box, widened by its scale
+    engine.document_state.get_element_mut(&badge).unwrap().transform.scale_x = 2.0;
+    engine.lifecycle_changes.push(ElementChange::Update {
+        element_id: badge.clone(),
+        properties: [("selected".to_string(), serde_json::json!(true))].into_iter().collect(),
+    });
+    let update = engine.render_frame(32.0).unwrap();
+    assert_eq!(update.dirty_regions, vec![rect(375.0, 400.0, 100.0, 50.0)]);
+    assert!(engine.render_frame(48.0).unwrap().dirty_regions.is_empty());
+
+    // A box that grows repaints where it was and where it is, with no change to name it
+    let wider = [("width".to_string(), serde_json::json!(200.0))].into_iter().collect();
+    engine.update_element_properties(&second, wider).unwrap();
+    let update = engine.render_frame(64.0).unwrap();
+    assert!(update.style_changes.is_empty());
+    assert_eq!(update.dirty_regions, vec![rect(50.0, 50.0, 200.0, 100.0)]);
+
+    // A removed element leaves its box behind
+    engine.delete_element(&first).unwrap();
+    let update = engine.render_frame(80.0).unwrap();
+    assert_eq!(update.dirty_regions, vec![rect(0.0, 0.0, 100.0, 100.0)]);
+
+    // An animation repaints the box its values place the element in, and the previous frame's box
+    let keyframe = |time: f64, x: f64, scale: f64| Keyframe {
+        time,
+        properties: [("x".to_string(), serde_json::json!(x)), ("transform.scale".to_string(), serde_json::json!(scale))].into_iter().collect(),
+    };
+    engine.document_state.get_element_mut(&badge).unwrap().transform.scale_x = 1.0;
+    engine.animation_controller.start_animation(Animation {
+        id: "slide".to_string(),
+        target_element: badge.clone(),
+        animation_type: AnimationType::Transform,
+        duration: 100.0,
+        easing: EasingFunction::Linear,
+        keyframes: vec![keyframe(0.0, 400.0, 1.0), keyframe(1.0, 600.0, 2.0)],
+        loop_count: 1,
+        direction: AnimationDirection::Normal,
+        noise: None,
+        composition: Default::default(),
+    });
+    engine.animation_controller.active_animations.get_mut("slide").unwrap().start_time = 100.0;
+    let update = engine.render_frame(150.0).unwrap();
+    assert_eq!(update.dirty_regions, vec![rect(487.5, 387.5, 75.0, 75.0)]);
+    // The last frame covers where the element was and where it lands
+    let update = engine.render_frame(200.0).unwrap();
+    assert_eq!(update.dirty_regions, vec![rect(575.0, 375.0, 100.0, 100.0), rect(487.5, 387.5, 75.0, 75.0)]);
+    assert!(engine.render_frame(216.0).unwrap().dirty_regions.is_empty());
+
+    // Layers drawn over the document repaint their area when they change
+    let watermark = serde_json::from_value(serde_json::json!({"watermark": {"text": "reviewer@example.com"}})).unwrap();
+    let update = engine.set_content_protection(watermark).unwrap();
+    assert!(!update.dom_operations.is_empty());
+    assert_eq!(update.dirty_regions, vec![engine.document_state.viewport.visible_area()]);
+
+    // Only a full resync repaints everything with no regions
+    let resync = engine.request_full_resync();
+    assert!(resync.full_resync && resync.dirty_regions.is_empty());
+
+    // Regions joined through a third merge into one; too many are sent as their union
+    let merged = dirty_regions::merge(vec![rect(0.0, 0.0, 10.0, 10.0), rect(20.0, 0.0, 10.0, 10.0), rect(5.0, 0.0, 20.0, 5.0)]);
+    assert_eq!(merged, vec![rect(0.0, 0.0, 30.0, 10.0)]);
+    let scattered: Vec<BoundingBox> = (0..40).map(|index| rect(index as f64 * 20.0, 0.0, 10.0, 10.0)).collect();
+    assert_eq!(dirty_regions::merge(scattered), vec![rect(0.0, 0.0, 790.0, 10.0)]);
+}
+
+#[wasm_bindgen_test]
+fn test_document_patch() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let element = |id: &str, element_type: &str, properties: serde_json::Value, children: Vec<&str>| serde_json::json!({
+        "id": id,
+        "element_type": element_type,
+        "properties": properties,
+        "children": children,
+        "event_handlers": [],
+        "transform": Transform::default(),
+        "style": {},
+    });
+    let document = serde_json::json!({
+        "elements": [
+            element("panel", "Container", serde_json::json!({"layout": "flex-column"}), vec!["title"]),
+            element("title", "Text", serde_json::json!({"text": "Q1"}), vec![]),
+            element("badge", "Interactive", serde_json::json!({"data_source": "sales", "label": "Sales"}), vec![]),
+        ],
+        "data_sources": {
+            "sales": DataSource::new("sales".to_string(), DataSourceType::Static, serde_json::json!([1, 2, 3])),
+        },
+    });
+    engine.load_document(&document.to_string()).unwrap();
+    engine.render_frame(0.0).unwrap();
+    let patch = |operations: serde_json::Value| -> Vec<patch::PatchOperation> { serde_json::from_value(operations).unwrap() };
+
+    // Elements are addressed by id; only the difference reaches the host
+    let result = engine.apply_document_patch(&patch(serde_json::json!([
+        {"op": "test", "path": "/elements/title/properties/text", "value": "Q1"},
+        {"op": "add", "path": "/elements/-", "value": element("note", "Text", serde_json::json!({"text": "Draft"}), vec![])},
+        {"op": "add", "path": "/elements/panel/children/0", "value": "note"},
+        {"op": "replace", "path": "/elements/title/style/background_color", "value": "#ffffff"},
+        {"op": "remove", "path": "/elements/badge/properties/data_source"},
+        {"op": "replace", "path": "/data_sources/sales/data", "value": [4, 5]},
+        {"op": "add", "path": "/style_tokens/--accent", "value": "#ff0000"},
+    ]))).unwrap();
+    assert_eq!(result, PatchResult {
+        created: vec!["note".to_string()],
+        updated: vec!["badge".to_string(), "title".to_string()],
+        data_sources: vec!["sales".to_string()],
+        style_tokens: vec!["--accent".to_string()],
+        ..Default::default()
+    });
+    assert_eq!(engine.document_state.data_sources["sales"].data, serde_json::json!([4, 5]));
+    assert_eq!(engine.document_state.get_element("panel").unwrap().children, vec!["note", "title"]);
+    assert_eq!(engine.document_state.render_tree.nodes["note"].parent.as_deref(), Some("panel"));
+    let update = engine.render_frame(16.0).unwrap();
+    assert!(update.dom_operations.iter().any(|operation| matches!(operation,
+        DOMOperation::Create { element_id, parent_id: Some(parent_id), .. } if element_id == "note" && parent_id == "panel")));
+    assert!(update.dom_operations.iter().any(|operation| matches!(operation,
+        DOMOperation::Move { element_id, new_parent_id, index: 0 } if element_id == "note" && new_parent_id == "panel")));
+    assert!(update.dom_operations.iter().any(|operation| matches!(operation,
+        DOMOperation::Update { element_id, attributes } if element_id == "badge" && attributes["data_source"] == "null")));
+    // The title also moves down the column, below the new note's one line of text
+    let title: Vec<(&str, &str)> = update.style_changes.iter()
+        .filter(|change| change.element_id == "title")
+        .map(|change| (change.property.as_str(), change.value.as_str()))
+        .collect();
+    assert!(title.contains(&("background-color", "\"#ffffff\"")));
+    assert!(title.contains(&("top", "\"19.2px\"")));
+
+    // A failed test, a member outside the patchable ones or a changed element type changes nothing
+    let failing = [
+        (serde_json::json!([{"op": "remove", "path": "/elements/note"}, {"op": "test", "path": "/elements/title/properties/text", "value": "Q2"}]), "INVALID_PATCH"),
+        (serde_json::json!([{"op": "add", "path": "/timers", "value": []}]), "INVALID_PATCH"),
+        (serde_json::json!([{"op": "add", "path": "/elements/panel/children/-", "value": "missing"}]), "INVALID_PATCH"),
+        (serde_json::json!([{"op": "replace", "path": "/elements/title/element_type", "value": "Image"}]), "ELEMENT_TYPE_MISMATCH"),
+    ];
+    for (operations, code) in failing {
+        assert_eq!(engine.apply_document_patch(&patch(operations)).unwrap_err().code, code);
+    }
+    assert_eq!(engine.document_state.elements.len(), 4);
+
+    // Removing an element and reparenting another
+    let result = engine.apply_document_patch(&patch(serde_json::json!([
+        {"op": "remove", "path": "/elements/panel/children/0"},
+        {"op": "remove", "path": "/elements/note"},
+        {"op": "add", "path": "/elements/panel/children/-", "value": "badge"},
+    ]))).unwrap();
+    assert_eq!(result.removed, vec!["note"]);
+    assert_eq!(result.moved, vec!["badge"]);
+    assert!(result.updated.is_empty());
+    let update = engine.render_frame(32.0).unwrap();
+    assert!(update.dom_operations.iter().any(|operation| matches!(operation, DOMOperation::Remove { element_id } if element_id == "note")));
+    assert!(update.dom_operations.iter().any(|operation| matches!(operation,
+        DOMOperation::Move { element_id, new_parent_id, index: 1 } if element_id == "badge" && new_parent_id == "panel")));
+}
+
+#[wasm_bindgen_test]
+fn test_numeric_guards() {
+    use crate::numeric::{checked_div, domain_span, finite_or, take_numeric_issues, unit_interval};
+    take_numeric_issues();
+    assert_eq!(checked_div(1.0, 0.0), None);
+    assert_eq!(checked_div(0.0, 0.0), None);
+    assert_eq!(checked_div(3.0, 2.0), Some(1.5));
+    assert_eq!(unit_interval(1.5, 0.0, "test.unit"), 1.0);
+    assert_eq!(domain_span(5.0, 5.0, "test.span"), 1.0);
+    assert_eq!(domain_span(-f64::MAX, f64::MAX, "test.span"), 1.0);
+    assert_eq!(finite_or(f64::NAN, 2.0, "test.finite"), 2.0);
+    assert_eq!(finite_or(f64::INFINITY, 2.0, "test.finite"), 2.0);
+    let issues = take_numeric_issues();
+    assert_eq!(issues.iter().map(|issue| (issue.site.as_str(), issue.count)).collect::<Vec<_>>(), vec![("test.finite", 2), ("test.span", 1)]);
+    assert!(take_numeric_issues().is_empty());
+
+    // A zero duration finishes at once, and keyframes at the same time do not divide by zero
+    let keyframe = |time: f64, opacity: f64| Keyframe {
+        time,
+        properties: [("opacity".to_string(), serde_json::json!(opacity))].into_iter().collect(),
+    };
+    let mut controller = AnimationController::new();
+    controller.start_animation(Animation {
+        id: "flash".to_string(),
+        target_element: "card".to_string(),
+        animation_type: AnimationType::Style,
+        duration: 0.0,
+        easing: EasingFunction::Cubic(0.0, f64::NAN, 1.0, 1.0),
+        keyframes: vec![keyframe(0.0, 0.0), keyframe(0.0, 0.5), keyframe(1.0, 1.0)],
+        loop_count: 1,
+        direction: AnimationDirection::Normal,
+        noise: None,
+        composition: Default::default(),
+    });
+    controller.active_animations.get_mut("flash").unwrap().start_time = 1_000.0;
+    let changes = controller.update_animations(&mut DocumentState::default(), 1_000.0).unwrap();
+    match &changes[0] {
+        ElementChange::AnimationUpdate { progress, values, .. } => {
+            assert_eq!(*progress, 1.0);
+            assert_eq!(values["opacity"], serde_json::json!(1.0));
+        }
+        other => panic!("unexpected change {:?}", other),
+    }
+    assert!(controller.active_animations.is_empty());
+    // The NaN from the easing curve fell back to the linear progress and was recorded
+    let sites: Vec<String> = take_numeric_issues().into_iter().map(|issue| issue.site).collect();
+    assert_eq!(sites, vec!["animation.easing"]);
+}
+
+#[wasm_bindgen_test]
+fn test_animation_composition() {
+    use crate::composition::{AnimationConflict, Composition, CompositionMode};
+    let mut document = DocumentState::default();
+    let mut controller = AnimationController::new();
+    let animation = |id: &str, property: &str, (from, to): (f64, f64), mode: CompositionMode, priority: i32| Animation {
+        id: id.to_string(),
+        target_element: "card".to_string(),
+        animation_type: AnimationType::Style,
+        duration: 1000.0,
+        easing: EasingFunction::Linear,
+        keyframes: [(0.0, from), (1.0, to)].into_iter()
+            .map(|(time, value)| Keyframe { time, properties: [(property.to_string(), serde_json::json!(value))].into_iter().collect() })
+            .collect(),
+        loop_count: -1,
+        direction: AnimationDirection::Normal,
+        noise: None,
+        composition: Composition { mode, priority },
+    };
+    let start = |controller: &mut AnimationController, animation: Animation| {
+        let id = animation.id.clone();
+        controller.start_animation(animation);
+        controller.active_animations.get_mut(&id).unwrap().start_time = 0.0;
+    };
+    let values = |changes: &[ElementChange], id: &str| changes.iter()
+        .find_map(|change| match change {
+            ElementChange::AnimationUpdate { animation_id, values, .. } if animation_id == id => Some(values.clone()),
+            _ => None,
+        })
+        .unwrap();
+
+    // An additive animation adds to the one that replaces; only the owner's update carries the value
+    start(&mut controller, animation("fade", "opacity", (0.0, 1.0), CompositionMode::Replace, 0));
+    start(&mut controller, animation("pulse", "opacity", (0.1, 0.1), CompositionMode::Additive, 0));
+    start(&mut controller, animation("slide", "x", (10.0, 10.0), CompositionMode::Replace, 0));
+    let changes = controller.update_animations(&mut document, 500.0).unwrap();
+    assert!((values(&changes, "fade")["opacity"].as_f64().unwrap() - 0.6).abs() < 1e-9);
+    assert!(!values(&changes, "pulse").contains_key("opacity"));
+    let fade_conflict = AnimationConflict {
+        element_id: "card".to_string(),
+        property: "opacity".to_string(),
+        owner: Some("fade".to_string()),
+        added: vec!["pulse".to_string()],
+        overridden: Vec::new(),
+    };
+    assert_eq!(controller.ownership.take_detected(), vec![fade_conflict.clone()]);
+
+    // A higher priority takes over; at equal priority the later writer wins whatever its id
+    start(&mut controller, animation("dim", "opacity", (0.2, 0.2), CompositionMode::Replace, 5));
+    start(&mut controller, animation("glide", "x", (40.0, 40.0), CompositionMode::Replace, 0));
+    let changes = controller.update_animations(&mut document, 600.0).unwrap();
+    assert!((values(&changes, "dim")["opacity"].as_f64().unwrap() - 0.3).abs() < 1e-9);
+    assert!(!values(&changes, "fade").contains_key("opacity"));
+    assert_eq!(values(&changes, "glide")["x"], serde_json::json!(40.0));
+    assert!(!values(&changes, "slide").contains_key("x"));
+    let conflicts = controller.ownership.conflicts().to_vec();
+    assert_eq!(conflicts[0], AnimationConflict { owner: Some("dim".to_string()), overridden: vec!["fade".to_string()], ..fade_conflict });
+    assert_eq!((conflicts[1].property.as_str(), conflicts[1].owner.as_deref()), ("x", Some("glide")));
+    assert_eq!(controller.ownership.take_detected().len(), 2);
+
+    // Settled conflicts are not reported again, and the outcome holds frame after frame
+    let changes = controller.update_animations(&mut document, 700.0).unwrap();
+    assert!(controller.ownership.take_detected().is_empty());
+    assert_eq!(values(&changes, "glide")["x"], serde_json::json!(40.0));
+}
+
+#[wasm_bindgen_test]
+fn test_content_protection() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "modify_element".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions.clone()).unwrap();
+    assert!(engine.export_data_snapshot().is_ok());
+    let protection = |value: serde_json::Value| -> ContentProtection { serde_json::from_value(value).unwrap() };
+    assert_eq!(engine.set_content_protection(protection(serde_json::json!({"watermark": {"text": " "}}))).unwrap_err().code, "INVALID_WATERMARK");
+    assert_eq!(engine.set_content_protection(protection(serde_json::json!({"watermark": {"text": "ada", "opacity": 2.0}}))).unwrap_err().code, "INVALID_WATERMARK");
+
+    // The watermark is drawn with the user id and the time protection was set
+    let update = engine.set_content_protection(protection(serde_json::json!({"view_only": true, "watermark": {"text": "ada@example.com"}}))).unwrap();
+    let label = |update: &RenderUpdate| update.dom_operations.iter().find_map(|op| match op {
+        DOMOperation::Update { element_id, attributes } if element_id == WatermarkLayer::TEXT_ID => attributes.get("textContent").cloned(),
+        _ => None,
+    });
+    let text = label(&update).unwrap();
+    assert!(text.starts_with("ada@example.com · ") && text.ends_with(" UTC"), "{}", text);
+    assert!(update.dom_operations.iter().any(|op| matches!(op, DOMOperation::Create { element_id, parent_id: None, .. } if element_id == WatermarkLayer::LAYER_ID)));
+    assert!(label(&engine.render_frame(16.0).unwrap()).is_none());
+    assert_eq!(label(&engine.request_full_resync()), Some(text));
+
+    // Documents cannot claim the layer's ids
+    let element = |id: &str| -> InteractiveElement {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "element_type": "Container", "properties": {}, "children": [],
+            "event_handlers": [], "transform": Transform::default(), "style": {},
+        })).unwrap()
+    };
+    assert_eq!(engine.document_state.add_element(element(WatermarkLayer::LAYER_ID)).unwrap_err().code, "RESERVED_ELEMENT_ID");
+    assert_eq!(engine.document_state.add_element(element("liv-watermark-text")).unwrap_err().code, "RESERVED_ELEMENT_ID");
+
+    // View-only refuses exports and records each refusal
+    assert_eq!(engine.export_data_snapshot().unwrap_err().code, "EXPORT_NOT_ALLOWED");
+    assert_eq!(engine.export_data_delta(&HashMap::new()).unwrap_err().code, "EXPORT_NOT_ALLOWED");
+    assert_eq!(engine.export_chart("chart", ExportFormat::Svg, 1.0).unwrap_err().code, "EXPORT_NOT_ALLOWED");
+    assert_eq!(engine.export_mutations().unwrap_err().code, "EXPORT_NOT_ALLOWED");
+    let audit = engine.get_security_audit();
+    assert_eq!(audit.len(), 4);
+    assert!(audit.iter().all(|denial| denial.capability == "export"));
+    assert!(engine.get_content_protection().view_only);
+
+    // Later calls cannot lift, replace or loosen protection
+    let before = engine.get_content_protection();
+    let locked = |result: Result<RenderUpdate, WASMError>| result.unwrap_err().code;
+    assert_eq!(locked(engine.set_content_protection(ContentProtection::default())), "CONTENT_PROTECTION_LOCKED");
+    assert_eq!(locked(engine.set_content_protection(protection(serde_json::json!({"view_only": false, "watermark": {"text": "ada@example.com"}})))), "CONTENT_PROTECTION_LOCKED");
+    assert_eq!(locked(engine.set_content_protection(protection(serde_json::json!({"view_only": true, "watermark": {"text": "ada@example.com", "opacity": 0.0}})))), "CONTENT_PROTECTION_LOCKED");
+    assert_eq!(locked(engine.set_content_protection(protection(serde_json::json!({"view_only": true})))), "CONTENT_PROTECTION_LOCKED");
+    assert_eq!(engine.get_content_protection(), before);
+    assert_eq!(engine.export_data_snapshot().unwrap_err().code, "EXPORT_NOT_ALLOWED");
+    assert!(engine.get_security_audit().iter().rev().skip(1).take(4).all(|denial| denial.capability == "content_protection"));
+
+    // Repeating the same protection changes nothing
+    assert!(engine.set_content_protection(before.clone()).unwrap().dom_operations.is_empty());
+    assert_eq!(engine.get_content_protection(), before);
+
+    // Tightening is allowed: an unprotected engine can add view-only, then a watermark
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    engine.set_content_protection(protection(serde_json::json!({"view_only": true}))).unwrap();
+    let update = engine.set_content_protection(protection(serde_json::json!({"view_only": true, "watermark": {"text": "ada"}}))).unwrap();
+    assert!(update.dom_operations.iter().any(|op| matches!(op, DOMOperation::Create { element_id, .. } if element_id == WatermarkLayer::LAYER_ID)));
+}
+
+#[wasm_bindgen_test]
+fn test_gradient_animation() {
+    let permissions = WASMPermissions {
+        memory_limit: 1024 * 1024,
+        allowed_imports: vec!["console".to_string()],
+        cpu_time_limit: 5000,
+        allow_networking: false,
+        allow_file_system: false,
+        allowed_interactions: vec!["create_element".to_string(), "create_animation".to_string()],
+        max_data_size: 1024 * 1024,
+        max_elements: 100,
+    };
+    let mut engine = InteractiveEngine::new(permissions).unwrap();
+    let stop = |offset: f64, color: &str, opacity: f64| GradientStop { offset, color: color.to_string(), opacity };
+    let linear = |x1: f64, x2: f64| GradientType::Linear { x1, y1: 0.0, x2, y2: 0.0 };
+
+    // A shape filled with the gradient, drawn by an element
+    let gradient = engine.vector_engine.create_gradient(linear(0.0, 100.0), vec![stop(0.0, "#000000", 1.0), stop(1.0, "#ffffff", 0.0)]).unwrap();
+    let shape = engine.vector_engine.create_shape(ShapeType::Rectangle, Position { x: 0.0, y: 0.0 }, Size { width: 100.0, height: 50.0 }).unwrap();
+    engine.vector_engine.set_shape_gradient(&shape, &gradient).unwrap();
+    let properties = [
+        ("shape_id".to_string(), serde_json::json!(shape)),
+        ("width".to_string(), serde_json::json!(100.0)),
+        ("height".to_string(), serde_json::json!(50.0)),
+    ];
+    engine.create_element(ElementType::Container, properties.into_iter().collect()).unwrap();
+    engine.render_frame(16.0).unwrap();
+    let whole_box = vec![BoundingBox { x: 0.0, y: 0.0, width: 100.0, height: 50.0 }];
+
+    // Halfway through, stops and geometry sit between the two ends and the shape is repainted
+    let animation = GradientAnimation {
+        stops: Some(vec![stop(0.5, "#ff0000", 1.0), stop(1.0, "#ffffff", 1.0)]),
+        gradient_type: Some(linear(50.0, 150.0)),
+        duration: 1000.0,
+        easing: EasingFunction::Linear,
+        loop_count: 1,
+        direction: AnimationDirection::Normal,
+    };
+    let animation_id = engine.animate_gradient(&gradient, &animation).unwrap();
+    engine.animation_controller.active_animations.get_mut(&animation_id).unwrap().start_time = 1_000.0;
+    let update = engine.render_frame(1_500.0).unwrap();
+    assert_eq!(update.dirty_regions, whole_box);
+    let current = &engine.vector_engine.gradients[&gradient];
+    assert_eq!(current.stops[0].offset, 0.25);
+    assert_eq!(current.stops[0].color, "#800000");
+    assert_eq!(current.stops[1].opacity, 0.5);
+    assert!(matches!(current.gradient_type, GradientType::Linear { x1, x2, .. } if x1 == 25.0 && x2 == 125.0));
+
+    // The last frame lands on the target, and the animation ends
+    engine.render_frame(2_000.0).unwrap();
+    assert_eq!(engine.vector_engine.gradients[&gradient].stops[0].color, "#ff0000");
+    assert!(engine.render_frame(2_016.0).unwrap().dirty_regions.is_empty());
+
+    // Targets keep the stop count and the kind of geometry
+    let error = |animation: GradientAnimation| engine.vector_engine.gradient_animation(&gradient, &animation).unwrap_err().code;
+    assert_eq!(error(GradientAnimation { stops: Some(vec![stop(0.0, "#000000", 1.0)]), ..animation.clone() }), "INVALID_GRADIENT");
+    let radial = GradientType::Radial { cx: 50.0, cy: 50.0, r: 50.0, fx: None, fy: None };
+    assert_eq!(error(GradientAnimation { gradient_type: Some(radial.clone()), ..animation.clone() }), "INVALID_GRADIENT");
+    assert_eq!(engine.animate_gradient("missing", &animation).unwrap_err().code, "GRADIENT_NOT_FOUND");
+
+    // Edits in place repaint the shapes drawn with the gradient
+    assert_eq!(engine.vector_engine.gradient_users(&gradient), vec![shape.clone()]);
+    engine.set_gradient_stops(&gradient, vec![stop(0.0, "#00ff00", 1.0)]).unwrap();
+    assert_eq!(engine.render_frame(2_032.0).unwrap().dirty_regions, whole_box);
+    engine.set_gradient_geometry(&gradient, radial).unwrap();
+    assert_eq!(engine.render_frame(2_048.0).unwrap().dirty_regions, whole_box);
+    assert_eq!(engine.set_gradient_stops(&gradient, vec![stop(1.5, "#00ff00", 1.0)]).unwrap_err().code, "INVALID_GRADIENT");
+
+    // A new gradient can start out animated
+    let animated = engine.create_animated_gradient(linear(0.0, 10.0), vec![stop(0.0, "#000000", 1.0), stop(1.0, "#000000", 1.0)], &animation).unwrap();
+    // Collecting garbage keeps it running, as its target is no element
+    engine.collect_garbage();
+    assert_eq!(engine.animation_controller.target_map()[&animated.animation_id], animated.gradient_id);
+    let gradients = engine.vector_engine.gradients.len();
+    assert!(engine.create_animated_gradient(linear(0.0, 10.0), vec![stop(0.0, "#000000", 1.0)], &animation).is_err());
+    assert_eq!(engine.vector_engine.gradients.len(), gradients);
+}
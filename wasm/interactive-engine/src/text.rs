@@ -0,0 +1,379 @@
+// Text elements outside linked stories: their text, or "spans" of rich text, is laid out like a
+// one-frame story (see layout_story), wrapped at the element's width and aligned by
+// "text_align". A Text element without a width is as wide as its longest line, only breaking at
+// newlines, and one without a height is as tall as its lines, so layout, hit testing and the
+// boxes around it reserve the room the text takes.
+//
+// Advances come from a FontTable registered for the element's "font_family", matched on weight
+// and style, when there is one; otherwise from the host's shaping callback or the built-in
+// estimate. A font table also gives its family's normal line height, and can carry glyph outlines
+// that vector Text shapes are drawn with (see text_outline).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::{
+    layout_story, ElementType, EstimatedShaper, FrameGeometry, InteractiveElement, LayoutError, LineBreaking, ShapedCluster,
+    PathCommand, ShapingRequest, StoryLayout, TextAlign, TextDirection, TextRun, TextShaper, TextStory,
+};
+
+const DEFAULT_FONT_SIZE: f64 = 16.0;
+const DEFAULT_LINE_HEIGHT: f64 = 1.2;
+
+// Metrics of one face of a font family, in font units
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(tsify::Tsify, schemars::JsonSchema))]
+pub struct FontTable {
+    pub family: String,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub italic: bool,
+    pub units_per_em: f64,
+    // Above and below the baseline, both positive
+    pub ascent: f64,
+    pub descent: f64,
+    #[serde(default)]
+    pub line_gap: f64,
+    // Advance of each character, keyed by the character
+    #[serde(default)]
+    pub advances: BTreeMap<char, f64>,
+    // Advance of characters missing from `advances`
+    pub default_advance: f64,
+    // Outline of each character as SVG path data (M, L, H, V, C, Q and Z), in font units with y
+    // up from the baseline and x from the start of the advance
+    #[serde(default)]
+    pub glyphs: BTreeMap<char, String>,
+}
+
+impl FontTable {
+    pub fn validate(&self) -> Result<(), LayoutError> {
+        let invalid = |reason: &str| Err(LayoutError::InvalidFontTable { family: self.family.clone(), reason: reason.to_string() });
+        let metric = |value: f64| value.is_finite() && value >= 0.0;
+        if self.family.trim().is_empty() {
+            return invalid("the family is empty");
+        }
+        if !(self.units_per_em.is_finite() && self.units_per_em > 0.0) {
+            return invalid("units_per_em must be positive");
+        }
+        if ![self.ascent, self.descent, self.line_gap, self.default_advance].into_iter().all(metric) || self.ascent + self.descent <= 0.0 {
+            return invalid("ascent, descent, line_gap and default_advance must be non-negative, with a positive ascent plus descent");
+        }
+        if let Some(character) = self.advances.iter().find(|(_, advance)| !metric(**advance)).map(|(character, _)| character) {
+            return invalid(&format!("the advance of '{}' is not a non-negative number", character));
+        }
+        for (character, outline) in &self.glyphs {
+            parse_path_data(outline).map_err(|reason| LayoutError::InvalidFontTable {
+                family: self.family.clone(),
+                reason: format!("the glyph of '{}' is not path data: {}", character, reason),
+            })?;
+        }
+        Ok(())
+    }
+
+    // Multiple of the font size from one baseline to the next
+    pub fn line_height(&self) -> f64 {
+        (self.ascent + self.descent + self.line_gap) / self.units_per_em
+    }
+
+    // In ems
+    pub fn advance(&self, character: char) -> f64 {
+        self.advances.get(&character).copied().unwrap_or(self.default_advance) / self.units_per_em
+    }
+}
+
+// "bold", or a weight of 700 or more
+pub fn is_bold(weight: Option<&str>) -> bool {
+    weight.is_some_and(|weight| weight.eq_ignore_ascii_case("bold") || weight.parse::<f64>().is_ok_and(|weight| weight >= 700.0))
+}
+
+// The family's face closest to the requested one; a matching style counts for more than a
+// matching weight
+pub fn find_font<'a>(fonts: &'a [FontTable], family: Option<&str>, bold: bool, italic: bool) -> Option<&'a FontTable> {
+    let family = family?;
+    fonts.iter()
+        .filter(|font| font.family.eq_ignore_ascii_case(family))
+        .min_by_key(|font| (font.italic != italic, font.bold != bold))
+}
+
+// Advances from the requested family's font table, with the estimate's cluster boundaries;
+// requests for a family without a table go to `fallback`
+pub struct FontTableShaper<'a> {
+    pub fonts: &'a [FontTable],
+    pub fallback: &'a dyn TextShaper,
+}
+
+impl TextShaper for FontTableShaper<'_> {
+    fn shape(&self, request: &ShapingRequest) -> Vec<ShapedCluster> {
+        let font = find_font(self.fonts, request.font_family.as_deref(), is_bold(request.font_weight.as_deref()), request.italic);
+        let Some(font) = font else {
+            return self.fallback.shape(request);
+        };
+        let chars: Vec<char> = request.text.chars().collect();
+        let mut clusters = EstimatedShaper.shape(request);
+        for cluster in &mut clusters {
+            cluster.advance = font.advance(chars[cluster.start]) * request.font_size;
+        }
+        clusters
+    }
+}
+
+// The story a Text element's own properties describe, with the element as its only frame; None
+// for other element types
+pub fn element_story(element: &InteractiveElement, fonts: &[FontTable]) -> Option<TextStory> {
+    if !matches!(element.element_type, ElementType::Text) {
+        return None;
+    }
+    let property = |names: &[&str]| names.iter().find_map(|name| element.properties.get(*name));
+    let positive = |names: &[&str]| property(names).and_then(|v| v.as_f64()).filter(|value| value.is_finite() && *value > 0.0);
+    let font_size = positive(&["font_size", "fontSize"]).unwrap_or(DEFAULT_FONT_SIZE);
+    let font_family = property(&["font_family", "fontFamily"]).and_then(|v| v.as_str()).map(str::to_string);
+    let font_weight = match property(&["font_weight", "fontWeight"]) {
+        Some(Value::String(weight)) => Some(weight.clone()),
+        Some(Value::Number(weight)) => Some(weight.to_string()),
+        _ => None,
+    };
+    let italic = property(&["italic"]).and_then(|v| v.as_bool()).unwrap_or(false);
+
+    // Spans inherit the element's weight and style
+    let runs = match property(&["spans"]).and_then(|spans| serde_json::from_value::<Vec<TextRun>>(spans.clone()).ok()) {
+        Some(spans) => spans.into_iter()
+            .map(|span| TextRun { font_weight: span.font_weight.or_else(|| font_weight.clone()), italic: span.italic || italic, ..span })
+            .collect(),
+        None => {
+            let text = property(&["text"]).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            vec![TextRun { text, font_size: None, font_weight: font_weight.clone(), italic, color: None }]
+        }
+    };
+    let line_height = positive(&["line_height"])
+        .or_else(|| find_font(fonts, font_family.as_deref(), is_bold(font_weight.as_deref()), italic).map(FontTable::line_height))
+        .unwrap_or(DEFAULT_LINE_HEIGHT);
+    let align = match property(&["text_align"]).and_then(|v| v.as_str()) {
+        Some("center") => TextAlign::Center,
+        Some("right") => TextAlign::Right,
+        Some("justify") => TextAlign::Justify,
+        _ => TextAlign::Left,
+    };
+
+    Some(TextStory {
+        id: element.id.clone(),
+        runs,
+        frames: vec![element.id.clone()],
+        font_size,
+        font_family,
+        line_height,
+        align,
+        hyphenation: None,
+        line_breaking: LineBreaking::Greedy,
+        widows: 1,
+        orphans: 1,
+        direction: TextDirection::Auto,
+    })
+}
+
+// The story laid out at `width`, or at its natural width, rounded up to whole pixels, when None;
+// with the width and the height its lines take
+pub fn measure(story: &TextStory, width: Option<f64>, fonts: &[FontTable], shaper: &dyn TextShaper) -> (StoryLayout, f64, f64) {
+    let shaper = FontTableShaper { fonts, fallback: shaper };
+    let lay_out = |story: &TextStory, width: f64| {
+        layout_story(story, &[FrameGeometry { width, height: f64::INFINITY, columns: 1, column_gap: 0.0 }], None, &shaper)
+    };
+    let width = width.unwrap_or_else(|| {
+        // Unbounded lines only end at newlines; alignment waits for the real width. f64::MAX
+        // rather than infinity, which the line breaker's arithmetic turns into NaN.
+        let unbounded = TextStory { align: TextAlign::Left, line_breaking: LineBreaking::Greedy, ..story.clone() };
+        let layout = lay_out(&unbounded, f64::MAX);
+        layout.frames.iter()
+            .flat_map(|frame| &frame.lines)
+            .flat_map(|line| &line.spans)
+            .map(|span| span.x + span.width)
+            .fold(0.0, f64::max)
+            .ceil()
+    });
+    let layout = lay_out(story, width);
+    let height = layout.frames.iter()
+        .flat_map(|frame| frame.lines.last())
+        .map(|line| line.y + line.height)
+        .fold(0.0, f64::max);
+    (layout, width, height)
+}
+
+// `text` set in `font` as one outline, with the top of the first line at `origin`. Lines break at
+// newlines and are one line height apart; `letter_spacing` is added after every character. The
+// characters that have no glyph outline, whitespace aside, are the error.
+pub fn text_outline(text: &str, font: &FontTable, font_size: f64, letter_spacing: f64, origin: (f64, f64)) -> Result<Vec<PathCommand>, Vec<char>> {
+    let missing: Vec<char> = text.chars().filter(|c| !c.is_whitespace() && !font.glyphs.contains_key(c)).collect();
+    if !missing.is_empty() {
+        return Err(missing);
+    }
+    let scale = font_size / font.units_per_em;
+    let mut commands = Vec::new();
+    for (line, content) in text.split('\n').enumerate() {
+        let baseline = origin.1 + font.ascent * scale + line as f64 * font.line_height() * font_size;
+        let mut pen = origin.0;
+        for character in content.chars() {
+            // Outlines were checked when the table was registered
+            let glyph = font.glyphs.get(&character).and_then(|outline| parse_path_data(outline).ok()).unwrap_or_default();
+            let place = |x: f64, y: f64| (pen + x * scale, baseline - y * scale);
+            commands.extend(glyph.into_iter().map(|command| match command {
+                PathCommand::MoveTo { x, y } => {
+                    let (x, y) = place(x, y);
+                    PathCommand::MoveTo { x, y }
+                }
+                PathCommand::LineTo { x, y } => {
+                    let (x, y) = place(x, y);
+                    PathCommand::LineTo { x, y }
+                }
+                PathCommand::CurveTo { x1, y1, x2, y2, x, y } => {
+                    let ((x1, y1), (x2, y2), (x, y)) = (place(x1, y1), place(x2, y2), place(x, y));
+                    PathCommand::CurveTo { x1, y1, x2, y2, x, y }
+                }
+                PathCommand::QuadraticCurveTo { x1, y1, x, y } => {
+                    let ((x1, y1), (x, y)) = (place(x1, y1), place(x, y));
+                    PathCommand::QuadraticCurveTo { x1, y1, x, y }
+                }
+                // Flipping y turns the sweep the other way
+                PathCommand::Arc { rx, ry, rotation, large_arc, sweep, x, y } => {
+                    let (x, y) = place(x, y);
+                    PathCommand::Arc { rx: rx * scale, ry: ry * scale, rotation: -rotation, large_arc, sweep: !sweep, x, y }
+                }
+                PathCommand::ClosePath => PathCommand::ClosePath,
+            }));
+            pen += font.advance(character) * font_size + letter_spacing;
+        }
+    }
+    Ok(commands)
+}
+
+enum PathToken {
+    Command(char),
+    Number(f64),
+}
+
+// SVG path data with M, L, H, V, C, Q and Z, absolute or relative, as path commands
+pub fn parse_path_data(data: &str) -> Result<Vec<PathCommand>, String> {
+    let tokens = path_tokens(data)?;
+    let mut commands = Vec::new();
+    let (mut current, mut start) = ((0.0, 0.0), (0.0, 0.0));
+    let mut repeat: Option<char> = None;
+    let mut index = 0;
+    while index < tokens.len() {
+        let letter = match tokens[index] {
+            PathToken::Command(letter) => {
+                index += 1;
+                letter
+            }
+            // Numbers after a command's own repeat it; after a move they are lines
+            PathToken::Number(_) => repeat.ok_or("numbers must follow a command other than Z")?,
+        };
+        let arity = match letter.to_ascii_uppercase() {
+            'Z' => 0,
+            'H' | 'V' => 1,
+            'M' | 'L' => 2,
+            'Q' => 4,
+            'C' => 6,
+            _ => return Err(format!("unsupported command '{}'", letter)),
+        };
+        let numbers: Vec<f64> = tokens.iter().skip(index).take(arity)
+            .map_while(|token| match token {
+                PathToken::Number(number) => Some(*number),
+                PathToken::Command(_) => None,
+            })
+            .collect();
+        if numbers.len() < arity {
+            return Err(format!("'{}' needs {} numbers", letter, arity));
+        }
+        index += arity;
+
+        let offset = if letter.is_ascii_lowercase() { current } else { (0.0, 0.0) };
+        let point = |i: usize| (offset.0 + numbers[i], offset.1 + numbers[i + 1]);
+        let command = match letter.to_ascii_uppercase() {
+            'M' => {
+                current = point(0);
+                start = current;
+                PathCommand::MoveTo { x: current.0, y: current.1 }
+            }
+            'L' => {
+                current = point(0);
+                PathCommand::LineTo { x: current.0, y: current.1 }
+            }
+            'H' => {
+                current.0 = offset.0 + numbers[0];
+                PathCommand::LineTo { x: current.0, y: current.1 }
+            }
+            'V' => {
+                current.1 = offset.1 + numbers[0];
+                PathCommand::LineTo { x: current.0, y: current.1 }
+            }
+            'Q' => {
+                let (control, end) = (point(0), point(2));
+                current = end;
+                PathCommand::QuadraticCurveTo { x1: control.0, y1: control.1, x: end.0, y: end.1 }
+            }
+            'C' => {
+                let (first, second, end) = (point(0), point(2), point(4));
+                current = end;
+                PathCommand::CurveTo { x1: first.0, y1: first.1, x2: second.0, y2: second.1, x: end.0, y: end.1 }
+            }
+            _ => {
+                current = start;
+                PathCommand::ClosePath
+            }
+        };
+        commands.push(command);
+        repeat = match letter {
+            'Z' | 'z' => None,
+            'M' => Some('L'),
+            'm' => Some('l'),
+            letter => Some(letter),
+        };
+    }
+    Ok(commands)
+}
+
+fn path_tokens(data: &str) -> Result<Vec<PathToken>, String> {
+    let bytes = data.as_bytes();
+    let digits_from = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if byte.is_ascii_whitespace() || byte == b',' {
+            i += 1;
+            continue;
+        }
+        if byte.is_ascii_alphabetic() {
+            tokens.push(PathToken::Command(byte as char));
+            i += 1;
+            continue;
+        }
+        // [sign] digits [. digits] [exponent]; a second "." starts the next number
+        let start = i;
+        let digits_start = if byte == b'+' || byte == b'-' { i + 1 } else { i };
+        let mut end = digits_from(digits_start);
+        if end < bytes.len() && bytes[end] == b'.' {
+            end = digits_from(end + 1);
+        }
+        if end == digits_start || &data[digits_start..end] == "." {
+            let found = data[start..].chars().next().unwrap_or_default();
+            return Err(format!("unexpected '{}'", found));
+        }
+        if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+            let sign = usize::from(end + 1 < bytes.len() && (bytes[end + 1] == b'+' || bytes[end + 1] == b'-'));
+            let exponent = digits_from(end + 1 + sign);
+            if exponent > end + 1 + sign {
+                end = exponent;
+            }
+        }
+        let number = data[start..end].parse::<f64>().map_err(|_| format!("'{}' is not a number", &data[start..end]))?;
+        tokens.push(PathToken::Number(number));
+        i = end;
+    }
+    Ok(tokens)
+}
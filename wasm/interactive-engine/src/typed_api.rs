@@ -0,0 +1,226 @@
+// Typed facade over the JSON string API.
+//
+// Every serialized struct derives `tsify::Tsify` and `schemars::JsonSchema` when the
+// `typescript` feature is enabled, so wasm-bindgen emits matching TypeScript
+// declarations into the generated `.d.ts`. `LivEngine` wraps the global engine with
+// methods that take and return those types directly instead of JSON strings.
+//
+// Values cross the boundary as `tsify::Ts<T>` and are converted here, so a malformed
+// argument becomes a thrown error instead of leaking the way `#[tsify(from_wasm_abi)]` does.
+
+use super::*;
+use serde::de::DeserializeOwned;
+use tsify::{Ts, Tsify};
+
+// tsify references `serde_json::Value` by its bare name; declare it once for the .d.ts
+#[wasm_bindgen(typescript_custom_section)]
+const TS_JSON_VALUE: &'static str = r#"
+export type Value = null | boolean | number | string | Value[] | { [key: string]: Value };
+"#;
+
+#[wasm_bindgen]
+pub struct LivEngine;
+
+#[wasm_bindgen]
+impl LivEngine {
+    #[wasm_bindgen(constructor)]
+    pub fn new(permissions: Ts<WASMPermissions>) -> Result<LivEngine, JsValue> {
+        let permissions = from_ts(permissions)?;
+        let engine = InteractiveEngine::new(permissions)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create engine: {}", e.message)))?;
+        install_engine(engine)
+            .map_err(|e| JsValue::from_str(&format!("Failed to create engine: {}", e.message)))?;
+        Ok(LivEngine)
+    }
+
+    #[wasm_bindgen(js_name = processInteraction)]
+    pub fn process_interaction(&self, event: Ts<InteractionEvent>) -> Result<Ts<RenderUpdate>, JsValue> {
+        let event = from_ts(event)?;
+        with_engine(|engine| {
+            engine.process_interaction(event)
+                .map_err(|e| JsValue::from_str(&format!("Interaction failed: {}", e.message)))
+        })
+        .and_then(into_ts)
+    }
+
+    #[wasm_bindgen(js_name = renderFrame)]
+    pub fn render_frame(&self, timestamp: f64) -> Result<Ts<RenderUpdate>, JsValue> {
+        with_engine(|engine| {
+            engine.render_frame(timestamp)
+                .map_err(|e| JsValue::from_str(&format!("Render failed: {}", e.message)))
+        })
+        .and_then(into_ts)
+    }
+
+    #[wasm_bindgen(js_name = getPerformanceStats)]
+    pub fn get_performance_stats(&self) -> Result<Ts<PerformanceStats>, JsValue> {
+        with_engine(|engine| Ok(engine.get_performance_stats())).and_then(into_ts)
+    }
+
+    #[wasm_bindgen(js_name = getInteractionMetrics)]
+    pub fn get_interaction_metrics(&self) -> Result<Ts<InteractionMetrics>, JsValue> {
+        with_engine(|engine| Ok(engine.get_interaction_metrics())).and_then(into_ts)
+    }
+
+    #[wasm_bindgen(js_name = getMetrics)]
+    pub fn get_metrics(&self) -> Result<Ts<MetricsSnapshot>, JsValue> {
+        with_engine(|engine| Ok(engine.get_metrics())).and_then(into_ts)
+    }
+
+    #[wasm_bindgen(js_name = getElementBounds)]
+    pub fn get_element_bounds(&self, element_id: &str) -> Result<Ts<BoundingBox>, JsValue> {
+        with_engine(|engine| {
+            engine.get_element_bounds(element_id)
+                .map_err(|e| JsValue::from_str(&format!("Failed to get bounds: {}", e.message)))
+        })
+        .and_then(into_ts)
+    }
+
+    #[wasm_bindgen(js_name = createChart)]
+    pub fn create_chart(&self, chart_type: &str, data_source_id: &str, config: Ts<ChartConfig>) -> Result<String, JsValue> {
+        let chart_type = parse_chart_type(chart_type)?;
+        let config = from_ts(config)?;
+        with_engine(|engine| {
+            engine.chart_renderer.create_chart(chart_type, data_source_id.to_string(), config)
+                .map_err(|e| JsValue::from_str(&format!("Failed to create chart: {}", e.message)))
+        })
+    }
+
+    #[wasm_bindgen(js_name = addChartSeries)]
+    pub fn add_chart_series(&self, chart_id: &str, series: Ts<ChartSeries>) -> Result<(), JsValue> {
+        let series = from_ts(series)?;
+        with_engine(|engine| {
+            engine.chart_renderer.add_series(chart_id, series)
+                .map_err(|e| JsValue::from_str(&format!("Failed to add series: {}", e.message)))
+        })
+    }
+
+    #[wasm_bindgen(js_name = renderChart)]
+    pub fn render_chart(&self, chart_id: &str, data: JsValue) -> Result<Ts<RenderedChart>, JsValue> {
+        let data_json: String = js_sys::JSON::stringify(&data)?.into();
+        let data: serde_json::Value = serde_json::from_str(&data_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse data: {}", e)))?;
+
+        with_engine(|engine| {
+            engine.chart_renderer.render_chart(chart_id, &data)
+                .map_err(|e| JsValue::from_str(&format!("Failed to render chart: {}", e.message)))
+        })
+        .and_then(into_ts)
+    }
+
+    #[wasm_bindgen(js_name = getChartPerformanceStats)]
+    pub fn get_chart_performance_stats(&self) -> Result<Ts<ChartPerformanceStats>, JsValue> {
+        with_engine(|engine| Ok(engine.chart_renderer.performance_stats.clone())).and_then(into_ts)
+    }
+
+    #[wasm_bindgen(js_name = getDataStatistics)]
+    pub fn get_data_statistics(&self, source_id: &str) -> Result<Ts<DataStatistics>, JsValue> {
+        with_engine(|engine| {
+            engine.document_state.data_sources.get(source_id)
+                .map(|data_source| data_source.get_data_statistics())
+                .ok_or_else(|| JsValue::from_str("Data source not found"))
+        })
+        .and_then(into_ts)
+    }
+
+    // Returns the JSON Schema for a serialized type, e.g. `LivEngine.jsonSchema("ChartConfig")`
+    #[wasm_bindgen(js_name = jsonSchema)]
+    pub fn json_schema(type_name: &str) -> Result<String, JsValue> {
+        get_json_schema(type_name)
+    }
+}
+
+fn from_ts<T>(value: Ts<T>) -> Result<T, JsValue>
+where
+    T: Tsify + DeserializeOwned,
+    T::JsType: Clone,
+{
+    value.to_rust().map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn into_ts<T: Tsify + Serialize>(value: T) -> Result<Ts<T>, JsValue> {
+    Ts::from_rust(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn with_engine<T>(f: impl FnOnce(&mut InteractiveEngine) -> Result<T, JsValue>) -> Result<T, JsValue> {
+    let mut global_engine = ENGINE.lock().unwrap();
+    match global_engine.as_mut() {
+        Some(engine) => f(engine),
+        None => Err(JsValue::from_str("Engine not initialized")),
+    }
+}
+
+fn parse_chart_type(chart_type: &str) -> Result<ChartType, JsValue> {
+    match chart_type {
+        "line" => Ok(ChartType::Line),
+        "bar" => Ok(ChartType::Bar),
+        "pie" => Ok(ChartType::Pie),
+        "scatter" => Ok(ChartType::Scatter),
+        "area" => Ok(ChartType::Area),
+        "histogram" => Ok(ChartType::Histogram),
+        "heatmap" => Ok(ChartType::Heatmap),
+        "treemap" => Ok(ChartType::Treemap),
+        "sankey" => Ok(ChartType::Sankey),
+        "radar" => Ok(ChartType::Radar),
+        "gauge" => Ok(ChartType::Gauge),
+        "candlestick" => Ok(ChartType::Candlestick),
+        "gantt" => Ok(ChartType::Gantt),
+        "waterfall" => Ok(ChartType::Waterfall),
+        "funnel" => Ok(ChartType::Funnel),
+        "pyramid" => Ok(ChartType::Pyramid),
+        "map" => Ok(ChartType::Map),
+        custom => with_engine(|engine| match engine.chart_renderer.has_custom_type(custom) {
+            true => Ok(ChartType::Custom(custom.to_string())),
+            false => Err(JsValue::from_str("Invalid chart type")),
+        }),
+    }
+}
+
+// JSON Schema (draft 2020-12) for the top-level types exchanged with JavaScript
+#[wasm_bindgen]
+pub fn get_json_schema(type_name: &str) -> Result<String, JsValue> {
+    let schema = match type_name {
+        "DocumentState" => schemars::schema_for!(DocumentState),
+        "InteractiveElement" => schemars::schema_for!(InteractiveElement),
+        "Animation" => schemars::schema_for!(Animation),
+        "Keyframe" => schemars::schema_for!(Keyframe),
+        "TimelinePlacement" => schemars::schema_for!(TimelinePlacement),
+        "TimelineState" => schemars::schema_for!(TimelineState),
+        "StreamConfig" => schemars::schema_for!(StreamConfig),
+        "StreamStatus" => schemars::schema_for!(StreamStatus),
+        "NoiseDriver" => schemars::schema_for!(NoiseDriver),
+        "Distribution" => schemars::schema_for!(Distribution),
+        "DataSource" => schemars::schema_for!(DataSource),
+        "RenderTree" => schemars::schema_for!(RenderTree),
+        "RenderUpdate" => schemars::schema_for!(RenderUpdate),
+        "BoundingBox" => schemars::schema_for!(BoundingBox),
+        "Viewport" => schemars::schema_for!(Viewport),
+        "Transform" => schemars::schema_for!(Transform),
+        "InteractionEvent" => schemars::schema_for!(InteractionEvent),
+        "TouchData" => schemars::schema_for!(TouchData),
+        "WASMPermissions" => schemars::schema_for!(WASMPermissions),
+        "WASMError" => schemars::schema_for!(WASMError),
+        "InteractionState" => schemars::schema_for!(InteractionState),
+        "InteractionMetrics" => schemars::schema_for!(InteractionMetrics),
+        "InteractionResponse" => schemars::schema_for!(InteractionResponse),
+        "GestureEvent" => schemars::schema_for!(GestureEvent),
+        "PerformanceStats" => schemars::schema_for!(PerformanceStats),
+        "MetricsSnapshot" => schemars::schema_for!(MetricsSnapshot),
+        "SecurityDenial" => schemars::schema_for!(SecurityDenial),
+        "ContentProtection" => schemars::schema_for!(ContentProtection),
+        "MutationExport" => schemars::schema_for!(MutationExport),
+        "DataStatistics" => schemars::schema_for!(DataStatistics),
+        "DataBinding" => schemars::schema_for!(DataBinding),
+        "ChartConfig" => schemars::schema_for!(ChartConfig),
+        "ChartSeries" => schemars::schema_for!(ChartSeries),
+        "ChartInteractions" => schemars::schema_for!(ChartInteractions),
+        "RenderedChart" => schemars::schema_for!(RenderedChart),
+        "ChartPerformanceStats" => schemars::schema_for!(ChartPerformanceStats),
+        "VectorShape" => schemars::schema_for!(VectorShape),
+        "VectorPath" => schemars::schema_for!(VectorPath),
+        _ => return Err(JsValue::from_str(&format!("No schema for type: {}", type_name))),
+    };
+
+    serde_json::to_string(&schema)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize schema: {}", e)))
+}